@@ -0,0 +1,25 @@
+use std::process::Command;
+
+/// Output of a git command, if git is available and succeeds
+fn git(args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|output| output.trim().to_string())
+        .filter(|output| !output.is_empty())
+}
+
+/// Pass the git commit and its commit time to the crate for the /version command
+/// The commit time is used instead of the build time to keep builds of the same commit reproducible
+fn main() {
+    let commit = git(&["rev-parse", "--short", "HEAD"]).unwrap_or_else(|| "unknown".to_string());
+    let timestamp = git(&["log", "-1", "--format=%ct"]).unwrap_or_else(|| "0".to_string());
+
+    println!("cargo:rustc-env=LEDGERBOT_GIT_COMMIT={}", commit);
+    println!("cargo:rustc-env=LEDGERBOT_COMMIT_TIMESTAMP={}", timestamp);
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs");
+}
@@ -1,32 +1,183 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
-use teloxide::{prelude::*, types::Chat};
-use yoroolbot::{command_trait::CommandTrait, markdown::MarkdownStringMessage, markdown_format};
+use chrono::Utc;
+
+use teloxide::{
+    prelude::*,
+    types::{Chat, MessageId, UserId},
+};
+use yoroolbot::{
+    command_trait::CommandTrait,
+    markdown::{MarkdownString, MarkdownStringMessage},
+    markdown_format, markdown_string,
+};
 
 use crate::{
     commands::{
-        Command, command_add_expense::CommandAddExpense, command_list::CommandList,
-        command_report::CommandReport, execute_command,
+        Command, ImportOutcome,
+        command_add_expense::CommandAddExpense,
+        command_list::CommandList,
+        command_report::{CommandReport, refresh_last_report},
+        execute_command,
     },
     config::BATCH_TIMEOUT_SECONDS,
-    storages::{BatchStorageTrait, StorageTrait},
+    storages::{BatchEntry, BatchHistoryEntry, BatchStorageTrait, StorageTrait},
+    utils::amount::Amount,
 };
 
+/// Batch entries of the parse results of message `message_id` sent by `user_id`
+/// Each result comes with the number of its line in the message, see `parse_expenses`
+pub fn batch_entries(
+    results: Vec<(usize, Result<Command, String>)>,
+    message_id: MessageId,
+    user_id: Option<UserId>,
+) -> Vec<BatchEntry> {
+    results
+        .into_iter()
+        .map(|(line, result)| BatchEntry {
+            result,
+            message_id,
+            line,
+            user_id,
+        })
+        .collect()
+}
+
 /// Add expense data to batch and return whether this is the first message in the batch
+/// The commands are audited as issued by `user_id`, the sender of the message
 pub async fn add_to_batch(
     batch_storage: Arc<dyn BatchStorageTrait>,
     chat: Chat,
-    commands: Vec<Result<Command, String>>,
+    message_id: MessageId,
+    user_id: Option<UserId>,
+    commands: Vec<(usize, Result<Command, String>)>,
 ) -> bool {
-    batch_storage.add_to_batch(chat.id, commands).await
+    batch_storage
+        .add_to_batch(chat.id, batch_entries(commands, message_id, user_id))
+        .await
+}
+
+/// Position of a batched line: the 1-based number of its message in the batch
+/// and of the line in that message
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatchLine {
+    pub message: usize,
+    pub line: usize,
+}
+
+/// Batched command with its position in the batch and its sender
+pub type BatchCommand = (BatchLine, Option<UserId>, Command);
+
+/// Split batched parse results into commands to execute and parse errors to report
+pub fn partition_batch(entries: Vec<BatchEntry>) -> (Vec<BatchCommand>, Vec<String>) {
+    let mut commands = Vec::new();
+    let mut errors = Vec::new();
+    let mut message_ids: Vec<MessageId> = Vec::new();
+    for entry in entries {
+        if message_ids.last() != Some(&entry.message_id) {
+            message_ids.push(entry.message_id);
+        }
+        let position = BatchLine {
+            message: message_ids.len(),
+            line: entry.line,
+        };
+        match entry.result {
+            Ok(cmd) => commands.push((position, entry.user_id, cmd)),
+            Err(err_msg) => errors.push(err_msg),
+        }
+    }
+    (commands, errors)
+}
+
+/// Line of a batch as shown to the user, naming the message only in batches of several
+fn format_batch_line(position: BatchLine, several_messages: bool) -> String {
+    if several_messages {
+        format!("{} of message {}", position.line, position.message)
+    } else {
+        position.line.to_string()
+    }
+}
+
+/// Format the outcomes of the category import commands of a batch as a single message
+/// Returns None when the batch contained no such commands
+pub fn format_import_summary(outcomes: &[(BatchLine, ImportOutcome)]) -> Option<MarkdownString> {
+    if outcomes.is_empty() {
+        return None;
+    }
+    let several_messages = outcomes.iter().any(|(position, _)| position.message > 1);
+    let created = outcomes
+        .iter()
+        .filter(|(_, outcome)| outcome.category_created)
+        .count();
+    let added: usize = outcomes
+        .iter()
+        .map(|(_, outcome)| outcome.filters_added)
+        .sum();
+    let invalid: usize = outcomes
+        .iter()
+        .map(|(_, outcome)| outcome.invalid_filters)
+        .sum();
+    let mut message = markdown_format!(
+        "📥 *Import summary:* {} categories created, {} filters added",
+        created,
+        added
+    );
+    if invalid > 0 {
+        let lines = outcomes
+            .iter()
+            .filter(|(_, outcome)| outcome.invalid_filters > 0)
+            .map(|(position, _)| format_batch_line(*position, several_messages))
+            .collect::<Vec<_>>()
+            .join(", ");
+        message = message + markdown_format!(", {} invalid regexes on lines {}", invalid, lines);
+    }
+    message = message + markdown_string!("\\.");
+    for (position, outcome) in outcomes {
+        if let Some(error) = &outcome.error {
+            message = message
+                + markdown_format!(
+                    "\n❌ Line {}: {}",
+                    format_batch_line(*position, several_messages),
+                    @raw error.clone()
+                );
+        }
+        for warning in &outcome.warnings {
+            message = message
+                + markdown_format!(
+                    "\n⚠️ Line {}: {}",
+                    format_batch_line(*position, several_messages),
+                    @raw warning.clone()
+                );
+        }
+    }
+    Some(message)
+}
+
+/// Format all parse errors of a batch as a single message
+/// Returns None when every line was parsed
+pub fn format_batch_errors(errors: &[String]) -> Option<MarkdownString> {
+    if errors.is_empty() {
+        return None;
+    }
+    let mut message = markdown_format!(
+        "⚠️ {} line\\(s\\) could not be parsed, all other lines were processed:\n",
+        errors.len()
+    );
+    for err_msg in errors {
+        message = message + markdown_format!("\n{}", err_msg);
+    }
+    Some(message)
 }
 
 /// Send batch report after timeout and execute stored commands
+/// In read-only mode mutating commands are skipped and reported once in the summary
 pub async fn execute_batch(
     bot: Bot,
     batch_storage: Arc<dyn BatchStorageTrait>,
     chat: Chat,
     storage: Arc<dyn StorageTrait>,
+    read_only: bool,
+    command_timeout: Duration,
 ) {
     // Wait for the timeout period
     tokio::time::sleep(tokio::time::Duration::from_secs(BATCH_TIMEOUT_SECONDS)).await;
@@ -34,46 +185,101 @@ pub async fn execute_batch(
     let batch_data = batch_storage.consume_batch(chat.id).await;
 
     let mut expense_count: usize = 0;
-    let mut total_amount: f64 = 0.0;
+    let mut total_amount = Amount::ZERO;
+    let mut refused_count: usize = 0;
 
     if let Some(state) = batch_data {
-        // Execute all stored commands
-        for result in state {
-            match result {
-                Ok(cmd) => {
-                    if let Command::AddExpense(CommandAddExpense {
-                        amount: Some(amt_val),
-                        ..
-                    }) = cmd
-                    {
-                        expense_count += 1;
-                        total_amount += amt_val;
-                    }
-                    let exec_result = execute_command(
-                        bot.clone(),
-                        chat.clone(),
-                        None,
-                        storage.clone(),
-                        cmd,
-                        true,
-                    )
-                    .await;
-                    if let Err(e) = exec_result {
-                        log::error!("Failed to execute batched command: {}", e);
-                    }
+        // Execute all valid commands, parse errors are reported together afterwards
+        let (commands, parse_errors) = partition_batch(state);
+        let mut history: Vec<BatchHistoryEntry> = Vec::new();
+        let mut import_outcomes: Vec<(BatchLine, ImportOutcome)> = Vec::new();
+        let mut mutated = false;
+        for (line, user_id, cmd) in commands {
+            if read_only && cmd.is_mutating() {
+                refused_count += 1;
+                history.push(Err(format!("{}: refused in read-only mode", cmd)));
+                continue;
+            }
+            if let Command::AddExpense(CommandAddExpense {
+                amount: Some(amt_val),
+                ..
+            }) = cmd
+            {
+                expense_count += 1;
+                total_amount += Amount::from(amt_val);
+            }
+            let command = cmd.to_string();
+            let mutating = cmd.is_mutating();
+            let exec_result = execute_command(
+                bot.clone(),
+                chat.clone(),
+                None,
+                storage.clone(),
+                cmd,
+                true,
+                None,
+                read_only,
+                user_id,
+                command_timeout,
+            )
+            .await;
+            match exec_result {
+                Ok(outcome) => {
+                    mutated |= mutating;
+                    import_outcomes.extend(outcome.map(|outcome| (line, outcome)));
+                    history.push(Ok(command));
                 }
-                Err(err_msg) => {
-                    // Send error message to user
-                    log::warn!("Parse error in batch for chat {}: {}", chat.id, err_msg);
-                    if let Err(e) = bot
-                        .markdown_message(chat.id, None, markdown_format!("❌ {}", err_msg))
-                        .await
-                    {
-                        log::error!("Failed to send error message: {}", e);
-                    }
+                Err(e) => {
+                    log::error!("Failed to execute batched command: {}", e);
+                    history.push(Err(format!("{}: {}", command, e)));
                 }
             }
         }
+        if mutated
+            && let Err(e) = refresh_last_report(
+                bot.clone(),
+                chat.clone(),
+                storage.clone(),
+                Utc::now().timestamp(),
+            )
+            .await
+        {
+            log::error!("Failed to refresh the report in chat {}: {}", chat.id, e);
+        }
+        history.extend(parse_errors.iter().cloned().map(Err));
+        batch_storage.add_to_history(chat.id, history).await;
+
+        if let Some(import_message) = format_import_summary(&import_outcomes)
+            && let Err(e) = bot.markdown_message(chat.id, None, import_message).await
+        {
+            log::error!("Failed to send import summary: {}", e);
+        }
+
+        if let Some(errors_message) = format_batch_errors(&parse_errors) {
+            log::warn!(
+                "{} parse errors in batch for chat {}",
+                parse_errors.len(),
+                chat.id
+            );
+            if let Err(e) = bot.markdown_message(chat.id, None, errors_message).await {
+                log::error!("Failed to send error message: {}", e);
+            }
+        }
+
+        if refused_count > 0
+            && let Err(e) = bot
+                .markdown_message(
+                    chat.id,
+                    None,
+                    markdown_format!(
+                        "🔒 Bot is in read\\-only mode\\. Ignored {} command\\(s\\)\\.",
+                        refused_count
+                    ),
+                )
+                .await
+        {
+            log::error!("Failed to send read-only notice: {}", e);
+        }
 
         if let Err(e) = bot
             .markdown_message(
@@ -85,11 +291,13 @@ pub async fn execute_batch(
             Total amount: {}\n\n\
             Use {} or {} to see all expenses\\.",
                     expense_count,
-                    total_amount,
-                    CommandList.to_command_string(false),
+                    total_amount.to_string(),
+                    CommandList::default().to_command_string(false),
                     CommandReport {
                         category: None,
-                        page: None
+                        page: None,
+                        min_amount: None,
+                        collapse: false,
                     }
                     .to_command_string(false)
                 ),
@@ -100,3 +308,149 @@ pub async fn execute_batch(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::parse_expenses::parse_expenses;
+
+    #[test]
+    fn test_partial_parse_failure_keeps_valid_lines() {
+        let text = "Coffee 5\nLunch 12\n/no_such_command\n\n# Evening\nTaxi 3\nDinner 25";
+        let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC
+
+        let (commands, errors) = partition_batch(batch_entries(
+            parse_expenses(text, None, timestamp, false, false),
+            MessageId(1),
+            Some(UserId(42)),
+        ));
+
+        assert_eq!(commands.len(), 4);
+        assert!(
+            commands
+                .iter()
+                .all(|(_, user_id, cmd)| *user_id == Some(UserId(42))
+                    && matches!(cmd, Command::AddExpense(_)))
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("/no_such_command"));
+
+        let message = format_batch_errors(&errors).unwrap();
+        assert!(message.as_str().starts_with("⚠️ 1 line"));
+        assert!(message.as_str().contains("/no\\_such\\_command"));
+        assert!(format_batch_errors(&[]).is_none());
+        // Line numbers count the parse errors, blank and comment lines too
+        let lines: Vec<usize> = commands
+            .iter()
+            .map(|(position, _, _)| position.line)
+            .collect();
+        assert_eq!(lines, vec![1, 2, 6, 7]);
+    }
+
+    #[test]
+    fn test_partition_batch_of_several_messages() {
+        let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC
+        let mut entries = batch_entries(
+            parse_expenses("# Receipts\nCoffee 5", None, timestamp, false, false),
+            MessageId(10),
+            None,
+        );
+        entries.extend(batch_entries(
+            parse_expenses("\nTea 3\nCake 4", None, timestamp, false, false),
+            MessageId(11),
+            None,
+        ));
+        let (commands, _) = partition_batch(entries);
+        let positions: Vec<BatchLine> = commands.iter().map(|(position, _, _)| *position).collect();
+        assert_eq!(
+            positions,
+            vec![
+                BatchLine {
+                    message: 1,
+                    line: 2
+                },
+                BatchLine {
+                    message: 2,
+                    line: 2
+                },
+                BatchLine {
+                    message: 2,
+                    line: 3
+                },
+            ]
+        );
+
+        // Lines of a batch of several messages name their message
+        let outcome = ImportOutcome {
+            error: Some(markdown_format!("Category `{}` does not exist", "Travel")),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_import_summary(&[(positions[2], outcome)])
+                .unwrap()
+                .as_str(),
+            "📥 *Import summary:* 0 categories created, 0 filters added\\.\n\
+             ❌ Line 3 of message 2: Category `Travel` does not exist"
+        );
+    }
+
+    #[test]
+    fn test_format_import_summary() {
+        assert!(format_import_summary(&[]).is_none());
+
+        let line = |line| BatchLine { message: 1, line };
+
+        let outcomes = vec![
+            (
+                line(1),
+                ImportOutcome {
+                    category_created: true,
+                    filters_added: 2,
+                    ..Default::default()
+                },
+            ),
+            (
+                line(2),
+                ImportOutcome {
+                    category_created: true,
+                    filters_added: 1,
+                    invalid_filters: 1,
+                    ..Default::default()
+                },
+            ),
+            (
+                line(3),
+                ImportOutcome {
+                    filters_added: 1,
+                    ..Default::default()
+                },
+            ),
+            (
+                line(5),
+                ImportOutcome {
+                    invalid_filters: 1,
+                    ..Default::default()
+                },
+            ),
+            (
+                line(6),
+                ImportOutcome {
+                    error: Some(markdown_format!("Category `{}` does not exist", "Travel")),
+                    ..Default::default()
+                },
+            ),
+        ];
+        assert_eq!(
+            format_import_summary(&outcomes).unwrap().as_str(),
+            "📥 *Import summary:* 2 categories created, 4 filters added, \
+             2 invalid regexes on lines 2, 5\\.\n\
+             ❌ Line 6: Category `Travel` does not exist"
+        );
+
+        // Nothing went wrong: only the counts
+        assert_eq!(
+            format_import_summary(&outcomes[..1]).unwrap().as_str(),
+            "📥 *Import summary:* 1 categories created, 2 filters added\\."
+        );
+    }
+}
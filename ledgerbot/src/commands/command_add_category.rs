@@ -3,28 +3,166 @@ use std::sync::Arc;
 use teloxide::{
     payloads::EditMessageReplyMarkupSetters,
     prelude::{Requester, ResponseResult},
-    types::{InlineKeyboardButton, InlineKeyboardMarkup},
+    types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup},
+    utils::command::ParseError,
 };
 use yoroolbot::{
-    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    command_trait::{
+        CommandReplyTarget, CommandTrait, EmptyArg, screen_spaces, split_with_screened_spaces,
+    },
+    markdown::MarkdownString,
     markdown_format, markdown_string,
 };
 
-use crate::{commands::Command, storages::CategoryStorageTrait};
+use crate::{
+    commands::{
+        Command, ImportOutcome, command_add_filter::equivalent_filter_warning,
+        command_uncategorized_name::check_category_name,
+    },
+    storages::{CategoryStorageTrait, StorageTrait},
+    utils::filter_pattern::compile_filter,
+};
 
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct CommandAddCategory {
     pub name: Option<String>,
+    /// Initial filters added right after the category is created
+    pub patterns: Vec<String>,
 }
 
 impl CommandAddCategory {
     pub fn new(name: impl Into<String>) -> Self {
         CommandAddCategory {
             name: Some(name.into()),
+            patterns: Vec::new(),
+        }
+    }
+
+    /// Parse `<name> [pattern...]` - everything after the name is treated as initial filters
+    pub fn parse_add_category_arguments(args: String) -> Result<(Self,), ParseError> {
+        let mut parts = split_with_screened_spaces(&args).into_iter();
+        let name = parts.next();
+        Ok((CommandAddCategory {
+            name,
+            patterns: parts.collect(),
+        },))
+    }
+
+    /// Build the command string with the initial filters appended after the name
+    pub fn to_add_category_command_string(&self, complete: bool) -> String {
+        let mut command = self.to_command_string(complete);
+        for pattern in &self.patterns {
+            if !command.ends_with(' ') {
+                command.push(' ');
+            }
+            command.push_str(&screen_spaces(pattern));
         }
+        command
     }
 }
 
+/// Result of adding a category together with its initial filters
+#[derive(Debug)]
+pub struct AddCategoryOutcome {
+    /// False when the category already existed and filters were appended to it
+    pub created: bool,
+    /// Per-pattern result in the order the patterns were given
+    pub filters: Vec<(String, Result<(), MarkdownString>)>,
+    /// Added filters matching the same as other filters of the category
+    pub warnings: Vec<MarkdownString>,
+}
+
+/// Create the category if it doesn't exist yet, then add each validated pattern to it
+pub async fn add_category_with_filters(
+    storage: &dyn CategoryStorageTrait,
+    chat_id: ChatId,
+    name: &str,
+    patterns: &[String],
+    case_insensitive: bool,
+) -> Result<AddCategoryOutcome, MarkdownString> {
+    let exists = storage
+        .get_chat_categories(chat_id)
+        .await?
+        .contains_key(name);
+    if !exists {
+        storage.add_category(chat_id, name.to_string()).await?;
+    }
+
+    let mut filters = Vec::new();
+    let mut warnings = Vec::new();
+    for pattern in patterns {
+        let result = match compile_filter(pattern, false) {
+            Ok(_) => {
+                storage
+                    .add_category_filter(chat_id, name.to_string(), pattern.clone())
+                    .await
+            }
+            Err(e) => Err(markdown_format!("Invalid regex pattern: {}", e.to_string())),
+        };
+        if result.is_ok() {
+            warnings.extend(
+                equivalent_filter_warning(storage, chat_id, name, pattern, case_insensitive).await,
+            );
+        }
+        filters.push((pattern.clone(), result));
+    }
+
+    Ok(AddCategoryOutcome {
+        created: !exists,
+        filters,
+        warnings,
+    })
+}
+
+/// Apply the command as part of a batch import without replying
+/// Returns None when the category name is missing, such a command shows its menu instead
+pub async fn import_category(
+    storage: &dyn CategoryStorageTrait,
+    chat_id: ChatId,
+    command: &CommandAddCategory,
+    uncategorized_name: &str,
+    case_insensitive: bool,
+) -> Option<ImportOutcome> {
+    let name = command.name.as_ref()?;
+    if let Err(err_msg) = check_category_name(name, uncategorized_name) {
+        return Some(ImportOutcome {
+            error: Some(err_msg),
+            ..Default::default()
+        });
+    }
+    let outcome = match add_category_with_filters(
+        storage,
+        chat_id,
+        name,
+        &command.patterns,
+        case_insensitive,
+    )
+    .await
+    {
+        Ok(outcome) => outcome,
+        Err(err_msg) => {
+            return Some(ImportOutcome {
+                error: Some(err_msg),
+                ..Default::default()
+            });
+        }
+    };
+    let mut import = ImportOutcome {
+        category_created: outcome.created,
+        warnings: outcome.warnings,
+        ..Default::default()
+    };
+    for (pattern, result) in outcome.filters {
+        match result {
+            Ok(()) => import.filters_added += 1,
+            Err(_) if compile_filter(&pattern, false).is_err() => import.invalid_filters += 1,
+            // Filters already present are expected when importing the same set again
+            Err(_) => {}
+        }
+    }
+    Some(import)
+}
+
 impl CommandTrait for CommandAddCategory {
     type A = String;
     type B = EmptyArg;
@@ -36,7 +174,7 @@ impl CommandTrait for CommandAddCategory {
     type H = EmptyArg;
     type I = EmptyArg;
 
-    type Context = Arc<dyn CategoryStorageTrait>;
+    type Context = Arc<dyn StorageTrait>;
 
     const NAME: &'static str = "add_category";
     const PLACEHOLDERS: &[&'static str] = &["<name>"];
@@ -52,7 +190,10 @@ impl CommandTrait for CommandAddCategory {
         _: Option<Self::H>,
         _: Option<Self::I>,
     ) -> Self {
-        CommandAddCategory { name: a }
+        CommandAddCategory {
+            name: a,
+            patterns: Vec::new(),
+        }
     }
 
     fn param1(&self) -> Option<&Self::A> {
@@ -77,20 +218,66 @@ impl CommandTrait for CommandAddCategory {
         storage: Self::Context,
         name: &String,
     ) -> teloxide::prelude::ResponseResult<()> {
-        match storage.add_category(target.chat.id, name.clone()).await {
-            Ok(()) => {
-                target
-                    .send_markdown_message(markdown_format!(
-                        "✅ Category `{}` created\\. Use {} to add regex patterns\\.",
-                        name,
-                        Command::ADD_FILTER
-                    ))
-                    .await?;
+        let settings = storage
+            .clone()
+            .as_settings_storage()
+            .get_chat_settings(target.chat.id)
+            .await;
+        if let Err(err_msg) = check_category_name(name, settings.uncategorized_name()) {
+            target.send_markdown_message(err_msg).await?;
+            return Ok(());
+        }
+        let storage = storage.as_category_storage();
+        if self.patterns.is_empty() {
+            match storage.add_category(target.chat.id, name.clone()).await {
+                Ok(()) => {
+                    target
+                        .send_markdown_message(markdown_format!(
+                            "✅ Category `{}` created\\. Use {} to add regex patterns\\.",
+                            name,
+                            Command::ADD_FILTER
+                        ))
+                        .await?;
+                }
+                Err(err_msg) => {
+                    target.send_markdown_message(err_msg).await?;
+                }
             }
+            return Ok(());
+        }
+
+        let outcome = match add_category_with_filters(
+            &*storage,
+            target.chat.id,
+            name,
+            &self.patterns,
+            settings.case_insensitive_filters,
+        )
+        .await
+        {
+            Ok(outcome) => outcome,
             Err(err_msg) => {
                 target.send_markdown_message(err_msg).await?;
+                return Ok(());
             }
+        };
+
+        let mut message = if outcome.created {
+            markdown_format!("✅ Category `{}` created\\.\n", name)
+        } else {
+            markdown_format!("ℹ️ Category `{}` already exists, adding filters\\.\n", name)
+        };
+        for (pattern, result) in outcome.filters {
+            message = message
+                + match result {
+                    Ok(()) => markdown_format!("\n✅ `{}`", pattern),
+                    Err(err_msg) => markdown_format!("\n❌ `{}`: {}", pattern, @raw err_msg),
+                };
+        }
+        for warning in outcome.warnings {
+            message = message + markdown_format!("\n⚠️ {}", @raw warning);
         }
+        target.send_markdown_message(message).await?;
         Ok(())
     }
 }
@@ -122,3 +309,125 @@ pub async fn add_category_menu(target: &CommandReplyTarget) -> ResponseResult<()
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storages::CategoryStorage;
+
+    fn patterns(list: &[&str]) -> Vec<String> {
+        list.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_add_category_with_patterns() {
+        let (cmd,) =
+            CommandAddCategory::parse_add_category_arguments("Food (?i)coffee (?i)tea".into())
+                .unwrap();
+        assert_eq!(cmd.name, Some("Food".to_string()));
+        assert_eq!(cmd.patterns, patterns(&["(?i)coffee", "(?i)tea"]));
+        assert_eq!(
+            cmd.to_add_category_command_string(false),
+            "/add_category Food (?i)coffee (?i)tea"
+        );
+
+        let (cmd,) = CommandAddCategory::parse_add_category_arguments("Food".into()).unwrap();
+        assert_eq!(cmd, CommandAddCategory::new("Food"));
+    }
+
+    #[tokio::test]
+    async fn test_add_category_with_filters_creates_category() {
+        let storage = CategoryStorage::new();
+        let chat_id = ChatId(1);
+
+        let outcome = add_category_with_filters(
+            &storage,
+            chat_id,
+            "Food",
+            &patterns(&["(?i)coffee", "(unclosed", "(?i)tea", "(?i)Coffee"]),
+            false,
+        )
+        .await
+        .unwrap();
+
+        assert!(outcome.created);
+        assert!(outcome.filters[0].1.is_ok());
+        assert!(outcome.filters[1].1.is_err());
+        assert!(outcome.filters[2].1.is_ok());
+        // A filter matching the same as another one is added with a warning
+        assert!(outcome.filters[3].1.is_ok());
+        assert_eq!(outcome.warnings.len(), 1);
+        let categories = storage.get_chat_categories(chat_id).await.unwrap();
+        assert_eq!(
+            categories["Food"],
+            patterns(&["(?i)coffee", "(?i)tea", "(?i)Coffee"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_category_with_filters_appends_to_existing() {
+        let storage = CategoryStorage::new();
+        let chat_id = ChatId(1);
+        storage
+            .add_category(chat_id, "Food".to_string())
+            .await
+            .unwrap();
+        storage
+            .add_category_filter(chat_id, "Food".to_string(), "(?i)coffee".to_string())
+            .await
+            .unwrap();
+
+        let outcome = add_category_with_filters(
+            &storage,
+            chat_id,
+            "Food",
+            &patterns(&["(?i)coffee", "(?i)tea"]),
+            false,
+        )
+        .await
+        .unwrap();
+
+        assert!(!outcome.created);
+        // Duplicate filter is reported, new one is appended
+        assert!(outcome.filters[0].1.is_err());
+        assert!(outcome.filters[1].1.is_ok());
+        let categories = storage.get_chat_categories(chat_id).await.unwrap();
+        assert_eq!(categories["Food"], patterns(&["(?i)coffee", "(?i)tea"]));
+    }
+
+    #[tokio::test]
+    async fn test_import_category_refuses_uncategorized_name() {
+        let storage = CategoryStorage::new();
+        let chat_id = ChatId(1);
+
+        let outcome = import_category(
+            &storage,
+            chat_id,
+            &CommandAddCategory::new("Misc"),
+            "Misc",
+            false,
+        )
+        .await
+        .unwrap();
+        assert!(outcome.error.is_some());
+        assert!(!outcome.category_created);
+        assert!(
+            storage
+                .get_chat_categories(chat_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let outcome = import_category(
+            &storage,
+            chat_id,
+            &CommandAddCategory::new("Misc"),
+            "Other",
+            false,
+        )
+        .await
+        .unwrap();
+        assert!(outcome.category_created);
+    }
+}
@@ -1,42 +1,115 @@
 use std::sync::Arc;
 
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveTime, Utc};
 use teloxide::prelude::ResponseResult;
 use yoroolbot::{
     command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown::MarkdownString,
     markdown_format,
 };
 
-use crate::storages::ExpenseStorageTrait;
+use crate::{
+    commands::{
+        command_pending_expense::{CommandPendingExpense, pending_expense_action},
+        confirmation::confirmation_buttons,
+        expenses::format_timestamp_with_time,
+    },
+    storages::{Expense, StorageTrait},
+    utils::{amount::Amount, parse_expenses::parse_account},
+};
+
+/// Default maximum length of an expense description, in characters
+pub const DEFAULT_MAX_DESCRIPTION_LENGTH: usize = 200;
+
+/// The description cut to `max_length` characters ending with an ellipsis, None if it fits
+pub fn cut_description(description: &str, max_length: usize) -> Option<String> {
+    if description.chars().count() <= max_length {
+        return None;
+    }
+    let mut truncated: String = description
+        .chars()
+        .take(max_length.saturating_sub(1))
+        .collect();
+    truncated.push('…');
+    Some(truncated)
+}
 
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct CommandAddExpense {
     pub date: Option<NaiveDate>,
     pub description: Option<String>,
     pub amount: Option<f64>,
+    /// Time of day, midnight if not set
+    pub time: Option<NaiveTime>,
+    /// Author of the forwarded message, not part of the command string
+    pub author: Option<String>,
+    /// Currency detected next to the amount, not part of the command string
+    pub currency: Option<String>,
+    /// Length of the description before it was cut to the limit, not part of the command string
+    pub truncated_from: Option<usize>,
+}
+
+impl CommandAddExpense {
+    /// Cut the description to `max_length` characters ending with an ellipsis
+    /// Returns true if the description was too long
+    pub fn truncate_description(&mut self, max_length: usize) -> bool {
+        let Some(description) = &self.description else {
+            return false;
+        };
+        let Some(truncated) = cut_description(description, max_length) else {
+            return false;
+        };
+        self.truncated_from = Some(description.chars().count());
+        self.description = Some(truncated);
+        true
+    }
+
+    /// Warning line about the cut description, empty if it was not cut
+    fn truncation_warning(&self) -> MarkdownString {
+        match self.truncated_from {
+            Some(length) => markdown_format!(
+                "\n⚠️ Description of {} characters was cut to {}\\.",
+                length,
+                self.description
+                    .as_ref()
+                    .map_or(0, |description| description.chars().count())
+            ),
+            None => MarkdownString::new(),
+        }
+    }
+}
+
+/// Currency of the chat's expenses which differs from `currency`, if any
+/// Expenses without a currency are in the chat's usual one and never conflict
+fn conflicting_currency<'a>(expenses: &'a [Expense], currency: Option<&str>) -> Option<&'a str> {
+    let currency = currency?;
+    expenses
+        .iter()
+        .filter_map(|expense| expense.currency.as_deref())
+        .find(|other| *other != currency)
 }
 
 impl CommandTrait for CommandAddExpense {
     type A = NaiveDate; // date (required)
     type B = String; // description (required, with escaped spaces)
     type C = f64; // amount (required)
-    type D = EmptyArg;
+    type D = NaiveTime; // time of day (optional)
     type E = EmptyArg;
     type F = EmptyArg;
     type G = EmptyArg;
     type H = EmptyArg;
     type I = EmptyArg;
 
-    type Context = Arc<dyn ExpenseStorageTrait>;
+    type Context = Arc<dyn StorageTrait>;
 
     const NAME: &'static str = "add_expense";
-    const PLACEHOLDERS: &[&'static str] = &["<date>", "<description>", "<amount>"];
+    const PLACEHOLDERS: &[&'static str] = &["<date>", "<description>", "<amount>", "<time>"];
 
     fn from_arguments(
         a: Option<Self::A>,
         b: Option<Self::B>,
         c: Option<Self::C>,
-        _: Option<Self::D>,
+        d: Option<Self::D>,
         _: Option<Self::E>,
         _: Option<Self::F>,
         _: Option<Self::G>,
@@ -47,6 +120,10 @@ impl CommandTrait for CommandAddExpense {
             date: a,
             description: b,
             amount: c,
+            time: d,
+            author: None,
+            currency: None,
+            truncated_from: None,
         }
     }
 
@@ -62,19 +139,24 @@ impl CommandTrait for CommandAddExpense {
         self.amount.as_ref()
     }
 
+    fn param4(&self) -> Option<&Self::D> {
+        self.time.as_ref()
+    }
+
     async fn run0(
         &self,
         target: &CommandReplyTarget,
         _storage: Self::Context,
     ) -> ResponseResult<()> {
-        // Generate usage string dynamically
-        let usage = self.to_command_string(true);
-
         // Generate example commands dynamically
         let example1 = CommandAddExpense {
             date: Some(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()),
             description: Some("Coffee".to_string()),
             amount: Some(5.50),
+            time: None,
+            author: None,
+            currency: None,
+            truncated_from: None,
         }
         .to_command_string(false);
 
@@ -82,6 +164,10 @@ impl CommandTrait for CommandAddExpense {
             date: Some(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()),
             description: Some("My Lunch".to_string()),
             amount: Some(12.00),
+            time: None,
+            author: None,
+            currency: None,
+            truncated_from: None,
         }
         .to_command_string(false);
 
@@ -89,18 +175,22 @@ impl CommandTrait for CommandAddExpense {
             date: Some(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()),
             description: Some("Groceries".to_string()),
             amount: Some(45.30),
+            time: NaiveTime::from_hms_opt(14, 30, 0),
+            author: None,
+            currency: None,
+            truncated_from: None,
         }
         .to_command_string(false);
 
         target
             .send_markdown_message(markdown_format!(
-                "📝 Usage: `{}`\n\n\
+                "📝 {}\n\n\
                  Examples:\n\
                  • `{}`\n\
                  • `{}` \\(with escaped space\\)\n\
-                 • `{}`\n\n\
+                 • `{}` \\(with time of day\\)\n\n\
                  Note: Use backslash to escape spaces in description: `My\\\\ Lunch`",
-                usage,
+                @raw self.usage_hint(),
                 example1,
                 example2,
                 example3
@@ -115,11 +205,10 @@ impl CommandTrait for CommandAddExpense {
         _storage: Self::Context,
         _date: &NaiveDate,
     ) -> ResponseResult<()> {
-        let usage = self.to_command_string(true);
         target
             .send_markdown_message(markdown_format!(
-                "❌ Missing description and amount\\. Usage: `{}`",
-                usage
+                "❌ Missing description and amount\\. {}",
+                @raw self.usage_hint()
             ))
             .await?;
         Ok(())
@@ -132,9 +221,11 @@ impl CommandTrait for CommandAddExpense {
         _date: &NaiveDate,
         _description: &String,
     ) -> ResponseResult<()> {
-        let usage = self.to_command_string(true);
         target
-            .send_markdown_message(markdown_format!("❌ Missing amount\\. Usage: `{}`", usage))
+            .send_markdown_message(markdown_format!(
+                "❌ Missing amount\\. {}",
+                @raw self.usage_hint()
+            ))
             .await?;
         Ok(())
     }
@@ -147,22 +238,125 @@ impl CommandTrait for CommandAddExpense {
         description: &String,
         amount: &f64,
     ) -> ResponseResult<()> {
-        // Use provided date
-        let timestamp = date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp();
+        // Date-only expenses are stored at midnight
+        self.run4(target, storage, date, description, amount, &NaiveTime::MIN)
+            .await
+    }
+
+    async fn run4(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        date: &NaiveDate,
+        description: &String,
+        amount: &f64,
+        time: &NaiveTime,
+    ) -> ResponseResult<()> {
+        // `inf` and `NaN` parse as f64, but would saturate every total
+        if !amount.is_finite() {
+            target
+                .send_markdown_message(markdown_format!(
+                    "❌ Amount must be a number\\. {}",
+                    @raw self.usage_hint()
+                ))
+                .await?;
+            return Ok(());
+        }
+        // Totals add up amounts, so a chat keeps one currency
+        let chat_expenses = storage
+            .clone()
+            .as_expense_storage()
+            .get_chat_expenses(target.chat.id)
+            .await;
+        if let Some(chat_currency) = conflicting_currency(&chat_expenses, self.currency.as_deref())
+        {
+            target
+                .send_markdown_message(markdown_format!(
+                    "❌ Expenses of this chat are in `{}`, not `{}`\\. Totals can't mix currencies\\.",
+                    chat_currency,
+                    self.currency.clone().unwrap_or_default()
+                ))
+                .await?;
+            return Ok(());
+        }
+        let timestamp = date.and_time(*time).and_utc().timestamp();
+        let amount_text = match &self.currency {
+            Some(currency) => format!("{} {}", amount, currency),
+            None => amount.to_string(),
+        };
+
+        let settings = storage
+            .clone()
+            .as_settings_storage()
+            .get_chat_settings(target.chat.id)
+            .await;
+        let expense = Expense {
+            timestamp,
+            description: description.clone(),
+            amount: Amount::from(*amount),
+            author: self.author.clone(),
+            account: parse_account(description),
+            currency: self.currency.clone(),
+        };
+        // Single expenses wait for the Confirm button if the chat asked for it
+        if settings.confirm_expenses && !target.batch {
+            let id = storage
+                .clone()
+                .as_pending_expense_storage()
+                .add_pending_expense(target.chat.id, expense)
+                .await;
+            storage
+                .as_confirmation_storage()
+                .set_pending_confirmation(
+                    target.chat.id,
+                    &pending_expense_action(id),
+                    Utc::now().timestamp(),
+                )
+                .await;
+            let buttons = confirmation_buttons(
+                CommandPendingExpense {
+                    confirm: Some(true),
+                    id: Some(id),
+                }
+                .to_command_string(false),
+                CommandPendingExpense {
+                    confirm: Some(false),
+                    id: Some(id),
+                }
+                .to_command_string(false),
+            );
+            target
+                .markdown_message_with_menu(
+                    markdown_format!(
+                        "📝 Adding: {} {} on {}{}",
+                        description,
+                        amount_text,
+                        format_timestamp_with_time(timestamp),
+                        @raw self.truncation_warning()
+                    ),
+                    buttons,
+                )
+                .await?;
+            return Ok(());
+        }
 
         // Store the expense
         storage
-            .add_expense(target.chat.id, description, *amount, timestamp)
+            .as_expense_storage()
+            .add_expenses(target.chat.id, vec![expense])
             .await;
 
         if !target.batch {
             // Send confirmation message
             target
                 .send_markdown_message(markdown_format!(
-                    "✅ Expense added: {} {} {}",
-                    date.to_string(),
-                    description,
-                    amount.to_string()
+                    "{}{}",
+                    settings.expense_template.render(
+                        &format_timestamp_with_time(timestamp),
+                        description,
+                        &amount_text
+                    ),
+                    @raw self.truncation_warning()
                 ))
                 .await?;
         }
@@ -176,3 +370,28 @@ impl From<CommandAddExpense> for crate::commands::Command {
         crate::commands::Command::AddExpense(cmd)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expense(currency: Option<&str>) -> Expense {
+        Expense {
+            timestamp: 0,
+            description: "Lunch".to_string(),
+            amount: Amount::from(12.0),
+            currency: currency.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_conflicting_currency() {
+        let expenses = vec![expense(None), expense(Some("EUR"))];
+        assert_eq!(conflicting_currency(&expenses, Some("USD")), Some("EUR"));
+        assert_eq!(conflicting_currency(&expenses, Some("EUR")), None);
+        // No currency given, or none stored yet: the chat's usual currency
+        assert_eq!(conflicting_currency(&expenses, None), None);
+        assert_eq!(conflicting_currency(&[expense(None)], Some("USD")), None);
+    }
+}
@@ -1,12 +1,24 @@
 use std::sync::Arc;
 
-use teloxide::prelude::ResponseResult;
+use teloxide::{prelude::ResponseResult, types::ChatId};
 use yoroolbot::{
-    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg, NoopCommand},
+    markdown::MarkdownString,
     markdown_format,
 };
 
-use crate::{commands::command_add_words_filter::CommandAddWordsFilter, storages::StorageTrait};
+use crate::{
+    commands::{
+        ImportOutcome, command_add_words_filter::CommandAddWordsFilter,
+        command_remove_filter::CommandRemoveFilter,
+    },
+    menus::select_category::select_category,
+    storages::{CategoryStorageTrait, StorageTrait},
+    utils::{
+        filter_pattern::{compile_filter, find_equivalent_filter, unsupported_regex_feature},
+        fuzzy_match::did_you_mean,
+    },
+};
 
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct CommandAddFilter {
@@ -14,6 +26,117 @@ pub struct CommandAddFilter {
     pub pattern: Option<String>,
 }
 
+/// Warning that the filter `pattern` just added to the category matches the same as another
+/// of its filters, with the command removing it. None when it matches differently
+/// Exact duplicates are refused by the storage itself
+pub async fn equivalent_filter_warning(
+    storage: &dyn CategoryStorageTrait,
+    chat_id: ChatId,
+    category: &str,
+    pattern: &str,
+    case_insensitive: bool,
+) -> Option<MarkdownString> {
+    let categories = storage.get_chat_categories(chat_id).await.ok()?;
+    let patterns = categories.get(category)?;
+    let position = patterns.iter().position(|existing| existing == pattern)?;
+    let equivalent = find_equivalent_filter(patterns, pattern, case_insensitive)?;
+    Some(markdown_format!(
+        "Filter `{}` matches the same as filter `{}` in category `{}`\\. Remove it with `{}` if it's not needed\\.",
+        pattern,
+        equivalent,
+        category,
+        CommandRemoveFilter::new(Some(category.to_string()), Some(position))
+            .to_command_string(false)
+            .trim_end()
+    ))
+}
+
+/// Append the equivalent filter warning to the reply on adding `pattern` to the category,
+/// with the chat's case-insensitivity setting
+pub async fn with_equivalent_filter_warning(
+    storage: &Arc<dyn StorageTrait>,
+    chat_id: ChatId,
+    category: &str,
+    pattern: &str,
+    message: MarkdownString,
+) -> MarkdownString {
+    let settings = storage
+        .clone()
+        .as_settings_storage()
+        .get_chat_settings(chat_id)
+        .await;
+    match equivalent_filter_warning(
+        &*storage.clone().as_category_storage(),
+        chat_id,
+        category,
+        pattern,
+        settings.case_insensitive_filters,
+    )
+    .await
+    {
+        Some(warning) => message + markdown_format!("\n⚠️ {}", @raw warning),
+        None => message,
+    }
+}
+
+/// Apply the command as part of a batch import without replying
+/// Returns None when the category or the pattern is missing, such a command shows a menu instead
+pub async fn import_filter(
+    storage: &dyn CategoryStorageTrait,
+    chat_id: ChatId,
+    command: &CommandAddFilter,
+    case_insensitive: bool,
+) -> Option<ImportOutcome> {
+    let (Some(category), Some(pattern)) = (&command.category, &command.pattern) else {
+        return None;
+    };
+    let categories = storage
+        .get_chat_categories(chat_id)
+        .await
+        .unwrap_or_default();
+    let Some(patterns) = categories.get(category) else {
+        return Some(ImportOutcome {
+            error: Some(markdown_format!("Category `{}` does not exist", category)),
+            ..Default::default()
+        });
+    };
+    if compile_filter(pattern, false).is_err() {
+        return Some(ImportOutcome {
+            invalid_filters: 1,
+            ..Default::default()
+        });
+    }
+    // Filters already present are expected when importing the same set again
+    if patterns.contains(pattern) {
+        return Some(ImportOutcome::default());
+    }
+    Some(
+        match storage
+            .add_category_filter(chat_id, category.clone(), pattern.clone())
+            .await
+        {
+            Ok(()) => ImportOutcome {
+                filters_added: 1,
+                warnings: equivalent_filter_warning(
+                    storage,
+                    chat_id,
+                    category,
+                    pattern,
+                    case_insensitive,
+                )
+                .await
+                .into_iter()
+                .collect(),
+                ..Default::default()
+            },
+            Err(err_msg) => ImportOutcome {
+                error: Some(err_msg),
+                ..Default::default()
+            },
+        },
+    )
+}
+
 impl CommandTrait for CommandAddFilter {
     type A = String;
     type B = String;
@@ -58,7 +181,21 @@ impl CommandTrait for CommandAddFilter {
         target: &CommandReplyTarget,
         storage: Self::Context,
     ) -> ResponseResult<()> {
-        CommandAddWordsFilter::default().run(target, storage).await
+        select_category(
+            target,
+            &storage.as_category_storage(),
+            markdown_format!(
+                "➕ Select Category to add filter\n{}",
+                @raw self.usage_hint()
+            ),
+            |name| CommandAddWordsFilter {
+                category: Some(name.to_string()),
+                page: Some(0),
+                words: None,
+            },
+            None::<NoopCommand>,
+        )
+        .await
     }
 
     async fn run1(
@@ -83,22 +220,118 @@ impl CommandTrait for CommandAddFilter {
         category: &String,
         pattern: &String,
     ) -> ResponseResult<()> {
-        let storage = storage.as_category_storage();
+        let category_storage = storage.clone().as_category_storage();
 
-        if let Err(msg) = storage
+        let categories = category_storage
+            .get_chat_categories(target.chat.id)
+            .await
+            .unwrap_or_default();
+        if !categories.contains_key(category) {
+            target
+                .send_markdown_message(markdown_format!(
+                    "❌ Category `{}` does not exist\\.{}",
+                    category,
+                    @raw did_you_mean(category, categories.keys())
+                ))
+                .await?;
+            return Ok(());
+        }
+
+        if let Err(e) = compile_filter(pattern, false) {
+            let message = match unsupported_regex_feature(pattern, &e) {
+                Some(feature) => {
+                    markdown_format!("❌ Filter `{}` is not added\\. {}", pattern, feature.hint())
+                }
+                None => markdown_format!(
+                    "❌ Invalid regex pattern `{}`:\n{}",
+                    pattern,
+                    @code e.to_string()
+                ),
+            };
+            target.send_markdown_message(message).await?;
+            return Ok(());
+        }
+
+        if let Err(msg) = category_storage
             .add_category_filter(target.chat.id, category.clone(), pattern.clone())
             .await
         {
             target.send_markdown_message(msg).await?;
             return Ok(());
         };
-        target
-            .send_markdown_message(markdown_format!(
+        let message = with_equivalent_filter_warning(
+            &storage,
+            target.chat.id,
+            category,
+            pattern,
+            markdown_format!(
                 "✅ Filter `{}` added to category `{}`\\.",
                 pattern,
                 category
-            ))
-            .await?;
+            ),
+        )
+        .await;
+        target.send_markdown_message(message).await?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storages::CategoryStorage;
+
+    #[tokio::test]
+    async fn test_import_filter_warns_about_equivalent_filter() {
+        let storage = CategoryStorage::new();
+        let chat_id = ChatId(1);
+        storage
+            .add_category(chat_id, "Food".to_string())
+            .await
+            .unwrap();
+        let import = |pattern: &str, case_insensitive| {
+            let command = CommandAddFilter {
+                category: Some("Food".to_string()),
+                pattern: Some(pattern.to_string()),
+            };
+            let storage = &storage;
+            async move {
+                import_filter(storage, chat_id, &command, case_insensitive)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        assert!(import("(?i)coffee", false).await.warnings.is_empty());
+        // Case-sensitive filters differing only in case are distinct
+        assert!(import("Tea", false).await.warnings.is_empty());
+        assert!(import("tea", false).await.warnings.is_empty());
+
+        let outcome = import("(?i)Coffee", false).await;
+        assert_eq!(outcome.filters_added, 1);
+        assert_eq!(
+            outcome.warnings[0].as_str(),
+            r"Filter `\(?i\)Coffee` matches the same as filter `\(?i\)coffee` in category `Food`\. Remove it with `/remove\_filter Food 3` if it's not needed\."
+        );
+
+        // With case-insensitive filters for the chat, case no longer matters
+        assert_eq!(import("TEA", true).await.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_add_filter_usage_hint() {
+        assert_eq!(
+            CommandAddFilter::default().usage_hint().as_str(),
+            r"Usage: `/add\_filter <category\> <pattern\>`"
+        );
+        let cmd = CommandAddFilter {
+            category: Some("Food & Drinks".to_string()),
+            pattern: None,
+        };
+        // Spaces are screened so the hint can be copied as is
+        assert_eq!(
+            cmd.usage_hint().as_str(),
+            r"Usage: `/add\_filter Food\\ &\\ Drinks <pattern\>`"
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use std::sync::Arc;
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg, NoopCommand},
+    markdown_format,
+};
+
+use crate::{
+    commands::command_add_filter::with_equivalent_filter_warning,
+    menus::select_category::select_category,
+    storages::StorageTrait,
+    utils::{filter_pattern::literal_filter, fuzzy_match::did_you_mean},
+};
+
+/// Add a filter matching descriptions which contain the text, ignoring case
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandAddLiteralFilter {
+    pub category: Option<String>,
+    pub text: Option<String>,
+}
+
+impl CommandTrait for CommandAddLiteralFilter {
+    type A = String;
+    type B = String;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "add_literal_filter";
+    const PLACEHOLDERS: &[&'static str] = &["<category>", "<text>"];
+
+    fn from_arguments(
+        category: Option<Self::A>,
+        text: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandAddLiteralFilter { category, text }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.category.as_ref()
+    }
+
+    fn param2(&self) -> Option<&Self::B> {
+        self.text.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        select_category(
+            target,
+            &storage.as_category_storage(),
+            markdown_format!(
+                "➕ Select Category to add text filter\n{}",
+                @raw self.usage_hint()
+            ),
+            |name| CommandAddLiteralFilter {
+                category: Some(name.to_string()),
+                text: None,
+            },
+            None::<NoopCommand>,
+        )
+        .await
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        _storage: Self::Context,
+        _category: &String,
+    ) -> ResponseResult<()> {
+        target
+            .send_markdown_message(markdown_format!(
+                "✏️ Send the text to match in expense descriptions\n{}",
+                @raw self.usage_hint()
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn run2(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        category: &String,
+        text: &String,
+    ) -> ResponseResult<()> {
+        let category_storage = storage.clone().as_category_storage();
+        let categories = category_storage
+            .get_chat_categories(target.chat.id)
+            .await
+            .unwrap_or_default();
+        if !categories.contains_key(category) {
+            target
+                .send_markdown_message(markdown_format!(
+                    "❌ Category `{}` does not exist\\.{}",
+                    category,
+                    @raw did_you_mean(category, categories.keys())
+                ))
+                .await?;
+            return Ok(());
+        }
+
+        let pattern = literal_filter(text);
+        if let Err(msg) = category_storage
+            .add_category_filter(target.chat.id, category.clone(), pattern.clone())
+            .await
+        {
+            target.send_markdown_message(msg).await?;
+            return Ok(());
+        };
+        let message = with_equivalent_filter_warning(
+            &storage,
+            target.chat.id,
+            category,
+            &pattern,
+            markdown_format!(
+                "✅ Text filter `{}` added to category `{}`\\.",
+                text,
+                category
+            ),
+        )
+        .await;
+        target.send_markdown_message(message).await?;
+        Ok(())
+    }
+}
+
+impl From<CommandAddLiteralFilter> for crate::commands::Command {
+    fn from(cmd: CommandAddLiteralFilter) -> Self {
+        crate::commands::Command::AddLiteralFilter(cmd)
+    }
+}
@@ -7,7 +7,9 @@ use yoroolbot::{
 };
 
 use crate::{
-    commands::command_add_filter::CommandAddFilter,
+    commands::{
+        command_add_filter::CommandAddFilter, command_preview_filter::CommandPreviewFilter,
+    },
     menus::{
         select_category::select_category,
         select_word::{Words, select_word},
@@ -130,9 +132,14 @@ impl CommandTrait for CommandAddWordsFilter {
             .get_chat_categories(target.chat.id)
             .await
             .unwrap_or_default();
+        let settings = storage
+            .clone()
+            .as_settings_storage()
+            .get_chat_settings(target.chat.id)
+            .await;
 
         // Extract words from uncategorized expenses
-        let words = extract_words(&expenses, &categories);
+        let words = extract_words(&expenses, &categories, settings.case_insensitive_filters);
 
         if words.is_empty() {
             target
@@ -190,6 +197,15 @@ impl CommandTrait for CommandAddWordsFilter {
                 category: Some(category.clone()),
                 pattern: selected_words.build_pattern(),
             },
+            CommandAddFilter {
+                category: Some(category.clone()),
+                pattern: selected_words.build_substring_pattern(),
+            },
+            CommandPreviewFilter {
+                category: Some(category.clone()),
+                pattern: selected_words.build_pattern(),
+                position: None,
+            },
             Some(CommandAddWordsFilter {
                 category: None,
                 page: None,
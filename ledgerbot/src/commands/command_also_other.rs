@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format,
+};
+
+use crate::storages::StorageTrait;
+
+/// Also list the expenses of a category in the uncategorized bucket, without counting them there
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandAlsoOther {
+    pub category: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+impl CommandTrait for CommandAlsoOther {
+    type A = String;
+    type B = bool;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "also_other";
+    const PLACEHOLDERS: &[&'static str] = &["<category>", "<enabled>"];
+
+    fn from_arguments(
+        category: Option<Self::A>,
+        enabled: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandAlsoOther { category, enabled }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.category.as_ref()
+    }
+
+    fn param2(&self) -> Option<&Self::B> {
+        self.enabled.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let settings = storage
+            .clone()
+            .as_settings_storage()
+            .get_chat_settings(target.chat.id)
+            .await;
+        let also_other = storage
+            .as_category_storage()
+            .get_also_other_categories(target.chat.id)
+            .await;
+        let categories = also_other
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let message = if categories.is_empty() {
+            markdown_format!(
+                "📂 No categories are also listed in `{}`\\.\n{}",
+                settings.uncategorized_name(),
+                @raw self.usage_hint()
+            )
+        } else {
+            markdown_format!(
+                "📂 Also listed in `{}`: {}\n{}",
+                settings.uncategorized_name(),
+                categories,
+                @raw self.usage_hint()
+            )
+        };
+        target.send_markdown_message(message).await?;
+        Ok(())
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        category: &String,
+    ) -> ResponseResult<()> {
+        let settings = storage
+            .clone()
+            .as_settings_storage()
+            .get_chat_settings(target.chat.id)
+            .await;
+        let also_other = storage
+            .as_category_storage()
+            .get_also_other_categories(target.chat.id)
+            .await;
+        target
+            .send_markdown_message(markdown_format!(
+                "📂 Expenses of `{}` are {}listed in `{}`\\.\n{}",
+                category,
+                if also_other.contains(category) { "also " } else { "not " },
+                settings.uncategorized_name(),
+                @raw self.usage_hint()
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn run2(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        category: &String,
+        enabled: &bool,
+    ) -> ResponseResult<()> {
+        if let Err(e) = storage
+            .clone()
+            .as_category_storage()
+            .set_category_also_other(target.chat.id, category, *enabled)
+            .await
+        {
+            target.send_markdown_message(e).await?;
+            return Ok(());
+        }
+        let settings = storage
+            .as_settings_storage()
+            .get_chat_settings(target.chat.id)
+            .await;
+        let uncategorized_name = settings.uncategorized_name();
+        target
+            .send_markdown_message(markdown_format!(
+                "✅ Expenses of `{}` will {}be listed in `{}`\\.",
+                category,
+                if *enabled { "also " } else { "no longer " },
+                uncategorized_name
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandAlsoOther> for crate::commands::Command {
+    fn from(cmd: CommandAlsoOther) -> Self {
+        crate::commands::Command::AlsoOther(cmd)
+    }
+}
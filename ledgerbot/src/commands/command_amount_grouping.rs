@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format,
+    storage::ButtonData,
+};
+
+use crate::{
+    storages::SettingsStorageTrait,
+    utils::format_amount::{AmountStyle, format_amount_localized},
+};
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandAmountGrouping {
+    pub enabled: Option<bool>,
+}
+
+impl CommandTrait for CommandAmountGrouping {
+    type A = bool;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn SettingsStorageTrait>;
+
+    const NAME: &'static str = "amount_grouping";
+    const PLACEHOLDERS: &[&'static str] = &["<enabled>"];
+
+    fn from_arguments(
+        enabled: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandAmountGrouping { enabled }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.enabled.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let settings = storage.get_chat_settings(target.chat.id).await;
+        let buttons = [true, false].map(|enabled| {
+            ButtonData::Callback(
+                format_amount_localized(
+                    12345.67,
+                    2,
+                    AmountStyle {
+                        grouping: enabled,
+                        ..settings.amount_style()
+                    },
+                ),
+                CommandAmountGrouping {
+                    enabled: Some(enabled),
+                }
+                .to_command_string(false),
+            )
+        });
+        target
+            .markdown_message_with_menu(
+                markdown_format!(
+                    "🔢 Amounts in reports are shown as `{}`\\. Select format:",
+                    format_amount_localized(12345.67, 2, settings.amount_style())
+                ),
+                vec![buttons.to_vec()],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        enabled: &bool,
+    ) -> ResponseResult<()> {
+        let mut settings = storage.get_chat_settings(target.chat.id).await;
+        settings.amount_grouping = *enabled;
+        let amount_style = settings.amount_style();
+        storage.set_chat_settings(target.chat.id, settings).await;
+        target
+            .markdown_message(markdown_format!(
+                "✅ Amounts in reports will be shown as `{}`\\.",
+                format_amount_localized(12345.67, 2, amount_style)
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandAmountGrouping> for crate::commands::Command {
+    fn from(cmd: CommandAmountGrouping) -> Self {
+        crate::commands::Command::AmountGrouping(cmd)
+    }
+}
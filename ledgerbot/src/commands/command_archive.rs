@@ -0,0 +1,167 @@
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use teloxide::{prelude::ResponseResult, utils::command::ParseError};
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format,
+};
+
+use crate::{commands::command_unarchive::CommandUnarchive, storages::StorageTrait};
+
+/// Keyword of `/archive before <date>`, leaving room for other ways to select expenses
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveSelector {
+    /// Expenses dated before the given day
+    #[default]
+    Before,
+}
+
+impl Display for ArchiveSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArchiveSelector::Before => write!(f, "before"),
+        }
+    }
+}
+
+impl FromStr for ArchiveSelector {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "before" => Ok(ArchiveSelector::Before),
+            _ => Err(ParseError::IncorrectFormat(
+                format!("Unknown selector `{}`, expected `before`", s).into(),
+            )),
+        }
+    }
+}
+
+/// Move expenses older than a date to the archive, out of the active list and reports
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandArchive {
+    pub selector: Option<ArchiveSelector>,
+    pub date: Option<NaiveDate>,
+}
+
+impl CommandTrait for CommandArchive {
+    type A = ArchiveSelector;
+    type B = NaiveDate;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "archive";
+    const PLACEHOLDERS: &[&'static str] = &["before", "<YYYY-MM-DD>"];
+
+    fn from_arguments(
+        selector: Option<Self::A>,
+        date: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandArchive { selector, date }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.selector.as_ref()
+    }
+
+    fn param2(&self) -> Option<&Self::B> {
+        self.date.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let archived = storage
+            .as_archive_storage()
+            .get_archived_expenses(target.chat.id)
+            .await;
+        target
+            .send_markdown_message(markdown_format!(
+                "🗄 {} expenses are archived\\.\n{}",
+                archived.len(),
+                @raw self.usage_hint()
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        _selector: &ArchiveSelector,
+    ) -> ResponseResult<()> {
+        self.run0(target, storage).await
+    }
+
+    async fn run2(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        _selector: &ArchiveSelector,
+        date: &NaiveDate,
+    ) -> ResponseResult<()> {
+        let chat_id = target.chat.id;
+        let before = Utc
+            .from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+            .timestamp();
+        let archived = storage
+            .clone()
+            .as_expense_storage()
+            .take_expenses_before(chat_id, before)
+            .await;
+        let count = archived.len();
+        if count > 0 {
+            storage
+                .as_archive_storage()
+                .add_archived_expenses(chat_id, archived)
+                .await;
+        }
+        target
+            .send_markdown_message(markdown_format!(
+                "🗄 Archived {} expenses dated before {}\\. Use {} to restore them\\.",
+                count,
+                date.format("%Y-%m-%d").to_string(),
+                CommandUnarchive.to_command_string(true)
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandArchive> for crate::commands::Command {
+    fn from(cmd: CommandArchive) -> Self {
+        crate::commands::Command::Archive(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_archive_arguments() {
+        let (command,) = CommandArchive::parse_arguments("before 2021-01-01".to_string()).unwrap();
+        assert_eq!(command.selector, Some(ArchiveSelector::Before));
+        assert_eq!(command.date, NaiveDate::from_ymd_opt(2021, 1, 1));
+        assert!(CommandArchive::parse_arguments("after 2021-01-01".to_string()).is_err());
+        assert!(CommandArchive::parse_arguments("before 01.01.2021".to_string()).is_err());
+    }
+}
@@ -0,0 +1,143 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, TimeZone, Utc};
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format, markdown_string,
+};
+
+use crate::storages::{AuditEntry, AuditStorageTrait};
+
+/// Number of entries shown when no count is given
+const DEFAULT_AUDIT_COUNT: usize = 10;
+/// Upper bound for the count to keep the reply within one message
+const MAX_AUDIT_COUNT: usize = 50;
+/// Longer commands are cut in the listing
+const MAX_COMMAND_CHARS: usize = 60;
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandAudit {
+    pub count: Option<usize>,
+}
+
+impl CommandTrait for CommandAudit {
+    type A = usize;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn AuditStorageTrait>;
+
+    const NAME: &'static str = "audit";
+    const PLACEHOLDERS: &[&'static str] = &["<count>"];
+
+    fn from_arguments(
+        count: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandAudit { count }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.count.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        self.run1(target, storage, &DEFAULT_AUDIT_COUNT).await
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        count: &usize,
+    ) -> ResponseResult<()> {
+        let count = (*count).clamp(1, MAX_AUDIT_COUNT);
+        let entries = storage.get_last_audit_entries(target.chat.id, count).await;
+        if entries.is_empty() {
+            target
+                .send_markdown_message(markdown_string!("📜 Audit log is empty\\."))
+                .await?;
+            return Ok(());
+        }
+        let table = format_audit_entries(&entries);
+        target
+            .send_markdown_message(markdown_format!(
+                "📜 Last {} command\\(s\\):\n{}",
+                entries.len(),
+                @code table
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+/// Format audit entries one per line as `date time user command`
+pub fn format_audit_entries(entries: &[AuditEntry]) -> String {
+    entries
+        .iter()
+        .map(|entry| {
+            let datetime: DateTime<Utc> = Utc.timestamp_opt(entry.timestamp, 0).unwrap();
+            let user = entry
+                .user_id
+                .map(|id| id.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let mut command: String = entry.command.chars().take(MAX_COMMAND_CHARS).collect();
+            if command.len() < entry.command.len() {
+                command.push('…');
+            }
+            format!("{} {} {}", datetime.format("%Y-%m-%d %H:%M"), user, command)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl From<CommandAudit> for crate::commands::Command {
+    fn from(cmd: CommandAudit) -> Self {
+        crate::commands::Command::Audit(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_audit_entries() {
+        let entries = vec![
+            AuditEntry {
+                timestamp: 1609459200,
+                user_id: Some(42),
+                command: "/report Food".to_string(),
+            },
+            AuditEntry {
+                timestamp: 1609462800,
+                user_id: None,
+                command: format!("/add_category {}", "x".repeat(100)),
+            },
+        ];
+        let text = format_audit_entries(&entries);
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines[0], "2021-01-01 00:00 42 /report Food");
+        assert!(lines[1].starts_with("2021-01-01 01:00 - /add_category x"));
+        assert!(lines[1].ends_with('…'));
+        assert_eq!(lines[1].chars().count(), "2021-01-01 01:00 - ".len() + 61);
+    }
+}
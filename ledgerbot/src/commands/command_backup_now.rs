@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format, markdown_string,
+};
+
+use crate::storages::StorageTrait;
+
+/// Write the chat's data to persistent storage immediately
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandBackupNow;
+
+impl CommandTrait for CommandBackupNow {
+    type A = EmptyArg;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "backup_now";
+    const PLACEHOLDERS: &[&'static str] = &[];
+
+    fn from_arguments(
+        _: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandBackupNow
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let chat_id = target.chat.id;
+        let result = async {
+            storage.clone().as_expense_storage().flush(chat_id).await?;
+            storage.clone().as_category_storage().flush(chat_id).await?;
+            storage.clone().as_settings_storage().flush(chat_id).await?;
+            storage
+                .clone()
+                .as_filter_stats_storage()
+                .flush(chat_id)
+                .await?;
+            storage.clone().as_audit_storage().flush(chat_id).await
+        }
+        .await;
+        match result {
+            Ok(()) => {
+                target
+                    .send_markdown_message(markdown_string!("💾 Chat data saved\\."))
+                    .await?;
+            }
+            Err(e) => {
+                target
+                    .send_markdown_message(markdown_format!("❌ Failed to save chat data: {}", e))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<CommandBackupNow> for crate::commands::Command {
+    fn from(cmd: CommandBackupNow) -> Self {
+        crate::commands::Command::BackupNow(cmd)
+    }
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format,
+};
+
+use crate::storages::SettingsStorageTrait;
+
+/// Make regex filters of the chat ignore case without the `(?i)` prefix
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandCaseInsensitive {
+    pub enabled: Option<bool>,
+}
+
+/// Describe whether filters ignore case
+fn case_mode(enabled: bool) -> &'static str {
+    if enabled {
+        "ignoring case"
+    } else {
+        "case-sensitively"
+    }
+}
+
+impl CommandTrait for CommandCaseInsensitive {
+    type A = bool;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn SettingsStorageTrait>;
+
+    const NAME: &'static str = "case_insensitive";
+    const PLACEHOLDERS: &[&'static str] = &["<enabled>"];
+
+    fn from_arguments(
+        enabled: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandCaseInsensitive { enabled }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.enabled.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let settings = storage.get_chat_settings(target.chat.id).await;
+        target
+            .send_markdown_message(markdown_format!(
+                "🔤 Filters are matched {}\\.\n{}",
+                case_mode(settings.case_insensitive_filters),
+                @raw self.usage_hint()
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        enabled: &bool,
+    ) -> ResponseResult<()> {
+        let mut settings = storage.get_chat_settings(target.chat.id).await;
+        settings.case_insensitive_filters = *enabled;
+        storage.set_chat_settings(target.chat.id, settings).await;
+        target
+            .send_markdown_message(markdown_format!(
+                "✅ Filters will be matched {}\\.",
+                case_mode(*enabled)
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandCaseInsensitive> for crate::commands::Command {
+    fn from(cmd: CommandCaseInsensitive) -> Self {
+        crate::commands::Command::CaseInsensitive(cmd)
+    }
+}
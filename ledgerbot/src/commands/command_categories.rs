@@ -1,6 +1,6 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
-use teloxide::prelude::{Requester, ResponseResult};
+use teloxide::prelude::ResponseResult;
 use yoroolbot::{
     command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
     markdown_format,
@@ -9,6 +9,7 @@ use yoroolbot::{
 use crate::{
     commands::{command_add_category::CommandAddCategory, command_add_filter::CommandAddFilter},
     storages::CategoryStorageTrait,
+    utils::parse_expenses::COMMENT_PREFIX,
 };
 
 #[derive(Default, Debug, Clone, PartialEq)]
@@ -63,39 +64,104 @@ impl CommandTrait for CommandCategories {
                 ))
                 .await?;
         } else {
-            let mut result = String::new();
-
-            // Sort categories for consistent output
-            let mut sorted_categories: Vec<_> = categories.iter().collect();
-            sorted_categories.sort_by(|a, b| a.0.cmp(b.0));
-
-            for (name, patterns) in sorted_categories {
-                // First create the category
-                result.push_str(&CommandAddCategory::new(name).to_command_string(true));
-                result.push('\n');
-
-                // Then assign patterns if they exist
-                for pattern in patterns {
-                    result.push_str(
-                        CommandAddFilter {
-                            category: Some(name.clone()),
-                            pattern: Some(pattern.clone()),
-                        }
-                        .to_command_string(true)
-                        .as_str(),
-                    );
-                    result.push('\n');
-                }
-            }
-            target.bot.send_message(chat_id, result).await?;
+            target
+                .send_markdown_message(markdown_format!(
+                    "{}",
+                    format_categories_listing(&categories)
+                ))
+                .await?;
         }
 
         Ok(())
     }
 }
 
+/// Format categories as commands re-creating them, followed by a summary comment line
+/// The summary line is skipped by `parse_expenses`, so the output can be sent back as is
+pub fn format_categories_listing(categories: &HashMap<String, Vec<String>>) -> String {
+    let mut result = String::new();
+
+    // Sort categories for consistent output
+    let mut sorted_categories: Vec<_> = categories.iter().collect();
+    sorted_categories.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (name, patterns) in sorted_categories {
+        // First create the category
+        result.push_str(&CommandAddCategory::new(name).to_command_string(true));
+        result.push('\n');
+
+        // Then assign patterns if they exist
+        for pattern in patterns {
+            result.push_str(
+                CommandAddFilter {
+                    category: Some(name.clone()),
+                    pattern: Some(pattern.clone()),
+                }
+                .to_command_string(true)
+                .as_str(),
+            );
+            result.push('\n');
+        }
+    }
+
+    let filter_count: usize = categories.values().map(Vec::len).sum();
+    result.push_str(&format!(
+        "{} {} categories, {} filters total",
+        COMMENT_PREFIX,
+        categories.len(),
+        filter_count
+    ));
+    result
+}
+
 impl From<CommandCategories> for crate::commands::Command {
     fn from(cmd: CommandCategories) -> Self {
         crate::commands::Command::Categories(cmd)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{commands::Command, utils::parse_expenses::parse_expenses};
+
+    #[test]
+    fn test_categories_listing_footer_and_reimport() {
+        let mut categories = HashMap::new();
+        categories.insert(
+            "Food".to_string(),
+            vec!["(?i)coffee".to_string(), "(?i)tea".to_string()],
+        );
+        categories.insert("Car".to_string(), vec!["(?i)fuel".to_string()]);
+        categories.insert("Eating Out".to_string(), vec![]);
+
+        let listing = format_categories_listing(&categories);
+        assert_eq!(
+            listing.lines().last(),
+            Some("# 3 categories, 3 filters total")
+        );
+
+        // The footer is not parsed as an expense, only the commands remain
+        let parsed = parse_expenses(&listing, None, 0, false, false);
+        let parsed: Vec<Command> = parsed
+            .into_iter()
+            .map(|(_, result)| result.unwrap())
+            .collect();
+        assert_eq!(parsed.len(), 6);
+        assert_eq!(
+            parsed[0],
+            Command::AddCategory(CommandAddCategory::new("Car"))
+        );
+        assert_eq!(
+            parsed[2],
+            Command::AddCategory(CommandAddCategory::new("Eating Out"))
+        );
+        assert_eq!(
+            parsed[5],
+            Command::AddFilter(CommandAddFilter {
+                category: Some("Food".to_string()),
+                pattern: Some("(?i)tea".to_string()),
+            })
+        );
+    }
+}
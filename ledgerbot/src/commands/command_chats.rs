@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown::MarkdownString,
+    markdown_format, markdown_string,
+};
+
+use crate::storages::CategoryStorageTrait;
+
+/// List the chats known to the category storage, for the bot administrator
+/// Refused for other users by the message handlers, see `Args::is_admin`
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandChats;
+
+impl CommandTrait for CommandChats {
+    type A = EmptyArg;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn CategoryStorageTrait>;
+
+    const NAME: &'static str = "chats";
+    const PLACEHOLDERS: &[&'static str] = &[];
+
+    fn from_arguments(
+        _: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandChats
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let chats = storage.get_known_chats().await;
+        if chats.is_empty() {
+            target
+                .send_markdown_message(markdown_string!("🗂️ No chats with categories\\."))
+                .await?;
+            return Ok(());
+        }
+        let mut lines = Vec::new();
+        for chat_id in &chats {
+            let line = match storage.get_chat_categories(*chat_id).await {
+                Ok(categories) => format!(
+                    "{}: {} categories, {} filters",
+                    chat_id,
+                    categories.len(),
+                    categories.values().map(Vec::len).sum::<usize>()
+                ),
+                Err(e) => format!("{}: {}", chat_id, e.to_plain_text()),
+            };
+            lines.push(line);
+        }
+        target
+            .send_markdown_message(markdown_format!(
+                "🗂️ {} chat\\(s\\) with categories:\n{}",
+                chats.len(),
+                @raw MarkdownString::code_block_from_lines(&lines)
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandChats> for crate::commands::Command {
+    fn from(cmd: CommandChats) -> Self {
+        crate::commands::Command::Chats(cmd)
+    }
+}
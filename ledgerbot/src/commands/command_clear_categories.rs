@@ -1,13 +1,17 @@
 use std::{collections::HashMap, sync::Arc};
 
-use teloxide::prelude::ResponseResult;
+use chrono::Utc;
+use teloxide::{prelude::ResponseResult, types::ChatId};
 use yoroolbot::{
     command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
-    markdown_string,
-    storage::ButtonData,
+    markdown::MarkdownString,
+    markdown_format, markdown_string,
 };
 
-use crate::storages::CategoryStorageTrait;
+use crate::{
+    commands::confirmation::{ConfirmationOutcome, confirmation_buttons, resolve_confirmation},
+    storages::StorageTrait,
+};
 
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct CommandClearCategories {
@@ -25,7 +29,7 @@ impl CommandTrait for CommandClearCategories {
     type H = EmptyArg;
     type I = EmptyArg;
 
-    type Context = Arc<dyn CategoryStorageTrait>;
+    type Context = Arc<dyn StorageTrait>;
 
     const NAME: &'static str = "clear_categories";
     const PLACEHOLDERS: &[&'static str] = &["<confirm>"];
@@ -51,18 +55,25 @@ impl CommandTrait for CommandClearCategories {
     async fn run0(
         &self,
         target: &CommandReplyTarget,
-        _storage: Self::Context,
+        storage: Self::Context,
     ) -> ResponseResult<()> {
-        // Show confirmation prompt with buttons
-        let message = markdown_string!("🗑️ Confirm clearing all categories\\?");
+        // Remember the request, only the Confirm button of this prompt clears
+        storage
+            .as_confirmation_storage()
+            .set_pending_confirmation(target.chat.id, Self::NAME, Utc::now().timestamp())
+            .await;
 
-        let buttons = vec![vec![ButtonData::SwitchInlineQuery(
-            "✅ Yes, Clear All".to_string(),
+        let message = markdown_string!("🗑️ Confirm clearing all categories\\?");
+        let buttons = confirmation_buttons(
             CommandClearCategories {
                 confirm: Some(true),
             }
             .to_command_string(false),
-        )]];
+            CommandClearCategories {
+                confirm: Some(false),
+            }
+            .to_command_string(false),
+        );
 
         target.markdown_message_with_menu(message, buttons).await?;
         Ok(())
@@ -74,26 +85,54 @@ impl CommandTrait for CommandClearCategories {
         storage: Self::Context,
         confirm: &bool,
     ) -> ResponseResult<()> {
-        if !*confirm {
-            target
-                .send_markdown_message(markdown_string!("❌ Clear categories cancelled\\."))
-                .await?;
-            return Ok(());
-        }
-
-        if let Err(e) = storage
-            .replace_categories(target.chat.id, HashMap::new())
-            .await
-        {
-            target.send_markdown_message(e).await?;
-            return Ok(());
-        }
+        let outcome = clear_categories_if_confirmed(
+            storage,
+            target.chat.id,
+            *confirm,
+            target.callback_query_id.is_some(),
+            Utc::now().timestamp(),
+        )
+        .await;
+        let message = match outcome {
+            Ok(ConfirmationOutcome::Confirmed) => markdown_string!("🗑️ All categories cleared\\!"),
+            Ok(ConfirmationOutcome::Cancelled) => {
+                markdown_string!("❌ Clear categories cancelled\\.")
+            }
+            Ok(ConfirmationOutcome::NotConfirmed) => markdown_format!(
+                "⚠️ Nothing cleared\\. Use {} and press Confirm\\.",
+                CommandClearCategories::default().to_command_string(true)
+            ),
+            Err(e) => e,
+        };
+        target.send_markdown_message(message).await?;
+        Ok(())
+    }
+}
 
-        target
-            .send_markdown_message(markdown_string!("🗑️ All categories cleared\\!"))
+/// Clear the chat's categories if the answer confirms a pending request
+pub async fn clear_categories_if_confirmed(
+    storage: Arc<dyn StorageTrait>,
+    chat_id: ChatId,
+    confirm: bool,
+    from_callback: bool,
+    now: i64,
+) -> Result<ConfirmationOutcome, MarkdownString> {
+    let outcome = resolve_confirmation(
+        storage.clone().as_confirmation_storage().as_ref(),
+        chat_id,
+        CommandClearCategories::NAME,
+        confirm,
+        from_callback,
+        now,
+    )
+    .await;
+    if outcome == ConfirmationOutcome::Confirmed {
+        storage
+            .as_category_storage()
+            .replace_categories(chat_id, HashMap::new())
             .await?;
-        Ok(())
     }
+    Ok(outcome)
 }
 
 impl From<CommandClearCategories> for crate::commands::Command {
@@ -101,3 +140,51 @@ impl From<CommandClearCategories> for crate::commands::Command {
         crate::commands::Command::ClearCategories(cmd)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storages::Storage;
+
+    #[tokio::test]
+    async fn test_clear_categories_confirm_and_unconfirmed() {
+        let chat_id = ChatId(1);
+        let now = 1609459200;
+        let storage: Arc<dyn StorageTrait> = Arc::new(Storage::new());
+        let category_storage = storage.clone().as_category_storage();
+        category_storage
+            .add_category(chat_id, "Food".to_string())
+            .await
+            .unwrap();
+
+        let outcome = clear_categories_if_confirmed(storage.clone(), chat_id, true, true, now)
+            .await
+            .unwrap();
+        assert_eq!(outcome, ConfirmationOutcome::NotConfirmed);
+        assert_eq!(
+            category_storage
+                .get_chat_categories(chat_id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        storage
+            .clone()
+            .as_confirmation_storage()
+            .set_pending_confirmation(chat_id, CommandClearCategories::NAME, now)
+            .await;
+        let outcome = clear_categories_if_confirmed(storage.clone(), chat_id, true, true, now)
+            .await
+            .unwrap();
+        assert_eq!(outcome, ConfirmationOutcome::Confirmed);
+        assert!(
+            category_storage
+                .get_chat_categories(chat_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -1,13 +1,16 @@
 use std::sync::Arc;
 
-use teloxide::prelude::ResponseResult;
+use chrono::Utc;
+use teloxide::{prelude::ResponseResult, types::ChatId};
 use yoroolbot::{
     command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
-    markdown_string,
-    storage::ButtonData,
+    markdown_format, markdown_string,
 };
 
-use crate::storages::ExpenseStorageTrait;
+use crate::{
+    commands::confirmation::{ConfirmationOutcome, confirmation_buttons, resolve_confirmation},
+    storages::StorageTrait,
+};
 
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct CommandClearExpenses {
@@ -25,7 +28,7 @@ impl CommandTrait for CommandClearExpenses {
     type H = EmptyArg;
     type I = EmptyArg;
 
-    type Context = Arc<dyn ExpenseStorageTrait>;
+    type Context = Arc<dyn StorageTrait>;
 
     const NAME: &'static str = "clear_expenses";
     const PLACEHOLDERS: &[&'static str] = &["<confirm>"];
@@ -51,18 +54,25 @@ impl CommandTrait for CommandClearExpenses {
     async fn run0(
         &self,
         target: &CommandReplyTarget,
-        _storage: Self::Context,
+        storage: Self::Context,
     ) -> ResponseResult<()> {
-        // Show confirmation prompt with buttons
-        let message = markdown_string!("🗑️ Confirm clearing all expenses\\?");
+        // Remember the request, only the Confirm button of this prompt clears
+        storage
+            .as_confirmation_storage()
+            .set_pending_confirmation(target.chat.id, Self::NAME, Utc::now().timestamp())
+            .await;
 
-        let buttons = vec![vec![ButtonData::SwitchInlineQuery(
-            "✅ Yes, Clear All".to_string(),
+        let message = markdown_string!("🗑️ Confirm clearing all expenses\\?");
+        let buttons = confirmation_buttons(
             CommandClearExpenses {
                 confirm: Some(true),
             }
             .to_command_string(false),
-        )]];
+            CommandClearExpenses {
+                confirm: Some(false),
+            }
+            .to_command_string(false),
+        );
 
         target.markdown_message_with_menu(message, buttons).await?;
         Ok(())
@@ -74,25 +84,154 @@ impl CommandTrait for CommandClearExpenses {
         storage: Self::Context,
         confirm: &bool,
     ) -> ResponseResult<()> {
-        if !*confirm {
-            target
-                .send_markdown_message(markdown_string!("❌ Clear expenses cancelled\\."))
-                .await?;
-            return Ok(());
-        }
-
-        let chat_id = target.chat.id;
-        storage.clear_chat_expenses(chat_id).await;
-
-        target
-            .send_markdown_message(markdown_string!("🗑️ All expenses cleared\\!"))
-            .await?;
+        let outcome = clear_expenses_if_confirmed(
+            storage,
+            target.chat.id,
+            *confirm,
+            target.callback_query_id.is_some(),
+            Utc::now().timestamp(),
+        )
+        .await;
+        let message = match outcome {
+            ConfirmationOutcome::Confirmed => markdown_string!("🗑️ All expenses cleared\\!"),
+            ConfirmationOutcome::Cancelled => markdown_string!("❌ Clear expenses cancelled\\."),
+            ConfirmationOutcome::NotConfirmed => markdown_format!(
+                "⚠️ Nothing cleared\\. Use {} and press Confirm\\.",
+                CommandClearExpenses::default().to_command_string(true)
+            ),
+        };
+        target.send_markdown_message(message).await?;
         Ok(())
     }
 }
 
+/// Clear the chat's expenses if the answer confirms a pending request
+pub async fn clear_expenses_if_confirmed(
+    storage: Arc<dyn StorageTrait>,
+    chat_id: ChatId,
+    confirm: bool,
+    from_callback: bool,
+    now: i64,
+) -> ConfirmationOutcome {
+    let outcome = resolve_confirmation(
+        storage.clone().as_confirmation_storage().as_ref(),
+        chat_id,
+        CommandClearExpenses::NAME,
+        confirm,
+        from_callback,
+        now,
+    )
+    .await;
+    if outcome == ConfirmationOutcome::Confirmed {
+        storage
+            .as_expense_storage()
+            .clear_chat_expenses(chat_id)
+            .await;
+    }
+    outcome
+}
+
 impl From<CommandClearExpenses> for crate::commands::Command {
     fn from(cmd: CommandClearExpenses) -> Self {
         crate::commands::Command::ClearExpenses(cmd)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        storages::{CONFIRMATION_TIMEOUT_SECONDS, Expense, Storage},
+        utils::amount::Amount,
+    };
+
+    const NOW: i64 = 1609459200;
+
+    async fn storage_with_expense(chat_id: ChatId) -> Arc<dyn StorageTrait> {
+        let storage: Arc<dyn StorageTrait> = Arc::new(Storage::new());
+        storage
+            .clone()
+            .as_expense_storage()
+            .add_expense(
+                chat_id,
+                Expense {
+                    timestamp: NOW,
+                    description: "Coffee".to_string(),
+                    amount: Amount::from(5.0),
+                    ..Default::default()
+                },
+            )
+            .await;
+        storage
+    }
+
+    async fn request_confirmation(storage: &Arc<dyn StorageTrait>, chat_id: ChatId, now: i64) {
+        storage
+            .clone()
+            .as_confirmation_storage()
+            .set_pending_confirmation(chat_id, CommandClearExpenses::NAME, now)
+            .await;
+    }
+
+    async fn expense_count(storage: &Arc<dyn StorageTrait>, chat_id: ChatId) -> usize {
+        storage
+            .clone()
+            .as_expense_storage()
+            .get_chat_expenses(chat_id)
+            .await
+            .len()
+    }
+
+    #[tokio::test]
+    async fn test_clear_expenses_confirm() {
+        let chat_id = ChatId(1);
+        let storage = storage_with_expense(chat_id).await;
+        request_confirmation(&storage, chat_id, NOW).await;
+
+        let outcome =
+            clear_expenses_if_confirmed(storage.clone(), chat_id, true, true, NOW + 5).await;
+        assert_eq!(outcome, ConfirmationOutcome::Confirmed);
+        assert_eq!(expense_count(&storage, chat_id).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_clear_expenses_cancel() {
+        let chat_id = ChatId(1);
+        let storage = storage_with_expense(chat_id).await;
+        request_confirmation(&storage, chat_id, NOW).await;
+
+        let outcome =
+            clear_expenses_if_confirmed(storage.clone(), chat_id, false, true, NOW + 5).await;
+        assert_eq!(outcome, ConfirmationOutcome::Cancelled);
+        assert_eq!(expense_count(&storage, chat_id).await, 1);
+
+        // The cancelled request can't be confirmed afterwards
+        let outcome =
+            clear_expenses_if_confirmed(storage.clone(), chat_id, true, true, NOW + 6).await;
+        assert_eq!(outcome, ConfirmationOutcome::NotConfirmed);
+        assert_eq!(expense_count(&storage, chat_id).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_clear_expenses_unconfirmed_keeps_data() {
+        let chat_id = ChatId(1);
+        let storage = storage_with_expense(chat_id).await;
+
+        // Never asked
+        let outcome = clear_expenses_if_confirmed(storage.clone(), chat_id, true, true, NOW).await;
+        assert_eq!(outcome, ConfirmationOutcome::NotConfirmed);
+
+        // Typed instead of pressing the button
+        request_confirmation(&storage, chat_id, NOW).await;
+        let outcome = clear_expenses_if_confirmed(storage.clone(), chat_id, true, false, NOW).await;
+        assert_eq!(outcome, ConfirmationOutcome::NotConfirmed);
+
+        // Expired
+        request_confirmation(&storage, chat_id, NOW).await;
+        let late = NOW + CONFIRMATION_TIMEOUT_SECONDS + 1;
+        let outcome = clear_expenses_if_confirmed(storage.clone(), chat_id, true, true, late).await;
+        assert_eq!(outcome, ConfirmationOutcome::NotConfirmed);
+
+        assert_eq!(expense_count(&storage, chat_id).await, 1);
+    }
+}
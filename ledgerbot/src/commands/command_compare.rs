@@ -0,0 +1,398 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr, sync::Arc};
+
+use chrono::{Datelike, Duration, NaiveDate, TimeZone, Utc};
+use teloxide::{prelude::ResponseResult, utils::command::ParseError};
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format,
+};
+
+use crate::{
+    commands::{report::category_totals, run_blocking},
+    storages::{Expense, StorageTrait},
+    utils::{
+        amount::Amount,
+        format_amount::{AmountStyle, format_amount_localized},
+    },
+};
+
+/// Calendar period compared by `/compare`
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum ComparePeriod {
+    /// Calendar month (UTC)
+    #[default]
+    Month,
+    /// Week starting on Monday (UTC)
+    Week,
+}
+
+impl Display for ComparePeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ComparePeriod::Month => write!(f, "month"),
+            ComparePeriod::Week => write!(f, "week"),
+        }
+    }
+}
+
+impl FromStr for ComparePeriod {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "month" => Ok(ComparePeriod::Month),
+            "week" => Ok(ComparePeriod::Week),
+            _ => Err(ParseError::IncorrectFormat(
+                format!("Unknown period `{}`, expected `month` or `week`", s).into(),
+            )),
+        }
+    }
+}
+
+/// Compare category totals of the current period with the previous one
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandCompare {
+    pub period: Option<ComparePeriod>,
+}
+
+impl CommandTrait for CommandCompare {
+    type A = ComparePeriod;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "compare";
+    const PLACEHOLDERS: &[&'static str] = &["<period>"];
+
+    fn from_arguments(
+        period: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandCompare { period }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.period.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        self.run1(target, storage, &ComparePeriod::default()).await
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        period: &ComparePeriod,
+    ) -> ResponseResult<()> {
+        let chat_id = target.chat.id;
+        let expenses = storage
+            .clone()
+            .as_expense_storage()
+            .get_chat_expenses(chat_id)
+            .await;
+        let categories = storage
+            .clone()
+            .as_category_storage()
+            .get_chat_categories(chat_id)
+            .await
+            .unwrap_or_default();
+        let also_other = storage
+            .clone()
+            .as_category_storage()
+            .get_also_other_categories(chat_id)
+            .await;
+        let settings = storage
+            .clone()
+            .as_settings_storage()
+            .get_chat_settings(chat_id)
+            .await;
+
+        let now = settings.utc_offset.local_timestamp(Utc::now().timestamp());
+        let (current_range, previous_range) = period_ranges(*period, now);
+        let changes = run_blocking({
+            let uncategorized_name = settings.uncategorized_name().to_string();
+            let case_insensitive = settings.case_insensitive_filters;
+            move || {
+                let totals = |range: (i64, i64)| {
+                    let in_range: Vec<Expense> = expenses
+                        .iter()
+                        .filter(|expense| (range.0..range.1).contains(&expense.timestamp))
+                        .cloned()
+                        .collect();
+                    category_totals(
+                        &in_range,
+                        &categories,
+                        &uncategorized_name,
+                        &also_other,
+                        case_insensitive,
+                    )
+                };
+                diff_category_totals(&totals(current_range), &totals(previous_range))
+            }
+        })
+        .await;
+        if changes.is_empty() {
+            target
+                .send_markdown_message(markdown_format!(
+                    "📭 No expenses this {} or the previous one\\.",
+                    period.to_string()
+                ))
+                .await?;
+            return Ok(());
+        }
+        target
+            .send_markdown_message(markdown_format!(
+                "📊 This {} vs the previous one:\n{}",
+                period.to_string(),
+                @code format_compare_table(&changes, settings.amount_style())
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+/// Timestamp ranges `[start, end)` of the period containing `now` and of the period before
+pub fn period_ranges(period: ComparePeriod, now: i64) -> ((i64, i64), (i64, i64)) {
+    let today = Utc.timestamp_opt(now, 0).unwrap().date_naive();
+    let (previous_start, current_start, next_start) = match period {
+        ComparePeriod::Month => {
+            let current = today.with_day(1).unwrap();
+            let previous = current.checked_sub_months(chrono::Months::new(1)).unwrap();
+            let next = current.checked_add_months(chrono::Months::new(1)).unwrap();
+            (previous, current, next)
+        }
+        ComparePeriod::Week => {
+            let current = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+            (
+                current - Duration::days(7),
+                current,
+                current + Duration::days(7),
+            )
+        }
+    };
+    let timestamp = |date: NaiveDate| date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp();
+    (
+        (timestamp(current_start), timestamp(next_start)),
+        (timestamp(previous_start), timestamp(current_start)),
+    )
+}
+
+/// Totals of a category in the current and the previous period
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategoryChange {
+    pub name: String,
+    pub current: Amount,
+    pub previous: Amount,
+}
+
+impl CategoryChange {
+    /// Change from the previous period, negative when spending went down
+    pub fn delta(&self) -> Amount {
+        Amount::from_minor_units(self.current.minor_units() - self.previous.minor_units())
+    }
+
+    /// Change in percent of the previous total, None for a category new in this period
+    pub fn percent(&self) -> Option<f64> {
+        (self.previous != Amount::ZERO)
+            .then(|| self.delta().minor_units() as f64 * 100.0 / self.previous.minor_units() as f64)
+    }
+}
+
+/// Diff category totals of two periods in name order
+/// A category present in one period only has zero total in the other
+pub fn diff_category_totals(
+    current: &[(String, Amount)],
+    previous: &[(String, Amount)],
+) -> Vec<CategoryChange> {
+    let mut changes: BTreeMap<&str, CategoryChange> = BTreeMap::new();
+    let new_change = |name: &String| CategoryChange {
+        name: name.clone(),
+        current: Amount::ZERO,
+        previous: Amount::ZERO,
+    };
+    for (name, amount) in current {
+        changes
+            .entry(name)
+            .or_insert_with(|| new_change(name))
+            .current += *amount;
+    }
+    for (name, amount) in previous {
+        changes
+            .entry(name)
+            .or_insert_with(|| new_change(name))
+            .previous += *amount;
+    }
+    changes.into_values().collect()
+}
+
+/// Format a signed amount with an explicit `+` or `-`
+fn format_signed(amount: Amount, style: AmountStyle) -> String {
+    let sign = if amount.minor_units() < 0 { "-" } else { "+" };
+    format!(
+        "{}{}",
+        sign,
+        format_amount_localized(amount.as_f64().abs(), 2, style)
+    )
+}
+
+/// Format the changes as an aligned table with current, previous and change columns
+/// Categories new in this period are marked `new` instead of a percentage
+pub fn format_compare_table(changes: &[CategoryChange], style: AmountStyle) -> String {
+    let total = CategoryChange {
+        name: "Total".to_string(),
+        current: changes.iter().map(|change| change.current).sum(),
+        previous: changes.iter().map(|change| change.previous).sum(),
+    };
+    let header = [
+        "Category".to_string(),
+        "Current".to_string(),
+        "Previous".to_string(),
+        "Change".to_string(),
+    ];
+    let row = |change: &CategoryChange| {
+        let percent = match change.percent() {
+            Some(percent) => format!("{:+.0}%", percent),
+            None => "new".to_string(),
+        };
+        [
+            change.name.clone(),
+            format_amount_localized(change.current.as_f64(), 2, style),
+            format_amount_localized(change.previous.as_f64(), 2, style),
+            format!("{} ({})", format_signed(change.delta(), style), percent),
+        ]
+    };
+    let rows: Vec<[String; 4]> = changes.iter().map(row).collect();
+    let total_row = row(&total);
+
+    let mut widths = [0; 4];
+    for cells in [&header].into_iter().chain(&rows).chain([&total_row]) {
+        for (width, cell) in widths.iter_mut().zip(cells) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let format_row = |cells: &[String; 4]| {
+        format!(
+            "{:<w0$}  {:>w1$}  {:>w2$}  {:>w3$}",
+            cells[0],
+            cells[1],
+            cells[2],
+            cells[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3]
+        )
+    };
+    let mut lines = vec![format_row(&header)];
+    lines.extend(rows.iter().map(format_row));
+    lines.push("-".repeat(widths.iter().sum::<usize>() + 6));
+    lines.push(format_row(&total_row));
+    lines.join("\n")
+}
+
+impl From<CommandCompare> for crate::commands::Command {
+    fn from(cmd: CommandCompare) -> Self {
+        crate::commands::Command::Compare(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn totals(items: &[(&str, f64)]) -> Vec<(String, Amount)> {
+        items
+            .iter()
+            .map(|(name, amount)| (name.to_string(), Amount::from(*amount)))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_category_totals() {
+        let current = totals(&[("Books", 15.0), ("Food", 50.0)]);
+        let previous = totals(&[("Food", 40.0), ("Taxi", 20.0)]);
+        let changes = diff_category_totals(&current, &previous);
+
+        assert_eq!(changes.len(), 3);
+        // Appeared in this period
+        assert_eq!(changes[0].name, "Books");
+        assert_eq!(changes[0].previous, Amount::ZERO);
+        assert_eq!(changes[0].delta(), Amount::from(15.0));
+        assert_eq!(changes[0].percent(), None);
+        // Changed between periods
+        assert_eq!(changes[1].name, "Food");
+        assert_eq!(changes[1].delta(), Amount::from(10.0));
+        assert_eq!(changes[1].percent(), Some(25.0));
+        // Disappeared in this period
+        assert_eq!(changes[2].name, "Taxi");
+        assert_eq!(changes[2].current, Amount::ZERO);
+        assert_eq!(changes[2].delta(), Amount::from(-20.0));
+        assert_eq!(changes[2].percent(), Some(-100.0));
+
+        assert!(diff_category_totals(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_format_compare_table() {
+        let changes = diff_category_totals(
+            &totals(&[("Books", 15.0), ("Food", 50.0)]),
+            &totals(&[("Food", 40.0), ("Taxi", 20.0)]),
+        );
+        let table = format_compare_table(&changes, AmountStyle::default());
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "Category  Current  Previous          Change");
+        assert_eq!(lines[1], "Books       15.00      0.00    +15.00 (new)");
+        assert_eq!(lines[2], "Food        50.00     40.00   +10.00 (+25%)");
+        assert_eq!(lines[3], "Taxi         0.00     20.00  -20.00 (-100%)");
+        assert_eq!(lines[5], "Total       65.00     60.00     +5.00 (+8%)");
+        // All rows have the same width
+        assert!(lines.iter().all(|line| line.len() == lines[0].len()));
+    }
+
+    #[test]
+    fn test_period_ranges() {
+        // 2024-03-14 12:00:00 UTC, a Thursday
+        let now = 1710417600;
+        let day = |y, m, d| {
+            NaiveDate::from_ymd_opt(y, m, d)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+                .and_utc()
+                .timestamp()
+        };
+        assert_eq!(
+            period_ranges(ComparePeriod::Month, now),
+            (
+                (day(2024, 3, 1), day(2024, 4, 1)),
+                (day(2024, 2, 1), day(2024, 3, 1))
+            )
+        );
+        assert_eq!(
+            period_ranges(ComparePeriod::Week, now),
+            (
+                (day(2024, 3, 11), day(2024, 3, 18)),
+                (day(2024, 3, 4), day(2024, 3, 11))
+            )
+        );
+    }
+}
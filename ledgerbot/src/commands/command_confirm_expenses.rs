@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format,
+};
+
+use crate::storages::SettingsStorageTrait;
+
+/// Ask to confirm single expenses with Confirm/Cancel buttons before storing them
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandConfirmExpenses {
+    pub enabled: Option<bool>,
+}
+
+/// Describe when single expenses are stored
+fn confirm_mode(enabled: bool) -> &'static str {
+    if enabled {
+        "after confirmation"
+    } else {
+        "immediately"
+    }
+}
+
+impl CommandTrait for CommandConfirmExpenses {
+    type A = bool;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn SettingsStorageTrait>;
+
+    const NAME: &'static str = "confirm_expenses";
+    const PLACEHOLDERS: &[&'static str] = &["<enabled>"];
+
+    fn from_arguments(
+        enabled: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandConfirmExpenses { enabled }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.enabled.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let settings = storage.get_chat_settings(target.chat.id).await;
+        target
+            .send_markdown_message(markdown_format!(
+                "📝 Single expenses are stored {}\\.\n{}",
+                confirm_mode(settings.confirm_expenses),
+                @raw self.usage_hint()
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        enabled: &bool,
+    ) -> ResponseResult<()> {
+        let mut settings = storage.get_chat_settings(target.chat.id).await;
+        settings.confirm_expenses = *enabled;
+        storage.set_chat_settings(target.chat.id, settings).await;
+        target
+            .send_markdown_message(markdown_format!(
+                "✅ Single expenses will be stored {}\\.",
+                confirm_mode(*enabled)
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandConfirmExpenses> for crate::commands::Command {
+    fn from(cmd: CommandConfirmExpenses) -> Self {
+        crate::commands::Command::ConfirmExpenses(cmd)
+    }
+}
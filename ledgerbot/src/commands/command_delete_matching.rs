@@ -0,0 +1,251 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown::MarkdownString,
+    markdown_format,
+};
+
+use crate::{
+    commands::{
+        confirmation::{ConfirmationOutcome, confirmation_buttons, resolve_confirmation},
+        run_blocking,
+    },
+    storages::{Expense, StorageTrait},
+    utils::{
+        amount::Amount,
+        filter_pattern::{compile_filter, unsupported_regex_feature},
+        format_amount::{AmountStyle, format_amount_localized},
+    },
+};
+
+/// Delete all expenses whose description matches a regex, after confirmation
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandDeleteMatching {
+    pub pattern: Option<String>,
+    pub confirm: Option<bool>,
+}
+
+/// Number and rendered total of the expenses
+fn count_and_total(expenses: &[Expense], style: AmountStyle) -> (usize, String) {
+    let total: Amount = expenses.iter().map(|expense| expense.amount).sum();
+    (
+        expenses.len(),
+        format_amount_localized(total.as_f64(), 2, style),
+    )
+}
+
+impl CommandDeleteMatching {
+    fn with_confirm(pattern: &str, confirm: bool) -> Self {
+        CommandDeleteMatching {
+            pattern: Some(pattern.to_string()),
+            confirm: Some(confirm),
+        }
+    }
+
+    /// Compile the pattern the way filters are compiled, or explain why it's rejected
+    async fn compile(
+        storage: &Arc<dyn StorageTrait>,
+        target: &CommandReplyTarget,
+        pattern: &str,
+    ) -> Result<regex::Regex, MarkdownString> {
+        let settings = storage
+            .clone()
+            .as_settings_storage()
+            .get_chat_settings(target.chat.id)
+            .await;
+        compile_filter(pattern, settings.case_insensitive_filters).map_err(|e| {
+            match unsupported_regex_feature(pattern, &e) {
+                Some(feature) => markdown_format!(
+                    "❌ Pattern `{}` is not supported\\. {}",
+                    pattern,
+                    feature.hint()
+                ),
+                None => markdown_format!(
+                    "❌ Invalid regex pattern `{}`:\n{}",
+                    pattern,
+                    @code e.to_string()
+                ),
+            }
+        })
+    }
+}
+
+impl CommandTrait for CommandDeleteMatching {
+    type A = String;
+    type B = bool;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "delete_matching";
+    const PLACEHOLDERS: &[&'static str] = &["<pattern>", "<confirm>"];
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.pattern.as_ref()
+    }
+
+    fn param2(&self) -> Option<&Self::B> {
+        self.confirm.as_ref()
+    }
+
+    fn from_arguments(
+        pattern: Option<Self::A>,
+        confirm: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandDeleteMatching { pattern, confirm }
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        _storage: Self::Context,
+    ) -> ResponseResult<()> {
+        target
+            .send_markdown_message(markdown_format!(
+                "🗑️ Delete all expenses with descriptions matching a regex pattern\n{}",
+                @raw self.usage_hint()
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        pattern: &String,
+    ) -> ResponseResult<()> {
+        let regex = match Self::compile(&storage, target, pattern).await {
+            Ok(regex) => regex,
+            Err(message) => {
+                target.send_markdown_message(message).await?;
+                return Ok(());
+            }
+        };
+        let settings = storage
+            .clone()
+            .as_settings_storage()
+            .get_chat_settings(target.chat.id)
+            .await;
+        let expenses = storage
+            .clone()
+            .as_expense_storage()
+            .get_chat_expenses(target.chat.id)
+            .await;
+        let matching: Vec<Expense> = run_blocking(move || {
+            expenses
+                .into_iter()
+                .filter(|expense| regex.is_match(&expense.description))
+                .collect()
+        })
+        .await;
+        if matching.is_empty() {
+            target
+                .send_markdown_message(markdown_format!(
+                    "🔍 No expenses match `{}`, nothing to delete\\.",
+                    pattern
+                ))
+                .await?;
+            return Ok(());
+        }
+        let (count, total) = count_and_total(&matching, settings.amount_style());
+
+        // Remember the request, only the Confirm button of this prompt deletes
+        storage
+            .as_confirmation_storage()
+            .set_pending_confirmation(target.chat.id, Self::NAME, Utc::now().timestamp())
+            .await;
+        let message = markdown_format!(
+            "🗑️ Confirm deleting {} expense\\(s\\) matching `{}`, total `{}`\\?",
+            count,
+            pattern,
+            total
+        );
+        let buttons = confirmation_buttons(
+            Self::with_confirm(pattern, true).to_command_string(false),
+            Self::with_confirm(pattern, false).to_command_string(false),
+        );
+        target.markdown_message_with_menu(message, buttons).await?;
+        Ok(())
+    }
+
+    async fn run2(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        pattern: &String,
+        confirm: &bool,
+    ) -> ResponseResult<()> {
+        let regex = match Self::compile(&storage, target, pattern).await {
+            Ok(regex) => regex,
+            Err(message) => {
+                target.send_markdown_message(message).await?;
+                return Ok(());
+            }
+        };
+        let outcome = resolve_confirmation(
+            storage.clone().as_confirmation_storage().as_ref(),
+            target.chat.id,
+            Self::NAME,
+            *confirm,
+            target.callback_query_id.is_some(),
+            Utc::now().timestamp(),
+        )
+        .await;
+        let message = match outcome {
+            ConfirmationOutcome::Confirmed => {
+                let settings = storage
+                    .clone()
+                    .as_settings_storage()
+                    .get_chat_settings(target.chat.id)
+                    .await;
+                let removed = storage
+                    .as_expense_storage()
+                    .remove_expenses_matching(target.chat.id, &regex)
+                    .await;
+                let (count, total) = count_and_total(&removed, settings.amount_style());
+                markdown_format!(
+                    "🗑️ Deleted {} expense\\(s\\) matching `{}`, total `{}`\\.",
+                    count,
+                    pattern,
+                    total
+                )
+            }
+            ConfirmationOutcome::Cancelled => {
+                markdown_format!("❌ Deleting expenses matching `{}` cancelled\\.", pattern)
+            }
+            ConfirmationOutcome::NotConfirmed => markdown_format!(
+                "⚠️ Nothing deleted\\. Use {} and press Confirm\\.",
+                CommandDeleteMatching {
+                    pattern: Some(pattern.clone()),
+                    confirm: None
+                }
+                .to_command_string(true)
+            ),
+        };
+        target.send_markdown_message(message).await?;
+        Ok(())
+    }
+}
+
+impl From<CommandDeleteMatching> for crate::commands::Command {
+    fn from(cmd: CommandDeleteMatching) -> Self {
+        crate::commands::Command::DeleteMatching(cmd)
+    }
+}
@@ -0,0 +1,169 @@
+use std::sync::Arc;
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg, NoopCommand},
+    markdown_format, markdown_string,
+};
+
+use crate::{menus::select_category::select_category, storages::CategoryStorageTrait};
+
+/// Show the exact stored patterns of a category with whitespace made visible
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandDumpFilters {
+    pub category: Option<String>,
+}
+
+impl CommandTrait for CommandDumpFilters {
+    type A = String;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn CategoryStorageTrait>;
+
+    const NAME: &'static str = "dump_filters";
+    const PLACEHOLDERS: &[&'static str] = &["<category>"];
+
+    fn from_arguments(
+        category: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandDumpFilters { category }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.category.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        select_category(
+            target,
+            &storage,
+            markdown_string!("🔬 Select Category to dump filters"),
+            |name| CommandDumpFilters {
+                category: Some(name.to_string()),
+            },
+            None::<NoopCommand>,
+        )
+        .await
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        name: &String,
+    ) -> ResponseResult<()> {
+        let categories = storage
+            .get_chat_categories(target.chat.id)
+            .await
+            .unwrap_or_default();
+        let Some(patterns) = categories.get(name) else {
+            target
+                .send_markdown_message(markdown_format!("❌ Category `{}` does not exist", name))
+                .await?;
+            return Ok(());
+        };
+        if patterns.is_empty() {
+            target
+                .send_markdown_message(markdown_format!(
+                    "📂 Category `{}` has no filters defined yet\\.",
+                    name
+                ))
+                .await?;
+            return Ok(());
+        }
+        target
+            .send_markdown_message(markdown_format!(
+                "🔬 Stored filters of category `{}`, spaces shown as `␣`:\n{}",
+                name,
+                @code format_dumped_filters(patterns)
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+/// Make whitespace and invisible characters of a pattern visible
+/// Spaces become `␣`, tabs `⇥`, newlines `↵`, other invisible characters `\u{..}` escapes
+pub fn visible_whitespace(pattern: &str) -> String {
+    pattern
+        .chars()
+        .map(|c| match c {
+            ' ' => "␣".to_string(),
+            '\t' => "⇥".to_string(),
+            '\n' => "↵".to_string(),
+            c if c.is_control() || c.is_whitespace() || is_invisible(c) => {
+                format!("\\u{{{:04x}}}", c as u32)
+            }
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// Zero-width characters, which are neither whitespace nor control characters
+fn is_invisible(c: char) -> bool {
+    matches!(c, '\u{200b}'..='\u{200d}' | '\u{2060}' | '\u{feff}')
+}
+
+/// Format patterns one per line with their storage index and visible whitespace
+pub fn format_dumped_filters(patterns: &[String]) -> String {
+    patterns
+        .iter()
+        .enumerate()
+        .map(|(idx, pattern)| {
+            format!(
+                "{}: {} ({} chars)",
+                idx,
+                visible_whitespace(pattern),
+                pattern.chars().count()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl From<CommandDumpFilters> for crate::commands::Command {
+    fn from(cmd: CommandDumpFilters) -> Self {
+        crate::commands::Command::DumpFilters(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visible_whitespace() {
+        assert_eq!(visible_whitespace("(?i)coffee"), "(?i)coffee");
+        assert_eq!(visible_whitespace("coffee shop "), "coffee␣shop␣");
+        assert_eq!(visible_whitespace("\ttea\n"), "⇥tea↵");
+        assert_eq!(
+            visible_whitespace("tea\u{00a0}\u{200b}"),
+            "tea\\u{00a0}\\u{200b}"
+        );
+
+        let patterns = vec!["coffee".to_string(), "tea ".to_string()];
+        assert_eq!(
+            format_dumped_filters(&patterns),
+            "0: coffee (6 chars)\n1: tea␣ (4 chars)"
+        );
+    }
+}
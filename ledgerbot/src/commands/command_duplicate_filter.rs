@@ -0,0 +1,202 @@
+use std::sync::Arc;
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg, NoopCommand},
+    markdown_format, markdown_string,
+};
+
+use crate::{
+    commands::command_add_filter::with_equivalent_filter_warning,
+    menus::{
+        select_category::select_category, select_category_filter::select_category_filter,
+        update_category_filter::update_category_filter,
+    },
+    storages::StorageTrait,
+    utils::filter_pattern::compile_filter,
+};
+
+/// Add a variant of an existing filter right after it, keeping the filter order
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandDuplicateFilter {
+    pub category: Option<String>,
+    pub position: Option<usize>,
+    pub pattern: Option<String>,
+}
+
+impl CommandTrait for CommandDuplicateFilter {
+    type A = String;
+    type B = usize;
+    type C = String;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "duplicate_filter";
+    const PLACEHOLDERS: &[&'static str] = &["<category>", "<position>", "<new_pattern>"];
+
+    fn from_arguments(
+        a: Option<Self::A>,
+        b: Option<Self::B>,
+        c: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandDuplicateFilter {
+            category: a,
+            position: b,
+            pattern: c,
+        }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.category.as_ref()
+    }
+    fn param2(&self) -> Option<&Self::B> {
+        self.position.as_ref()
+    }
+    fn param3(&self) -> Option<&Self::C> {
+        self.pattern.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        select_category(
+            target,
+            &storage.as_category_storage(),
+            markdown_string!("📑 Select Category for duplicating filter"),
+            |name| CommandDuplicateFilter {
+                category: Some(name.to_string()),
+                position: None,
+                pattern: None,
+            },
+            None::<NoopCommand>,
+        )
+        .await
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        name: &String,
+    ) -> ResponseResult<()> {
+        select_category_filter(
+            target,
+            &storage.as_category_storage(),
+            name,
+            markdown_format!("📑 Select Filter to duplicate in category `{}`", name),
+            |idx, _pattern| {
+                Some(CommandDuplicateFilter {
+                    category: Some(name.clone()),
+                    position: Some(idx),
+                    pattern: None,
+                })
+            },
+            Some(CommandDuplicateFilter::default()),
+        )
+        .await
+    }
+
+    async fn run2(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        name: &String,
+        idx: &usize,
+    ) -> ResponseResult<()> {
+        update_category_filter(
+            target,
+            &storage.as_category_storage(),
+            name,
+            *idx,
+            |pattern| {
+                markdown_format!(
+                    "📑 **Duplicating filter \\#{} in category `{}`:**\n\nCurrent pattern: `{}`",
+                    *idx,
+                    name,
+                    pattern
+                )
+            },
+            "📑 Edit copy",
+            |pattern| CommandDuplicateFilter {
+                category: Some(name.clone()),
+                position: Some(*idx),
+                pattern: Some(pattern.to_string()),
+            },
+            Some(CommandDuplicateFilter {
+                category: Some(name.clone()),
+                position: None,
+                pattern: None,
+            }),
+        )
+        .await
+    }
+
+    async fn run3(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        name: &String,
+        idx: &usize,
+        pattern: &String,
+    ) -> ResponseResult<()> {
+        if let Err(e) = compile_filter(pattern, false) {
+            target
+                .send_markdown_message(markdown_format!(
+                    "❌ Invalid regex pattern `{}`:\n{}",
+                    pattern,
+                    &e.to_string()
+                ))
+                .await?;
+            return Ok(());
+        }
+
+        match storage
+            .clone()
+            .as_category_storage()
+            .insert_category_filter(target.chat.id, name.clone(), *idx, pattern.clone())
+            .await
+        {
+            Ok(position) => {
+                let message = with_equivalent_filter_warning(
+                    &storage,
+                    target.chat.id,
+                    name,
+                    pattern,
+                    markdown_format!(
+                        "✅ Filter `{}` added to category `{}` at position \\#{}\\.",
+                        pattern.clone(),
+                        name.clone(),
+                        position
+                    ),
+                )
+                .await;
+                target.send_markdown_message(message).await?;
+            }
+            Err(e) => {
+                target.send_markdown_message(e).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl From<CommandDuplicateFilter> for crate::commands::Command {
+    fn from(cmd: CommandDuplicateFilter) -> Self {
+        crate::commands::Command::DuplicateFilter(cmd)
+    }
+}
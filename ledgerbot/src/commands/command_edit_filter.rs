@@ -1,18 +1,21 @@
 use std::sync::Arc;
 
-use teloxide::prelude::ResponseResult;
+use teloxide::{prelude::ResponseResult, types::ChatId};
 use yoroolbot::{
     command_trait::{CommandReplyTarget, CommandTrait, EmptyArg, NoopCommand},
+    markdown::MarkdownString,
     markdown_format, markdown_string,
 };
 
 use crate::{
+    commands::command_add_filter::with_equivalent_filter_warning,
     menus::{
         common::read_category_filter_by_index, select_category::select_category,
         select_category_filter::select_category_filter,
         update_category_filter::update_category_filter,
     },
-    storages::CategoryStorageTrait,
+    storages::{CategoryStorageTrait, StorageTrait},
+    utils::filter_pattern::compile_filter,
 };
 
 #[derive(Default, Debug, Clone, PartialEq)]
@@ -33,7 +36,7 @@ impl CommandTrait for CommandEditFilter {
     type H = EmptyArg;
     type I = EmptyArg;
 
-    type Context = Arc<dyn CategoryStorageTrait>;
+    type Context = Arc<dyn StorageTrait>;
 
     const NAME: &'static str = "edit_filter";
     const PLACEHOLDERS: &[&'static str] = &["<category>", "<position>", "<new_pattern>"];
@@ -73,7 +76,7 @@ impl CommandTrait for CommandEditFilter {
     ) -> ResponseResult<()> {
         select_category(
             target,
-            &storage,
+            &storage.as_category_storage(),
             markdown_string!("✏️ Select Category for editing filter"),
             |name| CommandEditFilter {
                 category: Some(name.to_string()),
@@ -93,7 +96,7 @@ impl CommandTrait for CommandEditFilter {
     ) -> ResponseResult<()> {
         select_category_filter(
             target,
-            &storage,
+            &storage.as_category_storage(),
             name,
             markdown_format!("✏️ Select Filter to edit in category `{}`", name),
             |idx, _pattern| {
@@ -117,7 +120,7 @@ impl CommandTrait for CommandEditFilter {
     ) -> ResponseResult<()> {
         update_category_filter(
             target,
-            &storage,
+            &storage.as_category_storage(),
             name,
             *idx,
             |pattern| {
@@ -153,7 +156,7 @@ impl CommandTrait for CommandEditFilter {
     ) -> ResponseResult<()> {
         let Some(old_pattern) = read_category_filter_by_index(
             target,
-            &storage,
+            &storage.clone().as_category_storage(),
             name,
             *idx,
             Some(CommandEditFilter {
@@ -167,44 +170,92 @@ impl CommandTrait for CommandEditFilter {
             return Ok(());
         };
 
-        if let Err(e) = regex::Regex::new(pattern) {
-            target
-                .send_markdown_message(markdown_format!(
-                    "❌ Invalid regex pattern `{}`:\n{}",
-                    pattern,
-                    &e.to_string()
-                ))
-                .await?;
-            return Ok(());
-        }
-
-        // Remove the old pattern and add the new one
-        if let Err(e) = storage
-            .remove_category_filter(target.chat.id, name, &old_pattern)
-            .await
-        {
-            target
-                .send_markdown_message(markdown_format!("❌ Failed to remove filter: {}", e))
-                .await?;
-        }
-
-        if let Err(e) = storage
-            .add_category_filter(target.chat.id, name.clone(), pattern.clone())
-            .await
+        if let Err(e) = edit_filter(
+            &storage.clone().as_category_storage(),
+            target.chat.id,
+            name,
+            *idx,
+            pattern,
+        )
+        .await
         {
             target.send_markdown_message(e).await?;
             return Ok(());
         }
 
-        target
-            .send_markdown_message(markdown_format!(
+        let message = with_equivalent_filter_warning(
+            &storage,
+            target.chat.id,
+            name,
+            pattern,
+            markdown_format!(
                 "✅ Filter updated in category `{}`\\.\n`{}` *before*\n`{}` *after*",
                 name.clone(),
                 old_pattern.clone(),
                 pattern.clone()
-            ))
-            .await?;
+            ),
+        )
+        .await;
+        target.send_markdown_message(message).await?;
 
         Ok(())
     }
 }
+
+/// Replace the filter at `idx` of the category in place, if the new pattern is a valid regex
+pub async fn edit_filter(
+    storage: &Arc<dyn CategoryStorageTrait>,
+    chat_id: ChatId,
+    name: &str,
+    idx: usize,
+    pattern: &str,
+) -> Result<(), MarkdownString> {
+    if let Err(e) = compile_filter(pattern, false) {
+        return Err(markdown_format!(
+            "❌ Invalid regex pattern `{}`:\n{}",
+            pattern,
+            &e.to_string()
+        ));
+    }
+    storage
+        .set_category_filter(chat_id, name, idx, pattern.to_string())
+        .await
+        .map_err(|e| markdown_format!("❌ Failed to update filter: {}", @raw e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storages::CategoryStorage;
+
+    #[tokio::test]
+    async fn test_edit_filter_keeps_position() {
+        let storage: Arc<dyn CategoryStorageTrait> = Arc::new(CategoryStorage::new());
+        let chat_id = ChatId(1);
+        storage
+            .add_category(chat_id, "Food".to_string())
+            .await
+            .unwrap();
+        for pattern in ["coffee", "lunch", "dinner"] {
+            storage
+                .add_category_filter(chat_id, "Food".to_string(), pattern.to_string())
+                .await
+                .unwrap();
+        }
+
+        edit_filter(&storage, chat_id, "Food", 0, "(?i)latte")
+            .await
+            .unwrap();
+        let categories = storage.get_chat_categories(chat_id).await.unwrap();
+        assert_eq!(categories["Food"], vec!["(?i)latte", "lunch", "dinner"]);
+
+        // An invalid regex is refused and the old pattern stays
+        assert!(
+            edit_filter(&storage, chat_id, "Food", 1, "(unclosed")
+                .await
+                .is_err()
+        );
+        let categories = storage.get_chat_categories(chat_id).await.unwrap();
+        assert_eq!(categories["Food"], vec!["(?i)latte", "lunch", "dinner"]);
+    }
+}
@@ -7,7 +7,9 @@ use yoroolbot::{
 };
 
 use crate::{
-    commands::command_edit_filter::CommandEditFilter,
+    commands::{
+        command_edit_filter::CommandEditFilter, command_preview_filter::CommandPreviewFilter,
+    },
     menus::{
         common::read_category_filter_by_index,
         select_category::select_category,
@@ -244,12 +246,17 @@ impl CommandTrait for CommandEditWordsFilter {
             words: Some(selected_words.clone()),
         };
 
-        // Apply command will edit the existing filter
+        // Apply commands will edit the existing filter (whole words or substrings)
         let apply_command = CommandEditFilter {
             category: Some(category.clone()),
             position: Some(position),
             pattern: selected_words.build_pattern(),
         };
+        let substring_apply_command = CommandEditFilter {
+            category: Some(category.clone()),
+            position: Some(position),
+            pattern: selected_words.build_substring_pattern(),
+        };
 
         select_word(
             target,
@@ -260,6 +267,12 @@ impl CommandTrait for CommandEditWordsFilter {
             word_command,
             page_command,
             apply_command,
+            substring_apply_command,
+            CommandPreviewFilter {
+                category: Some(category.clone()),
+                pattern: selected_words.build_pattern(),
+                position: Some(position),
+            },
             Some(CommandEditWordsFilter {
                 category: Some(category.clone()),
                 position: None,
@@ -0,0 +1,93 @@
+use std::sync::Arc;
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format,
+};
+
+use crate::{storages::SettingsStorageTrait, utils::expense_template::ExpenseTemplate};
+
+/// Set the message confirming an added expense, with `{date}`, `{description}`
+/// and `{amount}` placeholders
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandExpenseTemplate {
+    pub template: Option<ExpenseTemplate>,
+}
+
+impl CommandTrait for CommandExpenseTemplate {
+    type A = ExpenseTemplate;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn SettingsStorageTrait>;
+
+    const NAME: &'static str = "expense_template";
+    const PLACEHOLDERS: &[&'static str] = &["<template>"];
+    const MULTILINE_LAST_ARG: bool = true;
+
+    fn from_arguments(
+        template: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandExpenseTemplate { template }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.template.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let settings = storage.get_chat_settings(target.chat.id).await;
+        target
+            .send_markdown_message(markdown_format!(
+                "📝 Added expenses are confirmed with:\n{}\nThe template needs `{{description}}` and `{{amount}}`, `{{date}}` is optional\\.\nDefault: `{}`\n{}",
+                @code settings.expense_template.to_string(),
+                ExpenseTemplate::default().to_string(),
+                @raw self.usage_hint()
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        template: &ExpenseTemplate,
+    ) -> ResponseResult<()> {
+        let mut settings = storage.get_chat_settings(target.chat.id).await;
+        settings.expense_template = template.clone();
+        storage.set_chat_settings(target.chat.id, settings).await;
+        target
+            .send_markdown_message(markdown_format!(
+                "✅ Added expenses will be confirmed like:\n{}",
+                template.render("2024-01-31 12:00", "Coffee", "3.5")
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandExpenseTemplate> for crate::commands::Command {
+    fn from(cmd: CommandExpenseTemplate) -> Self {
+        crate::commands::Command::ExpenseTemplate(cmd)
+    }
+}
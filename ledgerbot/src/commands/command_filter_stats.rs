@@ -0,0 +1,215 @@
+use std::{collections::HashMap, sync::Arc};
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format, markdown_string,
+};
+
+use crate::{
+    commands::{report::build_category_matchers, run_blocking},
+    storages::{Expense, FilterTotals, StorageTrait},
+};
+
+/// Show how many stored expenses each filter matches, to find filters matching nothing,
+/// and how many expenses it has categorized in all reports so far
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandFilterStats;
+
+impl CommandTrait for CommandFilterStats {
+    type A = EmptyArg;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "filter_stats";
+    const PLACEHOLDERS: &[&'static str] = &[];
+
+    fn from_arguments(
+        _: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandFilterStats
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let chat_id = target.chat.id;
+        let expenses = storage
+            .clone()
+            .as_expense_storage()
+            .get_chat_expenses(chat_id)
+            .await;
+        let categories = storage
+            .clone()
+            .as_category_storage()
+            .get_chat_categories(chat_id)
+            .await
+            .unwrap_or_default();
+        let settings = storage
+            .clone()
+            .as_settings_storage()
+            .get_chat_settings(chat_id)
+            .await;
+        let totals = storage
+            .clone()
+            .as_filter_stats_storage()
+            .get_filter_totals(chat_id)
+            .await;
+
+        let expense_count = expenses.len();
+        let case_insensitive = settings.case_insensitive_filters;
+        let counts =
+            run_blocking(move || count_filter_matches(&expenses, &categories, case_insensitive))
+                .await;
+        if counts.is_empty() {
+            target
+                .send_markdown_message(markdown_string!("📂 No filters defined yet\\."))
+                .await?;
+            return Ok(());
+        }
+        target
+            .send_markdown_message(markdown_format!(
+                "📈 Expenses matched by each filter, out of {}, and categorized by it in reports so far:\n{}",
+                expense_count,
+                @code format_filter_stats(&counts, &totals)
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+/// Count the expenses matched by each filter as `(category, pattern, count)`
+/// Grouped by category in name order, the most matching filters first within a category
+/// Invalid patterns match nothing and are left out
+pub fn count_filter_matches(
+    expenses: &[Expense],
+    categories: &HashMap<String, Vec<String>>,
+    case_insensitive: bool,
+) -> Vec<(String, String, usize)> {
+    let mut counts: Vec<(String, String, usize)> =
+        build_category_matchers(categories, case_insensitive)
+            .into_iter()
+            .flat_map(|(category, regexes)| {
+                regexes.into_iter().map(move |(pattern, re)| {
+                    let count = expenses
+                        .iter()
+                        .filter(|expense| re.is_match(&expense.description))
+                        .count();
+                    (category.clone(), pattern, count)
+                })
+            })
+            .collect();
+    counts.sort_by(|a, b| a.0.cmp(&b.0).then(b.2.cmp(&a.2)).then(a.1.cmp(&b.1)));
+    counts
+}
+
+/// Format filter match counts under their category names, marking filters matching nothing
+/// Cumulative report counts from `totals` follow the pattern when there are any
+pub fn format_filter_stats(counts: &[(String, String, usize)], totals: &FilterTotals) -> String {
+    let width = counts
+        .iter()
+        .map(|(_, _, count)| count.to_string().len())
+        .max()
+        .unwrap_or(1);
+    let mut lines = Vec::new();
+    let mut last_category: Option<&str> = None;
+    for (category, pattern, count) in counts {
+        if last_category != Some(category.as_str()) {
+            lines.push(category.clone());
+            last_category = Some(category);
+        }
+        let unused = if *count == 0 { "  ← no matches" } else { "" };
+        let total = match totals.get(&(category.clone(), pattern.clone())) {
+            Some(total) => format!("  ({} in reports)", total),
+            None => String::new(),
+        };
+        lines.push(format!(
+            "  {:>width$}  {}{}{}",
+            count, pattern, total, unused
+        ));
+    }
+    lines.join("\n")
+}
+
+impl From<CommandFilterStats> for crate::commands::Command {
+    fn from(cmd: CommandFilterStats) -> Self {
+        crate::commands::Command::FilterStats(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::amount::Amount;
+
+    #[test]
+    fn test_count_filter_matches() {
+        let expenses: Vec<Expense> = ["Coffee", "Coffee beans", "Lunch", "Taxi"]
+            .iter()
+            .map(|description| Expense {
+                description: description.to_string(),
+                amount: Amount::from(1.0),
+                timestamp: 1609459200,
+                ..Default::default()
+            })
+            .collect();
+        let mut categories = HashMap::new();
+        categories.insert(
+            "Food".to_string(),
+            vec![
+                "(?i)lunch".to_string(),
+                "(?i)coffee".to_string(),
+                "(?i)pizza".to_string(),
+            ],
+        );
+        categories.insert(
+            "Travel".to_string(),
+            vec!["taxi".to_string(), "[invalid".to_string()],
+        );
+
+        let counts = count_filter_matches(&expenses, &categories, false);
+        let expected = |category: &str, pattern: &str, count: usize| {
+            (category.to_string(), pattern.to_string(), count)
+        };
+        assert_eq!(
+            counts,
+            vec![
+                expected("Food", "(?i)coffee", 2),
+                expected("Food", "(?i)lunch", 1),
+                expected("Food", "(?i)pizza", 0),
+                expected("Travel", "taxi", 0),
+            ]
+        );
+        // The chat setting applies to the counts too
+        let counts = count_filter_matches(&expenses, &categories, true);
+        assert_eq!(counts[3], expected("Travel", "taxi", 1));
+
+        assert_eq!(
+            format_filter_stats(&counts, &FilterTotals::new()),
+            "Food\n  2  (?i)coffee\n  1  (?i)lunch\n  0  (?i)pizza  ← no matches\nTravel\n  1  taxi"
+        );
+        let totals = FilterTotals::from([(("Food".to_string(), "(?i)coffee".to_string()), 7)]);
+        assert!(
+            format_filter_stats(&counts, &totals)
+                .starts_with("Food\n  2  (?i)coffee  (7 in reports)\n")
+        );
+    }
+}
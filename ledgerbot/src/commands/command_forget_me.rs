@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use teloxide::{prelude::ResponseResult, types::ChatId};
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown::MarkdownString,
+    markdown_format, markdown_string,
+};
+
+use crate::{
+    commands::confirmation::{ConfirmationOutcome, confirmation_buttons, resolve_confirmation},
+    storages::StorageTrait,
+};
+
+/// Delete everything the bot stores about the chat
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandForgetMe {
+    pub confirm: Option<bool>,
+}
+
+impl CommandTrait for CommandForgetMe {
+    type A = bool;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "forget_me";
+    const PLACEHOLDERS: &[&'static str] = &["<confirm>"];
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.confirm.as_ref()
+    }
+
+    fn from_arguments(
+        confirm: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandForgetMe { confirm }
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        // Remember the request, only the Confirm button of this prompt deletes
+        storage
+            .as_confirmation_storage()
+            .set_pending_confirmation(target.chat.id, Self::NAME, Utc::now().timestamp())
+            .await;
+
+        let message = markdown_string!(
+            "🗑️ Confirm deleting all data of this chat: expenses, archived expenses, categories, settings and audit log\\? This cannot be undone\\."
+        );
+        let buttons = confirmation_buttons(
+            CommandForgetMe {
+                confirm: Some(true),
+            }
+            .to_command_string(false),
+            CommandForgetMe {
+                confirm: Some(false),
+            }
+            .to_command_string(false),
+        );
+
+        target.markdown_message_with_menu(message, buttons).await?;
+        Ok(())
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        confirm: &bool,
+    ) -> ResponseResult<()> {
+        let outcome = forget_chat_if_confirmed(
+            storage,
+            target.chat.id,
+            *confirm,
+            target.callback_query_id.is_some(),
+            Utc::now().timestamp(),
+        )
+        .await;
+        let message = match outcome {
+            Ok(ConfirmationOutcome::Confirmed) => {
+                markdown_string!("🗑️ All data of this chat deleted\\.")
+            }
+            Ok(ConfirmationOutcome::Cancelled) => markdown_string!("❌ Deletion cancelled\\."),
+            Ok(ConfirmationOutcome::NotConfirmed) => markdown_format!(
+                "⚠️ Nothing deleted\\. Use {} and press Confirm\\.",
+                CommandForgetMe::default().to_command_string(true)
+            ),
+            Err(e) => e,
+        };
+        target.send_markdown_message(message).await?;
+        Ok(())
+    }
+}
+
+/// Delete all data of the chat if the answer confirms a pending request
+pub async fn forget_chat_if_confirmed(
+    storage: Arc<dyn StorageTrait>,
+    chat_id: ChatId,
+    confirm: bool,
+    from_callback: bool,
+    now: i64,
+) -> Result<ConfirmationOutcome, MarkdownString> {
+    let outcome = resolve_confirmation(
+        storage.clone().as_confirmation_storage().as_ref(),
+        chat_id,
+        CommandForgetMe::NAME,
+        confirm,
+        from_callback,
+        now,
+    )
+    .await;
+    if outcome == ConfirmationOutcome::Confirmed {
+        storage.clear_chat_all(chat_id).await?;
+    }
+    Ok(outcome)
+}
+
+impl From<CommandForgetMe> for crate::commands::Command {
+    fn from(cmd: CommandForgetMe) -> Self {
+        crate::commands::Command::ForgetMe(cmd)
+    }
+}
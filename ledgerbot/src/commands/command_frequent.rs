@@ -0,0 +1,220 @@
+use std::{collections::HashMap, sync::Arc};
+
+use chrono::{TimeZone, Utc};
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format, markdown_string,
+    storage::ButtonData,
+};
+
+use crate::{
+    commands::command_add_expense::CommandAddExpense,
+    storages::{Expense, StorageTrait},
+};
+
+/// Number of descriptions shown when no count is given
+const DEFAULT_FREQUENT_COUNT: usize = 10;
+/// Upper bound for the count to keep the keyboard manageable
+const MAX_FREQUENT_COUNT: usize = 30;
+/// Buttons in one row of the keyboard
+const BUTTONS_PER_ROW: usize = 2;
+
+/// The `count` most frequent descriptions with their number of expenses, most frequent
+/// first. Descriptions are compared exactly, ties go to the most recently used one
+pub fn most_frequent_descriptions(expenses: &[Expense], count: usize) -> Vec<(String, usize)> {
+    let mut stats: HashMap<&str, (usize, i64)> = HashMap::new();
+    for expense in expenses {
+        let (uses, last_used) = stats.entry(&expense.description).or_insert((0, i64::MIN));
+        *uses += 1;
+        *last_used = (*last_used).max(expense.timestamp);
+    }
+    let mut stats: Vec<(&str, usize, i64)> = stats
+        .into_iter()
+        .map(|(description, (uses, last_used))| (description, uses, last_used))
+        .collect();
+    stats.sort_by(|a, b| {
+        b.1.cmp(&a.1)
+            .then_with(|| b.2.cmp(&a.2))
+            .then_with(|| a.0.cmp(b.0))
+    });
+    stats
+        .into_iter()
+        .take(count)
+        .map(|(description, uses, _)| (description.to_string(), uses))
+        .collect()
+}
+
+/// Show the most frequent expense descriptions as buttons pre-filling `/add_expense`
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandFrequent {
+    pub count: Option<usize>,
+}
+
+impl CommandTrait for CommandFrequent {
+    type A = usize;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "frequent";
+    const PLACEHOLDERS: &[&'static str] = &["<count>"];
+
+    fn from_arguments(
+        count: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandFrequent { count }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.count.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        self.run1(target, storage, &DEFAULT_FREQUENT_COUNT).await
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        count: &usize,
+    ) -> ResponseResult<()> {
+        let chat_id = target.chat.id;
+        let expenses = storage
+            .clone()
+            .as_expense_storage()
+            .get_chat_expenses(chat_id)
+            .await;
+        let frequent = most_frequent_descriptions(&expenses, (*count).clamp(1, MAX_FREQUENT_COUNT));
+        if frequent.is_empty() {
+            target
+                .send_markdown_message(markdown_string!("No expenses recorded yet\\."))
+                .await?;
+            return Ok(());
+        }
+
+        // The buttons add the expense for today in the chat's time zone
+        let settings = storage
+            .clone()
+            .as_settings_storage()
+            .get_chat_settings(chat_id)
+            .await;
+        let today = Utc
+            .timestamp_opt(
+                settings.utc_offset.local_timestamp(Utc::now().timestamp()),
+                0,
+            )
+            .unwrap()
+            .date_naive();
+        let buttons: Vec<ButtonData> = frequent
+            .iter()
+            .map(|(description, uses)| {
+                let add_expense = CommandAddExpense {
+                    date: Some(today),
+                    description: Some(description.clone()),
+                    ..Default::default()
+                };
+                ButtonData::SwitchInlineQuery(
+                    format!("{} ({})", description, uses),
+                    add_expense.to_command_string(false),
+                )
+            })
+            .collect();
+        let rows: Vec<Vec<ButtonData>> = buttons
+            .chunks(BUTTONS_PER_ROW)
+            .map(|row| row.to_vec())
+            .collect();
+        target
+            .markdown_message_with_menu(
+                markdown_format!(
+                    "🔁 {} most frequent description\\(s\\), tap one and enter the amount:",
+                    frequent.len()
+                ),
+                rows,
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandFrequent> for crate::commands::Command {
+    fn from(cmd: CommandFrequent) -> Self {
+        crate::commands::Command::Frequent(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::amount::Amount;
+
+    fn expense(description: &str, timestamp: i64) -> Expense {
+        Expense {
+            timestamp,
+            description: description.to_string(),
+            amount: Amount::from(1.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_most_frequent_descriptions() {
+        let expenses = vec![
+            expense("Coffee", 1),
+            expense("Lunch", 2),
+            expense("Coffee", 3),
+            expense("coffee", 4),
+            expense("Taxi", 5),
+            expense("Coffee", 6),
+            expense("Lunch", 7),
+        ];
+        assert_eq!(
+            most_frequent_descriptions(&expenses, 2),
+            vec![("Coffee".to_string(), 3), ("Lunch".to_string(), 2)]
+        );
+        // Descriptions are compared exactly, the whole list when the count is larger
+        assert_eq!(most_frequent_descriptions(&expenses, 10).len(), 4);
+        assert!(most_frequent_descriptions(&[], 5).is_empty());
+    }
+
+    #[test]
+    fn test_most_frequent_descriptions_ties() {
+        // Same number of uses: the most recently used first, then alphabetically
+        let expenses = vec![
+            expense("Bread", 10),
+            expense("Milk", 20),
+            expense("Apples", 20),
+            expense("Bread", 1),
+            expense("Milk", 2),
+            expense("Apples", 3),
+        ];
+        assert_eq!(
+            most_frequent_descriptions(&expenses, 3),
+            vec![
+                ("Apples".to_string(), 2),
+                ("Milk".to_string(), 2),
+                ("Bread".to_string(), 2)
+            ]
+        );
+    }
+}
@@ -1,16 +1,184 @@
-use teloxide::{prelude::ResponseResult, utils::command::BotCommands};
+use std::{fmt::Display, str::FromStr};
+
+use teloxide::{
+    prelude::ResponseResult,
+    utils::command::{BotCommands, ParseError},
+};
 use yoroolbot::{
     command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
     markdown_format,
+    storage::ButtonData,
 };
 
 use super::Command;
 
+/// Group of commands shown together in the help menu
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum HelpSection {
+    /// The full help text, all commands
+    #[default]
+    All,
+    Expenses,
+    Categories,
+    Reports,
+    /// Commands which are in none of the other sections
+    Other,
+}
+
+impl HelpSection {
+    /// Sections offered as buttons, in order
+    pub const MENU: [HelpSection; 5] = [
+        HelpSection::Expenses,
+        HelpSection::Categories,
+        HelpSection::Reports,
+        HelpSection::Other,
+        HelpSection::All,
+    ];
+
+    const EXPENSES: &[&str] = &[
+        "add_expense",
+        "list",
+        "frequent",
+        "confirm_expenses",
+        "pair_amount_lines",
+        "pending_expense",
+        "expense_template",
+        "clear_expenses",
+        "import",
+        "delete_matching",
+        "archive",
+        "unarchive",
+    ];
+    const CATEGORIES: &[&str] = &[
+        "categories",
+        "add_category",
+        "rename_category",
+        "remove_category",
+        "clear_categories",
+        "add_filter",
+        "add_literal_filter",
+        "add_words_filter",
+        "edit_filter",
+        "edit_words_filter",
+        "preview_filter",
+        "duplicate_filter",
+        "move_filter",
+        "remove_filter",
+        "remove_filters",
+        "list_filters",
+        "dump_filters",
+        "filter_stats",
+        "suggest_categories",
+        "move_options",
+        "recategorize",
+        "set_color",
+        "case_insensitive",
+        "also_other",
+        "uncategorized_name",
+    ];
+    const REPORTS: &[&str] = &[
+        "report",
+        "report_file",
+        "report_account",
+        "total",
+        "report_page_size",
+        "compare",
+        "set_goal",
+        "amount_grouping",
+        "decimal_comma",
+        "two_column_summary",
+    ];
+
+    fn title(&self) -> &'static str {
+        match self {
+            HelpSection::All => "📖 All commands",
+            HelpSection::Expenses => "💰 Expenses",
+            HelpSection::Categories => "📂 Categories",
+            HelpSection::Reports => "📊 Reports",
+            HelpSection::Other => "⚙️ Other",
+        }
+    }
+
+    /// Whether the command, named without the leading `/`, belongs to the section
+    pub fn contains(&self, command: &str) -> bool {
+        let grouped = [Self::EXPENSES, Self::CATEGORIES, Self::REPORTS];
+        match self {
+            HelpSection::All => true,
+            HelpSection::Expenses => Self::EXPENSES.contains(&command),
+            HelpSection::Categories => Self::CATEGORIES.contains(&command),
+            HelpSection::Reports => Self::REPORTS.contains(&command),
+            HelpSection::Other => !grouped.iter().any(|names| names.contains(&command)),
+        }
+    }
+}
+
+impl Display for HelpSection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            HelpSection::All => "all",
+            HelpSection::Expenses => "expenses",
+            HelpSection::Categories => "categories",
+            HelpSection::Reports => "reports",
+            HelpSection::Other => "other",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for HelpSection {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        HelpSection::MENU
+            .into_iter()
+            .find(|section| section.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                ParseError::IncorrectFormat(
+                    format!(
+                        "Unknown help section `{}`, expected one of: all, expenses, categories, reports, other",
+                        s
+                    )
+                    .into(),
+                )
+            })
+    }
+}
+
+/// Buttons switching the help message to each section except the shown one
+pub fn help_menu(shown: HelpSection) -> Vec<Vec<ButtonData>> {
+    let buttons: Vec<ButtonData> = HelpSection::MENU
+        .into_iter()
+        .filter(|section| *section != shown)
+        .map(|section| {
+            ButtonData::Callback(
+                section.title().to_string(),
+                CommandHelp {
+                    section: Some(section),
+                }
+                .to_command_string(false),
+            )
+        })
+        .collect();
+    buttons.chunks(2).map(|row| row.to_vec()).collect()
+}
+
+/// Descriptions of the section's commands, one per line as in the full help text
+fn section_descriptions(section: HelpSection) -> String {
+    Command::bot_commands()
+        .into_iter()
+        .filter(|command| section.contains(command.command.trim_start_matches('/')))
+        .map(|command| format!("{} — {}", command.command, command.description))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 #[derive(Default, Debug, Clone, PartialEq)]
-pub struct CommandHelp;
+pub struct CommandHelp {
+    pub section: Option<HelpSection>,
+}
 
 impl CommandTrait for CommandHelp {
-    type A = EmptyArg;
+    type A = HelpSection;
     type B = EmptyArg;
     type C = EmptyArg;
     type D = EmptyArg;
@@ -23,10 +191,10 @@ impl CommandTrait for CommandHelp {
     type Context = ();
 
     const NAME: &'static str = "help";
-    const PLACEHOLDERS: &[&'static str] = &[];
+    const PLACEHOLDERS: &[&'static str] = &["<section>"];
 
     fn from_arguments(
-        _: Option<Self::A>,
+        section: Option<Self::A>,
         _: Option<Self::B>,
         _: Option<Self::C>,
         _: Option<Self::D>,
@@ -36,21 +204,39 @@ impl CommandTrait for CommandHelp {
         _: Option<Self::H>,
         _: Option<Self::I>,
     ) -> Self {
-        CommandHelp
+        CommandHelp { section }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.section.as_ref()
     }
 
     async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        context: Self::Context,
+    ) -> ResponseResult<()> {
+        self.run1(target, context, &HelpSection::All).await
+    }
+
+    async fn run1(
         &self,
         target: &CommandReplyTarget,
         _context: Self::Context,
+        section: &HelpSection,
     ) -> ResponseResult<()> {
-        target
-            .send_markdown_message(markdown_format!(
+        let text = match section {
+            HelpSection::All => markdown_format!(
                 "To add expenses forward messages or send text with lines in format:\n\
-            `\\[\\<yyyy\\-mm\\-dd\\>\\] \\<description\\> \\<amount\\>`\n\n\
+            `\\[\\<yyyy\\-mm\\-dd\\>\\] \\<description\\> \\<amount\\>`\n\
+            Lines after a `/import\\_raw` line are all added as expenses, even the ones starting with `/`\n\n\
             {}",
                 Command::descriptions().to_string()
-            ))
+            ),
+            _ => markdown_format!("*{}*\n{}", section.title(), section_descriptions(*section)),
+        };
+        target
+            .markdown_message_with_menu(text, help_menu(*section))
             .await?;
         Ok(())
     }
@@ -61,3 +247,70 @@ impl From<CommandHelp> for crate::commands::Command {
         crate::commands::Command::Help(cmd)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_help_menu_callbacks() {
+        let callbacks: Vec<(String, String)> = help_menu(HelpSection::Expenses)
+            .into_iter()
+            .flatten()
+            .map(|button| match button {
+                ButtonData::Callback(label, data) => (label, data),
+                _ => panic!("help menu buttons should be callbacks"),
+            })
+            .collect();
+        assert_eq!(
+            callbacks,
+            vec![
+                ("📂 Categories".to_string(), "/help categories".to_string()),
+                ("📊 Reports".to_string(), "/help reports".to_string()),
+                ("⚙️ Other".to_string(), "/help other".to_string()),
+                ("📖 All commands".to_string(), "/help all".to_string()),
+            ]
+        );
+        // Each callback parses back to the section it shows
+        for (_, data) in callbacks {
+            let command = Command::parse(&data, "bot").unwrap();
+            let Command::Help(help) = command else {
+                panic!("{} should parse as /help", data);
+            };
+            assert_eq!(
+                help.section.map(|section| section.to_string()),
+                data.strip_prefix("/help ").map(str::to_string)
+            );
+        }
+    }
+
+    #[test]
+    fn test_help_sections_cover_commands() {
+        let commands: Vec<String> = Command::bot_commands()
+            .into_iter()
+            .map(|command| command.command.trim_start_matches('/').to_string())
+            .collect();
+        // Every grouped name is an existing command, in exactly one section
+        for names in [
+            HelpSection::EXPENSES,
+            HelpSection::CATEGORIES,
+            HelpSection::REPORTS,
+        ] {
+            for name in names {
+                assert!(
+                    commands.contains(&name.to_string()),
+                    "{} is not a command",
+                    name
+                );
+            }
+        }
+        for command in &commands {
+            let sections = HelpSection::MENU
+                .into_iter()
+                .filter(|section| *section != HelpSection::All && section.contains(command))
+                .count();
+            assert_eq!(sections, 1, "{} should be in one section", command);
+        }
+        assert!(section_descriptions(HelpSection::Reports).starts_with("/report — "));
+    }
+}
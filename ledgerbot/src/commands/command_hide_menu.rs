@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use teloxide::{payloads::SendMessageSetters, prelude::ResponseResult};
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown::MarkdownStringMessage,
+    markdown_format,
+};
+
+use crate::{
+    commands::{command_show_menu::CommandShowMenu, command_start::menu_markup},
+    storages::SettingsStorageTrait,
+};
+
+/// Hide the persistent menu keyboard in this chat
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandHideMenu;
+
+impl CommandTrait for CommandHideMenu {
+    type A = EmptyArg;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn SettingsStorageTrait>;
+
+    const NAME: &'static str = "hide_menu";
+    const PLACEHOLDERS: &[&'static str] = &[];
+
+    fn from_arguments(
+        _: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandHideMenu
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let mut settings = storage.get_chat_settings(target.chat.id).await;
+        settings.hide_menu = true;
+        storage.set_chat_settings(target.chat.id, settings).await;
+        target
+            .bot
+            .send_markdown_message(
+                target.chat.id,
+                markdown_format!(
+                    "✅ Menu buttons are hidden\\. Use {} to show them again\\.",
+                    CommandShowMenu.to_command_string(true)
+                ),
+            )
+            .reply_markup(menu_markup(true))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandHideMenu> for crate::commands::Command {
+    fn from(cmd: CommandHideMenu) -> Self {
+        crate::commands::Command::HideMenu(cmd)
+    }
+}
@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use teloxide::{net::Download, prelude::*, types::FileId};
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown::MarkdownString,
+    markdown_format, markdown_string,
+};
+
+use crate::{storages::ExpenseStorageTrait, utils::parse_csv::parse_csv_expenses};
+
+/// Larger documents are refused, a CSV of expenses is far smaller
+const MAX_IMPORT_FILE_SIZE: u32 = 1024 * 1024;
+/// At most this many skipped rows are listed in the reply
+const MAX_LISTED_SKIPPED_ROWS: usize = 10;
+
+/// Add expenses from a replied-to CSV document with `date,description,amount` rows
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandImport {
+    /// Document of the message the command replies to, not part of the command string
+    pub file_id: Option<FileId>,
+    /// Longer descriptions are cut, set like the document and not part of the command string
+    pub max_description_length: Option<usize>,
+}
+
+impl CommandImport {
+    /// Download the document as text
+    async fn download(bot: &Bot, file_id: &FileId) -> Result<String, MarkdownString> {
+        let file = bot
+            .get_file(file_id.clone())
+            .await
+            .map_err(|e| markdown_format!("❌ Failed to get the document: {}", e.to_string()))?;
+        if file.size > MAX_IMPORT_FILE_SIZE {
+            return Err(markdown_format!(
+                "❌ The document is too large, at most {} KB can be imported\\.",
+                (MAX_IMPORT_FILE_SIZE / 1024) as usize
+            ));
+        }
+        let mut content = Vec::new();
+        bot.download_file(&file.path, &mut content)
+            .await
+            .map_err(|e| {
+                markdown_format!("❌ Failed to download the document: {}", e.to_string())
+            })?;
+        String::from_utf8(content)
+            .map_err(|_| markdown_string!("❌ The document is not a UTF\\-8 text file\\."))
+    }
+}
+
+impl CommandTrait for CommandImport {
+    type A = EmptyArg;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn ExpenseStorageTrait>;
+
+    const NAME: &'static str = "import";
+    const PLACEHOLDERS: &[&'static str] = &[];
+
+    fn from_arguments(
+        _: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandImport::default()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let Some(file_id) = &self.file_id else {
+            target
+                .send_markdown_message(markdown_format!(
+                    "📥 Reply with {} to a CSV document with `date,description,amount` rows, dates as `yyyy\\-mm\\-dd`\\.",
+                    Self::default().to_command_string(false)
+                ))
+                .await?;
+            return Ok(());
+        };
+        let text = match Self::download(&target.bot, file_id).await {
+            Ok(text) => text,
+            Err(message) => {
+                target.send_markdown_message(message).await?;
+                return Ok(());
+            }
+        };
+
+        let import = parse_csv_expenses(&text, self.max_description_length);
+        let imported = import.expenses.len();
+        if imported > 0 {
+            storage.add_expenses(target.chat.id, import.expenses).await;
+        }
+        let mut message = markdown_format!(
+            "📥 Imported {} expense\\(s\\), skipped {} row\\(s\\)\\.",
+            imported,
+            import.skipped.len()
+        );
+        if let Some(max_length) = self.max_description_length
+            && import.truncated > 0
+        {
+            message = message
+                + markdown_format!(
+                    "\n⚠️ {} description\\(s\\) cut to {} characters\\.",
+                    import.truncated,
+                    max_length
+                );
+        }
+        if !import.skipped.is_empty() {
+            let mut skipped: Vec<String> = import
+                .skipped
+                .iter()
+                .take(MAX_LISTED_SKIPPED_ROWS)
+                .map(|(line, reason)| format!("line {}: {}", line, reason))
+                .collect();
+            if import.skipped.len() > MAX_LISTED_SKIPPED_ROWS {
+                skipped.push(format!(
+                    "… and {} more",
+                    import.skipped.len() - MAX_LISTED_SKIPPED_ROWS
+                ));
+            }
+            message = message + markdown_format!("\n{}", @code skipped.join("\n"));
+        }
+        target.send_markdown_message(message).await?;
+        Ok(())
+    }
+}
+
+impl From<CommandImport> for crate::commands::Command {
+    fn from(cmd: CommandImport) -> Self {
+        crate::commands::Command::Import(cmd)
+    }
+}
@@ -1,15 +1,56 @@
-use std::sync::Arc;
+use std::{fmt::Display, str::FromStr, sync::Arc};
 
-use teloxide::prelude::ResponseResult;
-use yoroolbot::command_trait::{CommandReplyTarget, CommandTrait, EmptyArg};
+use teloxide::{prelude::ResponseResult, utils::command::ParseError};
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown::MarkdownString,
+};
 
-use crate::{commands::expenses::format_expenses_chronological, storages::ExpenseStorageTrait};
+use crate::{
+    commands::expenses::{format_expenses_by_date, format_expenses_chronological},
+    storages::StorageTrait,
+};
+
+/// How `/list` presents the expenses
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum ListMode {
+    /// Flat chronological list in input format
+    #[default]
+    Flat,
+    /// Expenses grouped under date headers with daily subtotals
+    Daily,
+}
+
+impl Display for ListMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListMode::Flat => write!(f, "flat"),
+            ListMode::Daily => write!(f, "daily"),
+        }
+    }
+}
+
+impl FromStr for ListMode {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flat" => Ok(ListMode::Flat),
+            "daily" => Ok(ListMode::Daily),
+            _ => Err(ParseError::IncorrectFormat(
+                format!("Unknown list mode `{}`, expected `flat` or `daily`", s).into(),
+            )),
+        }
+    }
+}
 
 #[derive(Default, Debug, Clone, PartialEq)]
-pub struct CommandList;
+pub struct CommandList {
+    pub mode: Option<ListMode>,
+}
 
 impl CommandTrait for CommandList {
-    type A = EmptyArg;
+    type A = ListMode;
     type B = EmptyArg;
     type C = EmptyArg;
     type D = EmptyArg;
@@ -19,13 +60,13 @@ impl CommandTrait for CommandList {
     type H = EmptyArg;
     type I = EmptyArg;
 
-    type Context = Arc<dyn ExpenseStorageTrait>;
+    type Context = Arc<dyn StorageTrait>;
 
     const NAME: &'static str = "list";
-    const PLACEHOLDERS: &[&'static str] = &[];
+    const PLACEHOLDERS: &[&'static str] = &["<mode>"];
 
     fn from_arguments(
-        _: Option<Self::A>,
+        mode: Option<Self::A>,
         _: Option<Self::B>,
         _: Option<Self::C>,
         _: Option<Self::D>,
@@ -35,18 +76,48 @@ impl CommandTrait for CommandList {
         _: Option<Self::H>,
         _: Option<Self::I>,
     ) -> Self {
-        CommandList
+        CommandList { mode }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.mode.as_ref()
     }
 
     async fn run0(
         &self,
         target: &CommandReplyTarget,
         storage: Self::Context,
+    ) -> ResponseResult<()> {
+        self.run1(target, storage, &ListMode::default()).await
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        mode: &ListMode,
     ) -> ResponseResult<()> {
         let chat_id = target.chat.id;
-        let chat_expenses = storage.get_chat_expenses(chat_id).await;
+        let chat_expenses = storage
+            .clone()
+            .as_expense_storage()
+            .get_chat_expenses_sorted(chat_id)
+            .await;
+
+        // The flat list keeps the input format, so that it can be sent back to the bot
+        let formatted: Result<Vec<MarkdownString>, MarkdownString> = match mode {
+            ListMode::Flat => format_expenses_chronological(&chat_expenses),
+            ListMode::Daily => {
+                let settings = storage
+                    .clone()
+                    .as_settings_storage()
+                    .get_chat_settings(chat_id)
+                    .await;
+                format_expenses_by_date(&chat_expenses, settings.decimal_comma)
+            }
+        };
 
-        match format_expenses_chronological(&chat_expenses) {
+        match formatted {
             Ok(messages) => {
                 // List of expenses - send each message
                 for message in messages {
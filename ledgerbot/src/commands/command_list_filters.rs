@@ -0,0 +1,195 @@
+use std::{collections::HashMap, sync::Arc};
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format, markdown_string,
+};
+
+use crate::{commands::command_remove_filter::CommandRemoveFilter, storages::CategoryStorageTrait};
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandListFilters {
+    pub category: Option<String>,
+}
+
+impl CommandTrait for CommandListFilters {
+    type A = String;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn CategoryStorageTrait>;
+
+    const NAME: &'static str = "list_filters";
+    const PLACEHOLDERS: &[&'static str] = &["<category>"];
+
+    fn from_arguments(
+        category: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandListFilters { category }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.category.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let categories = storage
+            .get_chat_categories(target.chat.id)
+            .await
+            .unwrap_or_default();
+        send_filters_list(target, &categories, None).await
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        name: &String,
+    ) -> ResponseResult<()> {
+        let categories = storage
+            .get_chat_categories(target.chat.id)
+            .await
+            .unwrap_or_default();
+        if !categories.contains_key(name) {
+            target
+                .send_markdown_message(markdown_format!("❌ Category `{}` does not exist", name))
+                .await?;
+            return Ok(());
+        }
+        send_filters_list(target, &categories, Some(name)).await
+    }
+}
+
+async fn send_filters_list(
+    target: &CommandReplyTarget,
+    categories: &HashMap<String, Vec<String>>,
+    category: Option<&str>,
+) -> ResponseResult<()> {
+    let listing = format_filters_list(categories, category);
+    if listing.is_empty() {
+        target
+            .send_markdown_message(markdown_string!("📂 No filters defined yet\\."))
+            .await?;
+        return Ok(());
+    }
+    target
+        .send_markdown_message(markdown_format!(
+            "🔍 Filters with the commands removing them:\n{}",
+            @code listing
+        ))
+        .await?;
+    Ok(())
+}
+
+/// Filters of one or all categories as `(category, index, pattern)`
+/// The index is the filter position in storage, as expected by `/remove_filter`
+pub fn filter_entries(
+    categories: &HashMap<String, Vec<String>>,
+    category: Option<&str>,
+) -> Vec<(String, usize, String)> {
+    let mut names: Vec<_> = categories
+        .keys()
+        .filter(|name| category.is_none_or(|category| category == name.as_str()))
+        .collect();
+    names.sort();
+    names
+        .into_iter()
+        .flat_map(|name| {
+            categories[name]
+                .iter()
+                .enumerate()
+                .map(|(idx, pattern)| (name.clone(), idx, pattern.clone()))
+        })
+        .collect()
+}
+
+/// Format filters one per line, prefixed with the `/remove_filter` command for it
+pub fn format_filters_list(
+    categories: &HashMap<String, Vec<String>>,
+    category: Option<&str>,
+) -> String {
+    filter_entries(categories, category)
+        .into_iter()
+        .map(|(name, idx, pattern)| {
+            format!(
+                "{}  {}",
+                CommandRemoveFilter::new(Some(name), Some(idx))
+                    .to_command_string(false)
+                    .trim_end(),
+                pattern
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl From<CommandListFilters> for crate::commands::Command {
+    fn from(cmd: CommandListFilters) -> Self {
+        crate::commands::Command::ListFilters(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use teloxide::utils::command::BotCommands;
+
+    use super::*;
+    use crate::commands::Command;
+
+    #[test]
+    fn test_filter_indices_match_remove_filter() {
+        let mut categories = HashMap::new();
+        categories.insert(
+            "Food".to_string(),
+            vec!["(?i)coffee".to_string(), "(?i)tea".to_string()],
+        );
+        categories.insert("Car".to_string(), vec!["(?i)fuel".to_string()]);
+        categories.insert("Empty".to_string(), vec![]);
+
+        let listing = format_filters_list(&categories, None);
+        let lines: Vec<_> = listing.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "/remove_filter Car 0  (?i)fuel",
+                "/remove_filter Food 0  (?i)coffee",
+                "/remove_filter Food 1  (?i)tea",
+            ]
+        );
+
+        // Each listed command removes exactly the filter shown next to it
+        for line in lines {
+            let (command, pattern) = line.split_once("  ").unwrap();
+            let Ok(Command::RemoveFilter(cmd)) = Command::parse(command, "ledgerbot") else {
+                panic!("Failed to parse {}", command);
+            };
+            let filters = &categories[cmd.category.as_ref().unwrap()];
+            assert_eq!(filters[cmd.position.unwrap()], pattern);
+        }
+
+        assert_eq!(
+            format_filters_list(&categories, Some("Food")),
+            "/remove_filter Food 0  (?i)coffee\n/remove_filter Food 1  (?i)tea"
+        );
+        assert_eq!(format_filters_list(&categories, Some("Empty")), "");
+    }
+}
@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown::MarkdownString,
+    markdown_format, markdown_string,
+};
+
+use crate::storages::{BatchHistoryEntry, BatchStorageTrait};
+
+/// Number of entries shown when no count is given
+const DEFAULT_LOG_COUNT: usize = 10;
+/// Upper bound for the count to keep the reply within one message
+const MAX_LOG_COUNT: usize = 50;
+
+/// Show how the last commands of forwarded messages were processed
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandLog {
+    pub count: Option<usize>,
+}
+
+impl CommandTrait for CommandLog {
+    type A = usize;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn BatchStorageTrait>;
+
+    const NAME: &'static str = "log";
+    const PLACEHOLDERS: &[&'static str] = &["<count>"];
+
+    fn from_arguments(
+        count: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandLog { count }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.count.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        self.run1(target, storage, &DEFAULT_LOG_COUNT).await
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        count: &usize,
+    ) -> ResponseResult<()> {
+        let count = (*count).clamp(1, MAX_LOG_COUNT);
+        let entries = storage.get_history(target.chat.id, count).await;
+        if entries.is_empty() {
+            target
+                .send_markdown_message(markdown_string!(
+                    "🗒 No forwarded messages were processed yet\\."
+                ))
+                .await?;
+            return Ok(());
+        }
+        target
+            .send_markdown_message(markdown_format!(
+                "🗒 Last {} processed command\\(s\\):\n{}",
+                entries.len(),
+                format_command_log(&entries)
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+/// Format processed commands as a numbered list marking each as succeeded or failed
+pub fn format_command_log(entries: &[BatchHistoryEntry]) -> MarkdownString {
+    entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| match entry {
+            Ok(command) => markdown_format!("{}\\. ✅ `{}`\n", index + 1, command),
+            Err(error) => markdown_format!("{}\\. ❌ {}\n", index + 1, error),
+        })
+        .collect()
+}
+
+impl From<CommandLog> for crate::commands::Command {
+    fn from(cmd: CommandLog) -> Self {
+        crate::commands::Command::Log(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_command_log() {
+        let entries = vec![
+            Ok("/add_expense 2021-01-01 Coffee 5.5".to_string()),
+            Err("Line 2: no amount in 'Lunch'".to_string()),
+            Ok("/add_expense 2021-01-01 Taxi 3".to_string()),
+        ];
+        assert_eq!(
+            format_command_log(&entries).as_str(),
+            "1\\. ✅ `/add\\_expense 2021\\-01\\-01 Coffee 5\\.5`\n\
+             2\\. ❌ Line 2: no amount in 'Lunch'\n\
+             3\\. ✅ `/add\\_expense 2021\\-01\\-01 Taxi 3`\n"
+        );
+        assert!(format_command_log(&[]).is_empty());
+    }
+}
@@ -0,0 +1,285 @@
+use std::sync::Arc;
+
+use teloxide::{prelude::ResponseResult, types::ChatId};
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg, NoopCommand},
+    markdown::MarkdownString,
+    markdown_format, markdown_string,
+};
+
+use crate::{
+    commands::command_add_filter::with_equivalent_filter_warning,
+    menus::{select_category::select_category, select_category_filter::select_category_filter},
+    storages::{CategoryStorageTrait, StorageTrait},
+};
+
+/// Move a filter from one category to another
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandMoveFilter {
+    pub from_category: Option<String>,
+    pub position: Option<usize>,
+    pub to_category: Option<String>,
+}
+
+impl CommandTrait for CommandMoveFilter {
+    type A = String;
+    type B = usize;
+    type C = String;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "move_filter";
+    const PLACEHOLDERS: &[&'static str] = &["<from_category>", "<position>", "<to_category>"];
+
+    fn from_arguments(
+        a: Option<Self::A>,
+        b: Option<Self::B>,
+        c: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandMoveFilter {
+            from_category: a,
+            position: b,
+            to_category: c,
+        }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.from_category.as_ref()
+    }
+    fn param2(&self) -> Option<&Self::B> {
+        self.position.as_ref()
+    }
+    fn param3(&self) -> Option<&Self::C> {
+        self.to_category.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        select_category(
+            target,
+            &storage.as_category_storage(),
+            markdown_string!("🔀 Select Category to move filter from"),
+            |name| CommandMoveFilter {
+                from_category: Some(name.to_string()),
+                position: None,
+                to_category: None,
+            },
+            None::<NoopCommand>,
+        )
+        .await
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        from: &String,
+    ) -> ResponseResult<()> {
+        select_category_filter(
+            target,
+            &storage.as_category_storage(),
+            from,
+            markdown_format!("🔀 Select Filter to move from category `{}`", from),
+            |idx, _pattern| {
+                Some(CommandMoveFilter {
+                    from_category: Some(from.clone()),
+                    position: Some(idx),
+                    to_category: None,
+                })
+            },
+            Some(CommandMoveFilter::default()),
+        )
+        .await
+    }
+
+    async fn run2(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        from: &String,
+        idx: &usize,
+    ) -> ResponseResult<()> {
+        select_category(
+            target,
+            &storage.as_category_storage(),
+            markdown_format!(
+                "🔀 Select Category to move filter \\#{} of `{}` to",
+                *idx,
+                from
+            ),
+            |name| CommandMoveFilter {
+                from_category: Some(from.clone()),
+                position: Some(*idx),
+                to_category: Some(name.to_string()),
+            },
+            Some(CommandMoveFilter {
+                from_category: Some(from.clone()),
+                position: None,
+                to_category: None,
+            }),
+        )
+        .await
+    }
+
+    async fn run3(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        from: &String,
+        idx: &usize,
+        to: &String,
+    ) -> ResponseResult<()> {
+        match move_filter(
+            &storage.clone().as_category_storage(),
+            target.chat.id,
+            from,
+            *idx,
+            to,
+        )
+        .await
+        {
+            Ok(pattern) => {
+                let message = with_equivalent_filter_warning(
+                    &storage,
+                    target.chat.id,
+                    to,
+                    &pattern,
+                    markdown_format!(
+                        "✅ Filter `{}` moved from category `{}` to `{}`\\.",
+                        pattern.clone(),
+                        from,
+                        to
+                    ),
+                )
+                .await;
+                target.send_markdown_message(message).await?;
+            }
+            Err(e) => {
+                target.send_markdown_message(e).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Move the filter at `index` of category `from` to the end of category `to`
+/// The filter is added to the target before being removed from the source, so a failure
+/// never loses it. Returns the moved pattern
+pub async fn move_filter(
+    storage: &Arc<dyn CategoryStorageTrait>,
+    chat_id: ChatId,
+    from: &str,
+    index: usize,
+    to: &str,
+) -> Result<String, MarkdownString> {
+    let categories = storage.get_chat_categories(chat_id).await?;
+    let Some(patterns) = categories.get(from) else {
+        return Err(markdown_format!("❌ Category `{}` does not exist", from));
+    };
+    if !categories.contains_key(to) {
+        return Err(markdown_format!("❌ Category `{}` does not exist", to));
+    }
+    if from == to {
+        return Err(markdown_format!(
+            "❌ Filter is already in category `{}`",
+            from
+        ));
+    }
+    let Some(pattern) = patterns.get(index) else {
+        return Err(markdown_format!(
+            "❌ Invalid filter position `{}`, category `{}` has {} filters",
+            index,
+            from,
+            patterns.len()
+        ));
+    };
+    storage
+        .add_category_filter(chat_id, to.to_string(), pattern.clone())
+        .await
+        .map_err(|e| markdown_format!("❌ Failed to add filter: {}", e))?;
+    storage
+        .remove_category_filter(chat_id, from, pattern)
+        .await
+        .map_err(|e| markdown_format!("❌ Failed to remove filter: {}", e))?;
+    Ok(pattern.clone())
+}
+
+impl From<CommandMoveFilter> for crate::commands::Command {
+    fn from(cmd: CommandMoveFilter) -> Self {
+        crate::commands::Command::MoveFilter(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storages::CategoryStorage;
+
+    async fn storage_with_filters() -> Arc<dyn CategoryStorageTrait> {
+        let storage = CategoryStorage::new();
+        let chat_id = ChatId(1);
+        for (category, patterns) in [("Food", vec!["coffee", "taxi"]), ("Travel", vec!["bus"])] {
+            storage
+                .add_category(chat_id, category.to_string())
+                .await
+                .unwrap();
+            for pattern in patterns {
+                storage
+                    .add_category_filter(chat_id, category.to_string(), pattern.to_string())
+                    .await
+                    .unwrap();
+            }
+        }
+        Arc::new(storage)
+    }
+
+    #[tokio::test]
+    async fn test_move_filter() {
+        let storage = storage_with_filters().await;
+        let chat_id = ChatId(1);
+
+        let moved = move_filter(&storage, chat_id, "Food", 1, "Travel").await;
+        assert_eq!(moved.unwrap(), "taxi");
+        let categories = storage.get_chat_categories(chat_id).await.unwrap();
+        assert_eq!(categories["Food"], vec!["coffee"]);
+        assert_eq!(categories["Travel"], vec!["bus", "taxi"]);
+    }
+
+    #[tokio::test]
+    async fn test_move_filter_errors() {
+        let storage = storage_with_filters().await;
+        let chat_id = ChatId(1);
+
+        for (from, index, to) in [
+            ("Missing", 0, "Travel"),
+            ("Food", 0, "Missing"),
+            ("Food", 2, "Travel"),
+            ("Food", 0, "Food"),
+        ] {
+            assert!(
+                move_filter(&storage, chat_id, from, index, to)
+                    .await
+                    .is_err()
+            );
+        }
+        // Nothing was changed by the failed moves
+        let categories = storage.get_chat_categories(chat_id).await.unwrap();
+        assert_eq!(categories["Food"], vec!["coffee", "taxi"]);
+        assert_eq!(categories["Travel"], vec!["bus"]);
+    }
+}
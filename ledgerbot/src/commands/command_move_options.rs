@@ -0,0 +1,182 @@
+use std::{collections::HashMap, sync::Arc};
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown::MarkdownString,
+    markdown_format,
+};
+
+use crate::{
+    commands::{
+        report::{build_category_matchers, find_matching_categories},
+        run_blocking,
+    },
+    storages::StorageTrait,
+};
+
+/// List all categories whose filters match a description, with the matching filter
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandMoveOptions {
+    pub description: Option<String>,
+}
+
+impl CommandTrait for CommandMoveOptions {
+    type A = String;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "move_options";
+    const PLACEHOLDERS: &[&'static str] = &["<description>"];
+
+    fn from_arguments(
+        description: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandMoveOptions { description }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.description.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        _storage: Self::Context,
+    ) -> ResponseResult<()> {
+        target
+            .send_markdown_message(markdown_format!(
+                "🔀 Shows all categories matching a description\\.\n{}\n\
+                 Note: Use backslash to escape spaces in description: `My\\\\ Lunch`",
+                @raw self.usage_hint()
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        description: &String,
+    ) -> ResponseResult<()> {
+        let chat_id = target.chat.id;
+        let categories = match storage
+            .clone()
+            .as_category_storage()
+            .get_chat_categories(chat_id)
+            .await
+        {
+            Ok(categories) => categories,
+            Err(e) => {
+                target.send_markdown_message(e).await?;
+                return Ok(());
+            }
+        };
+        let settings = storage
+            .as_settings_storage()
+            .get_chat_settings(chat_id)
+            .await;
+        let options = run_blocking({
+            let description = description.clone();
+            move || move_options(&description, &categories, settings.case_insensitive_filters)
+        })
+        .await;
+        target
+            .send_markdown_message(format_move_options(description, &options))
+            .await?;
+        Ok(())
+    }
+}
+
+/// All categories whose filters match the description, in name order
+/// Returns `(category, matched_pattern)` pairs, the first matching filter of each category
+pub fn move_options(
+    description: &str,
+    categories: &HashMap<String, Vec<String>>,
+    case_insensitive: bool,
+) -> Vec<(String, String)> {
+    let matchers = build_category_matchers(categories, case_insensitive);
+    let mut options = find_matching_categories(description, &matchers);
+    options.sort();
+    options
+}
+
+/// Format the matching categories as a list with the matching filter of each
+pub fn format_move_options(description: &str, options: &[(String, String)]) -> MarkdownString {
+    if options.is_empty() {
+        return markdown_format!("🔀 No category matches `{}`\\.", description);
+    }
+    let mut message = markdown_format!("🔀 Categories matching `{}`:", description);
+    for (category, pattern) in options {
+        message = message + markdown_format!("\n• {} — `{}`", category, pattern);
+    }
+    message
+}
+
+impl From<CommandMoveOptions> for crate::commands::Command {
+    fn from(cmd: CommandMoveOptions) -> Self {
+        crate::commands::Command::MoveOptions(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn categories() -> HashMap<String, Vec<String>> {
+        HashMap::from([
+            (
+                "Food".to_string(),
+                vec!["(?i)lunch".to_string(), "(?i)coffee".to_string()],
+            ),
+            ("Drinks".to_string(), vec!["(?i)coffee".to_string()]),
+            ("Transport".to_string(), vec!["(?i)taxi".to_string()]),
+        ])
+    }
+
+    #[test]
+    fn test_move_options() {
+        assert!(move_options("Books", &categories(), false).is_empty());
+        assert_eq!(
+            move_options("Taxi home", &categories(), false),
+            vec![("Transport".to_string(), "(?i)taxi".to_string())]
+        );
+        assert_eq!(
+            move_options("Coffee beans", &categories(), false),
+            vec![
+                ("Drinks".to_string(), "(?i)coffee".to_string()),
+                ("Food".to_string(), "(?i)coffee".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_move_options() {
+        assert_eq!(
+            format_move_options("Books", &[]).as_str(),
+            "🔀 No category matches `Books`\\."
+        );
+        let options = move_options("Coffee", &categories(), false);
+        assert_eq!(
+            format_move_options("Coffee", &options).as_str(),
+            "🔀 Categories matching `Coffee`:\n• Drinks — `\\(?i\\)coffee`\n• Food — `\\(?i\\)coffee`"
+        );
+    }
+}
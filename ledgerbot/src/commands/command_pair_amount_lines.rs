@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format,
+};
+
+use crate::storages::SettingsStorageTrait;
+
+/// Read a line with only an amount as the amount of the line before it,
+/// for lists with descriptions and amounts on separate lines
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandPairAmountLines {
+    pub enabled: Option<bool>,
+}
+
+/// Describe how a line with only an amount is read
+fn amount_line_mode(enabled: bool) -> &'static str {
+    if enabled {
+        "the amount of the line before it, if that line has no amount"
+    } else {
+        "an expense of its own"
+    }
+}
+
+impl CommandTrait for CommandPairAmountLines {
+    type A = bool;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn SettingsStorageTrait>;
+
+    const NAME: &'static str = "pair_amount_lines";
+    const PLACEHOLDERS: &[&'static str] = &["<enabled>"];
+
+    fn from_arguments(
+        enabled: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandPairAmountLines { enabled }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.enabled.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let settings = storage.get_chat_settings(target.chat.id).await;
+        target
+            .send_markdown_message(markdown_format!(
+                "🔗 A line with only an amount is read as {}\\.\n{}",
+                amount_line_mode(settings.pair_amount_lines),
+                @raw self.usage_hint()
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        enabled: &bool,
+    ) -> ResponseResult<()> {
+        let mut settings = storage.get_chat_settings(target.chat.id).await;
+        settings.pair_amount_lines = *enabled;
+        storage.set_chat_settings(target.chat.id, settings).await;
+        target
+            .send_markdown_message(markdown_format!(
+                "✅ A line with only an amount will be read as {}\\.",
+                amount_line_mode(*enabled)
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandPairAmountLines> for crate::commands::Command {
+    fn from(cmd: CommandPairAmountLines) -> Self {
+        crate::commands::Command::PairAmountLines(cmd)
+    }
+}
@@ -0,0 +1,272 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use teloxide::{prelude::ResponseResult, types::ChatId};
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format, markdown_string,
+};
+
+use crate::{
+    commands::{
+        command_confirm_expenses::CommandConfirmExpenses,
+        confirmation::{ConfirmationOutcome, resolve_confirmation},
+        expenses::format_timestamp_with_time,
+    },
+    storages::{Expense, StorageTrait},
+};
+
+/// Answer to the confirmation of a single expense: store it or discard it
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandPendingExpense {
+    pub confirm: Option<bool>,
+    /// Id of the pending expense the prompt was sent for
+    pub id: Option<u64>,
+}
+
+impl CommandTrait for CommandPendingExpense {
+    type A = bool;
+    type B = u64;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "pending_expense";
+    const PLACEHOLDERS: &[&'static str] = &["<confirm>", "<id>"];
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.confirm.as_ref()
+    }
+
+    fn param2(&self) -> Option<&Self::B> {
+        self.id.as_ref()
+    }
+
+    fn from_arguments(
+        confirm: Option<Self::A>,
+        id: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandPendingExpense { confirm, id }
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        _storage: Self::Context,
+    ) -> ResponseResult<()> {
+        target
+            .send_markdown_message(markdown_format!(
+                "ℹ️ Answers the confirmation of a single expense, see {}\\.\n{}",
+                CommandConfirmExpenses::default().to_command_string(true),
+                @raw self.usage_hint()
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        _confirm: &bool,
+    ) -> ResponseResult<()> {
+        self.run0(target, storage).await
+    }
+
+    async fn run2(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        confirm: &bool,
+        id: &u64,
+    ) -> ResponseResult<()> {
+        let template = storage
+            .clone()
+            .as_settings_storage()
+            .get_chat_settings(target.chat.id)
+            .await
+            .expense_template;
+        let (outcome, expense) = store_pending_expense_if_confirmed(
+            storage,
+            target.chat.id,
+            *id,
+            *confirm,
+            target.callback_query_id.is_some(),
+            Utc::now().timestamp(),
+        )
+        .await;
+        let message = match (outcome, expense) {
+            (ConfirmationOutcome::Confirmed, Some(expense)) => markdown_format!(
+                "{}",
+                template.render(
+                    &format_timestamp_with_time(expense.timestamp),
+                    &expense.description,
+                    &expense.amount.to_string()
+                )
+            ),
+            (ConfirmationOutcome::Cancelled, Some(_)) => {
+                markdown_string!("❌ Expense discarded\\.")
+            }
+            _ => markdown_string!("⚠️ No expense is waiting for confirmation\\."),
+        };
+        target.send_markdown_message(message).await?;
+        Ok(())
+    }
+}
+
+/// Name of the pending confirmation of the expense with the given id,
+/// each prompt is confirmed on its own
+pub fn pending_expense_action(id: u64) -> String {
+    format!("{}_{}", CommandPendingExpense::NAME, id)
+}
+
+/// Take the chat's pending expense with the given id and store it if the answer confirms it
+/// Returns the pending expense, if there was one, whatever the outcome
+pub async fn store_pending_expense_if_confirmed(
+    storage: Arc<dyn StorageTrait>,
+    chat_id: ChatId,
+    id: u64,
+    confirm: bool,
+    from_callback: bool,
+    now: i64,
+) -> (ConfirmationOutcome, Option<Expense>) {
+    let outcome = resolve_confirmation(
+        storage.clone().as_confirmation_storage().as_ref(),
+        chat_id,
+        &pending_expense_action(id),
+        confirm,
+        from_callback,
+        now,
+    )
+    .await;
+    let expense = storage
+        .clone()
+        .as_pending_expense_storage()
+        .take_pending_expense(chat_id, id)
+        .await;
+    if outcome == ConfirmationOutcome::Confirmed
+        && let Some(expense) = &expense
+    {
+        storage
+            .as_expense_storage()
+            .add_expenses(chat_id, vec![expense.clone()])
+            .await;
+    }
+    (outcome, expense)
+}
+
+impl From<CommandPendingExpense> for crate::commands::Command {
+    fn from(cmd: CommandPendingExpense) -> Self {
+        crate::commands::Command::PendingExpense(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{storages::Storage, utils::amount::Amount};
+
+    async fn request_confirmation(
+        storage: &Arc<dyn StorageTrait>,
+        chat_id: ChatId,
+        description: &str,
+        now: i64,
+    ) -> u64 {
+        let id = storage
+            .clone()
+            .as_pending_expense_storage()
+            .add_pending_expense(
+                chat_id,
+                Expense {
+                    timestamp: now,
+                    description: description.to_string(),
+                    amount: Amount::from(5.5),
+                    ..Default::default()
+                },
+            )
+            .await;
+        storage
+            .clone()
+            .as_confirmation_storage()
+            .set_pending_confirmation(chat_id, &pending_expense_action(id), now)
+            .await;
+        id
+    }
+
+    #[tokio::test]
+    async fn test_pending_expense_confirm_and_cancel() {
+        let chat_id = ChatId(1);
+        let now = 1609459200;
+        let storage: Arc<dyn StorageTrait> = Arc::new(Storage::new());
+        let expense_storage = storage.clone().as_expense_storage();
+
+        // Confirm stores the expense
+        let id = request_confirmation(&storage, chat_id, "Coffee", now).await;
+        let (outcome, expense) =
+            store_pending_expense_if_confirmed(storage.clone(), chat_id, id, true, true, now).await;
+        assert_eq!(outcome, ConfirmationOutcome::Confirmed);
+        assert_eq!(expense.unwrap().description, "Coffee");
+        assert_eq!(expense_storage.get_chat_expenses(chat_id).await.len(), 1);
+
+        // Cancel discards it
+        let id = request_confirmation(&storage, chat_id, "Coffee", now).await;
+        let (outcome, expense) =
+            store_pending_expense_if_confirmed(storage.clone(), chat_id, id, false, true, now)
+                .await;
+        assert_eq!(outcome, ConfirmationOutcome::Cancelled);
+        assert!(expense.is_some());
+        assert_eq!(expense_storage.get_chat_expenses(chat_id).await.len(), 1);
+
+        // Nothing is left to confirm afterwards
+        let (outcome, expense) =
+            store_pending_expense_if_confirmed(storage.clone(), chat_id, id, true, true, now).await;
+        assert_eq!(outcome, ConfirmationOutcome::NotConfirmed);
+        assert!(expense.is_none());
+        assert_eq!(expense_storage.get_chat_expenses(chat_id).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_two_pending_expenses_at_once() {
+        let chat_id = ChatId(1);
+        let now = 1609459200;
+        let storage: Arc<dyn StorageTrait> = Arc::new(Storage::new());
+        let expense_storage = storage.clone().as_expense_storage();
+        let coffee = request_confirmation(&storage, chat_id, "Coffee", now).await;
+        let lunch = request_confirmation(&storage, chat_id, "Lunch", now).await;
+        assert_ne!(coffee, lunch);
+
+        // Confirming the first prompt stores its own expense
+        let (outcome, expense) =
+            store_pending_expense_if_confirmed(storage.clone(), chat_id, coffee, true, true, now)
+                .await;
+        assert_eq!(outcome, ConfirmationOutcome::Confirmed);
+        assert_eq!(expense.unwrap().description, "Coffee");
+
+        // The second prompt still has its expense to confirm
+        let (outcome, expense) =
+            store_pending_expense_if_confirmed(storage.clone(), chat_id, lunch, true, true, now)
+                .await;
+        assert_eq!(outcome, ConfirmationOutcome::Confirmed);
+        assert_eq!(expense.unwrap().description, "Lunch");
+        let descriptions: Vec<String> = expense_storage
+            .get_chat_expenses(chat_id)
+            .await
+            .into_iter()
+            .map(|e| e.description)
+            .collect();
+        assert_eq!(descriptions, ["Coffee", "Lunch"]);
+    }
+}
@@ -0,0 +1,205 @@
+use std::sync::Arc;
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown::MarkdownString,
+    markdown_format,
+    storage::ButtonData,
+};
+
+use crate::{
+    commands::{command_add_filter::CommandAddFilter, command_edit_filter::CommandEditFilter},
+    storages::StorageTrait,
+};
+
+/// Show a filter generated by the word selection menu before applying it,
+/// with a button to edit it in the input field. With a position the filter replaces
+/// the existing one, otherwise it is added
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandPreviewFilter {
+    pub category: Option<String>,
+    pub pattern: Option<String>,
+    pub position: Option<usize>,
+}
+
+/// Message showing the previewed filter as a code span
+pub fn format_filter_preview(category: &str, pattern: &str) -> MarkdownString {
+    markdown_format!(
+        "🔍 Filter for category `{}`:\n`{}`\nEdit it before applying if needed\\.",
+        category,
+        pattern
+    )
+}
+
+impl CommandTrait for CommandPreviewFilter {
+    type A = String;
+    type B = String;
+    type C = usize;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "preview_filter";
+    const PLACEHOLDERS: &[&'static str] = &["<category>", "<pattern>", "<position>"];
+
+    fn from_arguments(
+        category: Option<Self::A>,
+        pattern: Option<Self::B>,
+        position: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandPreviewFilter {
+            category,
+            pattern,
+            position,
+        }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.category.as_ref()
+    }
+
+    fn param2(&self) -> Option<&Self::B> {
+        self.pattern.as_ref()
+    }
+
+    fn param3(&self) -> Option<&Self::C> {
+        self.position.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        _storage: Self::Context,
+    ) -> ResponseResult<()> {
+        target
+            .send_markdown_message(markdown_format!(
+                "🔍 No words selected for the filter\\.\n{}",
+                @raw self.usage_hint()
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        _category: &String,
+    ) -> ResponseResult<()> {
+        self.run0(target, storage).await
+    }
+
+    async fn run2(
+        &self,
+        target: &CommandReplyTarget,
+        _storage: Self::Context,
+        category: &String,
+        pattern: &String,
+    ) -> ResponseResult<()> {
+        let add_command = CommandAddFilter {
+            category: Some(category.clone()),
+            pattern: Some(pattern.clone()),
+        };
+        target
+            .markdown_message_with_menu(
+                format_filter_preview(category, pattern),
+                vec![vec![ButtonData::SwitchInlineQuery(
+                    "✏️ Edit and add".to_string(),
+                    add_command.to_command_string(false),
+                )]],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn run3(
+        &self,
+        target: &CommandReplyTarget,
+        _storage: Self::Context,
+        category: &String,
+        pattern: &String,
+        position: &usize,
+    ) -> ResponseResult<()> {
+        let edit_command = CommandEditFilter {
+            category: Some(category.clone()),
+            position: Some(*position),
+            pattern: Some(pattern.clone()),
+        };
+        target
+            .markdown_message_with_menu(
+                format_filter_preview(category, pattern),
+                vec![vec![ButtonData::SwitchInlineQuery(
+                    "✏️ Edit and apply".to_string(),
+                    edit_command.to_command_string(false),
+                )]],
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandPreviewFilter> for crate::commands::Command {
+    fn from(cmd: CommandPreviewFilter) -> Self {
+        crate::commands::Command::PreviewFilter(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::menus::select_word::Words;
+
+    #[test]
+    fn test_preview_of_selected_words() {
+        let words = Words::new(vec![
+            "coffee".to_string(),
+            "c++".to_string(),
+            "a.b".to_string(),
+            "(x)".to_string(),
+        ]);
+        let pattern = words.build_pattern().unwrap();
+        assert_eq!(pattern, r"(?i)\b(coffee|c\+\+|a\.b|\(x\))\b");
+
+        // The pattern survives the trip through the preview button
+        let preview = CommandPreviewFilter {
+            category: Some("Food".to_string()),
+            pattern: Some(pattern.clone()),
+            position: None,
+        };
+        let (parsed,) = CommandPreviewFilter::parse_arguments(
+            preview
+                .to_command_string(false)
+                .trim_start_matches("/preview_filter")
+                .to_string(),
+        )
+        .unwrap();
+        assert_eq!(parsed, preview);
+
+        // Backslashes of the regex are escaped in the code span
+        let message = format_filter_preview("Food", &pattern);
+        assert!(
+            message
+                .as_str()
+                .contains(r"`\(?i\)\\b\(coffee\|c\\\+\\\+\|a\\\.b\|")
+        );
+        assert_eq!(
+            message.to_plain_text(),
+            format!(
+                "🔍 Filter for category Food:\n{}\nEdit it before applying if needed.",
+                pattern
+            )
+        );
+    }
+}
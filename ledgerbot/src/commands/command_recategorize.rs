@@ -0,0 +1,245 @@
+use std::{collections::HashMap, sync::Arc};
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format, markdown_string,
+};
+
+use crate::{
+    commands::{
+        report::{build_category_matchers, check_category_conflicts, find_matching_categories},
+        run_blocking,
+    },
+    storages::{Expense, StorageTrait},
+};
+
+/// How category assignment of expenses changed between two filter sets
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RecategorizeDiff {
+    /// Expenses that were in "Other" and now match a category
+    pub moved_from_other: usize,
+    /// Expenses that matched a category and are now in "Other"
+    pub moved_to_other: usize,
+    /// Expenses that now match a different set of categories
+    pub moved_between: usize,
+    /// Expenses that now match more than one category
+    pub conflicts: usize,
+}
+
+/// Compare category assignment of expenses under `before` and `after` filters
+pub fn compute_recategorize_diff(
+    expenses: &[Expense],
+    before: &HashMap<String, Vec<String>>,
+    after: &HashMap<String, Vec<String>>,
+    case_insensitive: bool,
+) -> RecategorizeDiff {
+    let before_matchers = build_category_matchers(before, case_insensitive);
+    let after_matchers = build_category_matchers(after, case_insensitive);
+    let category_names = |matches: Vec<(String, String)>| {
+        let mut names: Vec<String> = matches.into_iter().map(|(name, _)| name).collect();
+        names.sort();
+        names
+    };
+
+    let mut diff = RecategorizeDiff::default();
+    for expense in expenses {
+        let old = category_names(find_matching_categories(
+            &expense.description,
+            &before_matchers,
+        ));
+        let new = category_names(find_matching_categories(
+            &expense.description,
+            &after_matchers,
+        ));
+        match (old.is_empty(), new.is_empty()) {
+            (true, false) => diff.moved_from_other += 1,
+            (false, true) => diff.moved_to_other += 1,
+            (false, false) if old != new => diff.moved_between += 1,
+            _ => {}
+        }
+        if new.len() > 1 {
+            diff.conflicts += 1;
+        }
+    }
+    diff
+}
+
+/// Report how categorization changed with the last change of the filters
+/// A dry run: neither expenses nor filters are modified
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandRecategorize;
+
+impl CommandTrait for CommandRecategorize {
+    type A = EmptyArg;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "recategorize";
+    const PLACEHOLDERS: &[&'static str] = &[];
+
+    fn from_arguments(
+        _: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandRecategorize
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let chat_id = target.chat.id;
+        let expenses = storage
+            .clone()
+            .as_expense_storage()
+            .get_chat_expenses(chat_id)
+            .await;
+        let categories = storage
+            .clone()
+            .as_category_storage()
+            .get_chat_categories(chat_id)
+            .await
+            .unwrap_or_default();
+        // Without a change since the bot started the filters are compared with themselves
+        let previous = storage
+            .clone()
+            .as_category_storage()
+            .get_previous_categories(chat_id)
+            .await;
+        let baseline_note = if previous.is_some() {
+            markdown_string!("since the last filter change")
+        } else {
+            markdown_string!("with no filter changes recorded")
+        };
+        let baseline = previous.unwrap_or_else(|| categories.clone());
+        let expense_count = expenses.len();
+        let case_insensitive = storage
+            .as_settings_storage()
+            .get_chat_settings(chat_id)
+            .await
+            .case_insensitive_filters;
+        let (diff, conflict_message) = run_blocking(move || {
+            let diff =
+                compute_recategorize_diff(&expenses, &baseline, &categories, case_insensitive);
+            let conflict_message =
+                check_category_conflicts(&expenses, &categories, case_insensitive);
+            (diff, conflict_message)
+        })
+        .await;
+
+        target
+            .send_markdown_message(markdown_format!(
+                "🔄 *Recategorization* \\(dry run\\), {} expenses {}:\n\
+                 • Moved from Other into categories: {}\n\
+                 • Moved back to Other: {}\n\
+                 • Moved between categories: {}\n\
+                 • Matching several categories: {}",
+                expense_count,
+                @raw baseline_note,
+                diff.moved_from_other,
+                diff.moved_to_other,
+                diff.moved_between,
+                diff.conflicts
+            ))
+            .await?;
+
+        if let Some(conflict_message) = conflict_message {
+            target.markdown_message(conflict_message).await?;
+        }
+        Ok(())
+    }
+}
+
+impl From<CommandRecategorize> for crate::commands::Command {
+    fn from(cmd: CommandRecategorize) -> Self {
+        crate::commands::Command::Recategorize(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::amount::Amount;
+
+    fn expense(description: &str) -> Expense {
+        Expense {
+            description: description.to_string(),
+            amount: Amount::from(1.0),
+            timestamp: 1609459200,
+            ..Default::default()
+        }
+    }
+
+    fn filters(entries: &[(&str, &str)]) -> HashMap<String, Vec<String>> {
+        entries
+            .iter()
+            .map(|(name, pattern)| (name.to_string(), vec![pattern.to_string()]))
+            .collect()
+    }
+
+    #[test]
+    fn test_recategorize_diff() {
+        let expenses = vec![
+            expense("Coffee"),
+            expense("Taxi"),
+            expense("Bus ticket"),
+            expense("Cinema"),
+            expense("Book"),
+        ];
+        let before = filters(&[
+            ("Food", "(?i)coffee"),
+            ("Fun", "(?i)cinema|book"),
+            ("Transport", "(?i)taxi"),
+        ]);
+        let after = filters(&[
+            ("Food", "(?i)coffee"),
+            ("Fun", "(?i)cinema"),
+            ("Transport", "(?i)taxi|bus"),
+            ("Education", "(?i)book|cinema"),
+        ]);
+
+        let diff = compute_recategorize_diff(&expenses, &before, &after, false);
+        assert_eq!(
+            diff,
+            RecategorizeDiff {
+                // Bus ticket
+                moved_from_other: 1,
+                moved_to_other: 0,
+                // Cinema (Fun -> Fun + Education), Book (Fun -> Education)
+                moved_between: 2,
+                // Cinema
+                conflicts: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_recategorize_diff_removed_filters() {
+        let expenses = vec![expense("Coffee"), expense("Taxi")];
+        let before = filters(&[("Food", "(?i)coffee"), ("Transport", "(?i)taxi")]);
+
+        let diff = compute_recategorize_diff(&expenses, &before, &HashMap::new(), false);
+        assert_eq!(diff.moved_to_other, 2);
+        assert_eq!(
+            compute_recategorize_diff(&expenses, &before, &before, false),
+            RecategorizeDiff::default()
+        );
+    }
+}
@@ -9,6 +9,7 @@ use yoroolbot::{
 use crate::{
     menus::{select_category::select_category, update_category::update_category},
     storages::CategoryStorageTrait,
+    utils::fuzzy_match::did_you_mean,
 };
 
 #[derive(Default, Debug, Clone, PartialEq)]
@@ -114,7 +115,14 @@ impl CommandTrait for CommandRemoveCategory {
             return Ok(());
         }
         if let Err(e) = storage.remove_category(target.chat.id, name).await {
-            target.send_markdown_message(e).await?;
+            let categories = storage
+                .get_chat_categories(target.chat.id)
+                .await
+                .unwrap_or_default();
+            target
+                .send_markdown_message(e + did_you_mean(name, categories.keys()))
+                .await?;
+            return Ok(());
         }
         target
             .send_markdown_message(markdown_format!("✅ Category `{}` removed\\.", name))
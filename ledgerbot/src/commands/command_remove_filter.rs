@@ -3,7 +3,7 @@ use std::sync::Arc;
 use teloxide::prelude::ResponseResult;
 use yoroolbot::{
     command_trait::{CommandReplyTarget, CommandTrait, EmptyArg, NoopCommand},
-    markdown_format, markdown_string,
+    markdown_format,
 };
 
 use crate::{
@@ -84,7 +84,10 @@ impl CommandTrait for CommandRemoveFilter {
         select_category(
             target,
             &storage,
-            markdown_string!("🗑️ Select Category for removing filter"),
+            markdown_format!(
+                "🗑️ Select Category for removing filter\n{}",
+                @raw self.usage_hint()
+            ),
             |name| CommandRemoveFilter {
                 category: Some(name.to_string()),
                 position: None,
@@ -105,7 +108,11 @@ impl CommandTrait for CommandRemoveFilter {
             target,
             &storage,
             name,
-            markdown_format!("🗑️ Select Filter to remove from category `{}`", name),
+            markdown_format!(
+                "🗑️ Select Filter to remove from category `{}`\n{}",
+                name,
+                @raw self.usage_hint()
+            ),
             |idx, _pattern| {
                 Some(CommandRemoveFilter {
                     category: Some(name.clone()),
@@ -215,3 +222,22 @@ impl From<CommandRemoveFilter> for crate::commands::Command {
         crate::commands::Command::RemoveFilter(cmd)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_filter_usage_hint() {
+        assert_eq!(
+            CommandRemoveFilter::default().usage_hint().as_str(),
+            r"Usage: `/remove\_filter <category\> <position\> <confirm\>`"
+        );
+        assert_eq!(
+            CommandRemoveFilter::new(Some("Food".to_string()), None)
+                .usage_hint()
+                .as_str(),
+            r"Usage: `/remove\_filter Food <position\> <confirm\>`"
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+use teloxide::{prelude::ResponseResult, utils::command::ParseError};
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown::MarkdownString,
+    markdown_format,
+};
+
+use crate::storages::CategoryStorageTrait;
+
+/// Space separated filter positions: `0 2 5`
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct FilterPositions(pub Vec<usize>);
+
+impl Display for FilterPositions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let positions: Vec<String> = self.0.iter().map(usize::to_string).collect();
+        write!(f, "{}", positions.join(" "))
+    }
+}
+
+impl FromStr for FilterPositions {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split_whitespace()
+            .map(|word| {
+                word.parse::<usize>().map_err(|_| {
+                    ParseError::IncorrectFormat(
+                        format!("Invalid position `{}`, expected a number", word).into(),
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(FilterPositions)
+    }
+}
+
+/// Remove filters at the given positions, highest first so that removals don't shift
+/// the positions still to remove. Duplicate positions are removed once
+/// Returns the removed `(position, filter)` pairs in ascending order and the positions
+/// out of range
+pub fn remove_filters_at(
+    filters: &mut Vec<String>,
+    positions: &[usize],
+) -> (Vec<(usize, String)>, Vec<usize>) {
+    let mut positions = positions.to_vec();
+    positions.sort_unstable();
+    positions.dedup();
+    let (valid, invalid): (Vec<usize>, Vec<usize>) = positions
+        .into_iter()
+        .partition(|&position| position < filters.len());
+    let mut removed: Vec<(usize, String)> = valid
+        .into_iter()
+        .rev()
+        .map(|position| (position, filters.remove(position)))
+        .collect();
+    removed.reverse();
+    (removed, invalid)
+}
+
+/// Remove several filters of a category at once by their positions
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandRemoveFilters {
+    pub category: Option<String>,
+    pub positions: Option<FilterPositions>,
+}
+
+impl CommandTrait for CommandRemoveFilters {
+    type A = String;
+    type B = FilterPositions;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn CategoryStorageTrait>;
+
+    const NAME: &'static str = "remove_filters";
+    const PLACEHOLDERS: &[&'static str] = &["<category>", "<positions>"];
+    const MULTILINE_LAST_ARG: bool = true;
+
+    fn from_arguments(
+        category: Option<Self::A>,
+        positions: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandRemoveFilters {
+            category,
+            positions,
+        }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.category.as_ref()
+    }
+
+    fn param2(&self) -> Option<&Self::B> {
+        self.positions.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        _storage: Self::Context,
+    ) -> ResponseResult<()> {
+        target
+            .send_markdown_message(markdown_format!(
+                "🗑️ Remove several filters of a category by their positions\\.\n{}",
+                @raw self.usage_hint()
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        _name: &String,
+    ) -> ResponseResult<()> {
+        self.run0(target, storage).await
+    }
+
+    async fn run2(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        name: &String,
+        positions: &FilterPositions,
+    ) -> ResponseResult<()> {
+        let (removed, invalid) = match storage
+            .remove_category_filters_at(target.chat.id, name, &positions.0)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                target
+                    .send_markdown_message(markdown_format!("❌ {}", @raw e))
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let mut message = if removed.is_empty() {
+            markdown_format!("❌ No filters removed from category `{}`\\.", name)
+        } else {
+            let removed = removed
+                .iter()
+                .map(|(position, pattern)| format!("#{} {}", position, pattern))
+                .collect::<Vec<_>>()
+                .join("\n");
+            markdown_format!(
+                "✅ Filters removed from category `{}`:\n{}",
+                name,
+                @code removed
+            )
+        };
+        if !invalid.is_empty() {
+            let invalid = FilterPositions(invalid).to_string();
+            message = message
+                + MarkdownString::from("\n")
+                + markdown_format!("⚠️ No filters at positions: {}", invalid);
+        }
+        target.send_markdown_message(message).await?;
+        Ok(())
+    }
+}
+
+impl From<CommandRemoveFilters> for crate::commands::Command {
+    fn from(cmd: CommandRemoveFilters) -> Self {
+        crate::commands::Command::RemoveFilters(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filters() -> Vec<String> {
+        ["a", "b", "c", "d", "e", "f"]
+            .iter()
+            .map(|pattern| pattern.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_remove_filters_at() {
+        // Positions refer to the list before any removal, whatever their order
+        let mut list = filters();
+        let (removed, invalid) = remove_filters_at(&mut list, &[5, 0, 2]);
+        assert_eq!(
+            removed,
+            vec![
+                (0, "a".to_string()),
+                (2, "c".to_string()),
+                (5, "f".to_string())
+            ]
+        );
+        assert!(invalid.is_empty());
+        assert_eq!(list, ["b", "d", "e"]);
+
+        // Duplicates are removed once
+        let mut list = filters();
+        let (removed, _) = remove_filters_at(&mut list, &[1, 1, 3, 1]);
+        assert_eq!(removed, vec![(1, "b".to_string()), (3, "d".to_string())]);
+        assert_eq!(list, ["a", "c", "e", "f"]);
+
+        // Out of range positions are reported and the rest still removed
+        let mut list = filters();
+        let (removed, invalid) = remove_filters_at(&mut list, &[9, 4, 6, 9]);
+        assert_eq!(removed, vec![(4, "e".to_string())]);
+        assert_eq!(invalid, vec![6, 9]);
+        assert_eq!(list, ["a", "b", "c", "d", "f"]);
+
+        let mut list = Vec::new();
+        assert_eq!(remove_filters_at(&mut list, &[0]), (vec![], vec![0]));
+    }
+
+    #[test]
+    fn test_parse_remove_filters_arguments() {
+        let (command,) = CommandRemoveFilters::parse_arguments("Food 0 2  5".to_string()).unwrap();
+        assert_eq!(command.category.as_deref(), Some("Food"));
+        assert_eq!(command.positions, Some(FilterPositions(vec![0, 2, 5])));
+        assert!(CommandRemoveFilters::parse_arguments("Food 0 two".to_string()).is_err());
+    }
+}
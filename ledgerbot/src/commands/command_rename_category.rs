@@ -7,8 +7,9 @@ use yoroolbot::{
 };
 
 use crate::{
+    commands::command_uncategorized_name::check_category_name,
     menus::{select_category::select_category, update_category::update_category},
-    storages::CategoryStorageTrait,
+    storages::StorageTrait,
 };
 
 #[derive(Default, Debug, Clone, PartialEq)]
@@ -28,7 +29,7 @@ impl CommandTrait for CommandRenameCategory {
     type H = EmptyArg;
     type I = EmptyArg;
 
-    type Context = Arc<dyn CategoryStorageTrait>;
+    type Context = Arc<dyn StorageTrait>;
 
     const NAME: &'static str = "rename_category";
     const PLACEHOLDERS: &[&'static str] = &["<old_name>", "<new_name>"];
@@ -62,7 +63,7 @@ impl CommandTrait for CommandRenameCategory {
     ) -> ResponseResult<()> {
         select_category(
             target,
-            &storage,
+            &storage.as_category_storage(),
             markdown_string!("✏️ Select Category to rename"),
             |name| CommandRenameCategory {
                 old_name: Some(name.to_string()),
@@ -83,7 +84,7 @@ impl CommandTrait for CommandRenameCategory {
     ) -> ResponseResult<()> {
         update_category(
             target,
-            &storage,
+            &storage.as_category_storage(),
             old_name,
             markdown_format!("✏️ Renaming category `{}`", old_name),
             "✏️ Rename",
@@ -108,11 +109,22 @@ impl CommandTrait for CommandRenameCategory {
         old_name: &String,
         new_name: &String,
     ) -> ResponseResult<()> {
+        let settings = storage
+            .clone()
+            .as_settings_storage()
+            .get_chat_settings(target.chat.id)
+            .await;
+        if let Err(e) = check_category_name(new_name, settings.uncategorized_name()) {
+            target.send_markdown_message(e).await?;
+            return Ok(());
+        }
         if let Err(e) = storage
+            .as_category_storage()
             .rename_category(target.chat.id, old_name, new_name)
             .await
         {
             target.send_markdown_message(e).await?;
+            return Ok(());
         }
         target
             .send_markdown_message(markdown_format!(
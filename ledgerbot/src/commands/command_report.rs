@@ -1,20 +1,216 @@
 use std::sync::Arc;
 
-use teloxide::prelude::ResponseResult;
-use yoroolbot::command_trait::{CommandReplyTarget, CommandTrait, EmptyArg};
+use chrono::{TimeZone, Utc};
+use teloxide::{
+    Bot,
+    prelude::ResponseResult,
+    types::{Chat, ChatId, MessageId},
+    utils::command::ParseError,
+};
+use yoroolbot::{
+    command_trait::{
+        CommandReplyTarget, CommandTrait, EmptyArg, is_message_not_modified, noop_callback,
+    },
+    storage::ButtonData,
+};
 
 use crate::{
-    commands::report::{
-        check_category_conflicts, filter_category_expenses, format_category_summary,
-        format_single_category_report,
+    commands::{
+        report::{
+            assign_filters, check_category_conflicts, collapse_by_description,
+            filter_category_expenses, filter_min_amount, format_category_summary,
+            format_collapsed_category_report, format_goal_progress, format_min_amount_note,
+            format_shown_note, format_single_category_report,
+        },
+        run_blocking,
     },
-    storages::StorageTrait,
+    storages::{CachedReport, Expense, LastReportMessage, ReportCacheKey, StorageTrait},
+    utils::{amount::Amount, format_amount::format_amount_localized},
 };
 
+/// Render the report summary (or the category conflicts message) for a chat
+/// The result is cached until expenses, categories or display settings change
+pub async fn render_category_summary(
+    storage: Arc<dyn StorageTrait>,
+    chat_id: ChatId,
+    min_amount: Option<f64>,
+) -> CachedReport {
+    let expense_storage = storage.clone().as_expense_storage();
+    let category_storage = storage.clone().as_category_storage();
+    let settings = storage
+        .clone()
+        .as_settings_storage()
+        .get_chat_settings(chat_id)
+        .await;
+    let cache = storage.clone().as_report_cache_storage();
+    // The current month is the one of the chat's time zone
+    let now = Utc
+        .timestamp_opt(
+            settings.utc_offset.local_timestamp(Utc::now().timestamp()),
+            0,
+        )
+        .unwrap();
+    let key = ReportCacheKey {
+        expenses_version: expense_storage.get_chat_version(chat_id).await,
+        categories_version: category_storage.get_chat_version(chat_id).await,
+        min_amount,
+        amount_style: settings.amount_style(),
+        uncategorized_name: settings.uncategorized_name().to_string(),
+        case_insensitive_filters: settings.case_insensitive_filters,
+        monthly_goal: settings.monthly_goal,
+        two_column_summary: settings.two_column_summary,
+        current_month: now.format("%Y-%m").to_string(),
+    };
+    if let Some(report) = cache.get_cached_report(chat_id, &key).await {
+        return report;
+    }
+
+    let all_expenses = expense_storage.get_chat_expenses(chat_id).await;
+    let chat_expenses = filter_min_amount(&all_expenses, min_amount);
+    let chat_categories = category_storage
+        .get_chat_categories(chat_id)
+        .await
+        .unwrap_or_default();
+    let also_other = category_storage.get_also_other_categories(chat_id).await;
+
+    // Check for category conflicts before generating report
+    let rendered = run_blocking(move || {
+        if let Some(conflict_message) = check_category_conflicts(
+            &chat_expenses,
+            &chat_categories,
+            settings.case_insensitive_filters,
+        ) {
+            return Err(conflict_message);
+        }
+        // All expenses count, whatever the threshold of this report
+        let assignments = assign_filters(
+            &all_expenses,
+            &chat_categories,
+            settings.case_insensitive_filters,
+        );
+        let (summary, buttons) = format_category_summary(
+            &chat_expenses,
+            &chat_categories,
+            min_amount,
+            settings.amount_style(),
+            settings.uncategorized_name(),
+            &also_other,
+            settings.case_insensitive_filters,
+            settings.two_column_summary,
+        );
+        // The goal covers all of the month's expenses, regardless of the threshold
+        let summary = match settings.monthly_goal {
+            Some(goal) => {
+                format_goal_progress(
+                    &all_expenses,
+                    goal,
+                    now.timestamp(),
+                    settings.amount_style(),
+                ) + summary
+            }
+            None => summary,
+        };
+        Ok((summary, buttons, assignments))
+    })
+    .await;
+    let report = match rendered {
+        Ok((summary, buttons, assignments)) => {
+            // Expenses categorized for the first time add to the cumulative filter statistics
+            storage
+                .clone()
+                .as_filter_stats_storage()
+                .add_filter_assignments(chat_id, assignments)
+                .await;
+            (summary, buttons)
+        }
+        Err(conflict_message) => (conflict_message, vec![]),
+    };
+    cache.set_cached_report(chat_id, key, report.clone()).await;
+    report
+}
+
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct CommandReport {
     pub category: Option<String>,
     pub page: Option<usize>,
+    /// Only include expenses with amount at or above this threshold
+    pub min_amount: Option<f64>,
+    /// Show expenses with the same description as one row with a count and total
+    pub collapse: bool,
+}
+
+impl CommandReport {
+    /// Keyword introducing the minimum amount threshold: `/report min 10 [category] [page]`
+    pub const MIN_AMOUNT_KEYWORD: &'static str = "min";
+
+    /// Keyword collapsing same descriptions into one row: `/report collapse [category] [page]`
+    pub const COLLAPSE_KEYWORD: &'static str = "collapse";
+
+    /// Ends the keywords, so a category named like one is still reachable: `/report -- min`
+    pub const END_OF_KEYWORDS: &'static str = "--";
+
+    /// Parse report arguments, accepting optional leading `min <amount>` and `collapse`
+    /// keywords in any order before the regular positional arguments
+    /// A `--` word ends the keywords, the words after it are always positional
+    pub fn parse_report_arguments(args: String) -> Result<(Self,), ParseError> {
+        let mut rest = args.trim_start();
+        let mut min_amount = None;
+        let mut collapse = false;
+        loop {
+            let (word, after) = rest.split_once(' ').unwrap_or((rest, ""));
+            if word == Self::END_OF_KEYWORDS {
+                rest = after.trim_start();
+                break;
+            } else if word == Self::COLLAPSE_KEYWORD && !collapse {
+                collapse = true;
+                rest = after.trim_start();
+            } else if word == Self::MIN_AMOUNT_KEYWORD && !after.is_empty() && min_amount.is_none()
+            {
+                let after = after.trim_start();
+                let (amount, after) = after.split_once(' ').unwrap_or((after, ""));
+                let amount = amount
+                    .parse::<f64>()
+                    .map_err(|e| ParseError::Custom(Box::new(e)))?;
+                if !amount.is_finite() {
+                    return Err(ParseError::IncorrectFormat(
+                        format!("Minimum amount must be a finite number, got `{}`", amount).into(),
+                    ));
+                }
+                min_amount = Some(amount);
+                rest = after.trim_start();
+            } else {
+                break;
+            }
+        }
+        let (mut report,) = Self::parse_arguments(rest.to_string())?;
+        report.min_amount = min_amount;
+        report.collapse = collapse;
+        Ok((report,))
+    }
+
+    /// Build the command string including the `collapse` and `min <amount>` prefixes when set
+    /// Categories which would be read as a keyword are preceded by `--`
+    pub fn to_report_command_string(&self, complete: bool) -> String {
+        let command = self.to_command_string(complete);
+        let mut prefix = format!("/{}", Self::NAME);
+        if self.collapse {
+            prefix = format!("{} {}", prefix, Self::COLLAPSE_KEYWORD);
+        }
+        if let Some(min_amount) = self.min_amount {
+            prefix = format!("{} {} {}", prefix, Self::MIN_AMOUNT_KEYWORD, min_amount);
+        }
+        if self.category.as_deref().is_some_and(|category| {
+            [
+                Self::MIN_AMOUNT_KEYWORD,
+                Self::COLLAPSE_KEYWORD,
+                Self::END_OF_KEYWORDS,
+            ]
+            .contains(&category)
+        }) {
+            prefix = format!("{} {}", prefix, Self::END_OF_KEYWORDS);
+        }
+        command.replacen(&format!("/{}", Self::NAME), &prefix, 1)
+    }
 }
 
 impl CommandTrait for CommandReport {
@@ -44,7 +240,12 @@ impl CommandTrait for CommandReport {
         _: Option<Self::H>,
         _: Option<Self::I>,
     ) -> Self {
-        CommandReport { category, page }
+        CommandReport {
+            category,
+            page,
+            min_amount: None,
+            collapse: false,
+        }
     }
 
     fn param1(&self) -> Option<&Self::A> {
@@ -60,35 +261,32 @@ impl CommandTrait for CommandReport {
         target: &CommandReplyTarget,
         storage: Self::Context,
     ) -> ResponseResult<()> {
-        let chat_id = target.chat.id;
-        let chat_expenses = storage
-            .clone()
-            .as_expense_storage()
-            .get_chat_expenses(chat_id)
-            .await;
-        let chat_categories = storage
-            .clone()
-            .as_category_storage()
-            .get_chat_categories(chat_id)
-            .await
-            .unwrap_or_default();
-
-        // Check for category conflicts before generating report
-        if let Some(conflict_message) = check_category_conflicts(&chat_expenses, &chat_categories) {
-            target.markdown_message(conflict_message).await?;
-            return Ok(());
+        let (message, mut buttons) =
+            render_category_summary(storage.clone(), target.chat.id, self.min_amount).await;
+        if !buttons.is_empty() {
+            buttons.push(report_option_buttons(self.min_amount));
         }
 
-        // Show summary with category selection menu
-        let (message, buttons) = format_category_summary(&chat_expenses, &chat_categories);
-
-        if buttons.is_empty() {
+        let sent = if buttons.is_empty() {
             // No categories, just send the message
-            target.markdown_message(message).await?;
+            target.markdown_message(message).await?
         } else {
             // Send message with category selection menu
-            target.markdown_message_with_menu(message, buttons).await?;
-        }
+            target.markdown_message_with_menu(message, buttons).await?
+        };
+
+        // Remember the summary to refresh it when the data changes
+        storage
+            .as_report_cache_storage()
+            .set_last_report_message(
+                target.chat.id,
+                LastReportMessage {
+                    message_id: sent.id,
+                    min_amount: self.min_amount,
+                    sent_at: sent.date.timestamp(),
+                },
+            )
+            .await;
 
         Ok(())
     }
@@ -110,54 +308,111 @@ impl CommandTrait for CommandReport {
         category: &Self::A,
         page: &Self::B,
     ) -> ResponseResult<()> {
-        const RECORDS_PER_PAGE: usize = 25;
-
         let chat_id = target.chat.id;
         let chat_expenses = storage
             .clone()
             .as_expense_storage()
             .get_chat_expenses(chat_id)
             .await;
+        let chat_expenses = filter_min_amount(&chat_expenses, self.min_amount);
         let chat_categories = storage
             .clone()
             .as_category_storage()
             .get_chat_categories(chat_id)
             .await
             .unwrap_or_default();
+        let also_other = storage
+            .clone()
+            .as_category_storage()
+            .get_also_other_categories(chat_id)
+            .await;
+
+        let settings = storage
+            .clone()
+            .as_settings_storage()
+            .get_chat_settings(chat_id)
+            .await;
+        let records_per_page = settings.report_page_size();
 
         // Filter expenses for the category
-        let filtered_expenses =
-            filter_category_expenses(category, &chat_expenses, &chat_categories);
+        // Collapsed reports paginate the rows of distinct descriptions
+        let (filtered_expenses, collapsed_rows) = run_blocking({
+            let category = category.clone();
+            let uncategorized_name = settings.uncategorized_name().to_string();
+            let case_insensitive = settings.case_insensitive_filters;
+            let collapse = self.collapse;
+            move || {
+                let filtered_expenses = filter_category_expenses(
+                    &category,
+                    &chat_expenses,
+                    &chat_categories,
+                    &uncategorized_name,
+                    &also_other,
+                    case_insensitive,
+                );
+                let collapsed_rows = collapse.then(|| collapse_by_description(&filtered_expenses));
+                let filtered_expenses: Vec<Expense> =
+                    filtered_expenses.into_iter().cloned().collect();
+                (filtered_expenses, collapsed_rows)
+            }
+        })
+        .await;
+        let filtered_expenses: Vec<&Expense> = filtered_expenses.iter().collect();
 
         // Calculate pagination
-        let total_expenses = filtered_expenses.len();
-        let total_pages = total_expenses.div_ceil(RECORDS_PER_PAGE);
+        let total_rows = match &collapsed_rows {
+            Some(rows) => rows.len(),
+            None => filtered_expenses.len(),
+        };
+        let total_pages = total_rows.div_ceil(records_per_page);
         let max_page = total_pages.saturating_sub(1);
         let page_number = page.min(&max_page);
 
         // Calculate total amount for the category
-        let total_amount: f64 = filtered_expenses.iter().map(|e| e.amount).sum();
+        let total_amount: Amount = filtered_expenses.iter().map(|e| e.amount).sum();
+        let total_amount =
+            format_amount_localized(total_amount.as_f64(), 2, settings.amount_style());
 
         // Format category report with pagination (just the data)
-        let report_text =
-            format_single_category_report(&filtered_expenses, *page_number, RECORDS_PER_PAGE);
+        let report_text = match &collapsed_rows {
+            Some(rows) => format_collapsed_category_report(
+                rows,
+                *page_number,
+                records_per_page,
+                settings.amount_style(),
+            ),
+            None => format_single_category_report(
+                &filtered_expenses,
+                *page_number,
+                records_per_page,
+                settings.amount_style(),
+            ),
+        };
 
-        // Build header with category name, page info, and total
+        // Build header with category name, threshold, page info, and total
+        let min_amount_note = format_min_amount_note(self.min_amount);
         let message = if filtered_expenses.is_empty() {
-            yoroolbot::markdown_format!("*{}*: No expenses in this category\\.", category)
+            yoroolbot::markdown_format!(
+                "*{}*{}: No expenses in this category\\.",
+                category,
+                @raw min_amount_note
+            )
         } else if total_pages > 1 {
             yoroolbot::markdown_format!(
-                "*{}*, total `{}`,  page {}/{}\n{}",
+                "*{}*{}, total `{}`,  page {}/{}{}\n{}",
                 category,
+                @raw min_amount_note,
                 total_amount,
                 page_number + 1,
                 total_pages,
+                format_shown_note(*page_number, records_per_page, total_rows),
                 @code report_text
             )
         } else {
             yoroolbot::markdown_format!(
-                "*{}*, total `{}`\n{}",
+                "*{}*{}, total `{}`\n{}",
                 category,
+                @raw min_amount_note,
                 total_amount,
                 @code report_text
             )
@@ -175,14 +430,16 @@ impl CommandTrait for CommandReport {
                 CommandReport {
                     category: Some(category.clone()),
                     page: Some(page_number - 1),
+                    min_amount: self.min_amount,
+                    collapse: self.collapse,
                 }
-                .to_command_string(false),
+                .to_report_command_string(false),
             ));
         } else {
             // Inactive previous button
             page_nav_row.push(yoroolbot::storage::ButtonData::Callback(
                 "◁ Prev".to_string(),
-                "noop".to_string(),
+                noop_callback(Some("Already on the first page")),
             ));
         }
 
@@ -193,40 +450,364 @@ impl CommandTrait for CommandReport {
                 CommandReport {
                     category: Some(category.clone()),
                     page: Some(page_number + 1),
+                    min_amount: self.min_amount,
+                    collapse: self.collapse,
                 }
-                .to_command_string(false),
+                .to_report_command_string(false),
             ));
         } else {
             // Inactive next button
             page_nav_row.push(yoroolbot::storage::ButtonData::Callback(
                 "Next ▷".to_string(),
-                "noop".to_string(),
+                noop_callback(Some("No more pages")),
             ));
         }
 
         nav_buttons.push(page_nav_row);
 
-        // Back button row
-        let back_button_row = vec![yoroolbot::storage::ButtonData::Callback(
-            "↩️ Back to Summary".to_string(),
-            CommandReport {
-                category: None,
-                page: None,
-            }
-            .to_command_string(false),
-        )];
+        // Collapse toggle and back button row
+        let toggle_text = if self.collapse {
+            "📜 Expand"
+        } else {
+            "🗜 Collapse"
+        };
+        let back_button_row = vec![
+            yoroolbot::storage::ButtonData::Callback(
+                toggle_text.to_string(),
+                CommandReport {
+                    category: Some(category.clone()),
+                    page: None,
+                    min_amount: self.min_amount,
+                    collapse: !self.collapse,
+                }
+                .to_report_command_string(false),
+            ),
+            yoroolbot::storage::ButtonData::Callback(
+                "↩️ Back to Summary".to_string(),
+                CommandReport {
+                    category: None,
+                    page: None,
+                    min_amount: self.min_amount,
+                    collapse: false,
+                }
+                .to_report_command_string(false),
+            ),
+        ];
         nav_buttons.push(back_button_row);
 
         target
             .markdown_message_with_menu(message, nav_buttons)
             .await?;
 
+        // The last summary now shows a category, it is no longer refreshed
+        let report_cache = storage.as_report_cache_storage();
+        if let Some(last) = report_cache.get_last_report_message(chat_id).await
+            && target.msg_id == Some(last.message_id)
+        {
+            report_cache.clear_last_report_message(chat_id).await;
+        }
+
         Ok(())
     }
 }
 
+/// Telegram doesn't allow editing messages older than 48 hours
+pub const REPORT_EDIT_LIMIT_SECONDS: i64 = 48 * 60 * 60;
+
+/// How to bring the last report summary up to date
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportRefresh {
+    /// Edit the summary message in place
+    Edit(MessageId),
+    /// The message is too old to edit, send a new summary
+    Resend,
+}
+
+/// Decide how to refresh the last report summary at `now`
+pub fn report_refresh(last: &LastReportMessage, now: i64) -> ReportRefresh {
+    if now - last.sent_at < REPORT_EDIT_LIMIT_SECONDS {
+        ReportRefresh::Edit(last.message_id)
+    } else {
+        ReportRefresh::Resend
+    }
+}
+
+/// Update the chat's last report summary after its data changed
+/// Sends a new summary if the old one is too old or can't be edited anymore
+pub async fn refresh_last_report(
+    bot: Bot,
+    chat: Chat,
+    storage: Arc<dyn StorageTrait>,
+    now: i64,
+) -> ResponseResult<()> {
+    let Some(last) = storage
+        .clone()
+        .as_report_cache_storage()
+        .get_last_report_message(chat.id)
+        .await
+    else {
+        return Ok(());
+    };
+    let report = CommandReport {
+        min_amount: last.min_amount,
+        ..Default::default()
+    };
+    let target = CommandReplyTarget::new(bot, chat, storage.clone().as_callback_data_storage());
+    if let ReportRefresh::Edit(message_id) = report_refresh(&last, now) {
+        match report
+            .run0(&target.clone().with_msg_id(message_id), storage.clone())
+            .await
+        {
+            Err(e) if !is_message_not_modified(&e) => {
+                log::warn!("Failed to edit the last report, sending a new one: {}", e);
+            }
+            _ => return Ok(()),
+        }
+    }
+    report.run0(&target, storage).await
+}
+
+/// Amount thresholds offered below the report summary
+pub const MIN_AMOUNT_PRESETS: [Option<f64>; 3] = [None, Some(10.0), Some(100.0)];
+
+/// Buttons re-running the report summary with another amount threshold
+/// The current threshold is marked and its button does nothing
+pub fn report_option_buttons(min_amount: Option<f64>) -> Vec<ButtonData> {
+    MIN_AMOUNT_PRESETS
+        .iter()
+        .map(|preset| {
+            let label = match preset {
+                Some(threshold) => format!("≥ {}", threshold),
+                None => "All amounts".to_string(),
+            };
+            if *preset == min_amount {
+                ButtonData::Callback(format!("✓ {}", label), noop_callback(None))
+            } else {
+                ButtonData::Callback(
+                    label,
+                    CommandReport {
+                        min_amount: *preset,
+                        ..Default::default()
+                    }
+                    .to_report_command_string(false),
+                )
+            }
+        })
+        .collect()
+}
+
 impl From<CommandReport> for crate::commands::Command {
     fn from(cmd: CommandReport) -> Self {
         crate::commands::Command::Report(cmd)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use yoroolbot::markdown_string;
+
+    use super::*;
+    use crate::{storages::Storage, utils::format_amount::AmountStyle};
+
+    fn expense(description: &str, amount: f64) -> Expense {
+        Expense {
+            timestamp: 1609459200,
+            description: description.to_string(),
+            amount: Amount::from(amount),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_report_refresh_edit_or_resend() {
+        let sent_at = 1609459200;
+        let last = LastReportMessage {
+            message_id: MessageId(7),
+            min_amount: None,
+            sent_at,
+        };
+        assert_eq!(
+            report_refresh(&last, sent_at),
+            ReportRefresh::Edit(MessageId(7))
+        );
+        assert_eq!(
+            report_refresh(&last, sent_at + REPORT_EDIT_LIMIT_SECONDS - 1),
+            ReportRefresh::Edit(MessageId(7))
+        );
+        assert_eq!(
+            report_refresh(&last, sent_at + REPORT_EDIT_LIMIT_SECONDS),
+            ReportRefresh::Resend
+        );
+        assert_eq!(
+            report_refresh(&last, sent_at + 7 * 24 * 60 * 60),
+            ReportRefresh::Resend
+        );
+    }
+
+    #[test]
+    fn test_report_option_buttons() {
+        let callbacks = |min_amount| {
+            report_option_buttons(min_amount)
+                .into_iter()
+                .map(|button| match button {
+                    ButtonData::Callback(label, data) => (label, data),
+                    _ => panic!("Unexpected button type"),
+                })
+                .collect::<Vec<_>>()
+        };
+        let pair = |label: &str, data: &str| (label.to_string(), data.to_string());
+
+        assert_eq!(
+            callbacks(None),
+            vec![
+                pair("✓ All amounts", "noop"),
+                pair("≥ 10", "/report min 10 "),
+                pair("≥ 100", "/report min 100 "),
+            ]
+        );
+        assert_eq!(
+            callbacks(Some(10.0)),
+            vec![
+                pair("All amounts", "/report "),
+                pair("✓ ≥ 10", "noop"),
+                pair("≥ 100", "/report min 100 "),
+            ]
+        );
+        // A custom threshold marks none of the presets
+        assert!(
+            callbacks(Some(5.0))
+                .iter()
+                .all(|(label, _)| !label.starts_with('✓'))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_report_cache_invalidation() {
+        let storage: Arc<dyn StorageTrait> = Arc::new(Storage::new());
+        let chat_id = ChatId(1);
+        let expense_storage = storage.clone().as_expense_storage();
+        let category_storage = storage.clone().as_category_storage();
+        expense_storage
+            .add_expense(chat_id, expense("Coffee", 5.0))
+            .await;
+        category_storage
+            .add_category(chat_id, "Food".to_string())
+            .await
+            .unwrap();
+
+        let (_, buttons) = render_category_summary(storage.clone(), chat_id, None).await;
+        assert!(!buttons.is_empty());
+
+        // Unchanged state serves the cached report: replace it with a marker to observe
+        let key = ReportCacheKey {
+            expenses_version: expense_storage.get_chat_version(chat_id).await,
+            categories_version: category_storage.get_chat_version(chat_id).await,
+            min_amount: None,
+            amount_style: AmountStyle::default(),
+            uncategorized_name: "Other".to_string(),
+            case_insensitive_filters: false,
+            monthly_goal: None,
+            two_column_summary: false,
+            current_month: Utc::now().format("%Y-%m").to_string(),
+        };
+        let cache = storage.clone().as_report_cache_storage();
+        assert!(cache.get_cached_report(chat_id, &key).await.is_some());
+        let marker = markdown_string!("cached");
+        cache
+            .set_cached_report(chat_id, key, (marker.clone(), vec![]))
+            .await;
+        let (message, _) = render_category_summary(storage.clone(), chat_id, None).await;
+        assert_eq!(message, marker);
+
+        // Another threshold is a different key
+        let (message, _) = render_category_summary(storage.clone(), chat_id, Some(1.0)).await;
+        assert_ne!(message, marker);
+
+        // Mutating expenses invalidates the cache
+        expense_storage
+            .add_expense(chat_id, expense("Lunch", 10.0))
+            .await;
+        let (message, _) = render_category_summary(storage.clone(), chat_id, None).await;
+        assert_ne!(message, marker);
+
+        // Mutating categories invalidates the cache
+        let key = ReportCacheKey {
+            expenses_version: expense_storage.get_chat_version(chat_id).await,
+            categories_version: category_storage.get_chat_version(chat_id).await,
+            min_amount: None,
+            amount_style: AmountStyle::default(),
+            uncategorized_name: "Other".to_string(),
+            case_insensitive_filters: false,
+            monthly_goal: None,
+            two_column_summary: false,
+            current_month: Utc::now().format("%Y-%m").to_string(),
+        };
+        cache
+            .set_cached_report(chat_id, key, (marker.clone(), vec![]))
+            .await;
+        category_storage
+            .add_category_filter(chat_id, "Food".to_string(), "(?i)coffee".to_string())
+            .await
+            .unwrap();
+        let (message, _) = render_category_summary(storage.clone(), chat_id, None).await;
+        assert_ne!(message, marker);
+    }
+
+    #[tokio::test]
+    async fn test_filter_totals_accumulate_over_reports() {
+        let storage: Arc<dyn StorageTrait> = Arc::new(Storage::new());
+        let chat_id = ChatId(1);
+        let expense_storage = storage.clone().as_expense_storage();
+        let category_storage = storage.clone().as_category_storage();
+        category_storage
+            .add_category(chat_id, "Food".to_string())
+            .await
+            .unwrap();
+        for pattern in ["(?i)coffee", "(?i)co"] {
+            category_storage
+                .add_category_filter(chat_id, "Food".to_string(), pattern.to_string())
+                .await
+                .unwrap();
+        }
+        for description in ["Coffee", "Cocoa", "Taxi"] {
+            expense_storage
+                .add_expense(chat_id, expense(description, 5.0))
+                .await;
+        }
+        let totals = || async {
+            storage
+                .clone()
+                .as_filter_stats_storage()
+                .get_filter_totals(chat_id)
+                .await
+        };
+        let filter = |pattern: &str| ("Food".to_string(), pattern.to_string());
+
+        // Each expense counts for the first filter matching it, uncategorized ones for none
+        render_category_summary(storage.clone(), chat_id, None).await;
+        let first = totals().await;
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[&filter("(?i)coffee")], 1);
+        assert_eq!(first[&filter("(?i)co")], 1);
+
+        // A cached report categorizes nothing new
+        render_category_summary(storage.clone(), chat_id, None).await;
+        assert_eq!(totals().await, first);
+
+        // The next generated report adds only the new expenses, a duplicate one included
+        expense_storage
+            .add_expense(chat_id, expense("Coffee beans", 8.0))
+            .await;
+        expense_storage
+            .add_expense(chat_id, expense("Cocoa", 5.0))
+            .await;
+        render_category_summary(storage.clone(), chat_id, None).await;
+        let second = totals().await;
+        assert_eq!(second[&filter("(?i)coffee")], 2);
+        assert_eq!(second[&filter("(?i)co")], 2);
+
+        // Another report of the same expenses, with a threshold, counts nothing again
+        render_category_summary(storage.clone(), chat_id, Some(1.0)).await;
+        assert_eq!(totals().await, second);
+    }
+}
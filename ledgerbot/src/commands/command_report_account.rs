@@ -0,0 +1,268 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown::MarkdownString,
+    markdown_format, markdown_string,
+    storage::ButtonData,
+};
+
+use crate::{
+    commands::{
+        report::{category_totals, format_summary_table},
+        run_blocking,
+    },
+    storages::{Expense, StorageTrait},
+    utils::{
+        amount::Amount,
+        format_amount::{AmountStyle, format_amount_localized},
+    },
+};
+
+/// Row of the account summary for expenses without an `@account` word
+const NO_ACCOUNT: &str = "(no account)";
+
+/// Total amount of each payment account in name order, followed by expenses without one
+pub fn account_totals(expenses: &[Expense]) -> Vec<(String, Amount)> {
+    let mut totals: BTreeMap<&str, Amount> = BTreeMap::new();
+    let mut without_account: Option<Amount> = None;
+    for expense in expenses {
+        let total = match &expense.account {
+            Some(account) => totals.entry(account).or_default(),
+            None => without_account.get_or_insert_default(),
+        };
+        *total += expense.amount;
+    }
+    totals
+        .into_iter()
+        .map(|(account, total)| (account.to_string(), total))
+        .chain(without_account.map(|total| (NO_ACCOUNT.to_string(), total)))
+        .collect()
+}
+
+/// Summary table of `(name, total)` rows in the chat's amount style
+fn totals_table(totals: &[(String, Amount)], style: AmountStyle) -> Vec<String> {
+    let format = |amount: Amount| format_amount_localized(amount.as_f64(), 2, style);
+    let rows: Vec<(String, String)> = totals
+        .iter()
+        .map(|(name, total)| (name.clone(), format(*total)))
+        .collect();
+    let total = totals.iter().map(|(_, total)| *total).sum();
+    format_summary_table(&rows, &format(total))
+}
+
+/// Summarize expenses by payment account, or one account's expenses by category
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandReportAccount {
+    pub account: Option<String>,
+}
+
+impl CommandTrait for CommandReportAccount {
+    type A = String;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "report_account";
+    const PLACEHOLDERS: &[&'static str] = &["<account>"];
+
+    fn from_arguments(
+        account: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandReportAccount { account }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.account.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let expenses = storage
+            .clone()
+            .as_expense_storage()
+            .get_chat_expenses(target.chat.id)
+            .await;
+        if !expenses.iter().any(|expense| expense.account.is_some()) {
+            target
+                .send_markdown_message(markdown_string!(
+                    "💳 No expenses with an account yet\\. Add a word like `@card` or `@cash` to the description of an expense\\."
+                ))
+                .await?;
+            return Ok(());
+        }
+        let settings = storage
+            .as_settings_storage()
+            .get_chat_settings(target.chat.id)
+            .await;
+
+        let totals = account_totals(&expenses);
+        let buttons: Vec<Vec<ButtonData>> = totals
+            .iter()
+            .filter(|(account, _)| account != NO_ACCOUNT)
+            .map(|(account, _)| {
+                ButtonData::Callback(
+                    format!("💳 {}", account),
+                    CommandReportAccount {
+                        account: Some(account.clone()),
+                    }
+                    .to_command_string(false),
+                )
+            })
+            .collect::<Vec<_>>()
+            .chunks(3)
+            .map(|row| row.to_vec())
+            .collect();
+        target
+            .markdown_message_with_menu(
+                markdown_format!(
+                    "💳 *Expenses by account*\n\n{}\n\nSelect an account to see its categories:",
+                    @raw MarkdownString::code_block_from_lines(&totals_table(
+                        &totals,
+                        settings.amount_style()
+                    ))
+                ),
+                buttons,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        account: &String,
+    ) -> ResponseResult<()> {
+        let chat_id = target.chat.id;
+        let account = account.trim_start_matches('@').to_lowercase();
+        let expenses: Vec<Expense> = storage
+            .clone()
+            .as_expense_storage()
+            .get_chat_expenses(chat_id)
+            .await
+            .into_iter()
+            .filter(|expense| expense.account.as_deref() == Some(account.as_str()))
+            .collect();
+        if expenses.is_empty() {
+            target
+                .send_markdown_message(markdown_format!(
+                    "💳 No expenses with account `{}`\\.",
+                    &account
+                ))
+                .await?;
+            return Ok(());
+        }
+        let categories = storage
+            .clone()
+            .as_category_storage()
+            .get_chat_categories(chat_id)
+            .await
+            .unwrap_or_default();
+        let also_other = storage
+            .clone()
+            .as_category_storage()
+            .get_also_other_categories(chat_id)
+            .await;
+        let settings = storage
+            .as_settings_storage()
+            .get_chat_settings(chat_id)
+            .await;
+
+        let expense_count = expenses.len();
+        let totals = run_blocking({
+            let uncategorized_name = settings.uncategorized_name().to_string();
+            let case_insensitive = settings.case_insensitive_filters;
+            move || {
+                category_totals(
+                    &expenses,
+                    &categories,
+                    &uncategorized_name,
+                    &also_other,
+                    case_insensitive,
+                )
+            }
+        })
+        .await;
+        target
+            .send_markdown_message(markdown_format!(
+                "💳 *Account {}*, {} expense\\(s\\)\n\n{}",
+                &account,
+                expense_count,
+                @raw MarkdownString::code_block_from_lines(&totals_table(
+                    &totals,
+                    settings.amount_style()
+                ))
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandReportAccount> for crate::commands::Command {
+    fn from(cmd: CommandReportAccount) -> Self {
+        crate::commands::Command::ReportAccount(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expense(amount: f64, account: Option<&str>) -> Expense {
+        Expense {
+            timestamp: 1609459200,
+            description: "item".to_string(),
+            amount: Amount::from(amount),
+            account: account.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_account_totals() {
+        let expenses = vec![
+            expense(5.0, Some("card")),
+            expense(2.5, None),
+            expense(10.0, Some("cash")),
+            expense(1.5, Some("card")),
+        ];
+        assert_eq!(
+            account_totals(&expenses),
+            vec![
+                ("card".to_string(), Amount::from(6.5)),
+                ("cash".to_string(), Amount::from(10.0)),
+                (NO_ACCOUNT.to_string(), Amount::from(2.5)),
+            ]
+        );
+
+        // The bucket without account appears only when needed
+        assert_eq!(
+            account_totals(&expenses[..1]),
+            vec![("card".to_string(), Amount::from(5.0))]
+        );
+        assert!(account_totals(&[]).is_empty());
+
+        let table = totals_table(&account_totals(&expenses), AmountStyle::default());
+        assert_eq!(table.last().unwrap(), "Total             19.00");
+    }
+}
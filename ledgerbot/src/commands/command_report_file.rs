@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use teloxide::{
+    prelude::{Requester, ResponseResult},
+    types::InputFile,
+};
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_string,
+};
+
+use crate::{
+    commands::{
+        report::{check_category_conflicts, format_report_markdown_file},
+        run_blocking,
+    },
+    storages::StorageTrait,
+};
+
+/// Name of the exported report file
+const REPORT_FILE_NAME: &str = "report.md";
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandReportFile;
+
+impl CommandTrait for CommandReportFile {
+    type A = EmptyArg;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "report_file";
+    const PLACEHOLDERS: &[&'static str] = &[];
+
+    fn from_arguments(
+        _: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandReportFile
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let chat_id = target.chat.id;
+        let expenses = storage
+            .clone()
+            .as_expense_storage()
+            .get_chat_expenses_sorted(chat_id)
+            .await;
+        if expenses.is_empty() {
+            target
+                .send_markdown_message(markdown_string!("No expenses recorded yet\\."))
+                .await?;
+            return Ok(());
+        }
+        let categories = storage
+            .clone()
+            .as_category_storage()
+            .get_chat_categories(chat_id)
+            .await
+            .unwrap_or_default();
+        let also_other = storage
+            .clone()
+            .as_category_storage()
+            .get_also_other_categories(chat_id)
+            .await;
+
+        let settings = storage
+            .clone()
+            .as_settings_storage()
+            .get_chat_settings(chat_id)
+            .await;
+
+        // Same as /report: refuse to export an ambiguous categorization
+        let rendered = run_blocking(move || {
+            if let Some(conflict_message) =
+                check_category_conflicts(&expenses, &categories, settings.case_insensitive_filters)
+            {
+                return Err(conflict_message);
+            }
+            Ok(format_report_markdown_file(
+                &expenses,
+                &categories,
+                settings.amount_style(),
+                settings.uncategorized_name(),
+                &also_other,
+                settings.case_insensitive_filters,
+            ))
+        })
+        .await;
+        let content = match rendered {
+            Ok(content) => content,
+            Err(conflict_message) => {
+                target.markdown_message(conflict_message).await?;
+                return Ok(());
+            }
+        };
+        target
+            .bot
+            .send_document(
+                chat_id,
+                InputFile::memory(content.into_bytes()).file_name(REPORT_FILE_NAME),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandReportFile> for crate::commands::Command {
+    fn from(cmd: CommandReportFile) -> Self {
+        crate::commands::Command::ReportFile(cmd)
+    }
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format,
+};
+
+use crate::storages::{DEFAULT_REPORT_PAGE_SIZE, MAX_REPORT_PAGE_SIZE, SettingsStorageTrait};
+
+/// Set the number of records shown on one page of a category report
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandReportPageSize {
+    pub size: Option<usize>,
+}
+
+impl CommandTrait for CommandReportPageSize {
+    type A = usize;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn SettingsStorageTrait>;
+
+    const NAME: &'static str = "report_page_size";
+    const PLACEHOLDERS: &[&'static str] = &["<records>"];
+
+    fn from_arguments(
+        size: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandReportPageSize { size }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.size.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let settings = storage.get_chat_settings(target.chat.id).await;
+        target
+            .send_markdown_message(markdown_format!(
+                "📄 Category reports show {} records per page\\.\n{}",
+                settings.report_page_size(),
+                @raw self.usage_hint()
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        size: &usize,
+    ) -> ResponseResult<()> {
+        if !(1..=MAX_REPORT_PAGE_SIZE).contains(size) {
+            target
+                .send_markdown_message(markdown_format!(
+                    "❌ Records per page must be from 1 to {}\\.",
+                    MAX_REPORT_PAGE_SIZE
+                ))
+                .await?;
+            return Ok(());
+        }
+        let mut settings = storage.get_chat_settings(target.chat.id).await;
+        settings.report_page_size = (*size != DEFAULT_REPORT_PAGE_SIZE).then_some(*size);
+        storage.set_chat_settings(target.chat.id, settings).await;
+        target
+            .send_markdown_message(markdown_format!(
+                "✅ Category reports will show {} records per page\\.",
+                *size
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandReportPageSize> for crate::commands::Command {
+    fn from(cmd: CommandReportPageSize) -> Self {
+        crate::commands::Command::ReportPageSize(cmd)
+    }
+}
@@ -0,0 +1,159 @@
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+use teloxide::{prelude::ResponseResult, utils::command::ParseError};
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format,
+};
+
+use crate::storages::CategoryStorageTrait;
+
+/// Display color in `#RRGGBB` form, stored with uppercase hex digits
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct HexColor(String);
+
+impl Display for HexColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for HexColor {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix('#') {
+            Some(digits) if digits.len() == 6 && digits.chars().all(|c| c.is_ascii_hexdigit()) => {
+                Ok(HexColor(format!("#{}", digits.to_ascii_uppercase())))
+            }
+            _ => Err(ParseError::IncorrectFormat(
+                format!("Invalid color `{}`, expected `#RRGGBB`", s).into(),
+            )),
+        }
+    }
+}
+
+/// Set the color of a category in image reports, text reports ignore it
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandSetColor {
+    pub category: Option<String>,
+    pub color: Option<HexColor>,
+}
+
+impl CommandTrait for CommandSetColor {
+    type A = String;
+    type B = HexColor;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn CategoryStorageTrait>;
+
+    const NAME: &'static str = "set_color";
+    const PLACEHOLDERS: &[&'static str] = &["<category>", "<#RRGGBB>"];
+
+    fn from_arguments(
+        category: Option<Self::A>,
+        color: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandSetColor { category, color }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.category.as_ref()
+    }
+
+    fn param2(&self) -> Option<&Self::B> {
+        self.color.as_ref()
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        category: &String,
+    ) -> ResponseResult<()> {
+        let colors = storage.get_category_colors(target.chat.id).await;
+        let message = match colors.get(category) {
+            Some(color) => markdown_format!(
+                "🎨 Color of `{}` is `{}`\\.\n{}",
+                category,
+                color,
+                @raw self.usage_hint()
+            ),
+            None => markdown_format!(
+                "🎨 No color set for `{}`\\.\n{}",
+                category,
+                @raw self.usage_hint()
+            ),
+        };
+        target.send_markdown_message(message).await?;
+        Ok(())
+    }
+
+    async fn run2(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        category: &String,
+        color: &HexColor,
+    ) -> ResponseResult<()> {
+        let message = match storage
+            .set_category_color(target.chat.id, category, color.to_string())
+            .await
+        {
+            Ok(()) => markdown_format!(
+                "✅ Color of `{}` set to `{}`\\.",
+                category,
+                color.to_string()
+            ),
+            Err(error) => markdown_format!("❌ {}", @raw error),
+        };
+        target.send_markdown_message(message).await?;
+        Ok(())
+    }
+}
+
+impl From<CommandSetColor> for crate::commands::Command {
+    fn from(cmd: CommandSetColor) -> Self {
+        crate::commands::Command::SetColor(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(
+            "#ff8800".parse::<HexColor>().unwrap().to_string(),
+            "#FF8800"
+        );
+        assert_eq!(
+            "#00AAbb".parse::<HexColor>().unwrap().to_string(),
+            "#00AABB"
+        );
+        for invalid in [
+            "ff8800", "#ff880", "#ff88001", "#gg8800", "#", "red", "#ff 880",
+        ] {
+            assert!(invalid.parse::<HexColor>().is_err(), "{}", invalid);
+        }
+
+        let (command,) = CommandSetColor::parse_arguments("Food #12abEF".to_string()).unwrap();
+        assert_eq!(command.category.as_deref(), Some("Food"));
+        assert_eq!(command.color, Some(HexColor("#12ABEF".to_string())));
+        assert!(CommandSetColor::parse_arguments("Food blue".to_string()).is_err());
+    }
+}
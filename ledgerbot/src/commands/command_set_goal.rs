@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format, markdown_string,
+};
+
+use crate::{
+    storages::SettingsStorageTrait,
+    utils::{amount::Amount, format_amount::format_amount_localized},
+};
+
+/// Set the overall monthly spending goal shown in /report, zero removes it
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandSetGoal {
+    pub amount: Option<f64>,
+}
+
+impl CommandTrait for CommandSetGoal {
+    type A = f64;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn SettingsStorageTrait>;
+
+    const NAME: &'static str = "set_goal";
+    const PLACEHOLDERS: &[&'static str] = &["<amount>"];
+
+    fn from_arguments(
+        amount: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandSetGoal { amount }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.amount.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let settings = storage.get_chat_settings(target.chat.id).await;
+        let message = match settings.monthly_goal {
+            Some(goal) => markdown_format!(
+                "🎯 Monthly goal is `{}`\\.\n{}",
+                format_amount_localized(goal.as_f64(), 2, settings.amount_style()),
+                @raw self.usage_hint()
+            ),
+            None => markdown_format!("🎯 No monthly goal is set\\.\n{}", @raw self.usage_hint()),
+        };
+        target.send_markdown_message(message).await?;
+        Ok(())
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        amount: &f64,
+    ) -> ResponseResult<()> {
+        if !amount.is_finite() {
+            target
+                .send_markdown_message(markdown_string!("❌ Monthly goal must be a number\\."))
+                .await?;
+            return Ok(());
+        }
+        let goal = Amount::from(*amount);
+        if goal < Amount::ZERO {
+            target
+                .send_markdown_message(markdown_string!("❌ Monthly goal can't be negative\\."))
+                .await?;
+            return Ok(());
+        }
+        let mut settings = storage.get_chat_settings(target.chat.id).await;
+        settings.monthly_goal = (goal > Amount::ZERO).then_some(goal);
+        let amount_style = settings.amount_style();
+        storage.set_chat_settings(target.chat.id, settings).await;
+        let message = if goal > Amount::ZERO {
+            markdown_format!(
+                "✅ Monthly goal set to `{}`\\.",
+                format_amount_localized(goal.as_f64(), 2, amount_style)
+            )
+        } else {
+            markdown_string!("✅ Monthly goal removed\\.")
+        };
+        target.send_markdown_message(message).await?;
+        Ok(())
+    }
+}
+
+impl From<CommandSetGoal> for crate::commands::Command {
+    fn from(cmd: CommandSetGoal) -> Self {
+        crate::commands::Command::SetGoal(cmd)
+    }
+}
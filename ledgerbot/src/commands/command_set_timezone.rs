@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format,
+};
+
+use crate::{storages::SettingsStorageTrait, utils::timezone::UtcOffset};
+
+/// Set the chat's time zone, which decides the date of expenses sent without one
+/// and the current month of reports
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandSetTimezone {
+    pub offset: Option<UtcOffset>,
+}
+
+impl CommandTrait for CommandSetTimezone {
+    type A = UtcOffset;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn SettingsStorageTrait>;
+
+    const NAME: &'static str = "set_timezone";
+    const PLACEHOLDERS: &[&'static str] = &["<offset>"];
+
+    fn from_arguments(
+        offset: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandSetTimezone { offset }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.offset.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let settings = storage.get_chat_settings(target.chat.id).await;
+        target
+            .send_markdown_message(markdown_format!(
+                "🕓 Time zone of the chat is `{}`\\.\n{}",
+                settings.utc_offset.to_string(),
+                @raw self.usage_hint()
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        offset: &UtcOffset,
+    ) -> ResponseResult<()> {
+        let mut settings = storage.get_chat_settings(target.chat.id).await;
+        settings.utc_offset = *offset;
+        storage.set_chat_settings(target.chat.id, settings).await;
+        target
+            .send_markdown_message(markdown_format!(
+                "✅ Time zone of the chat set to `{}`\\.",
+                offset.to_string()
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandSetTimezone> for crate::commands::Command {
+    fn from(cmd: CommandSetTimezone) -> Self {
+        crate::commands::Command::SetTimezone(cmd)
+    }
+}
@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use teloxide::{payloads::SendMessageSetters, prelude::ResponseResult};
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown::MarkdownStringMessage,
+    markdown_format,
+};
+
+use crate::{
+    commands::{command_hide_menu::CommandHideMenu, command_start::menu_markup},
+    storages::SettingsStorageTrait,
+};
+
+/// Show the persistent menu keyboard in this chat
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandShowMenu;
+
+impl CommandTrait for CommandShowMenu {
+    type A = EmptyArg;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn SettingsStorageTrait>;
+
+    const NAME: &'static str = "show_menu";
+    const PLACEHOLDERS: &[&'static str] = &[];
+
+    fn from_arguments(
+        _: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandShowMenu
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let mut settings = storage.get_chat_settings(target.chat.id).await;
+        settings.hide_menu = false;
+        storage.set_chat_settings(target.chat.id, settings).await;
+        target
+            .bot
+            .send_markdown_message(
+                target.chat.id,
+                markdown_format!(
+                    "✅ Menu buttons are shown\\. Use {} to hide them\\.",
+                    CommandHideMenu.to_command_string(true)
+                ),
+            )
+            .reply_markup(menu_markup(false))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandShowMenu> for crate::commands::Command {
+    fn from(cmd: CommandShowMenu) -> Self {
+        crate::commands::Command::ShowMenu(cmd)
+    }
+}
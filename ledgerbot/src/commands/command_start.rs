@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use teloxide::{
     payloads::SendMessageSetters,
     prelude::ResponseResult,
@@ -9,7 +11,10 @@ use yoroolbot::{
     markdown_format,
 };
 
-use crate::commands::command_help::CommandHelp;
+use crate::{
+    commands::{command_help::CommandHelp, command_show_menu::CommandShowMenu},
+    storages::SettingsStorageTrait,
+};
 
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct CommandStart;
@@ -25,7 +30,7 @@ impl CommandTrait for CommandStart {
     type H = EmptyArg;
     type I = EmptyArg;
 
-    type Context = ();
+    type Context = Arc<dyn SettingsStorageTrait>;
 
     const NAME: &'static str = "start";
     const PLACEHOLDERS: &[&'static str] = &[];
@@ -47,23 +52,31 @@ impl CommandTrait for CommandStart {
     async fn run0(
         &self,
         target: &CommandReplyTarget,
-        _context: Self::Context,
+        storage: Self::Context,
     ) -> ResponseResult<()> {
         // Send a follow-up message to set the persistent reply keyboard menu
+        // unless the chat asked to hide it
+        let hide_menu = storage.get_chat_settings(target.chat.id).await.hide_menu;
+        let message = if hide_menu {
+            markdown_format!(
+                "🤖 *Expense Bot v{}*\nMenu buttons are hidden, use {} to show them",
+                env!("CARGO_PKG_VERSION"),
+                CommandShowMenu.to_command_string(true)
+            )
+        } else {
+            markdown_format!(
+                "🤖 *Expense Bot v{}*\nMenu buttons are available",
+                env!("CARGO_PKG_VERSION")
+            )
+        };
         target
             .bot
-            .send_markdown_message(
-                target.chat.id,
-                markdown_format!(
-                    "🤖 *Expense Bot v{}*\nMenu buttons are available",
-                    env!("CARGO_PKG_VERSION")
-                ),
-            )
-            .reply_markup(create_menu_keyboard())
+            .send_markdown_message(target.chat.id, message)
+            .reply_markup(menu_markup(hide_menu))
             .await?;
 
         // Use CommandHelp to display help
-        CommandHelp.run(target, ()).await?;
+        CommandHelp::default().run(target, ()).await?;
 
         Ok(())
     }
@@ -75,6 +88,15 @@ impl From<CommandStart> for crate::commands::Command {
     }
 }
 
+/// Reply markup showing the persistent menu keyboard, or removing it if the chat hides it
+pub fn menu_markup(hide_menu: bool) -> ReplyMarkup {
+    if hide_menu {
+        ReplyMarkup::kb_remove()
+    } else {
+        create_menu_keyboard()
+    }
+}
+
 /// Create a persistent menu keyboard that shows on the left of the input field
 pub fn create_menu_keyboard() -> ReplyMarkup {
     let keyboard = vec![vec![
@@ -89,3 +111,33 @@ pub fn create_menu_keyboard() -> ReplyMarkup {
             .persistent(),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use teloxide::types::ChatId;
+
+    use super::*;
+    use crate::storages::SettingsStorage;
+
+    #[tokio::test]
+    async fn test_menu_markup_follows_stored_preference() {
+        let storage = SettingsStorage::new();
+        let chat_id = ChatId(1);
+
+        // Shown by default
+        let settings = storage.get_chat_settings(chat_id).await;
+        assert!(matches!(
+            menu_markup(settings.hide_menu),
+            ReplyMarkup::Keyboard(_)
+        ));
+
+        let mut settings = settings;
+        settings.hide_menu = true;
+        storage.set_chat_settings(chat_id, settings).await;
+        let settings = storage.get_chat_settings(chat_id).await;
+        assert!(matches!(
+            menu_markup(settings.hide_menu),
+            ReplyMarkup::KeyboardRemove(_)
+        ));
+    }
+}
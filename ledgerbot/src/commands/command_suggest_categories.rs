@@ -0,0 +1,243 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+};
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format, markdown_string,
+};
+
+use crate::{
+    commands::{command_categories::format_categories_listing, run_blocking},
+    menus::select_word::build_words_pattern,
+    storages::{Expense, StorageTrait},
+    utils::extract_words::uncategorized_words,
+};
+
+/// Words found in fewer uncategorized expenses are not suggested
+pub const MIN_SUGGESTION_EXPENSES: usize = 2;
+
+/// At most this many categories are suggested at once
+pub const MAX_SUGGESTIONS: usize = 10;
+
+/// Propose categories for the most frequent words of uncategorized expenses
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandSuggestCategories;
+
+impl CommandTrait for CommandSuggestCategories {
+    type A = EmptyArg;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "suggest_categories";
+    const PLACEHOLDERS: &[&'static str] = &[];
+
+    fn from_arguments(
+        _: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandSuggestCategories
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let chat_id = target.chat.id;
+        let expenses = storage
+            .clone()
+            .as_expense_storage()
+            .get_chat_expenses(chat_id)
+            .await;
+        let categories = storage
+            .clone()
+            .as_category_storage()
+            .get_chat_categories(chat_id)
+            .await
+            .unwrap_or_default();
+        let settings = storage
+            .clone()
+            .as_settings_storage()
+            .get_chat_settings(chat_id)
+            .await;
+
+        let suggestions = run_blocking(move || {
+            suggest_categories(
+                &expenses,
+                &categories,
+                settings.case_insensitive_filters,
+                MIN_SUGGESTION_EXPENSES,
+            )
+        })
+        .await;
+        if suggestions.is_empty() {
+            target
+                .send_markdown_message(markdown_string!(
+                    "💡 No words repeat across uncategorized expenses, nothing to suggest\\."
+                ))
+                .await?;
+            return Ok(());
+        }
+        target
+            .send_markdown_message(markdown_format!(
+                "💡 Suggested categories, send back the commands you want to keep:\n{}",
+                @code format_categories_listing(&suggestions)
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+/// Propose categories for words found in at least `min_expenses` uncategorized expenses
+/// Words found in exactly the same expenses are joined into one category filter.
+/// Words of expenses already covered by a suggestion are skipped, so that no expense
+/// matches two suggested categories. Names already used by categories are skipped too
+pub fn suggest_categories(
+    expenses: &[Expense],
+    categories: &HashMap<String, Vec<String>>,
+    case_insensitive: bool,
+    min_expenses: usize,
+) -> HashMap<String, Vec<String>> {
+    // Uncategorized expenses containing each word
+    let mut word_expenses: HashMap<String, BTreeSet<usize>> = HashMap::new();
+    for (idx, words) in uncategorized_words(expenses, categories, case_insensitive)
+        .into_iter()
+        .enumerate()
+    {
+        for word in words {
+            word_expenses.entry(word).or_default().insert(idx);
+        }
+    }
+    let mut ranked: Vec<(String, BTreeSet<usize>)> = word_expenses
+        .into_iter()
+        .filter(|(_, expenses)| expenses.len() >= min_expenses)
+        .collect();
+    ranked.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(&b.0)));
+
+    // Groups of words with the expenses they cover, the most frequent first
+    let mut groups: Vec<(Vec<String>, BTreeSet<usize>)> = Vec::new();
+    let mut covered: BTreeSet<usize> = BTreeSet::new();
+    for (word, word_expenses) in ranked {
+        if let Some((words, _)) = groups
+            .iter_mut()
+            .find(|(_, expenses)| *expenses == word_expenses)
+        {
+            words.push(word);
+        } else if word_expenses.is_disjoint(&covered) && groups.len() < MAX_SUGGESTIONS {
+            covered.extend(word_expenses.iter().copied());
+            groups.push((vec![word], word_expenses));
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter_map(|(words, _)| {
+            let name = capitalize(&words[0]);
+            if categories.contains_key(&name) {
+                return None;
+            }
+            let pattern = build_words_pattern(&words, true)?;
+            Some((name, vec![pattern]))
+        })
+        .collect()
+}
+
+/// Capitalize the first letter of a word to use it as a category name
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+impl From<CommandSuggestCategories> for crate::commands::Command {
+    fn from(cmd: CommandSuggestCategories) -> Self {
+        crate::commands::Command::SuggestCategories(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{commands::Command, utils::amount::Amount, utils::parse_expenses::parse_expenses};
+
+    fn expenses(descriptions: &[&str]) -> Vec<Expense> {
+        descriptions
+            .iter()
+            .map(|description| Expense {
+                description: description.to_string(),
+                amount: Amount::from(1.0),
+                timestamp: 1609459200,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_suggest_categories() {
+        let expenses = expenses(&[
+            "Bus ticket",
+            "bus ticket",
+            "Coffee",
+            "Coffee beans",
+            "Coffee at Starbucks",
+            "Lunch",
+            "Lunch",
+            "Starbucks",
+        ]);
+        let mut categories = HashMap::new();
+        categories.insert("Food".to_string(), vec!["(?i)lunch".to_string()]);
+
+        let suggestions = suggest_categories(&expenses, &categories, false, 2);
+        let mut expected = HashMap::new();
+        // "bus" and "ticket" always come together and share a filter
+        expected.insert("Bus".to_string(), vec![r"(?i)\b(bus|ticket)\b".to_string()]);
+        // "starbucks" is skipped, one of its expenses is already covered by "coffee"
+        expected.insert("Coffee".to_string(), vec![r"(?i)\b(coffee)\b".to_string()]);
+        assert_eq!(suggestions, expected);
+
+        // Existing category names are not suggested again
+        categories.insert("Coffee".to_string(), vec![]);
+        let suggestions = suggest_categories(&expenses, &categories, false, 2);
+        assert!(!suggestions.contains_key("Coffee"));
+    }
+
+    #[test]
+    fn test_suggested_commands_parse() {
+        let expenses = expenses(&["Bus ticket", "Bus ticket", "Coffee", "Coffee"]);
+        let suggestions = suggest_categories(&expenses, &HashMap::new(), false, 2);
+        let listing = format_categories_listing(&suggestions);
+
+        let results = parse_expenses(&listing, None, 1609459200, false, false);
+        assert_eq!(results.len(), 4);
+        for (_, result) in &results {
+            assert!(matches!(
+                result,
+                Ok(Command::AddCategory(_)) | Ok(Command::AddFilter(_))
+            ));
+        }
+        assert!(matches!(
+            &results[1].1,
+            Ok(Command::AddFilter(cmd)) if cmd.pattern.as_deref() == Some(r"(?i)\b(bus|ticket)\b")
+        ));
+    }
+}
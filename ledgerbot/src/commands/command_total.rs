@@ -0,0 +1,204 @@
+use std::sync::Arc;
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format,
+};
+
+use crate::{
+    storages::{Expense, StorageTrait},
+    utils::{amount::Amount, format_amount::format_amount_localized},
+};
+
+/// Show only the grand total of expenses, optionally of a date range
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandTotal {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+/// Number and total amount of the expenses dated from `from` to `to`, both inclusive
+pub fn total_between(
+    expenses: &[Expense],
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> (usize, Amount) {
+    let in_range = |expense: &&Expense| {
+        let date = Utc
+            .timestamp_opt(expense.timestamp, 0)
+            .unwrap()
+            .date_naive();
+        from.is_none_or(|from| date >= from) && to.is_none_or(|to| date <= to)
+    };
+    expenses
+        .iter()
+        .filter(in_range)
+        .fold((0, Amount::default()), |(count, total), expense| {
+            (count + 1, total + expense.amount)
+        })
+}
+
+impl CommandTotal {
+    async fn reply(
+        target: &CommandReplyTarget,
+        storage: Arc<dyn StorageTrait>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> ResponseResult<()> {
+        let expenses = storage
+            .clone()
+            .as_expense_storage()
+            .get_chat_expenses(target.chat.id)
+            .await;
+        let settings = storage
+            .as_settings_storage()
+            .get_chat_settings(target.chat.id)
+            .await;
+        let (count, total) = total_between(&expenses, from, to);
+        let format_date =
+            |date: Option<NaiveDate>| date.map(|date| date.format("%Y-%m-%d").to_string());
+        let range = match (format_date(from), format_date(to)) {
+            (Some(from), Some(to)) => format!(" from {} to {}", from, to),
+            (Some(from), None) => format!(" since {}", from),
+            (None, Some(to)) => format!(" until {}", to),
+            (None, None) => String::new(),
+        };
+        target
+            .send_markdown_message(markdown_format!(
+                "💰 Total{}: `{}` in {} expense\\(s\\)",
+                range,
+                format_amount_localized(total.as_f64(), 2, settings.amount_style()),
+                count
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+impl CommandTrait for CommandTotal {
+    type A = NaiveDate;
+    type B = NaiveDate;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "total";
+    const PLACEHOLDERS: &[&'static str] = &["<from>", "<to>"];
+
+    fn from_arguments(
+        from: Option<Self::A>,
+        to: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandTotal { from, to }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.from.as_ref()
+    }
+
+    fn param2(&self) -> Option<&Self::B> {
+        self.to.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        Self::reply(target, storage, None, None).await
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        from: &NaiveDate,
+    ) -> ResponseResult<()> {
+        Self::reply(target, storage, Some(*from), None).await
+    }
+
+    async fn run2(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        from: &NaiveDate,
+        to: &NaiveDate,
+    ) -> ResponseResult<()> {
+        Self::reply(target, storage, Some(*from), Some(*to)).await
+    }
+}
+
+impl From<CommandTotal> for crate::commands::Command {
+    fn from(cmd: CommandTotal) -> Self {
+        crate::commands::Command::Total(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expense(amount: f64, date: &str) -> Expense {
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+        Expense {
+            timestamp: date.and_hms_opt(12, 0, 0).unwrap().and_utc().timestamp(),
+            description: "item".to_string(),
+            amount: Amount::from(amount),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_total_between() {
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok();
+
+        // Empty
+        assert_eq!(total_between(&[], None, None), (0, Amount::default()));
+        assert_eq!(
+            total_between(&[], date("2024-01-01"), date("2024-12-31")),
+            (0, Amount::default())
+        );
+
+        // Populated, the range includes both of its days
+        let expenses = vec![
+            expense(5.5, "2024-01-05"),
+            expense(10.0, "2024-01-10"),
+            expense(2.25, "2024-01-20"),
+        ];
+        assert_eq!(
+            total_between(&expenses, None, None),
+            (3, Amount::from(17.75))
+        );
+        assert_eq!(
+            total_between(&expenses, date("2024-01-10"), None),
+            (2, Amount::from(12.25))
+        );
+        assert_eq!(
+            total_between(&expenses, None, date("2024-01-10")),
+            (2, Amount::from(15.5))
+        );
+        assert_eq!(
+            total_between(&expenses, date("2024-01-06"), date("2024-01-19")),
+            (1, Amount::from(10.0))
+        );
+        assert_eq!(
+            total_between(&expenses, date("2024-02-01"), None),
+            (0, Amount::default())
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format,
+};
+
+use crate::storages::SettingsStorageTrait;
+
+/// Lay out the report summary in two columns when there are many categories,
+/// saving space on wide clients
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandTwoColumnSummary {
+    pub enabled: Option<bool>,
+}
+
+/// Describe the layout of the report summary
+fn summary_layout(enabled: bool) -> &'static str {
+    if enabled {
+        "in two columns when there are many categories"
+    } else {
+        "in one column"
+    }
+}
+
+impl CommandTrait for CommandTwoColumnSummary {
+    type A = bool;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn SettingsStorageTrait>;
+
+    const NAME: &'static str = "two_column_summary";
+    const PLACEHOLDERS: &[&'static str] = &["<enabled>"];
+
+    fn from_arguments(
+        enabled: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandTwoColumnSummary { enabled }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.enabled.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let settings = storage.get_chat_settings(target.chat.id).await;
+        target
+            .send_markdown_message(markdown_format!(
+                "📊 Report categories are shown {}\\.\n{}",
+                summary_layout(settings.two_column_summary),
+                @raw self.usage_hint()
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        enabled: &bool,
+    ) -> ResponseResult<()> {
+        let mut settings = storage.get_chat_settings(target.chat.id).await;
+        settings.two_column_summary = *enabled;
+        storage.set_chat_settings(target.chat.id, settings).await;
+        target
+            .send_markdown_message(markdown_format!(
+                "✅ Report categories will be shown {}\\.",
+                summary_layout(*enabled)
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandTwoColumnSummary> for crate::commands::Command {
+    fn from(cmd: CommandTwoColumnSummary) -> Self {
+        crate::commands::Command::TwoColumnSummary(cmd)
+    }
+}
@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown_format,
+};
+
+use crate::storages::StorageTrait;
+
+/// Restore all archived expenses back into the active list and reports
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandUnarchive;
+
+impl CommandTrait for CommandUnarchive {
+    type A = EmptyArg;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "unarchive";
+    const PLACEHOLDERS: &[&'static str] = &[];
+
+    fn from_arguments(
+        _: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandUnarchive
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let chat_id = target.chat.id;
+        let expenses = storage
+            .clone()
+            .as_archive_storage()
+            .take_archived_expenses(chat_id)
+            .await;
+        let count = expenses.len();
+        if count > 0 {
+            storage
+                .as_expense_storage()
+                .restore_expenses(chat_id, expenses)
+                .await;
+        }
+        target
+            .send_markdown_message(markdown_format!(
+                "🗄 Restored {} archived expenses\\.",
+                count
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandUnarchive> for crate::commands::Command {
+    fn from(cmd: CommandUnarchive) -> Self {
+        crate::commands::Command::Unarchive(cmd)
+    }
+}
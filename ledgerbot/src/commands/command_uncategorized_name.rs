@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown::MarkdownString,
+    markdown_format,
+};
+
+use crate::storages::{DEFAULT_UNCATEGORIZED_NAME, StorageTrait};
+
+/// Refuse a category named like the bucket of uncategorized expenses
+/// Both would be reported under the same name, and its button would show only one of them
+pub fn check_category_name(name: &str, uncategorized_name: &str) -> Result<(), MarkdownString> {
+    if name == uncategorized_name {
+        return Err(markdown_format!(
+            "❌ `{}` is the name of uncategorized expenses\\. Choose another name or change it with {}\\.",
+            name,
+            CommandUncategorizedName::default().to_command_string(false)
+        ));
+    }
+    Ok(())
+}
+
+/// Set the name under which expenses matching no category are reported
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandUncategorizedName {
+    pub name: Option<String>,
+}
+
+impl CommandTrait for CommandUncategorizedName {
+    type A = String;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = Arc<dyn StorageTrait>;
+
+    const NAME: &'static str = "uncategorized_name";
+    const PLACEHOLDERS: &[&'static str] = &["<name>"];
+
+    fn from_arguments(
+        name: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandUncategorizedName { name }
+    }
+
+    fn param1(&self) -> Option<&Self::A> {
+        self.name.as_ref()
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+    ) -> ResponseResult<()> {
+        let settings = storage
+            .as_settings_storage()
+            .get_chat_settings(target.chat.id)
+            .await;
+        target
+            .send_markdown_message(markdown_format!(
+                "📂 Uncategorized expenses are shown as `{}`\\.\n{}",
+                settings.uncategorized_name(),
+                @raw self.usage_hint()
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn run1(
+        &self,
+        target: &CommandReplyTarget,
+        storage: Self::Context,
+        name: &String,
+    ) -> ResponseResult<()> {
+        let categories = storage
+            .clone()
+            .as_category_storage()
+            .get_chat_categories(target.chat.id)
+            .await
+            .unwrap_or_default();
+        if categories.contains_key(name) {
+            target
+                .send_markdown_message(markdown_format!(
+                    "❌ Category `{}` already exists, its expenses would be mixed with uncategorized ones\\. Choose another name\\.",
+                    name
+                ))
+                .await?;
+            return Ok(());
+        }
+        let settings_storage = storage.as_settings_storage();
+        let mut settings = settings_storage.get_chat_settings(target.chat.id).await;
+        settings.uncategorized_name =
+            (name.as_str() != DEFAULT_UNCATEGORIZED_NAME).then(|| name.clone());
+        settings_storage
+            .set_chat_settings(target.chat.id, settings)
+            .await;
+        target
+            .send_markdown_message(markdown_format!(
+                "✅ Uncategorized expenses will be shown as `{}`\\.",
+                name
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandUncategorizedName> for crate::commands::Command {
+    fn from(cmd: CommandUncategorizedName) -> Self {
+        crate::commands::Command::UncategorizedName(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_category_name() {
+        assert!(check_category_name("Food", "Other").is_ok());
+        assert!(check_category_name("Other", "Misc").is_ok());
+        assert!(check_category_name("Misc", "Misc").is_err());
+        // Names differing in case are different rows
+        assert!(check_category_name("other", "Other").is_ok());
+    }
+}
@@ -0,0 +1,125 @@
+use chrono::{TimeZone, Utc};
+use teloxide::prelude::ResponseResult;
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait, EmptyArg},
+    markdown::MarkdownString,
+    markdown_format,
+};
+
+/// Show the bot version, git commit and commit time, to tell which build is running
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandVersion;
+
+impl CommandTrait for CommandVersion {
+    type A = EmptyArg;
+    type B = EmptyArg;
+    type C = EmptyArg;
+    type D = EmptyArg;
+    type E = EmptyArg;
+    type F = EmptyArg;
+    type G = EmptyArg;
+    type H = EmptyArg;
+    type I = EmptyArg;
+
+    type Context = ();
+
+    const NAME: &'static str = "version";
+    const PLACEHOLDERS: &[&'static str] = &[];
+
+    fn from_arguments(
+        _: Option<Self::A>,
+        _: Option<Self::B>,
+        _: Option<Self::C>,
+        _: Option<Self::D>,
+        _: Option<Self::E>,
+        _: Option<Self::F>,
+        _: Option<Self::G>,
+        _: Option<Self::H>,
+        _: Option<Self::I>,
+    ) -> Self {
+        CommandVersion
+    }
+
+    async fn run0(
+        &self,
+        target: &CommandReplyTarget,
+        _context: Self::Context,
+    ) -> ResponseResult<()> {
+        target
+            .send_markdown_message(format_build_info(&BuildInfo::current()))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<CommandVersion> for crate::commands::Command {
+    fn from(cmd: CommandVersion) -> Self {
+        crate::commands::Command::Version(cmd)
+    }
+}
+
+/// Version and build details of the running binary
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    pub commit: &'static str,
+    /// Unix timestamp of the commit, 0 if unknown
+    pub timestamp: i64,
+}
+
+impl BuildInfo {
+    /// Build info set by the build script
+    pub fn current() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            commit: env!("LEDGERBOT_GIT_COMMIT"),
+            timestamp: env!("LEDGERBOT_COMMIT_TIMESTAMP")
+                .parse()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Format build info as the /version reply
+pub fn format_build_info(info: &BuildInfo) -> MarkdownString {
+    let committed = Utc
+        .timestamp_opt(info.timestamp, 0)
+        .single()
+        .filter(|_| info.timestamp > 0)
+        .map(|datetime| datetime.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    markdown_format!(
+        "🤖 *Expense Bot v{}*\nCommit: `{}`\nCommitted: {}",
+        info.version,
+        info.commit,
+        committed
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_build_info() {
+        let info = BuildInfo {
+            version: "1.2.3",
+            commit: "abc1234",
+            timestamp: 1609504200,
+        };
+        assert_eq!(
+            format_build_info(&info).as_str(),
+            "🤖 *Expense Bot v1\\.2\\.3*\nCommit: `abc1234`\nCommitted: 2021\\-01\\-01 12:30 UTC"
+        );
+
+        let unknown = BuildInfo {
+            timestamp: 0,
+            ..info
+        };
+        assert!(
+            format_build_info(&unknown)
+                .as_str()
+                .ends_with("Committed: unknown")
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use teloxide::types::ChatId;
+use yoroolbot::storage::ButtonData;
+
+use crate::storages::ConfirmationStorageTrait;
+
+/// Result of answering a confirmation prompt
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfirmationOutcome {
+    /// Confirm button pressed for a pending request, the action may proceed
+    Confirmed,
+    /// Cancel button pressed or the request declined
+    Cancelled,
+    /// No pending request: never asked, expired, already answered or not sent from the button
+    NotConfirmed,
+}
+
+/// Resolve the answer to a pending confirmation of `action`
+/// Only the Confirm button (`from_callback`) of a pending, not yet expired request confirms
+pub async fn resolve_confirmation(
+    storage: &dyn ConfirmationStorageTrait,
+    chat_id: ChatId,
+    action: &str,
+    confirm: bool,
+    from_callback: bool,
+    now: i64,
+) -> ConfirmationOutcome {
+    let pending = storage
+        .take_pending_confirmation(chat_id, action, now)
+        .await;
+    if !confirm {
+        ConfirmationOutcome::Cancelled
+    } else if pending && from_callback {
+        ConfirmationOutcome::Confirmed
+    } else {
+        ConfirmationOutcome::NotConfirmed
+    }
+}
+
+/// Confirm/Cancel button row for a confirmation prompt
+pub fn confirmation_buttons(
+    confirm_command: String,
+    cancel_command: String,
+) -> Vec<Vec<ButtonData>> {
+    vec![vec![
+        ButtonData::Callback("✅ Confirm".to_string(), confirm_command),
+        ButtonData::Callback("❌ Cancel".to_string(), cancel_command),
+    ]]
+}
@@ -1,12 +1,32 @@
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeZone, Utc};
 use yoroolbot::{markdown::MarkdownString, markdown_format};
 
-use crate::storages::Expense;
+use crate::{
+    storages::Expense,
+    utils::{
+        amount::Amount,
+        format_amount::{AmountStyle, format_amount_localized},
+    },
+};
 
-/// Format timestamp as YYYY-MM-DD string
-fn format_timestamp(timestamp: i64) -> String {
+/// Format timestamp as YYYY-MM-DD string, followed by HH:MM unless the time is midnight
+pub fn format_timestamp_with_time(timestamp: i64) -> String {
     let datetime: DateTime<Utc> = Utc.timestamp_opt(timestamp, 0).unwrap();
-    datetime.format("%Y-%m-%d").to_string()
+    if datetime.time() == NaiveTime::MIN {
+        datetime.format("%Y-%m-%d").to_string()
+    } else {
+        datetime.format("%Y-%m-%d %H:%M").to_string()
+    }
+}
+
+/// Expenses in chronological order, sorted only if they aren't already,
+/// as they are when read with `get_chat_expenses_sorted`
+fn chronological(expenses: &[Expense]) -> Vec<&Expense> {
+    let mut sorted: Vec<&Expense> = expenses.iter().collect();
+    if !expenses.is_sorted_by_key(|e| e.timestamp) {
+        sorted.sort_by_key(|e| e.timestamp);
+    }
+    sorted
 }
 
 /// Format expenses as a chronological list without category grouping
@@ -21,20 +41,28 @@ pub fn format_expenses_chronological(
         ));
     }
 
-    // Sort by timestamp (chronological order)
-    let mut sorted_expenses = expenses.to_vec();
-    sorted_expenses.sort_by_key(|e| e.timestamp);
+    let sorted_expenses = chronological(expenses);
 
     let mut messages = Vec::new();
     let mut current_message = MarkdownString::new();
 
     for expense in sorted_expenses {
-        let date_str = format_timestamp(expense.timestamp);
+        let date_str = format_timestamp_with_time(expense.timestamp);
+        let author_note = match &expense.author {
+            Some(author) => markdown_format!(" 👤 _{}_", author),
+            None => MarkdownString::new(),
+        };
+        let account_note = match &expense.account {
+            Some(account) => markdown_format!(" 💳 {}", account),
+            None => MarkdownString::new(),
+        };
         let expense_line = markdown_format!(
-            "{} {} {}\n",
+            "{} {} {}{}{}\n",
             &date_str,
             &expense.description,
-            &expense.amount.to_string()
+            &expense.amount.as_f64().to_string(),
+            @raw author_note,
+            @raw account_note
         );
 
         // Try to add the expense line to current message
@@ -43,7 +71,7 @@ pub fn format_expenses_chronological(
 
         if test_message.is_truncated() {
             // Current message would overflow, start a new one
-            if current_message.as_str().is_empty() {
+            if current_message.is_empty() {
                 // Edge case: single expense line is too long, add it anyway
                 current_message.push(&expense_line);
             }
@@ -57,16 +85,133 @@ pub fn format_expenses_chronological(
     }
 
     // Add the last message if it has content
-    if !current_message.as_str().is_empty() {
+    if !current_message.is_empty() {
         messages.push(current_message);
     }
 
     Ok(messages)
 }
 
+/// Group expenses by date in chronological order
+/// Returns (date, expenses of that date, daily subtotal) for each date
+pub fn group_expenses_by_date(expenses: &[Expense]) -> Vec<(NaiveDate, Vec<&Expense>, Amount)> {
+    let sorted_expenses = chronological(expenses);
+
+    let mut groups: Vec<(NaiveDate, Vec<&Expense>, Amount)> = Vec::new();
+    for expense in sorted_expenses {
+        let date = Utc
+            .timestamp_opt(expense.timestamp, 0)
+            .unwrap()
+            .date_naive();
+        match groups.last_mut() {
+            Some((last_date, items, subtotal)) if *last_date == date => {
+                items.push(expense);
+                *subtotal += expense.amount;
+            }
+            _ => groups.push((date, vec![expense], expense.amount)),
+        }
+    }
+    groups
+}
+
+/// Format expenses grouped by date with daily subtotals and a grand total
+/// Each message is a code block table; the table is split into several messages if needed
+/// With `decimal_comma` amounts are shown as 5,50 instead of 5.50
+pub fn format_expenses_by_date(
+    expenses: &[Expense],
+    decimal_comma: bool,
+) -> Result<Vec<MarkdownString>, MarkdownString> {
+    if expenses.is_empty() {
+        return Err(markdown_format!(
+            "📝 No expenses recorded yet\\. Send a message like `2024\\-10\\-09 Coffee 5\\.50` to add one\\."
+        ));
+    }
+
+    const DESCRIPTION_WIDTH: usize = 20;
+    let style = AmountStyle {
+        grouping: false,
+        decimal_comma,
+    };
+    let show = |amount: Amount| format_amount_localized(amount.as_f64(), 2, style);
+    let groups = group_expenses_by_date(expenses);
+    let total: Amount = groups.iter().map(|(_, _, subtotal)| subtotal).sum();
+
+    let amount_width = groups
+        .iter()
+        .flat_map(|(_, items, subtotal)| {
+            items
+                .iter()
+                .map(|e| e.amount)
+                .chain(std::iter::once(*subtotal))
+        })
+        .chain(std::iter::once(total))
+        .map(|amount| show(amount).len())
+        .max()
+        .unwrap_or(0);
+
+    // Each table line: label padded to the description column, then the aligned amount
+    let table_line = |label: &str, amount: Amount| {
+        let label_width = label.chars().count();
+        let padding = " ".repeat((DESCRIPTION_WIDTH + 2).saturating_sub(label_width));
+        format!(
+            "{}{} {:>width$}",
+            label,
+            padding,
+            show(amount),
+            width = amount_width
+        )
+    };
+
+    let mut lines = Vec::new();
+    for (date, items, subtotal) in &groups {
+        lines.push(table_line(&date.format("%Y-%m-%d").to_string(), *subtotal));
+        for expense in items {
+            lines.push(table_line(
+                &format!("  {}", expense.description),
+                expense.amount,
+            ));
+        }
+    }
+    lines.push("-".repeat(DESCRIPTION_WIDTH + 3 + amount_width));
+    lines.push(table_line("Total", total));
+
+    // Pack lines into code block messages, leaving room for the code fences and escaping
+    // Length is counted in UTF-16 code units like Telegram does
+    const MAX_TABLE_LENGTH: usize = 3900;
+    let mut messages = Vec::new();
+    let mut current_table: Vec<String> = Vec::new();
+    let mut table_length = 0;
+    for line in lines {
+        let line_length = line.encode_utf16().count();
+        if !current_table.is_empty() && table_length + line_length + 1 > MAX_TABLE_LENGTH {
+            messages.push(MarkdownString::code_block_from_lines(&current_table));
+            current_table.clear();
+            table_length = 0;
+        }
+        if !current_table.is_empty() {
+            table_length += 1;
+        }
+        current_table.push(line);
+        table_length += line_length;
+    }
+    if !current_table.is_empty() {
+        messages.push(MarkdownString::code_block_from_lines(&current_table));
+    }
+
+    Ok(messages)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{commands::expenses::format_expenses_chronological, storages::Expense};
+    use chrono::NaiveDate;
+
+    use crate::{
+        commands::expenses::{
+            format_expenses_by_date, format_expenses_chronological, group_expenses_by_date,
+        },
+        storages::Expense,
+        utils::amount::Amount,
+    };
 
     #[test]
     fn test_format_expenses_chronological() {
@@ -78,18 +223,21 @@ mod tests {
         let expenses = vec![
             Expense {
                 description: "Lunch".to_string(),
-                amount: 12.00,
+                amount: Amount::from(12.00),
                 timestamp: timestamp2,
+                ..Default::default()
             },
             Expense {
                 description: "Coffee".to_string(),
-                amount: 5.50,
+                amount: Amount::from(5.50),
                 timestamp: timestamp1,
+                ..Default::default()
             },
             Expense {
                 description: "Dinner".to_string(),
-                amount: 25.00,
+                amount: Amount::from(25.00),
                 timestamp: timestamp3,
+                ..Default::default()
             },
         ];
 
@@ -112,6 +260,38 @@ mod tests {
         assert!(content.contains("25"));
     }
 
+    #[test]
+    fn test_format_expenses_chronological_time_and_author() {
+        let expenses = vec![
+            Expense {
+                description: "Coffee".to_string(),
+                amount: Amount::from(5.50),
+                timestamp: 1609459200 + 14 * 3600 + 30 * 60, // 2021-01-01 14:30 UTC
+                author: Some("Alice".to_string()),
+                ..Default::default()
+            },
+            Expense {
+                description: "Tea".to_string(),
+                amount: Amount::from(3.00),
+                timestamp: 1609545600, // 2021-01-02 00:00 UTC
+                ..Default::default()
+            },
+            Expense {
+                description: "Taxi @card".to_string(),
+                amount: Amount::from(9.00),
+                timestamp: 1609545600,
+                account: Some("card".to_string()),
+                ..Default::default()
+            },
+        ];
+
+        let messages = format_expenses_chronological(&expenses).unwrap();
+        let content = messages[0].as_str();
+        assert!(content.contains("2021\\-01\\-01 14:30 Coffee 5\\.5 👤 _Alice_\n"));
+        assert!(content.contains("2021\\-01\\-02 Tea 3\n"));
+        assert!(content.contains("2021\\-01\\-02 Taxi @card 9 💳 card\n"));
+    }
+
     #[test]
     fn test_format_expenses_chronological_empty() {
         // Test with no expenses
@@ -135,8 +315,9 @@ mod tests {
         for i in 0..150 {
             expenses.push(Expense {
                 description: format!("Expense number {}", i),
-                amount: 10.50 + (i as f64),
+                amount: Amount::from(10.50 + (i as f64)),
                 timestamp: base_timestamp + (i * 86400), // One day apart
+                ..Default::default()
             });
         }
 
@@ -155,7 +336,7 @@ mod tests {
 
         // All messages should be non-empty
         for (idx, message) in messages.iter().enumerate() {
-            assert!(!message.as_str().is_empty(), "Message {} is empty", idx);
+            assert!(!message.is_empty(), "Message {} is empty", idx);
         }
 
         // Verify all expenses are included across all messages
@@ -170,4 +351,110 @@ mod tests {
         assert!(combined.contains("Expense number 50"));
         assert!(combined.contains("Expense number 149"));
     }
+
+    #[test]
+    fn test_group_expenses_by_date() {
+        let day1 = 1609459200; // 2021-01-01 00:00:00 UTC
+        let day2 = 1609545600; // 2021-01-02 00:00:00 UTC
+        let expenses = vec![
+            Expense {
+                description: "Lunch".to_string(),
+                amount: Amount::from(12.00),
+                timestamp: day2 + 3600,
+                ..Default::default()
+            },
+            Expense {
+                description: "Coffee".to_string(),
+                amount: Amount::from(5.50),
+                timestamp: day1,
+                ..Default::default()
+            },
+            Expense {
+                description: "Tea".to_string(),
+                amount: Amount::from(2.00),
+                timestamp: day1 + 60,
+                ..Default::default()
+            },
+        ];
+
+        let groups = group_expenses_by_date(&expenses);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0, NaiveDate::from_ymd_opt(2021, 1, 1).unwrap());
+        assert_eq!(
+            groups[0]
+                .1
+                .iter()
+                .map(|e| e.description.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Coffee", "Tea"]
+        );
+        assert_eq!(groups[0].2, Amount::from(7.50));
+        assert_eq!(groups[1].0, NaiveDate::from_ymd_opt(2021, 1, 2).unwrap());
+        assert_eq!(groups[1].1.len(), 1);
+        assert_eq!(groups[1].2, Amount::from(12.00));
+        assert!(group_expenses_by_date(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_format_expenses_by_date() {
+        let day1 = 1609459200; // 2021-01-01 00:00:00 UTC
+        let expenses = vec![
+            Expense {
+                description: "Coffee".to_string(),
+                amount: Amount::from(5.50),
+                timestamp: day1,
+                ..Default::default()
+            },
+            Expense {
+                description: "Tea".to_string(),
+                amount: Amount::from(2.00),
+                timestamp: day1 + 60,
+                ..Default::default()
+            },
+        ];
+
+        let messages = format_expenses_by_date(&expenses, false).unwrap();
+
+        assert_eq!(messages.len(), 1);
+        let content = messages[0].as_str();
+        assert!(content.starts_with("```"));
+        // Labels are padded to the description column, amounts right-aligned after it
+        assert!(content.contains(&format!("2021-01-01{}7.50", " ".repeat(13))));
+        assert!(content.contains(&format!("  Coffee{}5.50", " ".repeat(15))));
+        assert!(content.contains(&format!("  Tea{}2.00", " ".repeat(18))));
+        assert!(content.contains(&format!("Total{}7.50", " ".repeat(18))));
+        assert!(format_expenses_by_date(&[], false).is_err());
+
+        let messages = format_expenses_by_date(&expenses, true).unwrap();
+        assert!(
+            messages[0]
+                .as_str()
+                .contains(&format!("Total{}7,50", " ".repeat(18)))
+        );
+    }
+
+    #[test]
+    fn test_format_expenses_by_date_large_list() {
+        let base_timestamp = 1609459200; // 2021-01-01 00:00:00 UTC
+        let expenses: Vec<Expense> = (0..300)
+            .map(|i| Expense {
+                description: format!("Expense number {}", i),
+                amount: Amount::from(10.50 + (i as f64)),
+                timestamp: base_timestamp + (i / 3) * 86400,
+                ..Default::default()
+            })
+            .collect();
+
+        let messages = format_expenses_by_date(&expenses, false).unwrap();
+
+        assert!(messages.len() > 1);
+        for message in &messages {
+            assert!(!message.is_truncated());
+        }
+        let combined: String = messages.iter().map(|m| m.as_str()).collect();
+        assert!(combined.contains("Expense number 0"));
+        assert!(combined.contains("Expense number 299"));
+        assert!(combined.contains("Total"));
+    }
 }
@@ -1,43 +1,142 @@
 pub mod command_add_category;
 pub mod command_add_expense;
 pub mod command_add_filter;
+pub mod command_add_literal_filter;
 pub mod command_add_words_filter;
+pub mod command_also_other;
+pub mod command_amount_grouping;
+pub mod command_archive;
+pub mod command_audit;
+pub mod command_backup_now;
+pub mod command_case_insensitive;
 pub mod command_categories;
+pub mod command_chats;
 pub mod command_clear_categories;
 pub mod command_clear_expenses;
+pub mod command_compare;
+pub mod command_confirm_expenses;
+pub mod command_decimal_comma;
+pub mod command_delete_matching;
+pub mod command_dump_filters;
+pub mod command_duplicate_filter;
 pub mod command_edit_filter;
 pub mod command_edit_words_filter;
+pub mod command_expense_template;
+pub mod command_filter_stats;
+pub mod command_forget_me;
+pub mod command_frequent;
 pub mod command_help;
+pub mod command_hide_menu;
+pub mod command_import;
 pub mod command_list;
+pub mod command_list_filters;
+pub mod command_log;
+pub mod command_move_filter;
+pub mod command_move_options;
+pub mod command_pair_amount_lines;
+pub mod command_pending_expense;
+pub mod command_preview_filter;
+pub mod command_recategorize;
 pub mod command_remove_category;
 pub mod command_remove_filter;
+pub mod command_remove_filters;
 pub mod command_rename_category;
 pub mod command_report;
+pub mod command_report_account;
+pub mod command_report_file;
+pub mod command_report_page_size;
+pub mod command_set_color;
+pub mod command_set_goal;
+pub mod command_set_timezone;
+pub mod command_show_menu;
 pub mod command_start;
+pub mod command_suggest_categories;
+pub mod command_total;
+pub mod command_two_column_summary;
+pub mod command_unarchive;
+pub mod command_uncategorized_name;
+pub mod command_version;
+pub mod confirmation;
 pub mod expenses;
 pub mod report;
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
 
 use teloxide::{
     prelude::*,
-    types::{Chat, MessageId},
+    types::{CallbackQueryId, Chat, MessageId, UserId},
     utils::command::BotCommands,
 };
-use yoroolbot::command_trait::{CommandReplyTarget, CommandTrait};
+use yoroolbot::{
+    command_trait::{CommandReplyTarget, CommandTrait},
+    markdown::MarkdownString,
+    markdown_string,
+};
 
 use crate::{
     commands::{
-        command_add_category::CommandAddCategory, command_add_expense::CommandAddExpense,
-        command_add_filter::CommandAddFilter, command_add_words_filter::CommandAddWordsFilter,
-        command_categories::CommandCategories, command_clear_categories::CommandClearCategories,
-        command_clear_expenses::CommandClearExpenses, command_edit_filter::CommandEditFilter,
-        command_edit_words_filter::CommandEditWordsFilter, command_help::CommandHelp,
-        command_list::CommandList, command_remove_category::CommandRemoveCategory,
-        command_remove_filter::CommandRemoveFilter, command_rename_category::CommandRenameCategory,
-        command_report::CommandReport, command_start::CommandStart,
+        command_add_category::{CommandAddCategory, import_category},
+        command_add_expense::CommandAddExpense,
+        command_add_filter::{CommandAddFilter, import_filter},
+        command_add_literal_filter::CommandAddLiteralFilter,
+        command_add_words_filter::CommandAddWordsFilter,
+        command_also_other::CommandAlsoOther,
+        command_amount_grouping::CommandAmountGrouping,
+        command_archive::CommandArchive,
+        command_audit::CommandAudit,
+        command_backup_now::CommandBackupNow,
+        command_case_insensitive::CommandCaseInsensitive,
+        command_categories::CommandCategories,
+        command_chats::CommandChats,
+        command_clear_categories::CommandClearCategories,
+        command_clear_expenses::CommandClearExpenses,
+        command_compare::CommandCompare,
+        command_confirm_expenses::CommandConfirmExpenses,
+        command_decimal_comma::CommandDecimalComma,
+        command_delete_matching::CommandDeleteMatching,
+        command_dump_filters::CommandDumpFilters,
+        command_duplicate_filter::CommandDuplicateFilter,
+        command_edit_filter::CommandEditFilter,
+        command_edit_words_filter::CommandEditWordsFilter,
+        command_expense_template::CommandExpenseTemplate,
+        command_filter_stats::CommandFilterStats,
+        command_forget_me::CommandForgetMe,
+        command_frequent::CommandFrequent,
+        command_help::CommandHelp,
+        command_hide_menu::CommandHideMenu,
+        command_import::CommandImport,
+        command_list::CommandList,
+        command_list_filters::CommandListFilters,
+        command_log::CommandLog,
+        command_move_filter::CommandMoveFilter,
+        command_move_options::CommandMoveOptions,
+        command_pair_amount_lines::CommandPairAmountLines,
+        command_pending_expense::CommandPendingExpense,
+        command_preview_filter::CommandPreviewFilter,
+        command_recategorize::CommandRecategorize,
+        command_remove_category::CommandRemoveCategory,
+        command_remove_filter::CommandRemoveFilter,
+        command_remove_filters::CommandRemoveFilters,
+        command_rename_category::CommandRenameCategory,
+        command_report::{CommandReport, refresh_last_report},
+        command_report_account::CommandReportAccount,
+        command_report_file::CommandReportFile,
+        command_report_page_size::CommandReportPageSize,
+        command_set_color::CommandSetColor,
+        command_set_goal::CommandSetGoal,
+        command_set_timezone::CommandSetTimezone,
+        command_show_menu::CommandShowMenu,
+        command_start::CommandStart,
+        command_suggest_categories::CommandSuggestCategories,
+        command_total::CommandTotal,
+        command_two_column_summary::CommandTwoColumnSummary,
+        command_unarchive::CommandUnarchive,
+        command_uncategorized_name::CommandUncategorizedName,
+        command_version::CommandVersion,
     },
-    storages::StorageTrait,
+    storages::{AuditEntry, StorageTrait},
 };
 
 /// Bot commands
@@ -58,13 +157,26 @@ pub enum Command {
     )]
     Help(CommandHelp),
     #[command(
-        description = "list expenses chronologically in input format",
+        description = "show bot version and build info",
+        rename = "version",
+        parse_with = CommandVersion::parse_arguments
+    )]
+    Version(CommandVersion),
+    #[command(
+        description = "list chats with stored categories, for the bot administrator",
+        rename = "chats",
+        parse_with = CommandChats::parse_arguments,
+        hide
+    )]
+    Chats(CommandChats),
+    #[command(
+        description = "list expenses chronologically in input format (`daily` groups by date)",
         parse_with = CommandList::parse_arguments
     )]
     List(CommandList),
     #[command(
-        description = "show expenses report",
-        parse_with = CommandReport::parse_arguments
+        description = "show expenses report, `/report [collapse] [min <amount>] [--] [category] [page]`",
+        parse_with = CommandReport::parse_report_arguments
     )]
     Report(CommandReport),
     #[command(
@@ -73,6 +185,17 @@ pub enum Command {
         parse_with = CommandClearExpenses::parse_arguments
     )]
     ClearExpenses(CommandClearExpenses),
+    #[command(
+        description = "add expenses from a replied-to CSV document of date,description,amount rows",
+        parse_with = CommandImport::parse_arguments
+    )]
+    Import(CommandImport),
+    #[command(
+        description = "delete all expenses with descriptions matching a regex pattern",
+        rename = "delete_matching",
+        parse_with = CommandDeleteMatching::parse_arguments
+    )]
+    DeleteMatching(CommandDeleteMatching),
     #[command(
         description = "list all categories with filters in command format",
         parse_with = CommandCategories::parse_arguments
@@ -85,9 +208,9 @@ pub enum Command {
     )]
     ClearCategories(CommandClearCategories),
     #[command(
-        description = "add expense category",
+        description = "add expense category, optionally with initial filters",
         rename = "add_category",
-        parse_with = CommandAddCategory::parse_arguments
+        parse_with = CommandAddCategory::parse_add_category_arguments
     )]
     AddCategory(CommandAddCategory),
     #[command(
@@ -120,6 +243,18 @@ pub enum Command {
         parse_with = CommandEditFilter::parse_arguments
     )]
     EditFilter(CommandEditFilter),
+    #[command(
+        description = "add filter to category right after the filter at position",
+        rename = "duplicate_filter",
+        parse_with = CommandDuplicateFilter::parse_arguments
+    )]
+    DuplicateFilter(CommandDuplicateFilter),
+    #[command(
+        description = "move filter at position to another category",
+        rename = "move_filter",
+        parse_with = CommandMoveFilter::parse_arguments
+    )]
+    MoveFilter(CommandMoveFilter),
     #[command(
         description = "add expense with explicit date, description and amount",
         rename = "add_expense",
@@ -138,11 +273,294 @@ pub enum Command {
         parse_with = CommandEditWordsFilter::parse_arguments
     )]
     EditWordsFilter(CommandEditWordsFilter),
+    #[command(
+        description = "toggle thousands separators in report amounts",
+        rename = "amount_grouping",
+        parse_with = CommandAmountGrouping::parse_arguments
+    )]
+    AmountGrouping(CommandAmountGrouping),
+    #[command(
+        description = "toggle decimal comma in report amounts",
+        rename = "decimal_comma",
+        parse_with = CommandDecimalComma::parse_arguments
+    )]
+    DecimalComma(CommandDecimalComma),
+    #[command(
+        description = "show last executed commands",
+        parse_with = CommandAudit::parse_arguments
+    )]
+    Audit(CommandAudit),
+    #[command(
+        description = "show how categorization changed with the last filter change",
+        parse_with = CommandRecategorize::parse_arguments
+    )]
+    Recategorize(CommandRecategorize),
+    #[command(
+        description = "send the full report as a Markdown file",
+        rename = "report_file",
+        parse_with = CommandReportFile::parse_arguments
+    )]
+    ReportFile(CommandReportFile),
+    #[command(
+        description = "show totals by payment account, the @account word of descriptions, or one account by category",
+        rename = "report_account",
+        parse_with = CommandReportAccount::parse_arguments
+    )]
+    ReportAccount(CommandReportAccount),
+    #[command(
+        description = "show only the total of all expenses, optionally from one date to another",
+        parse_with = CommandTotal::parse_arguments
+    )]
+    Total(CommandTotal),
+    #[command(
+        description = "set the number of records on a page of a category report",
+        rename = "report_page_size",
+        parse_with = CommandReportPageSize::parse_arguments
+    )]
+    ReportPageSize(CommandReportPageSize),
+    #[command(
+        description = "list filters with their positions for removal",
+        rename = "list_filters",
+        parse_with = CommandListFilters::parse_arguments
+    )]
+    ListFilters(CommandListFilters),
+    #[command(
+        description = "add filter matching descriptions containing the text",
+        rename = "add_literal_filter",
+        parse_with = CommandAddLiteralFilter::parse_arguments
+    )]
+    AddLiteralFilter(CommandAddLiteralFilter),
+    #[command(
+        description = "save chat data to disk now",
+        rename = "backup_now",
+        parse_with = CommandBackupNow::parse_arguments
+    )]
+    BackupNow(CommandBackupNow),
+    #[command(
+        description = "set the name shown for uncategorized expenses",
+        rename = "uncategorized_name",
+        parse_with = CommandUncategorizedName::parse_arguments
+    )]
+    UncategorizedName(CommandUncategorizedName),
+    #[command(
+        description = "also list a category's expenses among uncategorized ones",
+        rename = "also_other",
+        parse_with = CommandAlsoOther::parse_arguments
+    )]
+    AlsoOther(CommandAlsoOther),
+    #[command(
+        description = "show how the last forwarded commands were processed",
+        rename = "log",
+        parse_with = CommandLog::parse_arguments
+    )]
+    Log(CommandLog),
+    #[command(
+        description = "match regex filters ignoring case",
+        rename = "case_insensitive",
+        parse_with = CommandCaseInsensitive::parse_arguments
+    )]
+    CaseInsensitive(CommandCaseInsensitive),
+    #[command(
+        description = "set the overall monthly spending goal",
+        rename = "set_goal",
+        parse_with = CommandSetGoal::parse_arguments
+    )]
+    SetGoal(CommandSetGoal),
+    #[command(
+        description = "show how many expenses each filter matches",
+        rename = "filter_stats",
+        parse_with = CommandFilterStats::parse_arguments
+    )]
+    FilterStats(CommandFilterStats),
+    #[command(
+        description = "show stored filters of category with whitespace made visible",
+        rename = "dump_filters",
+        parse_with = CommandDumpFilters::parse_arguments
+    )]
+    DumpFilters(CommandDumpFilters),
+    #[command(
+        description = "suggest categories from frequent words of uncategorized expenses",
+        rename = "suggest_categories",
+        parse_with = CommandSuggestCategories::parse_arguments
+    )]
+    SuggestCategories(CommandSuggestCategories),
+    #[command(
+        description = "ask to confirm single expenses before storing them",
+        rename = "confirm_expenses",
+        parse_with = CommandConfirmExpenses::parse_arguments
+    )]
+    ConfirmExpenses(CommandConfirmExpenses),
+    #[command(
+        description = "set the message confirming an added expense",
+        rename = "expense_template",
+        parse_with = CommandExpenseTemplate::parse_arguments
+    )]
+    ExpenseTemplate(CommandExpenseTemplate),
+    #[command(
+        description = "store or discard the expense waiting for confirmation",
+        rename = "pending_expense",
+        parse_with = CommandPendingExpense::parse_arguments
+    )]
+    PendingExpense(CommandPendingExpense),
+    #[command(
+        description = "list all categories whose filters match a description",
+        rename = "move_options",
+        parse_with = CommandMoveOptions::parse_arguments
+    )]
+    MoveOptions(CommandMoveOptions),
+    #[command(
+        description = "show the most frequent descriptions as buttons to add them again",
+        rename = "frequent",
+        parse_with = CommandFrequent::parse_arguments
+    )]
+    Frequent(CommandFrequent),
+    #[command(
+        description = "hide the menu buttons in this chat",
+        rename = "hide_menu",
+        parse_with = CommandHideMenu::parse_arguments
+    )]
+    HideMenu(CommandHideMenu),
+    #[command(
+        description = "show the menu buttons in this chat",
+        rename = "show_menu",
+        parse_with = CommandShowMenu::parse_arguments
+    )]
+    ShowMenu(CommandShowMenu),
+    #[command(
+        description = "compare category totals with the previous month or week",
+        rename = "compare",
+        parse_with = CommandCompare::parse_arguments
+    )]
+    Compare(CommandCompare),
+    #[command(
+        description = "set the color of a category in image reports",
+        rename = "set_color",
+        parse_with = CommandSetColor::parse_arguments
+    )]
+    SetColor(CommandSetColor),
+    #[command(
+        description = "show the report summary in two columns when there are many categories",
+        rename = "two_column_summary",
+        parse_with = CommandTwoColumnSummary::parse_arguments
+    )]
+    TwoColumnSummary(CommandTwoColumnSummary),
+    #[command(
+        description = "read a line with only an amount as the amount of the line before it",
+        rename = "pair_amount_lines",
+        parse_with = CommandPairAmountLines::parse_arguments
+    )]
+    PairAmountLines(CommandPairAmountLines),
+    #[command(
+        description = "move expenses older than a date to the archive, out of reports",
+        rename = "archive",
+        parse_with = CommandArchive::parse_arguments
+    )]
+    Archive(CommandArchive),
+    #[command(
+        description = "restore archived expenses",
+        rename = "unarchive",
+        parse_with = CommandUnarchive::parse_arguments
+    )]
+    Unarchive(CommandUnarchive),
+    #[command(
+        description = "set the time zone of dates, as an offset from UTC",
+        rename = "set_timezone",
+        parse_with = CommandSetTimezone::parse_arguments
+    )]
+    SetTimezone(CommandSetTimezone),
+    #[command(
+        description = "remove several filters of a category by their positions",
+        rename = "remove_filters",
+        parse_with = CommandRemoveFilters::parse_arguments
+    )]
+    RemoveFilters(CommandRemoveFilters),
+    #[command(
+        description = "show a filter built from selected words before applying it",
+        rename = "preview_filter",
+        parse_with = CommandPreviewFilter::parse_arguments
+    )]
+    PreviewFilter(CommandPreviewFilter),
+    #[command(
+        description = "delete all data the bot stores about this chat",
+        rename = "forget_me",
+        parse_with = CommandForgetMe::parse_arguments
+    )]
+    ForgetMe(CommandForgetMe),
 }
 
 // Command constants as string representations
 impl Command {
     pub const ADD_FILTER: &'static str = "/add_filter";
+
+    /// Whether the command is reserved for the bot administrator, see `Args::is_admin`
+    pub fn is_admin_only(&self) -> bool {
+        matches!(self, Command::Chats(_))
+    }
+
+    /// Whether the command can modify expenses or categories
+    /// Such commands are refused in read-only mode
+    pub fn is_mutating(&self) -> bool {
+        match self {
+            Command::Start(_)
+            | Command::Help(_)
+            | Command::Version(_)
+            | Command::Chats(_)
+            | Command::List(_)
+            | Command::Report(_)
+            | Command::Categories(_)
+            | Command::Audit(_)
+            | Command::ReportFile(_)
+            | Command::ReportAccount(_)
+            | Command::Total(_)
+            | Command::ListFilters(_)
+            | Command::BackupNow(_)
+            | Command::Log(_)
+            | Command::FilterStats(_)
+            | Command::DumpFilters(_)
+            | Command::SuggestCategories(_)
+            | Command::MoveOptions(_)
+            | Command::Frequent(_)
+            | Command::Compare(_)
+            | Command::Recategorize(_)
+            | Command::PreviewFilter(_) => false,
+            Command::ClearExpenses(_)
+            | Command::Import(_)
+            | Command::DeleteMatching(_)
+            | Command::ClearCategories(_)
+            | Command::AddCategory(_)
+            | Command::AddFilter(_)
+            | Command::RemoveCategory(_)
+            | Command::RenameCategory(_)
+            | Command::RemoveFilter(_)
+            | Command::EditFilter(_)
+            | Command::DuplicateFilter(_)
+            | Command::MoveFilter(_)
+            | Command::AddExpense(_)
+            | Command::AddWordsFilter(_)
+            | Command::EditWordsFilter(_)
+            | Command::AmountGrouping(_)
+            | Command::DecimalComma(_)
+            | Command::AddLiteralFilter(_)
+            | Command::UncategorizedName(_)
+            | Command::AlsoOther(_)
+            | Command::CaseInsensitive(_)
+            | Command::SetGoal(_)
+            | Command::ConfirmExpenses(_)
+            | Command::ExpenseTemplate(_)
+            | Command::ReportPageSize(_)
+            | Command::PendingExpense(_)
+            | Command::HideMenu(_)
+            | Command::ShowMenu(_)
+            | Command::SetColor(_)
+            | Command::TwoColumnSummary(_)
+            | Command::PairAmountLines(_)
+            | Command::Archive(_)
+            | Command::Unarchive(_)
+            | Command::SetTimezone(_)
+            | Command::RemoveFilters(_)
+            | Command::ForgetMe(_) => true,
+        }
+    }
 }
 
 impl From<Command> for String {
@@ -150,22 +568,74 @@ impl From<Command> for String {
         match val {
             Command::Start(start) => start.to_command_string(true),
             Command::Help(help) => help.to_command_string(true),
+            Command::Version(version) => version.to_command_string(true),
+            Command::Chats(chats) => chats.to_command_string(true),
             Command::List(list) => list.to_command_string(true),
-            Command::Report(report) => report.to_command_string(true),
+            Command::Report(report) => report.to_report_command_string(true),
             Command::ClearExpenses(clear_expenses) => clear_expenses.to_command_string(true),
+            Command::Import(import) => import.to_command_string(true),
+            Command::DeleteMatching(delete_matching) => delete_matching.to_command_string(true),
             Command::Categories(categories) => categories.to_command_string(true),
             Command::ClearCategories(clear_categories) => clear_categories.to_command_string(true),
-            Command::AddCategory(add_category) => add_category.to_command_string(true),
+            Command::AddCategory(add_category) => add_category.to_add_category_command_string(true),
             Command::AddFilter(add_filter) => add_filter.to_command_string(true),
             Command::RemoveCategory(remove_category) => remove_category.to_command_string(true),
             Command::RenameCategory(rename_category) => rename_category.to_command_string(true),
             Command::RemoveFilter(remove_filter) => remove_filter.to_command_string(true),
             Command::EditFilter(edit_filter) => edit_filter.to_command_string(true),
+            Command::DuplicateFilter(duplicate_filter) => duplicate_filter.to_command_string(true),
+            Command::MoveFilter(move_filter) => move_filter.to_command_string(true),
             Command::AddExpense(add_expense) => add_expense.to_command_string(true),
             Command::AddWordsFilter(add_words_filter) => add_words_filter.to_command_string(true),
             Command::EditWordsFilter(edit_words_filter) => {
                 edit_words_filter.to_command_string(true)
             }
+            Command::AmountGrouping(amount_grouping) => amount_grouping.to_command_string(true),
+            Command::DecimalComma(decimal_comma) => decimal_comma.to_command_string(true),
+            Command::Audit(audit) => audit.to_command_string(true),
+            Command::Recategorize(recategorize) => recategorize.to_command_string(true),
+            Command::ReportFile(report_file) => report_file.to_command_string(true),
+            Command::ReportAccount(report_account) => report_account.to_command_string(true),
+            Command::Total(total) => total.to_command_string(true),
+            Command::ReportPageSize(report_page_size) => report_page_size.to_command_string(true),
+            Command::ListFilters(list_filters) => list_filters.to_command_string(true),
+            Command::AddLiteralFilter(add_literal_filter) => {
+                add_literal_filter.to_command_string(true)
+            }
+            Command::BackupNow(backup_now) => backup_now.to_command_string(true),
+            Command::UncategorizedName(uncategorized_name) => {
+                uncategorized_name.to_command_string(true)
+            }
+            Command::AlsoOther(also_other) => also_other.to_command_string(true),
+            Command::Log(log) => log.to_command_string(true),
+            Command::CaseInsensitive(case_insensitive) => case_insensitive.to_command_string(true),
+            Command::SetGoal(set_goal) => set_goal.to_command_string(true),
+            Command::FilterStats(filter_stats) => filter_stats.to_command_string(true),
+            Command::DumpFilters(dump_filters) => dump_filters.to_command_string(true),
+            Command::SuggestCategories(suggest_categories) => {
+                suggest_categories.to_command_string(true)
+            }
+            Command::ConfirmExpenses(confirm_expenses) => confirm_expenses.to_command_string(true),
+            Command::ExpenseTemplate(expense_template) => expense_template.to_command_string(true),
+            Command::PendingExpense(pending_expense) => pending_expense.to_command_string(true),
+            Command::MoveOptions(move_options) => move_options.to_command_string(true),
+            Command::Frequent(frequent) => frequent.to_command_string(true),
+            Command::HideMenu(hide_menu) => hide_menu.to_command_string(true),
+            Command::ShowMenu(show_menu) => show_menu.to_command_string(true),
+            Command::Compare(compare) => compare.to_command_string(true),
+            Command::SetColor(set_color) => set_color.to_command_string(true),
+            Command::TwoColumnSummary(two_column_summary) => {
+                two_column_summary.to_command_string(true)
+            }
+            Command::PairAmountLines(pair_amount_lines) => {
+                pair_amount_lines.to_command_string(true)
+            }
+            Command::Archive(archive) => archive.to_command_string(true),
+            Command::Unarchive(unarchive) => unarchive.to_command_string(true),
+            Command::SetTimezone(set_timezone) => set_timezone.to_command_string(true),
+            Command::RemoveFilters(remove_filters) => remove_filters.to_command_string(true),
+            Command::PreviewFilter(preview_filter) => preview_filter.to_command_string(true),
+            Command::ForgetMe(forget_me) => forget_me.to_command_string(true),
         }
     }
 }
@@ -176,7 +646,56 @@ impl std::fmt::Display for Command {
     }
 }
 
+/// Effect of a category import command run in a batch, reported once in the batch summary
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ImportOutcome {
+    pub category_created: bool,
+    pub filters_added: usize,
+    /// Patterns rejected as invalid regexes
+    pub invalid_filters: usize,
+    /// Reason the command could not be applied at all
+    pub error: Option<MarkdownString>,
+    /// Problems of the applied command worth a look, like a filter matching the same as another
+    pub warnings: Vec<MarkdownString>,
+}
+
+/// Error of a command which did not finish within the allowed time
+#[derive(Debug)]
+pub struct CommandTimeout(pub Duration);
+
+impl std::fmt::Display for CommandTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "command did not finish in {}s", self.0.as_secs_f64())
+    }
+}
+
+impl std::error::Error for CommandTimeout {}
+
+/// Run synchronous CPU-bound work, like categorizing and rendering expenses, on a blocking thread
+/// The command timeout fires only at await points, so such work must not run on the async task
+pub async fn run_blocking<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> T {
+    tokio::task::spawn_blocking(work)
+        .await
+        .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+}
+
+/// Wait for the command to finish, failing with `CommandTimeout` after `timeout`
+/// Only awaiting commands can be interrupted, CPU-bound parts go through `run_blocking`
+async fn run_with_timeout<T>(
+    timeout: Duration,
+    command: impl Future<Output = Result<T, Box<dyn std::error::Error + Send + Sync>>>,
+) -> Result<T, Box<dyn std::error::Error + Send + Sync>> {
+    tokio::time::timeout(timeout, command)
+        .await
+        .unwrap_or_else(|_| Err(CommandTimeout(timeout).into()))
+}
+
 /// Execute a single command (helper function for batch processing and text message handling)
+/// In read-only mode mutating commands are refused with a message instead of being run
+/// Every command that is run is recorded in the chat's audit log
+/// A command running longer than `command_timeout` is abandoned and fails with `CommandTimeout`
+/// Category import commands in a batch don't reply, their `ImportOutcome` is returned instead
+#[allow(clippy::too_many_arguments)]
 pub async fn execute_command(
     bot: Bot,
     chat: Chat,
@@ -184,82 +703,447 @@ pub async fn execute_command(
     storage: Arc<dyn StorageTrait>,
     cmd: Command,
     batch: bool,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let target = CommandReplyTarget {
-        bot: bot.clone(),
-        chat: chat.clone(),
-        msg_id,
-        batch,
-        callback_data_storage: storage.clone().as_callback_data_storage(),
+    callback_query_id: Option<CallbackQueryId>,
+    read_only: bool,
+    user_id: Option<UserId>,
+    command_timeout: Duration,
+) -> Result<Option<ImportOutcome>, Box<dyn std::error::Error + Send + Sync>> {
+    let target = CommandReplyTarget::new(
+        bot.clone(),
+        chat.clone(),
+        storage.clone().as_callback_data_storage(),
+    )
+    .with_msg_id(msg_id)
+    .with_batch(batch)
+    .with_callback_query(callback_query_id);
+    let result = if read_only && cmd.is_mutating() {
+        // Batched commands are refused silently, the batch summary reports them once
+        if target.batch {
+            return Ok(None);
+        }
+        target
+            .send_markdown_message(markdown_string!("🔒 Bot is in read\\-only mode\\."))
+            .await
+            .map(|_| None)
+            .map_err(|e| e.into())
+    } else {
+        let command = cmd.to_string();
+        let mutating = cmd.is_mutating();
+        storage
+            .clone()
+            .as_audit_storage()
+            .add_audit_entry(
+                chat.id,
+                AuditEntry {
+                    timestamp: Utc::now().timestamp(),
+                    user_id: user_id.map(|id| id.0),
+                    command: command.clone(),
+                },
+            )
+            .await;
+        let result =
+            run_with_timeout(command_timeout, run_command(&target, storage.clone(), cmd)).await;
+        if let Err(e) = &result
+            && e.is::<CommandTimeout>()
+        {
+            log::error!("Command {} in chat {} timed out: {}", command, chat.id, e);
+        }
+        // A batch refreshes the report once, after all its commands
+        if mutating
+            && !target.batch
+            && result.is_ok()
+            && let Err(e) =
+                refresh_last_report(bot, chat.clone(), storage, Utc::now().timestamp()).await
+        {
+            log::error!("Failed to refresh the report in chat {}: {}", chat.id, e);
+        }
+        result
     };
+    // Stop the button spinner once the command is processed, even if it failed
+    // A failed answer (e.g. an expired query) must not hide the command's own result
+    if let Err(e) = target.answer_callback(None).await {
+        log::warn!("Failed to answer callback query: {}", e);
+    }
+    result
+}
+
+/// Dispatch the command to its implementation with the context it needs
+async fn run_command(
+    target: &CommandReplyTarget,
+    storage: Arc<dyn StorageTrait>,
+    cmd: Command,
+) -> Result<Option<ImportOutcome>, Box<dyn std::error::Error + Send + Sync>> {
     match cmd {
         Command::Start(start) => {
-            start.run(&target, ()).await?;
+            start
+                .run(target, storage.clone().as_settings_storage())
+                .await?;
+        }
+        Command::Version(version) => {
+            version.run(target, ()).await?;
+        }
+        Command::Chats(chats) => {
+            chats
+                .run(target, storage.clone().as_category_storage())
+                .await?;
         }
         Command::Help(help) => {
-            help.run(&target, ()).await?;
+            help.run(target, ()).await?;
         }
         Command::List(list) => {
-            list.run(&target, storage.clone().as_expense_storage())
-                .await?;
+            list.run(target, storage.clone()).await?;
         }
         Command::Report(report) => {
-            report.run(&target, storage.clone()).await?;
+            report.run(target, storage.clone()).await?;
         }
         Command::ClearExpenses(clear_expenses) => {
-            clear_expenses
-                .run(&target, storage.clone().as_expense_storage())
+            clear_expenses.run(target, storage.clone()).await?;
+        }
+        Command::Import(import) => {
+            import
+                .run(target, storage.clone().as_expense_storage())
                 .await?;
         }
+        Command::DeleteMatching(delete_matching) => {
+            delete_matching.run(target, storage.clone()).await?;
+        }
         Command::ClearCategories(clear_categories) => {
-            clear_categories
-                .run(&target, storage.clone().as_category_storage())
-                .await?;
+            clear_categories.run(target, storage.clone()).await?;
         }
         Command::AddCategory(add_category) => {
-            add_category
-                .run(&target, storage.clone().as_category_storage())
-                .await?;
+            if target.batch {
+                let settings = storage
+                    .clone()
+                    .as_settings_storage()
+                    .get_chat_settings(target.chat.id)
+                    .await;
+                if let Some(outcome) = import_category(
+                    &*storage.clone().as_category_storage(),
+                    target.chat.id,
+                    &add_category,
+                    settings.uncategorized_name(),
+                    settings.case_insensitive_filters,
+                )
+                .await
+                {
+                    return Ok(Some(outcome));
+                }
+            }
+            add_category.run(target, storage.clone()).await?;
         }
         Command::Categories(categories) => {
             categories
-                .run(&target, storage.clone().as_category_storage())
+                .run(target, storage.clone().as_category_storage())
                 .await?;
         }
         Command::AddFilter(add_filter) => {
-            add_filter.run(&target, storage.clone()).await?;
+            if target.batch {
+                let case_insensitive = storage
+                    .clone()
+                    .as_settings_storage()
+                    .get_chat_settings(target.chat.id)
+                    .await
+                    .case_insensitive_filters;
+                if let Some(outcome) = import_filter(
+                    &*storage.clone().as_category_storage(),
+                    target.chat.id,
+                    &add_filter,
+                    case_insensitive,
+                )
+                .await
+                {
+                    return Ok(Some(outcome));
+                }
+            }
+            add_filter.run(target, storage.clone()).await?;
         }
         Command::RemoveCategory(remove_category) => {
             remove_category
-                .run(&target, storage.clone().as_category_storage())
+                .run(target, storage.clone().as_category_storage())
                 .await?;
         }
         Command::RenameCategory(rename_category) => {
-            rename_category
-                .run(&target, storage.clone().as_category_storage())
-                .await?;
+            rename_category.run(target, storage.clone()).await?;
         }
         Command::RemoveFilter(remove_filter) => {
             remove_filter
-                .run(&target, storage.clone().as_category_storage())
+                .run(target, storage.clone().as_category_storage())
                 .await?;
         }
         Command::EditFilter(edit_filter) => {
-            edit_filter
-                .run(&target, storage.clone().as_category_storage())
-                .await?;
+            edit_filter.run(target, storage.clone()).await?;
+        }
+        Command::DuplicateFilter(duplicate_filter) => {
+            duplicate_filter.run(target, storage.clone()).await?;
+        }
+        Command::MoveFilter(move_filter) => {
+            move_filter.run(target, storage.clone()).await?;
         }
         Command::AddExpense(add_expense) => {
-            add_expense
-                .run(&target, storage.clone().as_expense_storage())
-                .await?;
+            add_expense.run(target, storage.clone()).await?;
         }
         Command::AddWordsFilter(add_words_filter) => {
-            add_words_filter.run(&target, storage.clone()).await?;
+            add_words_filter.run(target, storage.clone()).await?;
         }
         Command::EditWordsFilter(edit_words_filter) => {
-            edit_words_filter.run(&target, storage.clone()).await?;
+            edit_words_filter.run(target, storage.clone()).await?;
+        }
+        Command::AmountGrouping(amount_grouping) => {
+            amount_grouping
+                .run(target, storage.clone().as_settings_storage())
+                .await?;
+        }
+        Command::DecimalComma(decimal_comma) => {
+            decimal_comma
+                .run(target, storage.clone().as_settings_storage())
+                .await?;
+        }
+        Command::Audit(audit) => {
+            audit
+                .run(target, storage.clone().as_audit_storage())
+                .await?;
+        }
+        Command::Recategorize(recategorize) => {
+            recategorize.run(target, storage.clone()).await?;
+        }
+        Command::ReportFile(report_file) => {
+            report_file.run(target, storage.clone()).await?;
+        }
+        Command::ReportAccount(report_account) => {
+            report_account.run(target, storage.clone()).await?;
+        }
+        Command::Total(total) => {
+            total.run(target, storage.clone()).await?;
+        }
+        Command::ReportPageSize(report_page_size) => {
+            report_page_size
+                .run(target, storage.clone().as_settings_storage())
+                .await?;
+        }
+        Command::ListFilters(list_filters) => {
+            list_filters
+                .run(target, storage.clone().as_category_storage())
+                .await?;
+        }
+        Command::AddLiteralFilter(add_literal_filter) => {
+            add_literal_filter.run(target, storage.clone()).await?;
         }
+        Command::BackupNow(backup_now) => {
+            backup_now.run(target, storage.clone()).await?;
+        }
+        Command::UncategorizedName(uncategorized_name) => {
+            uncategorized_name.run(target, storage.clone()).await?;
+        }
+        Command::AlsoOther(also_other) => {
+            also_other.run(target, storage.clone()).await?;
+        }
+        Command::Log(log) => {
+            log.run(target, storage.clone().as_batch_storage()).await?;
+        }
+        Command::CaseInsensitive(case_insensitive) => {
+            case_insensitive
+                .run(target, storage.clone().as_settings_storage())
+                .await?;
+        }
+        Command::SetGoal(set_goal) => {
+            set_goal
+                .run(target, storage.clone().as_settings_storage())
+                .await?;
+        }
+        Command::FilterStats(filter_stats) => {
+            filter_stats.run(target, storage.clone()).await?;
+        }
+        Command::DumpFilters(dump_filters) => {
+            dump_filters
+                .run(target, storage.clone().as_category_storage())
+                .await?;
+        }
+        Command::SuggestCategories(suggest_categories) => {
+            suggest_categories.run(target, storage.clone()).await?;
+        }
+        Command::ConfirmExpenses(confirm_expenses) => {
+            confirm_expenses
+                .run(target, storage.clone().as_settings_storage())
+                .await?;
+        }
+        Command::ExpenseTemplate(expense_template) => {
+            expense_template
+                .run(target, storage.clone().as_settings_storage())
+                .await?;
+        }
+        Command::PendingExpense(pending_expense) => {
+            pending_expense.run(target, storage.clone()).await?;
+        }
+        Command::MoveOptions(move_options) => {
+            move_options.run(target, storage.clone()).await?;
+        }
+        Command::Frequent(frequent) => {
+            frequent.run(target, storage.clone()).await?;
+        }
+        Command::HideMenu(hide_menu) => {
+            hide_menu
+                .run(target, storage.clone().as_settings_storage())
+                .await?;
+        }
+        Command::ShowMenu(show_menu) => {
+            show_menu
+                .run(target, storage.clone().as_settings_storage())
+                .await?;
+        }
+        Command::Compare(compare) => {
+            compare.run(target, storage.clone()).await?;
+        }
+        Command::SetColor(set_color) => {
+            set_color
+                .run(target, storage.clone().as_category_storage())
+                .await?;
+        }
+        Command::TwoColumnSummary(two_column_summary) => {
+            two_column_summary
+                .run(target, storage.clone().as_settings_storage())
+                .await?;
+        }
+        Command::PairAmountLines(pair_amount_lines) => {
+            pair_amount_lines
+                .run(target, storage.clone().as_settings_storage())
+                .await?;
+        }
+        Command::Archive(archive) => {
+            archive.run(target, storage.clone()).await?;
+        }
+        Command::Unarchive(unarchive) => {
+            unarchive.run(target, storage.clone()).await?;
+        }
+        Command::SetTimezone(set_timezone) => {
+            set_timezone
+                .run(target, storage.clone().as_settings_storage())
+                .await?;
+        }
+        Command::RemoveFilters(remove_filters) => {
+            remove_filters
+                .run(target, storage.clone().as_category_storage())
+                .await?;
+        }
+        Command::PreviewFilter(preview_filter) => {
+            preview_filter.run(target, storage.clone()).await?;
+        }
+        Command::ForgetMe(forget_me) => {
+            forget_me.run(target, storage.clone()).await?;
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_only_command_table() {
+        let bot_name = "ledgerbot";
+        let table = [
+            ("/start", false),
+            ("/version", false),
+            ("/chats", false),
+            ("/help", false),
+            ("/list", false),
+            ("/report", false),
+            ("/categories", false),
+            ("/clear_expenses", true),
+            ("/clear_categories", true),
+            ("/add_category Food", true),
+            ("/add_filter Food (?i)coffee", true),
+            ("/remove_category Food", true),
+            ("/rename_category Food Meals", true),
+            ("/remove_filter Food 0", true),
+            ("/edit_filter Food 0 (?i)tea", true),
+            ("/duplicate_filter Food 0 (?i)tea", true),
+            ("/move_filter Food 0 Drinks", true),
+            ("/add_expense 2024-01-01 Coffee 5", true),
+            ("/add_words_filter Food", true),
+            ("/edit_words_filter Food", true),
+            ("/amount_grouping true", true),
+            ("/decimal_comma true", true),
+            ("/audit 5", false),
+            ("/recategorize", false),
+            ("/report_file", false),
+            ("/report_account card", false),
+            ("/total 2024-01-01 2024-01-31", false),
+            ("/import", true),
+            ("/delete_matching Coffee", true),
+            ("/report_page_size 10", true),
+            ("/list_filters Food", false),
+            ("/add_literal_filter Food coffee", true),
+            ("/backup_now", false),
+            ("/uncategorized_name Misc", true),
+            ("/also_other Food true", true),
+            ("/log 5", false),
+            ("/case_insensitive true", true),
+            ("/set_goal 1000", true),
+            ("/filter_stats", false),
+            ("/dump_filters Food", false),
+            ("/suggest_categories", false),
+            ("/confirm_expenses true", true),
+            ("/expense_template Added {description} {amount}", true),
+            ("/pending_expense true 1", true),
+            ("/move_options Coffee", false),
+            ("/frequent 5", false),
+            ("/hide_menu", true),
+            ("/show_menu", true),
+            ("/compare month", false),
+            ("/set_color Food #FF8800", true),
+            ("/two_column_summary true", true),
+            ("/pair_amount_lines true", true),
+            ("/archive before 2024-01-01", true),
+            ("/unarchive", true),
+            ("/set_timezone +03:00", true),
+            ("/remove_filters Food 0 2", true),
+            ("/preview_filter Food (?i)tea", false),
+            ("/forget_me", true),
+        ];
+        for (text, mutating) in table {
+            let cmd = Command::parse(text, bot_name).unwrap();
+            assert_eq!(cmd.is_mutating(), mutating, "{}", text);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_command_times_out() {
+        // A command which hangs far longer than allowed
+        let slow_command = async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(())
+        };
+        let error = run_with_timeout(Duration::from_millis(10), slow_command)
+            .await
+            .unwrap_err();
+        assert!(error.is::<CommandTimeout>());
+        assert_eq!(error.to_string(), "command did not finish in 0.01s");
+
+        // Commands finishing in time keep their own result
+        assert!(
+            run_with_timeout(Duration::from_secs(60), async { Ok(()) })
+                .await
+                .is_ok()
+        );
+        let failing_command = async { Err::<(), _>("failed".into()) };
+        let error = run_with_timeout(Duration::from_secs(60), failing_command)
+            .await
+            .unwrap_err();
+        assert!(!error.is::<CommandTimeout>());
+    }
+
+    #[tokio::test]
+    async fn test_blocking_work_times_out() {
+        // CPU-bound work on a blocking thread doesn't keep the timeout from firing
+        let busy_command = async {
+            run_blocking(|| std::thread::sleep(Duration::from_millis(500))).await;
+            Ok(())
+        };
+        let error = run_with_timeout(Duration::from_millis(10), busy_command)
+            .await
+            .unwrap_err();
+        assert!(error.is::<CommandTimeout>());
+        assert_eq!(run_blocking(|| 2 + 2).await, 4);
     }
-    Ok(())
 }
@@ -1,11 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
-use yoroolbot::{
-    command_trait::CommandTrait, markdown::MarkdownString, markdown_format, markdown_string,
-    storage::ButtonData,
-};
+use chrono::{Datelike, TimeZone, Utc};
+
+use yoroolbot::{markdown::MarkdownString, markdown_format, markdown_string, storage::ButtonData};
 
-use crate::{storages::Expense, utils::format_timestamp};
+use crate::{
+    storages::{Expense, FilterAssignments},
+    utils::{
+        amount::Amount,
+        filter_pattern::compile_filter,
+        format_amount::{AmountStyle, format_amount_localized},
+        format_timestamp,
+    },
+};
 
 /// Represents a conflict where an expense matches multiple categories
 #[derive(Debug, Clone)]
@@ -14,43 +21,163 @@ struct CategoryConflict {
     matching_categories: Vec<(String, String)>, // (category_name, matched_pattern)
 }
 
-/// Check if any expense matches multiple categories
-/// Returns Some with formatted error message if conflicts are found, None otherwise
-pub fn check_category_conflicts(
-    expenses: &[Expense],
-    categories: &HashMap<String, Vec<String>>,
-) -> Option<MarkdownString> {
-    let mut conflicts: Vec<CategoryConflict> = Vec::new();
+/// Regex matchers of a category: (category_name, [(pattern, regex)])
+pub type CategoryMatchers = Vec<(String, Vec<(String, regex::Regex)>)>;
 
-    // Build regex matchers for each category
-    let category_matchers: Vec<(String, Vec<(String, regex::Regex)>)> = categories
+/// Build regex matchers for each category, invalid patterns are skipped
+pub fn build_category_matchers(
+    categories: &HashMap<String, Vec<String>>,
+    case_insensitive: bool,
+) -> CategoryMatchers {
+    categories
         .iter()
         .map(|(name, patterns)| {
             let regexes: Vec<(String, regex::Regex)> = patterns
                 .iter()
                 .filter_map(|pattern| {
-                    regex::Regex::new(pattern)
+                    compile_filter(pattern, case_insensitive)
                         .ok()
                         .map(|re| (pattern.clone(), re))
                 })
                 .collect();
             (name.clone(), regexes)
         })
-        .collect();
+        .collect()
+}
 
-    // Check each expense for conflicts
+/// Find all categories matching the description
+/// Returns (category_name, matched_pattern) pairs, each category at most once
+pub fn find_matching_categories(
+    description: &str,
+    category_matchers: &CategoryMatchers,
+) -> Vec<(String, String)> {
+    let mut matching_categories: Vec<(String, String)> = Vec::new();
+    for (category_name, regexes) in category_matchers {
+        for (pattern, re) in regexes {
+            if re.is_match(description) {
+                matching_categories.push((category_name.clone(), pattern.clone()));
+                break; // Only add category once, even if multiple patterns match
+            }
+        }
+    }
+    matching_categories
+}
+
+/// Category and filter an expense is assigned to: the first matching filter of the first
+/// matching category, with the matchers sorted by category name
+fn assign_category<'a>(
+    description: &str,
+    sorted_matchers: &'a CategoryMatchers,
+) -> Option<(&'a String, &'a String)> {
+    sorted_matchers.iter().find_map(|(category_name, regexes)| {
+        regexes
+            .iter()
+            .find(|(_, re)| re.is_match(description))
+            .map(|(pattern, _)| (category_name, pattern))
+    })
+}
+
+/// Find the filter assigning each expense to its category, as the report groups them
+/// Expenses are identified by timestamp, description and position among the expenses
+/// with both the same, uncategorized ones are left out
+pub fn assign_filters(
+    expenses: &[Expense],
+    categories: &HashMap<String, Vec<String>>,
+    case_insensitive: bool,
+) -> FilterAssignments {
+    let mut category_matchers = build_category_matchers(categories, case_insensitive);
+    category_matchers.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut seen: HashMap<(i64, &str), usize> = HashMap::new();
+    let mut assignments = FilterAssignments::new();
+    for expense in expenses {
+        let position = seen
+            .entry((expense.timestamp, expense.description.as_str()))
+            .or_default();
+        let key = (expense.timestamp, expense.description.clone(), *position);
+        *position += 1;
+        if let Some((category_name, pattern)) =
+            assign_category(&expense.description, &category_matchers)
+        {
+            assignments.push((key, (category_name.clone(), pattern.clone())));
+        }
+    }
+    assignments
+}
+
+/// Group expenses by category, each expense goes into the first matching category
+/// Categories are tried in name order, expenses matching none are returned separately
+/// Expenses of the categories in `also_other` are additionally returned in the third list,
+/// to be shown in the uncategorized listing but not counted there
+pub fn categorize_expenses(
+    expenses: &[Expense],
+    categories: &HashMap<String, Vec<String>>,
+    also_other: &BTreeSet<String>,
+    case_insensitive: bool,
+) -> (BTreeMap<String, Vec<Expense>>, Vec<Expense>, Vec<Expense>) {
+    let mut category_matchers = build_category_matchers(categories, case_insensitive);
+    category_matchers.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut categorized: BTreeMap<String, Vec<Expense>> = BTreeMap::new();
+    let mut uncategorized: Vec<Expense> = Vec::new();
+    let mut also_in_other: Vec<Expense> = Vec::new();
     for expense in expenses {
-        let mut matching_categories: Vec<(String, String)> = Vec::new();
-
-        // Find all categories that match this expense
-        for (category_name, regexes) in &category_matchers {
-            for (pattern, re) in regexes {
-                if re.is_match(&expense.description) {
-                    matching_categories.push((category_name.clone(), pattern.clone()));
-                    break; // Only add category once, even if multiple patterns match
+        match assign_category(&expense.description, &category_matchers) {
+            Some((category_name, _)) => {
+                if also_other.contains(category_name) {
+                    also_in_other.push(expense.clone());
                 }
+                categorized
+                    .entry(category_name.clone())
+                    .or_default()
+                    .push(expense.clone())
             }
+            None => uncategorized.push(expense.clone()),
         }
+    }
+    (categorized, uncategorized, also_in_other)
+}
+
+/// Total amount of each category in name order, followed by the uncategorized bucket
+/// An expense matching several categories is counted in the first one only
+/// Expenses of the categories in `also_other` are counted in their own category only
+pub fn category_totals(
+    expenses: &[Expense],
+    categories: &HashMap<String, Vec<String>>,
+    uncategorized_name: &str,
+    also_other: &BTreeSet<String>,
+    case_insensitive: bool,
+) -> Vec<(String, Amount)> {
+    let (categorized, uncategorized, also_in_other) =
+        categorize_expenses(expenses, categories, also_other, case_insensitive);
+    let mut totals: Vec<(String, Amount)> = categorized
+        .iter()
+        .map(|(name, items)| (name.clone(), items.iter().map(|e| e.amount).sum()))
+        .collect();
+    if !uncategorized.is_empty() || !also_in_other.is_empty() {
+        totals.push((
+            uncategorized_name.to_string(),
+            uncategorized.iter().map(|e| e.amount).sum(),
+        ));
+    }
+    totals
+}
+
+/// Check if any expense matches multiple categories
+/// Returns Some with formatted error message if conflicts are found, None otherwise
+pub fn check_category_conflicts(
+    expenses: &[Expense],
+    categories: &HashMap<String, Vec<String>>,
+    case_insensitive: bool,
+) -> Option<MarkdownString> {
+    let mut conflicts: Vec<CategoryConflict> = Vec::new();
+
+    let category_matchers = build_category_matchers(categories, case_insensitive);
+
+    // Check each expense for conflicts
+    for expense in expenses {
+        let matching_categories =
+            find_matching_categories(&expense.description, &category_matchers);
 
         // If expense matches more than one category, it's a conflict
         if matching_categories.len() > 1 {
@@ -63,29 +190,34 @@ pub fn check_category_conflicts(
 
     // If there are conflicts, format and return error message
     if !conflicts.is_empty() {
-        let mut error_message = markdown_string!("❌ *Category Conflicts Detected*\n\n");
-        error_message = error_message
-            + markdown_string!(
-                "The following expenses match multiple categories\\.\n\
-                 Please adjust your filters to avoid overlapping categories\\.\n\n"
-            );
-
-        for conflict in conflicts {
-            let date_str = format_timestamp(conflict.expense.timestamp);
-            error_message = error_message
-                + markdown_format!(
+        let conflict_lines: MarkdownString = conflicts
+            .into_iter()
+            .map(|conflict| {
+                let date_str = format_timestamp(conflict.expense.timestamp);
+                let matching_lines: MarkdownString = conflict
+                    .matching_categories
+                    .into_iter()
+                    .map(|(category_name, pattern)| {
+                        markdown_format!("  • {} \\(filter: `{}`\\)\n", &*category_name, &*pattern)
+                    })
+                    .collect();
+                markdown_format!(
                     "📝 *Expense:* {} {} {}\n",
                     &*date_str,
                     &*conflict.expense.description,
-                    conflict.expense.amount
-                );
-            error_message = error_message + markdown_string!("*Matching categories:*\n");
-            for (category_name, pattern) in conflict.matching_categories {
-                error_message = error_message
-                    + markdown_format!("  • {} \\(filter: `{}`\\)\n", &*category_name, &*pattern);
-            }
-            error_message = error_message + markdown_string!("\n");
-        }
+                    conflict.expense.amount.to_string()
+                ) + markdown_string!("*Matching categories:*\n")
+                    + matching_lines
+                    + markdown_string!("\n")
+            })
+            .collect();
+
+        let error_message = markdown_string!("❌ *Category Conflicts Detected*\n\n")
+            + markdown_string!(
+                "The following expenses match multiple categories\\.\n\
+                 Please adjust your filters to avoid overlapping categories\\.\n\n"
+            )
+            + conflict_lines;
 
         return Some(error_message);
     }
@@ -93,32 +225,98 @@ pub fn check_category_conflicts(
     None
 }
 
+/// Keep only expenses with amount at or above the threshold
+/// Returns all expenses unchanged when no threshold is set
+pub fn filter_min_amount(expenses: &[Expense], min_amount: Option<f64>) -> Vec<Expense> {
+    match min_amount {
+        Some(min_amount) => expenses
+            .iter()
+            .filter(|expense| expense.amount >= Amount::from(min_amount))
+            .cloned()
+            .collect(),
+        None => expenses.to_vec(),
+    }
+}
+
+/// Format the minimum amount threshold for report headers
+/// Returns an empty MarkdownString when no threshold is set
+pub fn format_min_amount_note(min_amount: Option<f64>) -> MarkdownString {
+    match min_amount {
+        Some(min_amount) => markdown_format!(" \\(amounts ≥ `{}`\\)", min_amount),
+        None => MarkdownString::new(),
+    }
+}
+
+/// Number of cells in the monthly goal progress bar
+const PROGRESS_BAR_WIDTH: usize = 10;
+
+/// Render spending against a goal as a text bar with percent: `[#####-----] 50%`
+/// The bar is full past the goal while the percent keeps growing
+pub fn format_progress_bar(spent: Amount, goal: Amount) -> String {
+    let percent = if goal.minor_units() > 0 {
+        // Widened so that a huge total can't overflow the multiplication
+        let percent = i128::from(spent.minor_units().max(0)) * 100 / i128::from(goal.minor_units());
+        usize::try_from(percent).unwrap_or(usize::MAX)
+    } else {
+        0
+    };
+    let filled = (percent.min(100) * PROGRESS_BAR_WIDTH) / 100;
+    format!(
+        "[{}{}] {}%",
+        "#".repeat(filled),
+        "-".repeat(PROGRESS_BAR_WIDTH - filled),
+        percent
+    )
+}
+
+/// Total of the expenses in the calendar month (UTC) containing `now`
+pub fn month_total(expenses: &[Expense], now: i64) -> Amount {
+    let now = Utc.timestamp_opt(now, 0).unwrap();
+    expenses
+        .iter()
+        .filter(|expense| {
+            let date = Utc.timestamp_opt(expense.timestamp, 0).unwrap();
+            date.year() == now.year() && date.month() == now.month()
+        })
+        .map(|expense| expense.amount)
+        .sum()
+}
+
+/// Format the current month's spending against the monthly goal
+pub fn format_goal_progress(
+    expenses: &[Expense],
+    goal: Amount,
+    now: i64,
+    amount_style: AmountStyle,
+) -> MarkdownString {
+    let spent = month_total(expenses, now);
+    markdown_format!(
+        "🎯 Monthly goal: `{}` of `{}` `{}`\n",
+        format_amount_localized(spent.as_f64(), 2, amount_style),
+        format_amount_localized(goal.as_f64(), 2, amount_style),
+        format_progress_bar(spent, goal)
+    )
+}
+
 /// Filter expenses for a specific category
+/// `uncategorized_name` selects the expenses matching no category,
+/// together with the expenses of the categories in `also_other`
 pub fn filter_category_expenses<'a>(
     category_name: &str,
     all_expenses: &'a [Expense],
     categories: &HashMap<String, Vec<String>>,
+    uncategorized_name: &str,
+    also_other: &BTreeSet<String>,
+    case_insensitive: bool,
 ) -> Vec<&'a Expense> {
-    if category_name == "Other" {
-        // "Other" category: uncategorized expenses
-        let category_matchers: Vec<(String, Vec<regex::Regex>)> = categories
-            .iter()
-            .map(|(name, patterns)| {
-                let regexes: Vec<regex::Regex> = patterns
-                    .iter()
-                    .filter_map(|pattern| regex::Regex::new(pattern).ok())
-                    .collect();
-                (name.clone(), regexes)
-            })
-            .collect();
-
+    if category_name == uncategorized_name {
+        // Uncategorized bucket ("Other" by default): expenses matching no category
+        let category_matchers = build_category_matchers(categories, case_insensitive);
         all_expenses
             .iter()
             .filter(|expense| {
-                // Check if expense doesn't match any category
-                !category_matchers
-                    .iter()
-                    .any(|(_, regexes)| regexes.iter().any(|re| re.is_match(&expense.description)))
+                let matching = find_matching_categories(&expense.description, &category_matchers);
+                matching.is_empty() || matching.iter().any(|(name, _)| also_other.contains(name))
             })
             .collect()
     } else {
@@ -127,7 +325,7 @@ pub fn filter_category_expenses<'a>(
         if let Some(patterns) = patterns {
             let regexes: Vec<regex::Regex> = patterns
                 .iter()
-                .filter_map(|pattern| regex::Regex::new(pattern).ok())
+                .filter_map(|pattern| compile_filter(pattern, case_insensitive).ok())
                 .collect();
 
             all_expenses
@@ -181,10 +379,12 @@ fn wrap_text(text: &str, max_width: usize) -> Vec<String> {
 
 /// Format a simple report for single category with pagination
 /// Returns only the formatted expense data (without header or total)
+/// Amounts are shown in the chat's `amount_style`
 pub fn format_single_category_report(
     expenses: &[&Expense],
     page_number: usize,
     records_per_page: usize,
+    amount_style: AmountStyle,
 ) -> String {
     if expenses.is_empty() {
         return String::new();
@@ -204,7 +404,11 @@ pub fn format_single_category_report(
     // Find maximum amount width for alignment
     let max_amount_width = records_to_show
         .iter()
-        .map(|e| format!("{:.2}", e.amount).len())
+        .map(|e| {
+            format_amount_localized(e.amount.as_f64(), 2, amount_style)
+                .chars()
+                .count()
+        })
         .max()
         .unwrap_or(0);
 
@@ -231,7 +435,11 @@ pub fn format_single_category_report(
         let description_lines = wrap_text(&expense.description, DESCRIPTION_WIDTH);
 
         // Format with aligned amount after description
-        let amount_str = format!("{:>width$.2}", expense.amount, width = max_amount_width);
+        let amount_str = format!(
+            "{:>width$}",
+            format_amount_localized(expense.amount.as_f64(), 2, amount_style),
+            width = max_amount_width
+        );
 
         // First line with date, description, and amount
         // Pad description to fixed width using char count for Unicode support
@@ -269,102 +477,303 @@ pub fn format_single_category_report(
     report_lines.join("\n")
 }
 
-/// Format category summary with interactive menu for category selection
-pub fn format_category_summary(
-    expenses: &[Expense],
-    categories: &HashMap<String, Vec<String>>,
-) -> (MarkdownString, Vec<Vec<ButtonData>>) {
-    if expenses.is_empty() {
-        return (markdown_string!("No expenses recorded yet\\."), vec![]);
+/// Note on how many of the rows a report page shows, empty when the page shows all of them
+pub fn format_shown_note(page_number: usize, records_per_page: usize, total: usize) -> String {
+    let shown = total
+        .saturating_sub(page_number * records_per_page)
+        .min(records_per_page);
+    if shown < total {
+        format!(", showing {} of {}", shown, total)
+    } else {
+        String::new()
+    }
+}
+
+/// Collapse expenses with the same description into one row of (description, count, total)
+/// Rows keep the order of the first expense with each description
+pub fn collapse_by_description(expenses: &[&Expense]) -> Vec<(String, usize, Amount)> {
+    let mut rows: Vec<(String, usize, Amount)> = Vec::new();
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    for expense in expenses {
+        match positions.get(expense.description.as_str()) {
+            Some(&position) => {
+                rows[position].1 += 1;
+                rows[position].2 += expense.amount;
+            }
+            None => {
+                positions.insert(&expense.description, rows.len());
+                rows.push((expense.description.clone(), 1, expense.amount));
+            }
+        }
     }
+    rows
+}
 
-    // Build regex matchers for each category
-    let category_matchers: Vec<(String, Vec<regex::Regex>)> = categories
+/// Format collapsed rows as `Coffee x7  35.00` lines for the given page
+/// The count is shown only for descriptions occurring more than once
+pub fn format_collapsed_category_report(
+    rows: &[(String, usize, Amount)],
+    page_number: usize,
+    records_per_page: usize,
+    amount_style: AmountStyle,
+) -> String {
+    const DESCRIPTION_WIDTH: usize = 20;
+
+    let rows_to_show: Vec<&(String, usize, Amount)> = rows
         .iter()
-        .map(|(name, patterns)| {
-            let regexes: Vec<regex::Regex> = patterns
-                .iter()
-                .filter_map(|pattern| regex::Regex::new(pattern).ok())
-                .collect();
-            (name.clone(), regexes)
-        })
+        .skip(page_number * records_per_page)
+        .take(records_per_page)
         .collect();
+    let max_amount_width = rows_to_show
+        .iter()
+        .map(|(_, _, total)| {
+            format_amount_localized(total.as_f64(), 2, amount_style)
+                .chars()
+                .count()
+        })
+        .max()
+        .unwrap_or(0);
 
-    // Group expenses by category
-    let mut categorized: HashMap<String, Vec<Expense>> = HashMap::new();
-    let mut uncategorized: Vec<Expense> = Vec::new();
-
-    for expense in expenses.iter() {
-        let mut matched = false;
+    rows_to_show
+        .iter()
+        .map(|(description, count, total)| {
+            let label = if *count > 1 {
+                format!("{} x{}", description, count)
+            } else {
+                description.clone()
+            };
+            let padding = " ".repeat(DESCRIPTION_WIDTH.saturating_sub(label.chars().count()));
+            format!(
+                "{}{}  {:>width$}",
+                label,
+                padding,
+                format_amount_localized(total.as_f64(), 2, amount_style),
+                width = max_amount_width
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
-        // Try to match against each category
-        for (category_name, regexes) in &category_matchers {
-            // Check if description matches any of the patterns in this category
-            if regexes.iter().any(|re| re.is_match(&expense.description)) {
-                categorized
-                    .entry(category_name.clone())
-                    .or_default()
-                    .push(expense.clone());
-                matched = true;
-                break; // Each expense goes into first matching category
-            }
-        }
+/// Render the full report as a plain Markdown document for export
+/// Contains the category summary table followed by a table of expenses per category
+/// Unlike chat messages no MarkdownV2 escaping is applied, only `|` is escaped in table cells
+pub fn format_report_markdown_file(
+    expenses: &[Expense],
+    categories: &HashMap<String, Vec<String>>,
+    amount_style: AmountStyle,
+    uncategorized_name: &str,
+    also_other: &BTreeSet<String>,
+    case_insensitive: bool,
+) -> String {
+    let cell = |text: &str| text.replace('|', "\\|");
+    let amount = |value: Amount| format_amount_localized(value.as_f64(), 2, amount_style);
 
-        if !matched {
-            uncategorized.push(expense.clone());
-        }
+    let (categorized, uncategorized, also_in_other) =
+        categorize_expenses(expenses, categories, also_other, case_insensitive);
+    // Sections are (name, subtotal, listed expenses)
+    let mut sections: Vec<(&str, Amount, Vec<&Expense>)> = categorized
+        .iter()
+        .map(|(name, items)| {
+            (
+                name.as_str(),
+                items.iter().map(|e| e.amount).sum(),
+                items.iter().collect(),
+            )
+        })
+        .collect();
+    if !uncategorized.is_empty() || !also_in_other.is_empty() {
+        // Expenses also listed here are already counted in their own category
+        sections.push((
+            uncategorized_name,
+            uncategorized.iter().map(|e| e.amount).sum(),
+            uncategorized.iter().chain(&also_in_other).collect(),
+        ));
     }
 
-    // Sort category names for consistent output
-    let mut category_names: Vec<String> = categorized.keys().cloned().collect();
-    category_names.sort();
-
-    // Calculate totals
-    let mut category_subtotals: Vec<(String, f64)> = Vec::new();
-    let mut total = 0.0;
+    let mut lines = vec![
+        "# Expense report".to_string(),
+        String::new(),
+        "| Category | Amount |".to_string(),
+        "|---|---:|".to_string(),
+    ];
+    let mut total = Amount::ZERO;
+    for (name, subtotal, _) in &sections {
+        total += *subtotal;
+        lines.push(format!("| {} | {} |", cell(name), amount(*subtotal)));
+    }
+    lines.push(format!("| **Total** | **{}** |", amount(total)));
 
-    for category_name in &category_names {
-        if let Some(items) = categorized.get(category_name) {
-            let category_total: f64 = items.iter().map(|e| e.amount).sum();
-            category_subtotals.push((category_name.clone(), category_total));
-            total += category_total;
+    for (name, _, items) in &mut sections {
+        items.sort_by_key(|e| e.timestamp);
+        lines.push(String::new());
+        lines.push(format!("## {}", name));
+        lines.push(String::new());
+        lines.push("| Date | Description | Amount |".to_string());
+        lines.push("|---|---|---:|".to_string());
+        for expense in items.iter() {
+            lines.push(format!(
+                "| {} | {} | {} |",
+                format_timestamp(expense.timestamp),
+                cell(&expense.description),
+                amount(expense.amount)
+            ));
         }
     }
+    lines.push(String::new());
+    lines.join("\n")
+}
 
-    if !uncategorized.is_empty() {
-        let category_total: f64 = uncategorized.iter().map(|e| e.amount).sum();
-        category_subtotals.push(("Other".to_string(), category_total));
-        total += category_total;
-    }
+/// Minimum width of the amount column of the summary table
+const SUMMARY_AMOUNT_WIDTH: usize = 10;
 
-    // Build summary table
-    let max_name_len = category_subtotals
+/// Format `(name, rendered amount)` rows, a separator and the total row as aligned columns
+/// Widths are measured on the rendered strings in characters, so that amounts with
+/// currency symbols or thousands separators and non-ASCII names stay aligned
+pub fn format_summary_table(rows: &[(String, String)], total: &str) -> Vec<String> {
+    let name_width = rows
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Total".len());
+    let amount_width = rows
         .iter()
-        .map(|(name, _)| name.len())
+        .map(|(_, amount)| amount.as_str())
+        .chain([total])
+        .map(|amount| amount.chars().count())
         .max()
         .unwrap_or(0)
-        .max(5); // At least as wide as "Total"
+        .max(SUMMARY_AMOUNT_WIDTH);
 
-    let mut table_lines = Vec::new();
+    let row = |name: &str, amount: &str| {
+        format!(
+            "{:<name_width$} {:>amount_width$}",
+            name,
+            amount,
+            name_width = name_width,
+            amount_width = amount_width
+        )
+    };
+    let mut table_lines: Vec<String> = rows
+        .iter()
+        .map(|(name, amount)| row(name, amount))
+        .collect();
+    table_lines.push("-".repeat(name_width + 1 + amount_width));
+    table_lines.push(row("Total", total));
+    table_lines
+}
+
+/// Minimum number of categories for the two-column summary layout to be used
+pub const TWO_COLUMN_MIN_ROWS: usize = 8;
+
+/// Spaces between the two columns of the summary table
+const TWO_COLUMN_GAP: &str = "   ";
+
+/// Align `(name, rendered amount)` rows as one column: names to the left, amounts to the right
+fn align_column(rows: &[(String, String)]) -> Vec<String> {
+    let width = |cell: fn(&(String, String)) -> &String| {
+        rows.iter()
+            .map(|row| cell(row).chars().count())
+            .max()
+            .unwrap_or(0)
+    };
+    let name_width = width(|(name, _)| name);
+    let amount_width = width(|(_, amount)| amount);
+    rows.iter()
+        .map(|(name, amount)| format!("{:<name_width$} {:>amount_width$}", name, amount))
+        .collect()
+}
 
-    // Add each category row
-    for (category_name, subtotal) in &category_subtotals {
-        let padded_name = format!("{:<width$}", category_name, width = max_name_len);
-        let amount_str = format!("{:>10.2}", subtotal);
-        table_lines.push(format!("{} {}", padded_name, amount_str));
+/// Pack `(name, rendered amount)` rows into two columns filled top to bottom, the left one
+/// taking the extra row of an odd count. Amounts are aligned within each column
+pub fn pack_two_columns(rows: &[(String, String)]) -> Vec<String> {
+    let (left, right) = rows.split_at(rows.len().div_ceil(2));
+    let right = align_column(right);
+    align_column(left)
+        .into_iter()
+        .enumerate()
+        .map(|(index, line)| match right.get(index) {
+            Some(right_line) => format!("{}{}{}", line, TWO_COLUMN_GAP, right_line),
+            None => line,
+        })
+        .collect()
+}
+
+/// Format the summary table with rows in two columns, followed by a separator and
+/// the total row spanning both of them
+pub fn format_two_column_summary_table(rows: &[(String, String)], total: &str) -> Vec<String> {
+    let mut table_lines = pack_two_columns(rows);
+    let width = table_lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Total".len() + 1 + total.chars().count());
+    table_lines.push("-".repeat(width));
+    table_lines.push(format!(
+        "Total{:>amount_width$}",
+        total,
+        amount_width = width - "Total".len()
+    ));
+    table_lines
+}
+
+/// Format category summary with interactive menu for category selection
+/// The minimum amount threshold is shown in the header and preserved in the category buttons
+/// Amounts are shown in the chat's `amount_style`
+/// Expenses matching no category are listed under `uncategorized_name`
+/// The categories in `also_other` keep the uncategorized button available for their expenses
+/// With `two_columns` a summary of many categories is laid out in two columns
+#[allow(clippy::too_many_arguments)]
+pub fn format_category_summary(
+    expenses: &[Expense],
+    categories: &HashMap<String, Vec<String>>,
+    min_amount: Option<f64>,
+    amount_style: AmountStyle,
+    uncategorized_name: &str,
+    also_other: &BTreeSet<String>,
+    case_insensitive: bool,
+    two_columns: bool,
+) -> (MarkdownString, Vec<Vec<ButtonData>>) {
+    if expenses.is_empty() {
+        return (markdown_string!("No expenses recorded yet\\."), vec![]);
     }
 
-    // Add separator line
-    table_lines.push("-".repeat(max_name_len + 11));
+    let category_subtotals = category_totals(
+        expenses,
+        categories,
+        uncategorized_name,
+        also_other,
+        case_insensitive,
+    );
+    let total: Amount = category_subtotals
+        .iter()
+        .map(|(_, subtotal)| *subtotal)
+        .sum();
 
-    // Add total row
-    let total_label = format!("{:<width$}", "Total", width = max_name_len);
-    let total_amount = format!("{:>10.2}", total);
-    table_lines.push(format!("{} {}", total_label, total_amount));
+    // Build summary table
+    let rows: Vec<(String, String)> = category_subtotals
+        .iter()
+        .map(|(name, subtotal)| {
+            (
+                name.clone(),
+                format_amount_localized(subtotal.as_f64(), 2, amount_style),
+            )
+        })
+        .collect();
+    let total = format_amount_localized(total.as_f64(), 2, amount_style);
+    let table_lines = if two_columns && rows.len() >= TWO_COLUMN_MIN_ROWS {
+        format_two_column_summary_table(&rows, &total)
+    } else {
+        format_summary_table(&rows, &total)
+    };
 
-    // Join all lines and use @code modifier to wrap in code block
-    let table_content = table_lines.join("\n");
-    let summary_message = markdown_format!("📊 *Expense Summary*\n\n{}\n\n", @code table_content);
+    let summary_message = markdown_format!(
+        "📊 *Expense Summary*{}\n\n{}\n\n",
+        @raw format_min_amount_note(min_amount),
+        @raw MarkdownString::code_block_from_lines(&table_lines)
+    );
     let summary_message = summary_message + markdown_string!("Select a category to view details:");
 
     // Create inline keyboard button data using Callback
@@ -377,10 +786,12 @@ pub fn format_category_summary(
         let command = crate::commands::command_report::CommandReport {
             category: Some(category_name.clone()),
             page: None,
+            min_amount,
+            collapse: false,
         };
         current_row.push(ButtonData::Callback(
             category_name.clone(),
-            command.to_command_string(false),
+            command.to_report_command_string(false),
         ));
 
         // Start a new row after 4 buttons
@@ -397,3 +808,837 @@ pub fn format_category_summary(
 
     (summary_message, buttons)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn test_expenses() -> Vec<Expense> {
+        let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC
+        vec![
+            Expense {
+                description: "Coffee".to_string(),
+                amount: Amount::from(5.50),
+                timestamp,
+                ..Default::default()
+            },
+            Expense {
+                description: "Lunch".to_string(),
+                amount: Amount::from(12.00),
+                timestamp,
+                ..Default::default()
+            },
+            Expense {
+                description: "Taxi".to_string(),
+                amount: Amount::from(3.00),
+                timestamp,
+                ..Default::default()
+            },
+            Expense {
+                description: "Dinner".to_string(),
+                amount: Amount::from(25.00),
+                timestamp,
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_collapse_by_description() {
+        let mut expenses = test_expenses();
+        expenses.push(Expense {
+            description: "Coffee".to_string(),
+            amount: Amount::from(4.50),
+            timestamp: 1609545600,
+            ..Default::default()
+        });
+        let refs: Vec<&Expense> = expenses.iter().collect();
+
+        let rows = collapse_by_description(&refs);
+        assert_eq!(
+            rows,
+            vec![
+                ("Coffee".to_string(), 2, Amount::from(10.00)),
+                ("Lunch".to_string(), 1, Amount::from(12.00)),
+                ("Taxi".to_string(), 1, Amount::from(3.00)),
+                ("Dinner".to_string(), 1, Amount::from(25.00)),
+            ]
+        );
+
+        assert_eq!(
+            format_collapsed_category_report(&rows, 0, 2, AmountStyle::default()),
+            "Coffee x2             10.00\nLunch                 12.00"
+        );
+        assert_eq!(
+            format_collapsed_category_report(&rows, 1, 2, AmountStyle::default()),
+            "Taxi                   3.00\nDinner                25.00"
+        );
+    }
+
+    fn test_categories() -> HashMap<String, Vec<String>> {
+        let mut categories = HashMap::new();
+        categories.insert(
+            "Food".to_string(),
+            vec!["(?i)coffee|lunch|dinner".to_string()],
+        );
+        categories
+    }
+
+    #[test]
+    fn test_category_totals() {
+        let no_also_other = BTreeSet::new();
+        let totals = |categories: &HashMap<String, Vec<String>>| {
+            category_totals(&test_expenses(), categories, "Other", &no_also_other, false)
+        };
+
+        // Taxi is not matched by any filter
+        assert_eq!(
+            totals(&test_categories()),
+            vec![
+                ("Food".to_string(), Amount::from(42.50)),
+                ("Other".to_string(), Amount::from(3.00)),
+            ]
+        );
+
+        // Everything categorized, no uncategorized bucket
+        let mut categories = test_categories();
+        categories.insert("Travel".to_string(), vec!["Taxi".to_string()]);
+        assert_eq!(
+            totals(&categories),
+            vec![
+                ("Food".to_string(), Amount::from(42.50)),
+                ("Travel".to_string(), Amount::from(3.00)),
+            ]
+        );
+
+        // Coffee matches both categories and is counted once, in "Drinks"
+        categories.insert("Drinks".to_string(), vec!["(?i)coffee".to_string()]);
+        let result = totals(&categories);
+        assert_eq!(
+            result,
+            vec![
+                ("Drinks".to_string(), Amount::from(5.50)),
+                ("Food".to_string(), Amount::from(37.00)),
+                ("Travel".to_string(), Amount::from(3.00)),
+            ]
+        );
+        let sum: Amount = result.iter().map(|(_, total)| *total).sum();
+        assert_eq!(sum, Amount::from(45.50));
+    }
+
+    #[test]
+    fn test_categorize_expenses_first_match_wins() {
+        let mut categories = test_categories();
+        categories.insert("Drinks".to_string(), vec!["(?i)coffee".to_string()]);
+
+        let (categorized, uncategorized, _) =
+            categorize_expenses(&test_expenses(), &categories, &BTreeSet::new(), false);
+        // Coffee matches both, "Drinks" is tried before "Food"
+        let names = |items: &Vec<Expense>| {
+            items
+                .iter()
+                .map(|e| e.description.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&categorized["Drinks"]), vec!["Coffee"]);
+        assert_eq!(names(&categorized["Food"]), vec!["Lunch", "Dinner"]);
+        assert_eq!(names(&uncategorized), vec!["Taxi"]);
+    }
+
+    #[test]
+    fn test_categorize_expenses_uncategorized() {
+        let (categorized, uncategorized, _) =
+            categorize_expenses(&test_expenses(), &HashMap::new(), &BTreeSet::new(), false);
+        assert!(categorized.is_empty());
+        assert_eq!(uncategorized.len(), 4);
+
+        // Categories without matches are not listed
+        let mut categories = test_categories();
+        categories.insert("Travel".to_string(), vec!["(?i)plane".to_string()]);
+        let (categorized, uncategorized, _) =
+            categorize_expenses(&test_expenses(), &categories, &BTreeSet::new(), false);
+        assert_eq!(categorized.keys().collect::<Vec<_>>(), vec!["Food"]);
+        assert_eq!(uncategorized.len(), 1);
+    }
+
+    #[test]
+    fn test_categorize_expenses_literal_and_regex_filters() {
+        let mut categories = HashMap::new();
+        categories.insert(
+            "Food".to_string(),
+            vec![
+                crate::utils::filter_pattern::literal_filter("LUN"),
+                "^Din".to_string(),
+            ],
+        );
+        categories.insert(
+            "Travel".to_string(),
+            vec![crate::utils::filter_pattern::literal_filter("t.xi")],
+        );
+
+        let (categorized, uncategorized, _) =
+            categorize_expenses(&test_expenses(), &categories, &BTreeSet::new(), false);
+        let names = |items: &Vec<Expense>| {
+            items
+                .iter()
+                .map(|e| e.description.clone())
+                .collect::<Vec<_>>()
+        };
+        // Literal "LUN" matches "Lunch" ignoring case, regex "^Din" matches "Dinner"
+        assert_eq!(names(&categorized["Food"]), vec!["Lunch", "Dinner"]);
+        // "." in literal filter is not a wildcard
+        assert!(!categorized.contains_key("Travel"));
+        assert_eq!(names(&uncategorized), vec!["Coffee", "Taxi"]);
+    }
+
+    #[test]
+    fn test_custom_uncategorized_name() {
+        let expenses = test_expenses();
+        let categories = test_categories();
+
+        let (message, buttons) = format_category_summary(
+            &expenses,
+            &categories,
+            None,
+            AmountStyle::default(),
+            "Misc",
+            &BTreeSet::new(),
+            false,
+            false,
+        );
+        assert!(message.as_str().contains("Misc"));
+        assert!(!message.as_str().contains("Other"));
+        let button_commands: Vec<String> = buttons
+            .iter()
+            .flatten()
+            .map(|button| match button {
+                ButtonData::Callback(_, command) => command.clone(),
+                _ => panic!("Unexpected button kind"),
+            })
+            .collect();
+        assert!(button_commands.contains(&"/report Misc ".to_string()));
+
+        // The renamed bucket still selects the uncategorized expenses
+        let misc = filter_category_expenses(
+            "Misc",
+            &expenses,
+            &categories,
+            "Misc",
+            &BTreeSet::new(),
+            false,
+        );
+        assert_eq!(misc.len(), 1);
+        assert_eq!(misc[0].description, "Taxi");
+        // "Other" is now just an unknown category
+        assert!(
+            filter_category_expenses(
+                "Other",
+                &expenses,
+                &categories,
+                "Misc",
+                &BTreeSet::new(),
+                false
+            )
+            .is_empty()
+        );
+
+        let content = format_report_markdown_file(
+            &expenses,
+            &categories,
+            AmountStyle::default(),
+            "Misc",
+            &BTreeSet::new(),
+            false,
+        );
+        assert!(content.contains("| Misc | 3.00 |"));
+        assert!(content.contains("## Misc"));
+    }
+
+    #[test]
+    fn test_format_progress_bar() {
+        let goal = Amount::from(200.0);
+        assert_eq!(format_progress_bar(Amount::ZERO, goal), "[----------] 0%");
+        assert_eq!(
+            format_progress_bar(Amount::from(100.0), goal),
+            "[#####-----] 50%"
+        );
+        assert_eq!(
+            format_progress_bar(Amount::from(39.99), goal),
+            "[#---------] 19%"
+        );
+        assert_eq!(
+            format_progress_bar(Amount::from(200.0), goal),
+            "[##########] 100%"
+        );
+        assert_eq!(
+            format_progress_bar(Amount::from(300.0), goal),
+            "[##########] 150%"
+        );
+        // Refunds exceeding expenses don't make the bar negative
+        assert_eq!(
+            format_progress_bar(Amount::from(-5.0), goal),
+            "[----------] 0%"
+        );
+        // The largest total doesn't overflow
+        assert_eq!(
+            format_progress_bar(Amount::from_minor_units(i64::MAX), goal),
+            format!("[##########] {}%", i64::MAX / 200)
+        );
+    }
+
+    #[test]
+    fn test_goal_progress_counts_current_month_only() {
+        let mut expenses = test_expenses(); // 45.50 on 2021-01-01
+        expenses.push(Expense {
+            description: "Rent".to_string(),
+            amount: Amount::from(500.0),
+            timestamp: 1606780800, // 2020-12-01, previous month
+            ..Default::default()
+        });
+        let now = 1611964800; // 2021-01-30
+        assert_eq!(month_total(&expenses, now), Amount::from(45.50));
+        assert_eq!(
+            format_goal_progress(&expenses, Amount::from(91.0), now, AmountStyle::default())
+                .as_str(),
+            "🎯 Monthly goal: `45\\.50` of `91\\.00` `\\[\\#\\#\\#\\#\\#\\-\\-\\-\\-\\-\\] 50%`\n"
+        );
+    }
+
+    #[test]
+    fn test_case_insensitive_filters() {
+        let expenses = test_expenses();
+        let mut categories = HashMap::new();
+        categories.insert("Food".to_string(), vec!["coffee|lunch".to_string()]);
+
+        // Off: lowercase patterns miss capitalized descriptions
+        let (categorized, uncategorized, _) =
+            categorize_expenses(&expenses, &categories, &BTreeSet::new(), false);
+        assert!(categorized.is_empty());
+        assert_eq!(uncategorized.len(), 4);
+
+        // On: the same patterns match ignoring case
+        let (categorized, uncategorized, _) =
+            categorize_expenses(&expenses, &categories, &BTreeSet::new(), true);
+        assert_eq!(categorized["Food"].len(), 2);
+        assert_eq!(uncategorized.len(), 2);
+        let food = filter_category_expenses(
+            "Food",
+            &expenses,
+            &categories,
+            "Other",
+            &BTreeSet::new(),
+            true,
+        );
+        assert_eq!(food.len(), 2);
+    }
+
+    #[test]
+    fn test_also_other_listed_without_double_counting() {
+        let expenses = test_expenses();
+        let mut categories = test_categories();
+        categories.insert("Travel".to_string(), vec!["(?i)taxi".to_string()]);
+        let also_other = BTreeSet::from(["Food".to_string()]);
+
+        let (categorized, uncategorized, also_in_other) =
+            categorize_expenses(&expenses, &categories, &also_other, false);
+        assert_eq!(categorized["Food"].len(), 3);
+        assert!(uncategorized.is_empty());
+        assert_eq!(also_in_other.len(), 3);
+
+        // Food expenses are listed under "Other" too
+        let other =
+            filter_category_expenses("Other", &expenses, &categories, "Other", &also_other, false);
+        let names: Vec<&str> = other.iter().map(|e| e.description.as_str()).collect();
+        assert_eq!(names, vec!["Coffee", "Lunch", "Dinner"]);
+        let food =
+            filter_category_expenses("Food", &expenses, &categories, "Other", &also_other, false);
+        assert_eq!(food.len(), 3);
+
+        // Totals count each expense once, the "Other" row stays at zero
+        let (message, buttons) = format_category_summary(
+            &expenses,
+            &categories,
+            None,
+            AmountStyle::default(),
+            "Other",
+            &also_other,
+            false,
+            false,
+        );
+        assert!(message.as_str().contains("Total       45.50"));
+        assert!(message.as_str().contains("Other        0.00"));
+        assert!(message.as_str().contains("Food        42.50"));
+        assert_eq!(buttons.iter().flatten().count(), 3);
+
+        let content = format_report_markdown_file(
+            &expenses,
+            &categories,
+            AmountStyle::default(),
+            "Other",
+            &also_other,
+            false,
+        );
+        assert!(content.contains("| Other | 0.00 |"));
+        assert!(content.contains("| **Total** | **45.50** |"));
+        let other_section = &content[content.find("## Other").unwrap()..];
+        assert!(other_section.contains("| Lunch | 12.00 |"));
+    }
+
+    #[test]
+    fn test_format_report_markdown_file() {
+        let mut expenses = test_expenses();
+        expenses.push(Expense {
+            description: "Lunch | big".to_string(),
+            amount: Amount::from(1000.0),
+            timestamp: 1609545600, // 2021-01-02
+            ..Default::default()
+        });
+
+        let content = format_report_markdown_file(
+            &expenses,
+            &test_categories(),
+            AmountStyle {
+                grouping: true,
+                decimal_comma: false,
+            },
+            "Other",
+            &BTreeSet::new(),
+            false,
+        );
+        let expected = "\
+# Expense report
+
+| Category | Amount |
+|---|---:|
+| Food | 1,042.50 |
+| Other | 3.00 |
+| **Total** | **1,045.50** |
+
+## Food
+
+| Date | Description | Amount |
+|---|---|---:|
+| 2021-01-01 | Coffee | 5.50 |
+| 2021-01-01 | Lunch | 12.00 |
+| 2021-01-01 | Dinner | 25.00 |
+| 2021-01-02 | Lunch \\| big | 1,000.00 |
+
+## Other
+
+| Date | Description | Amount |
+|---|---|---:|
+| 2021-01-01 | Taxi | 3.00 |
+";
+        assert_eq!(content, expected);
+    }
+
+    #[test]
+    fn test_filter_min_amount() {
+        let expenses = test_expenses();
+
+        // No threshold keeps everything
+        assert_eq!(filter_min_amount(&expenses, None).len(), 4);
+
+        // Threshold is inclusive
+        let filtered = filter_min_amount(&expenses, Some(12.0));
+        let descriptions: Vec<&str> = filtered.iter().map(|e| e.description.as_str()).collect();
+        assert_eq!(descriptions, vec!["Lunch", "Dinner"]);
+    }
+
+    #[test]
+    fn test_min_amount_excluded_from_category_expenses() {
+        let expenses = filter_min_amount(&test_expenses(), Some(10.0));
+        let categories = test_categories();
+
+        let food = filter_category_expenses(
+            "Food",
+            &expenses,
+            &categories,
+            "Other",
+            &BTreeSet::new(),
+            false,
+        );
+        assert_eq!(food.len(), 2);
+        assert!(food.iter().all(|e| e.description != "Coffee"));
+
+        // Taxi (3.00) is below the threshold, so "Other" is empty
+        let other = filter_category_expenses(
+            "Other",
+            &expenses,
+            &categories,
+            "Other",
+            &BTreeSet::new(),
+            false,
+        );
+        assert!(other.is_empty());
+    }
+
+    #[test]
+    fn test_min_amount_excluded_from_summary() {
+        let expenses = filter_min_amount(&test_expenses(), Some(10.0));
+        let categories = test_categories();
+
+        let (message, buttons) = format_category_summary(
+            &expenses,
+            &categories,
+            Some(10.0),
+            AmountStyle::default(),
+            "Other",
+            &BTreeSet::new(),
+            false,
+            false,
+        );
+        let content = message.as_str();
+
+        // Total includes only Lunch and Dinner
+        assert!(content.contains("37.00"));
+        assert!(!content.contains("45.50"));
+        // Threshold is shown in the header
+        assert!(content.contains("amounts ≥ `10`"));
+        // "Other" bucket disappears because Taxi is excluded
+        assert_eq!(buttons.len(), 1);
+        assert_eq!(buttons[0].len(), 1);
+        match &buttons[0][0] {
+            ButtonData::Callback(label, data) => {
+                assert_eq!(label, "Food");
+                assert_eq!(data, "/report min 10 Food ");
+            }
+            _ => panic!("Expected callback button"),
+        }
+    }
+
+    #[test]
+    fn test_amount_grouping_in_reports() {
+        let expenses = vec![Expense {
+            timestamp: 1609459200,
+            description: "Laptop".to_string(),
+            amount: Amount::from(12345.67),
+            ..Default::default()
+        }];
+
+        let (grouped, _) = format_category_summary(
+            &expenses,
+            &HashMap::new(),
+            None,
+            AmountStyle {
+                grouping: true,
+                decimal_comma: false,
+            },
+            "Other",
+            &BTreeSet::new(),
+            false,
+            false,
+        );
+        assert!(grouped.as_str().contains("12,345.67"));
+        let (plain, _) = format_category_summary(
+            &expenses,
+            &HashMap::new(),
+            None,
+            AmountStyle::default(),
+            "Other",
+            &BTreeSet::new(),
+            false,
+            false,
+        );
+        assert!(plain.as_str().contains("12345.67"));
+
+        let refs: Vec<&Expense> = expenses.iter().collect();
+        assert!(
+            format_single_category_report(
+                &refs,
+                0,
+                25,
+                AmountStyle {
+                    grouping: true,
+                    decimal_comma: false
+                }
+            )
+            .contains("12,345.67")
+        );
+        assert!(
+            format_single_category_report(&refs, 0, 25, AmountStyle::default())
+                .contains("12345.67")
+        );
+
+        // The same stored value shown with a decimal comma
+        let comma = AmountStyle {
+            grouping: true,
+            decimal_comma: true,
+        };
+        assert!(format_single_category_report(&refs, 0, 25, comma).contains("12.345,67"));
+        let (summary, _) = format_category_summary(
+            &expenses,
+            &HashMap::new(),
+            None,
+            comma,
+            "Other",
+            &BTreeSet::new(),
+            false,
+            false,
+        );
+        assert!(summary.as_str().contains("12.345,67"));
+    }
+
+    #[test]
+    fn test_summary_table_alignment() {
+        let widths = |table: &str| {
+            table
+                .lines()
+                .map(|line| line.chars().count())
+                .collect::<Vec<_>>()
+        };
+
+        // Without symbols: rows, separator and total all end in the same column
+        let rows = vec![
+            ("Food".to_string(), "12.50".to_string()),
+            ("Transport".to_string(), "3.00".to_string()),
+        ];
+        let table = format_summary_table(&rows, "15.50").join("\n");
+        assert_eq!(
+            table,
+            "Food           12.50\n\
+             Transport       3.00\n\
+             --------------------\n\
+             Total          15.50"
+        );
+
+        // Symbols and non-ASCII names are measured in characters, not bytes
+        let rows = vec![
+            ("Café".to_string(), "€1,234,567.50".to_string()),
+            ("Transport".to_string(), "€3.00".to_string()),
+        ];
+        let table = format_summary_table(&rows, "€1,234,570.50").join("\n");
+        assert!(widths(&table).iter().all(|&width| width == 23));
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "Café      €1,234,567.50");
+        assert_eq!(lines[1], "Transport         €3.00");
+        assert_eq!(lines[2], "-".repeat(23));
+        assert_eq!(lines[3], "Total     €1,234,570.50");
+    }
+
+    fn summary_rows(rows: &[(&str, &str)]) -> Vec<(String, String)> {
+        rows.iter()
+            .map(|(name, amount)| (name.to_string(), amount.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_pack_two_columns() {
+        // Even count: both columns have the same height
+        let rows = summary_rows(&[
+            ("Food", "12.50"),
+            ("Transport", "3.00"),
+            ("Rent", "1,000.00"),
+            ("Fun", "7.25"),
+        ]);
+        assert_eq!(
+            pack_two_columns(&rows),
+            vec![
+                "Food      12.50   Rent 1,000.00",
+                "Transport  3.00   Fun      7.25",
+            ]
+        );
+
+        // Odd count: the left column takes the extra row
+        let rows = summary_rows(&[("A", "1.00"), ("B", "2.00"), ("C", "3.00")]);
+        assert_eq!(pack_two_columns(&rows), vec!["A 1.00   C 3.00", "B 2.00"]);
+
+        // Names of varying widths, non-ASCII included, keep each column's amounts aligned
+        let rows = summary_rows(&[
+            ("Café", "5.00"),
+            ("Groceries and household", "120.00"),
+            ("Gym", "30.00"),
+            ("Books", "8.00"),
+            ("X", "1.00"),
+        ]);
+        let lines = pack_two_columns(&rows);
+        assert_eq!(lines.len(), 3);
+        let left_width = "Groceries and household 120.00".chars().count();
+        for line in &lines {
+            let left: String = line.chars().take(left_width).collect();
+            assert!(left.ends_with(".00"), "{:?}", line);
+        }
+        assert_eq!(lines[0], "Café                      5.00   Books 8.00");
+        assert_eq!(lines[1], "Groceries and household 120.00   X     1.00");
+        assert_eq!(lines[2], "Gym                      30.00");
+
+        assert!(pack_two_columns(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_two_column_summary() {
+        let rows = summary_rows(&[("A", "1.00"), ("B", "2.00"), ("C", "3.00")]);
+        assert_eq!(
+            format_two_column_summary_table(&rows, "6.00"),
+            vec![
+                "A 1.00   C 3.00",
+                "B 2.00",
+                "---------------",
+                "Total      6.00"
+            ]
+        );
+
+        // The option applies only to summaries of many categories
+        let expenses: Vec<Expense> = (0..TWO_COLUMN_MIN_ROWS)
+            .map(|index| Expense {
+                description: format!("item{}", index),
+                amount: Amount::from(1.0),
+                timestamp: 1609459200,
+                ..Default::default()
+            })
+            .collect();
+        let categories: HashMap<String, Vec<String>> = (0..TWO_COLUMN_MIN_ROWS)
+            .map(|index| (format!("Cat{}", index), vec![format!("^item{}$", index)]))
+            .collect();
+        let summary = |expenses: &[Expense], two_columns| {
+            format_category_summary(
+                expenses,
+                &categories,
+                None,
+                AmountStyle::default(),
+                "Other",
+                &BTreeSet::new(),
+                false,
+                two_columns,
+            )
+            .0
+            .as_str()
+            .lines()
+            .count()
+        };
+        let single = summary(&expenses, false);
+        assert_eq!(summary(&expenses, true), single - TWO_COLUMN_MIN_ROWS / 2);
+        let few = &expenses[..TWO_COLUMN_MIN_ROWS - 1];
+        assert_eq!(summary(few, true), summary(few, false));
+    }
+
+    /// Every MarkdownV2 special character, the backslash included
+    const SPECIAL_CHARS: &str = "_*[]()~`>#+-=|{}.!\\";
+
+    /// Check that the text is valid MarkdownV2 with user input properly escaped
+    /// Outside code the compile time validator applies; inside code blocks only
+    /// backticks and backslashes are special and must be escaped
+    fn assert_valid_markdown(text: &str) {
+        yoroolbot::markdown::validate_markdownv2_format(text);
+        for block in text.split("```").skip(1).step_by(2) {
+            let mut chars = block.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => assert!(
+                        matches!(chars.next(), Some('`' | '\\')),
+                        "Stray backslash in code block {:?}",
+                        block
+                    ),
+                    '`' => panic!("Unescaped backtick in code block {:?}", block),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn special_expenses() -> (Vec<Expense>, HashMap<String, Vec<String>>, String) {
+        let category = format!("Odd{}", SPECIAL_CHARS);
+        let expenses = vec![
+            Expense {
+                description: format!("special {} item", SPECIAL_CHARS),
+                amount: Amount::from(5.50),
+                timestamp: 1609459200,
+                author: Some(format!("user{}", SPECIAL_CHARS)),
+                ..Default::default()
+            },
+            Expense {
+                description: format!("plain{}", SPECIAL_CHARS),
+                amount: Amount::from(1234.00),
+                timestamp: 1609459200,
+                ..Default::default()
+            },
+        ];
+        let categories = HashMap::from([(category.clone(), vec!["special".to_string()])]);
+        (expenses, categories, category)
+    }
+
+    #[test]
+    fn test_formatters_escape_special_characters() {
+        let (expenses, categories, category) = special_expenses();
+        let style = AmountStyle {
+            grouping: true,
+            decimal_comma: false,
+        };
+
+        // Summary with the category and the uncategorized bucket named with special characters
+        let uncategorized = format!("Other{}", SPECIAL_CHARS);
+        let (summary, buttons) = format_category_summary(
+            &expenses,
+            &categories,
+            Some(1.5),
+            style,
+            &uncategorized,
+            &BTreeSet::new(),
+            false,
+            false,
+        );
+        assert_valid_markdown(summary.as_str());
+        assert!(summary.as_str().contains("Odd_*[]()~\\`>#+-=|{}.!\\\\"));
+        assert_eq!(buttons[0].len(), 2);
+
+        // Single category report, as sent by /report <category>
+        let refs: Vec<&Expense> = expenses.iter().collect();
+        let report = format_single_category_report(&refs, 0, 25, style);
+        let message = markdown_format!(
+            "*{}*{}, total `{}`\n{}",
+            &category,
+            @raw format_min_amount_note(Some(1.5)),
+            "1,239.50",
+            @code report
+        );
+        assert_valid_markdown(message.as_str());
+
+        // Collapsed report
+        let rows = collapse_by_description(&refs);
+        let report = format_collapsed_category_report(&rows, 0, 25, style);
+        assert_valid_markdown(markdown_format!("*{}*\n{}", &category, @code report).as_str());
+
+        // Expense lists
+        let messages = crate::commands::expenses::format_expenses_chronological(&expenses).unwrap();
+        for message in &messages {
+            assert_valid_markdown(message.as_str());
+        }
+        let messages =
+            crate::commands::expenses::format_expenses_by_date(&expenses, false).unwrap();
+        for message in &messages {
+            assert_valid_markdown(message.as_str());
+        }
+    }
+
+    #[test]
+    fn test_report_page_size_note() {
+        let expenses: Vec<Expense> = (0..10)
+            .map(|i| Expense {
+                timestamp: 1609459200 + i * 86400,
+                description: format!("Item {}", i),
+                amount: Amount::from(1.0),
+                ..Default::default()
+            })
+            .collect();
+        let refs: Vec<&Expense> = expenses.iter().collect();
+        let rows = |page_size: usize| {
+            format_single_category_report(&refs, 0, page_size, AmountStyle::default())
+                .lines()
+                .count()
+        };
+
+        // Page size below the number of expenses: truncated, with the note
+        assert_eq!(rows(4), 4);
+        assert_eq!(format_shown_note(0, 4, refs.len()), ", showing 4 of 10");
+        // The last page shows the remainder
+        assert_eq!(format_shown_note(2, 4, refs.len()), ", showing 2 of 10");
+
+        // Equal to and above the number of expenses: everything, no note
+        assert_eq!(rows(10), 10);
+        assert_eq!(format_shown_note(0, 10, refs.len()), "");
+        assert_eq!(rows(25), 10);
+        assert_eq!(format_shown_note(0, 25, refs.len()), "");
+    }
+}
@@ -1,6 +1,9 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
 use clap::Parser;
+use teloxide::types::UserId;
+
+use crate::commands::command_add_expense::DEFAULT_MAX_DESCRIPTION_LENGTH;
 
 pub const PREDEFINED_BOT_TOKEN_RELEASE: Option<&str> = option_env!("PREDEFINED_BOT_TOKEN_RELEASE");
 pub const PREDEFINED_BOT_TOKEN_DEBUG: Option<&str> = option_env!("PREDEFINED_BOT_TOKEN_DEBUG");
@@ -30,9 +33,72 @@ pub struct Args {
         help = "Enable persistent category storage with optional path (default: ./categories)"
     )]
     pub persistent_storage: Option<Option<PathBuf>>,
+
+    #[arg(
+        long,
+        help = "Refuse commands which modify expenses or categories, allow only queries"
+    )]
+    pub read_only: bool,
+
+    #[arg(
+        long,
+        help = "Use the message time of day for expenses entered without time (default: midnight)"
+    )]
+    pub date_only_message_time: bool,
+
+    #[arg(
+        long,
+        help = "Report lines without description or amount as errors instead of incomplete expenses"
+    )]
+    pub strict_parsing: bool,
+
+    #[arg(
+        long,
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Maximum number of commands per minute for each chat (default: unlimited)"
+    )]
+    pub commands_per_minute: Option<u32>,
+
+    #[arg(
+        long,
+        default_value_t = 30,
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Maximum time in seconds a single command may run before it is abandoned"
+    )]
+    pub command_timeout_secs: u64,
+
+    #[arg(
+        long,
+        default_value_t = DEFAULT_MAX_DESCRIPTION_LENGTH,
+        help = "Maximum length of an expense description, longer ones are cut with an ellipsis"
+    )]
+    pub max_description_length: usize,
+
+    #[arg(
+        long,
+        help = "Print annotated examples of the storage file formats and exit"
+    )]
+    pub dump_schema: bool,
+
+    #[arg(
+        long,
+        help = "Telegram user id allowed to use administrator commands like /chats (default: nobody)"
+    )]
+    pub admin_user_id: Option<u64>,
 }
 
 impl Args {
+    /// Maximum time a single command may run
+    pub fn command_timeout(&self) -> Duration {
+        Duration::from_secs(self.command_timeout_secs)
+    }
+
+    /// Whether the user may run administrator commands
+    pub fn is_admin(&self, user_id: Option<UserId>) -> bool {
+        self.admin_user_id
+            .is_some_and(|admin| user_id == Some(UserId(admin)))
+    }
+
     /// Get the bot token from CLI args or predefined token
     pub fn get_token(&self) -> String {
         if let Some(env_name) = &self.bot_token_env {
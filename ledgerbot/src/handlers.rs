@@ -1,13 +1,27 @@
 use std::sync::Arc;
 
-use teloxide::{prelude::*, types::CallbackQuery, utils::command::BotCommands};
-use yoroolbot::{markdown::MarkdownStringMessage, markdown_format, storage::unpack_callback_data};
+use teloxide::{
+    RequestError,
+    prelude::*,
+    types::{CallbackQuery, MessageOrigin},
+    utils::command::BotCommands,
+};
+use yoroolbot::{
+    command_trait::{CommandTrait, is_message_not_modified, parse_noop_callback},
+    markdown::MarkdownStringMessage,
+    markdown_format,
+    storage::unpack_callback_data,
+};
 
 use crate::{
     batch::{add_to_batch, execute_batch},
-    commands::{Command, execute_command},
+    commands::{Command, command_help::CommandHelp, execute_command},
+    config::Args,
     storages::StorageTrait,
-    utils::parse_expenses::parse_expenses,
+    utils::{
+        parse_expenses::{pair_amount_lines, parse_expenses, truncate_descriptions},
+        rate_limiter::RateLimiter,
+    },
 };
 
 /// Handle text messages containing potential expense data
@@ -15,6 +29,8 @@ pub async fn handle_text_message(
     bot: Bot,
     msg: Message,
     storage: Arc<dyn StorageTrait>,
+    args: Arc<Args>,
+    rate_limiter: Arc<RateLimiter>,
 ) -> ResponseResult<()> {
     if let Some(text) = msg.text() {
         // Get bot username for filtering
@@ -22,11 +38,46 @@ pub async fn handle_text_message(
 
         // Get message timestamp (Unix timestamp in seconds)
         // Use forward_date if available (for forwarded messages), otherwise use msg.date
-        let timestamp = msg.forward_date().unwrap_or(msg.date).timestamp();
+        // The expense date is the one of the chat's time zone
+        let settings = storage
+            .clone()
+            .as_settings_storage()
+            .get_chat_settings(msg.chat.id)
+            .await;
+        let timestamp = settings
+            .utc_offset
+            .local_timestamp(msg.forward_date().unwrap_or(msg.date).timestamp());
+
+        // Descriptions and amounts on separate lines, if the chat asked for it
+        let paired_text;
+        let text = if settings.pair_amount_lines {
+            paired_text = pair_amount_lines(text);
+            paired_text.as_str()
+        } else {
+            text
+        };
 
         // Parse commands from the message, with bot name filtering and timestamp
         // Text expenses are now converted to Command::Expense variants
-        let parsed_results = parse_expenses(text, bot_name.as_deref(), timestamp);
+        // The line numbers are kept aside for the batch summary
+        let (line_numbers, parsed_results): (Vec<usize>, Vec<_>) = parse_expenses(
+            text,
+            bot_name.as_deref(),
+            timestamp,
+            args.date_only_message_time,
+            args.strict_parsing,
+        )
+        .into_iter()
+        .unzip();
+
+        // Attribute expenses from forwarded messages to the original author
+        let parsed_results = with_author(parsed_results, forward_author(&msg));
+        let parsed_results = with_reply_document(parsed_results, &msg);
+        let parsed_results = refuse_admin_commands(
+            parsed_results,
+            args.is_admin(msg.from.as_ref().map(|user| user.id)),
+        );
+        let parsed_results = truncate_descriptions(parsed_results, args.max_description_length);
 
         log::info!(
             "Parsed {} results from chat {}",
@@ -34,6 +85,19 @@ pub async fn handle_text_message(
             msg.chat.id
         );
 
+        // Silence would look like the bot is broken, point to the help in private chats
+        if needs_help_hint(&parsed_results, msg.chat.is_private()) {
+            bot.send_markdown_message(
+                msg.chat.id,
+                markdown_format!(
+                    "🤔 No expenses or commands found in the message\\. See {} for the format\\.",
+                    CommandHelp::default().to_command_string(false)
+                ),
+            )
+            .await?;
+            return Ok(());
+        }
+
         // Check if we should process this message in batch mode
         let is_multiline = text.lines().filter(|line| !line.trim().is_empty()).count() > 1;
         let is_forwarded = msg.forward_date().is_some();
@@ -43,19 +107,43 @@ pub async fn handle_text_message(
         if is_multiline || is_forwarded {
             // Add to batch storage for deferred execution
             let batch_storage = storage.clone().as_batch_storage();
-            let is_first_message =
-                add_to_batch(batch_storage.clone(), msg.chat.clone(), parsed_results).await;
+            let is_first_message = add_to_batch(
+                batch_storage.clone(),
+                msg.chat.clone(),
+                msg.id,
+                msg.from.as_ref().map(|user| user.id),
+                line_numbers.into_iter().zip(parsed_results).collect(),
+            )
+            .await;
 
             // Start timeout task only for the first message in batch
+            // The whole batch takes one rate limit token, charged for its first message
             if is_first_message {
+                if !acquire_or_notify(&bot, &rate_limiter, msg.chat.id).await? {
+                    batch_storage.consume_batch(msg.chat.id).await;
+                    return Ok(());
+                }
                 let bot_clone = bot.clone();
                 let storage_clone = storage.clone();
                 tokio::spawn(async move {
-                    execute_batch(bot_clone, batch_storage, msg.chat.clone(), storage_clone).await;
+                    execute_batch(
+                        bot_clone,
+                        batch_storage,
+                        msg.chat.clone(),
+                        storage_clone,
+                        args.read_only,
+                        args.command_timeout(),
+                    )
+                    .await;
                 });
             }
         } else {
             // Single-line message: execute immediately (existing behavior)
+            if !parsed_results.is_empty()
+                && !acquire_or_notify(&bot, &rate_limiter, msg.chat.id).await?
+            {
+                return Ok(());
+            }
             for result in parsed_results {
                 match result {
                     Ok(cmd) => {
@@ -67,6 +155,10 @@ pub async fn handle_text_message(
                             storage.clone(),
                             cmd,
                             false,
+                            None,
+                            args.read_only,
+                            msg.from.as_ref().map(|user| user.id),
+                            args.command_timeout(),
                         )
                         .await;
                         if let Err(e) = exec_result {
@@ -95,22 +187,129 @@ pub async fn handle_text_message(
     Ok(())
 }
 
+/// Take a rate limit token for the chat's commands, false if they must be dropped
+/// A chat over the limit is told to slow down at most once per refill window
+async fn acquire_or_notify(
+    bot: &Bot,
+    rate_limiter: &RateLimiter,
+    chat_id: ChatId,
+) -> ResponseResult<bool> {
+    let Err(limited) = rate_limiter.try_acquire(chat_id) else {
+        return Ok(true);
+    };
+    log::warn!("Rate limit exceeded in chat {}", chat_id);
+    if limited.notify {
+        bot.send_markdown_message(
+            chat_id,
+            markdown_format!("🐢 Please slow down, too many commands\\. Try again in a minute\\."),
+        )
+        .await?;
+    }
+    Ok(false)
+}
+
+/// Name of the original author of a forwarded message, if Telegram provides it
+pub fn forward_author(msg: &Message) -> Option<String> {
+    match msg.forward_origin()? {
+        MessageOrigin::User { sender_user, .. } => Some(sender_user.full_name()),
+        MessageOrigin::HiddenUser {
+            sender_user_name, ..
+        } => Some(sender_user_name.clone()),
+        MessageOrigin::Chat {
+            sender_chat,
+            author_signature,
+            ..
+        } => author_signature
+            .clone()
+            .or_else(|| sender_chat.title().map(str::to_string)),
+        MessageOrigin::Channel {
+            chat,
+            author_signature,
+            ..
+        } => author_signature
+            .clone()
+            .or_else(|| chat.title().map(str::to_string)),
+    }
+}
+
+/// Set the author of all parsed expenses
+fn with_author(
+    results: Vec<Result<Command, String>>,
+    author: Option<String>,
+) -> Vec<Result<Command, String>> {
+    if author.is_none() {
+        return results;
+    }
+    results
+        .into_iter()
+        .map(|result| match result {
+            Ok(Command::AddExpense(mut cmd)) => {
+                cmd.author = author.clone();
+                Ok(Command::AddExpense(cmd))
+            }
+            other => other,
+        })
+        .collect()
+}
+
+/// Give `/import` commands the document of the message they reply to
+fn with_reply_document(
+    results: Vec<Result<Command, String>>,
+    msg: &Message,
+) -> Vec<Result<Command, String>> {
+    let Some(document) = msg.reply_to_message().and_then(|reply| reply.document()) else {
+        return results;
+    };
+    results
+        .into_iter()
+        .map(|result| match result {
+            Ok(Command::Import(mut cmd)) => {
+                cmd.file_id = Some(document.file.id.clone());
+                Ok(Command::Import(cmd))
+            }
+            other => other,
+        })
+        .collect()
+}
+
+/// Replace administrator commands with an error unless the sender is the administrator
+fn refuse_admin_commands(
+    results: Vec<Result<Command, String>>,
+    is_admin: bool,
+) -> Vec<Result<Command, String>> {
+    if is_admin {
+        return results;
+    }
+    results
+        .into_iter()
+        .map(|result| match result {
+            Ok(cmd) if cmd.is_admin_only() => Err(format!(
+                "Command {} is only available to the bot administrator",
+                cmd
+            )),
+            other => other,
+        })
+        .collect()
+}
+
 /// Handle callback queries from inline keyboard buttons
 pub async fn handle_callback_query(
     bot: Bot,
     q: CallbackQuery,
     storage: Arc<dyn StorageTrait>,
+    args: Arc<Args>,
+    rate_limiter: Arc<RateLimiter>,
 ) -> ResponseResult<()> {
     let bot_username = bot.get_me().await?.username().to_string();
-    // Answer the callback query to remove the loading state
-    bot.answer_callback_query(q.id.clone()).await?;
 
     // Get the message that contained the button
-    let Some(message) = q.message else {
-        return Ok(());
-    };
-
-    let Some(msg) = message.regular_message() else {
+    let Some(msg) = q
+        .message
+        .as_ref()
+        .and_then(|message| message.regular_message())
+    else {
+        // Answer the callback query to remove the loading state
+        bot.answer_callback_query(q.id.clone()).await?;
         return Ok(());
     };
 
@@ -119,19 +318,47 @@ pub async fn handle_callback_query(
 
     // Parse callback data string into enum
     let Some(data_str) = &q.data else {
+        bot.answer_callback_query(q.id.clone()).await?;
         return Ok(());
     };
 
     log::info!("Received callback data: {}", data_str);
 
+    // Drop button presses from chats exceeding the rate limit
+    // The spinner is stopped for every press, the toast is shown once per refill window
+    if let Err(limited) = rate_limiter.try_acquire(chat_id) {
+        log::warn!("Rate limit exceeded in chat {}", chat_id);
+        let request = bot.answer_callback_query(q.id.clone());
+        if limited.notify {
+            request
+                .text("🐢 Please slow down, too many commands. Try again in a minute.")
+                .await?;
+        } else {
+            request.await?;
+        }
+        return Ok(());
+    }
+
     // Unpack callback data from storage if needed
     let callback_storage = storage.clone().as_callback_data_storage();
     let unpacked_data = unpack_callback_data(&callback_storage, data_str).await;
 
     log::info!("Unpacked callback data: {}", unpacked_data);
 
+    // Inactive buttons only stop the spinner, optionally with a toast
+    if let Some(toast) = parse_noop_callback(&unpacked_data) {
+        let request = bot.answer_callback_query(q.id.clone());
+        match toast {
+            Some(toast) => request.text(toast).await?,
+            None => request.await?,
+        };
+        return Ok(());
+    }
+
     // Try to parse the callback data as command
-    if let Ok(cmd) = Command::parse(&unpacked_data, &bot_username) {
+    if let Ok(cmd) = Command::parse(&unpacked_data, &bot_username)
+        && (!cmd.is_admin_only() || args.is_admin(Some(q.from.id)))
+    {
         log::info!("Parsed command from callback: {:?}", cmd);
         // Execute the command using the shared execute_command function
         if let Err(e) = execute_command(
@@ -141,9 +368,21 @@ pub async fn handle_callback_query(
             storage.clone(),
             cmd.clone(),
             false,
+            // The callback query is answered after the command is processed
+            Some(q.id.clone()),
+            args.read_only,
+            Some(q.from.id),
+            args.command_timeout(),
         )
         .await
         {
+            // Navigating to a menu with unchanged content is not a failure
+            if e.downcast_ref::<RequestError>()
+                .is_some_and(is_message_not_modified)
+            {
+                log::debug!("Message not modified by command from callback: {}", cmd);
+                return Ok(());
+            }
             log::error!("Failed to execute command from callback: {}", e);
             bot.send_markdown_message(
                 chat_id,
@@ -158,5 +397,98 @@ pub async fn handle_callback_query(
         return Ok(());
     }
 
+    bot.answer_callback_query(q.id.clone()).await?;
     Ok(())
 }
+
+/// Whether to answer a message in which nothing was recognized with a hint to /help
+/// Only private chats get the hint, in groups most messages are not meant for the bot
+pub fn needs_help_hint(parsed_results: &[Result<Command, String>], is_private: bool) -> bool {
+    is_private && parsed_results.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(forward_origin: &str) -> Message {
+        let json = format!(
+            r#"{{
+                "message_id": 1,
+                "date": 1609459200,
+                "chat": {{"id": 1, "type": "private", "first_name": "Bob"}},
+                "text": "Coffee 5"{}
+            }}"#,
+            forward_origin
+        );
+        // YAML is a superset of JSON
+        serde_yaml::from_str(&json).expect("Failed to deserialize message")
+    }
+
+    #[test]
+    fn test_forward_author() {
+        let msg = message("");
+        assert_eq!(forward_author(&msg), None);
+
+        let msg = message(
+            r#", "forward_origin": {"type": "user", "date": 1609455600,
+                "sender_user": {"id": 2, "is_bot": false, "first_name": "Alice", "last_name": "Smith"}}"#,
+        );
+        assert_eq!(forward_author(&msg), Some("Alice Smith".to_string()));
+
+        let msg = message(
+            r#", "forward_origin": {"type": "hidden_user", "date": 1609455600,
+                "sender_user_name": "Carol"}"#,
+        );
+        assert_eq!(forward_author(&msg), Some("Carol".to_string()));
+
+        let msg = message(
+            r#", "forward_origin": {"type": "channel", "date": 1609455600, "message_id": 7,
+                "chat": {"id": -100, "type": "channel", "title": "Receipts"}}"#,
+        );
+        assert_eq!(forward_author(&msg), Some("Receipts".to_string()));
+    }
+
+    /// Parse results without their line numbers, as the handler passes them on
+    fn parse_results(text: &str) -> Vec<Result<Command, String>> {
+        parse_expenses(text, None, 1609459200, false, false)
+            .into_iter()
+            .map(|(_, result)| result)
+            .collect()
+    }
+
+    #[test]
+    fn test_with_author() {
+        let results = parse_results("Coffee 5\n/help");
+        let results = with_author(results, Some("Alice".to_string()));
+        assert!(matches!(&results[0], Ok(Command::AddExpense(cmd))
+            if cmd.author.as_deref() == Some("Alice")));
+        assert!(matches!(&results[1], Ok(Command::Help(_))));
+    }
+
+    #[test]
+    fn test_refuse_admin_commands() {
+        let results = || parse_results("/chats\n/help");
+        let refused = refuse_admin_commands(results(), false);
+        assert!(matches!(&refused[0], Err(err) if err.contains("/chats")));
+        assert!(matches!(&refused[1], Ok(Command::Help(_))));
+
+        let allowed = refuse_admin_commands(results(), true);
+        assert!(matches!(&allowed[0], Ok(Command::Chats(_))));
+    }
+
+    #[test]
+    fn test_needs_help_hint() {
+        let hint = |text: &str, is_private: bool| needs_help_hint(&parse_results(text), is_private);
+        // Nothing recognized: hint in private chats only
+        assert!(hint("", true));
+        assert!(hint("  \n\t\n", true));
+        assert!(hint("# just a note", true));
+        assert!(!hint("", false));
+        assert!(!hint("  \n\t\n", false));
+        // Expenses, commands and parse errors are answered on their own
+        assert!(!hint("Coffee 5", true));
+        assert!(!hint("/help", true));
+        assert!(!hint("hello", true));
+    }
+}
@@ -14,27 +14,47 @@ use handlers::{handle_callback_query, handle_text_message};
 use storages::StorageTrait;
 use teloxide::prelude::*;
 
-use crate::storages::{PersistentCategoryStorage, Storage};
+use crate::{
+    storages::{
+        PersistentAuditStorage, PersistentCategoryStorage, PersistentFilterStatsStorage,
+        PersistentSettingsStorage, Storage,
+    },
+    utils::{rate_limiter::RateLimiter, storage_schema::format_storage_schema},
+};
 
 #[tokio::main]
 async fn main() {
     let args = Args::parse();
+    if args.dump_schema {
+        print!("{}", format_storage_schema());
+        return;
+    }
 
     pretty_env_logger::init();
     log::info!("Starting expense calculation bot...");
 
     let token = args.get_token();
+    if args.read_only {
+        log::info!("Running in read-only mode");
+    }
     let bot = Bot::new(token);
 
     // Initialize main storage based on CLI arguments
-    let storage = if let Some(storage_path) = args.persistent_storage {
+    let storage = if let Some(storage_path) = args.persistent_storage.clone() {
         // Use persistent storage with provided path or default
         let storage_dir = storage_path.unwrap_or_else(|| PathBuf::from("categories"));
         log::info!(
             "Using persistent category storage in directory: {:?}",
             storage_dir
         );
-        Storage::new().categories_storage(PersistentCategoryStorage::new(storage_dir))
+        let audit_dir = storage_dir.join("audit");
+        let settings_dir = storage_dir.join("settings");
+        let filter_stats_dir = storage_dir.join("filter_stats");
+        Storage::new()
+            .categories_storage(PersistentCategoryStorage::new(storage_dir))
+            .settings_storage(PersistentSettingsStorage::new(settings_dir))
+            .audit_storage(PersistentAuditStorage::new(audit_dir))
+            .filter_stats_storage(PersistentFilterStatsStorage::new(filter_stats_dir))
     } else {
         // Use in-memory storage
         log::info!("Using in-memory category storage");
@@ -43,6 +63,10 @@ async fn main() {
 
     // Wrap storage in Arc<dyn StorageTrait> for use throughout the bot
     let storage_trait: Arc<dyn StorageTrait> = Arc::new(storage);
+    // Per-chat command rate limiting
+    let rate_limiter = Arc::new(RateLimiter::new(args.commands_per_minute));
+    // Share CLI arguments (e.g. read-only mode) with the handlers
+    let args = Arc::new(args);
 
     // Create handler using modern teloxide patterns
     let handler = dptree::entry()
@@ -58,7 +82,7 @@ async fn main() {
         .branch(Update::filter_callback_query().endpoint(handle_callback_query));
 
     Dispatcher::builder(bot, handler)
-        .dependencies(dptree::deps![storage_trait])
+        .dependencies(dptree::deps![storage_trait, args, rate_limiter])
         .enable_ctrlc_handler()
         .build()
         .dispatch()
@@ -67,3 +67,53 @@ fn create_categories_menu(
         .collect::<Vec<_>>();
     create_buttons_menu(&texts, &values, back_command, inline)
 }
+
+#[cfg(test)]
+mod tests {
+    use teloxide::types::InlineKeyboardButtonKind;
+
+    use yoroolbot::command_trait::NoopCommand;
+
+    use super::*;
+    use crate::{
+        commands::{Command, command_remove_category::CommandRemoveCategory},
+        utils::parse_expenses::parse_expenses,
+    };
+
+    #[test]
+    fn test_multi_word_category_survives_menu_round_trip() {
+        let categories = vec!["Eating Out".to_string()];
+        let operation = |name: &str| {
+            CommandRemoveCategory {
+                name: Some(name.to_string()),
+                confirm: None,
+            }
+            .to_command_string(false)
+        };
+
+        for inline in [false, true] {
+            let menu = create_categories_menu(&categories, operation, None::<NoopCommand>, inline);
+            let text = match &menu.inline_keyboard[0][0].kind {
+                InlineKeyboardButtonKind::CallbackData(data) => data.clone(),
+                // Telegram puts the bot mention before the inline query
+                InlineKeyboardButtonKind::SwitchInlineQueryCurrentChat(query) => {
+                    format!("@ledgerbot {}", query)
+                }
+                kind => panic!("Unexpected button {:?}", kind),
+            };
+            let parsed = parse_expenses(&text, Some("ledgerbot"), 0, false, false);
+            assert_eq!(
+                parsed,
+                vec![(
+                    1,
+                    Ok(Command::RemoveCategory(CommandRemoveCategory {
+                        name: Some("Eating Out".to_string()),
+                        confirm: None,
+                    }))
+                )],
+                "{}",
+                text
+            );
+        }
+    }
+}
@@ -7,7 +7,7 @@ use teloxide::{
     utils::command::ParseError,
 };
 use yoroolbot::{
-    command_trait::{CommandReplyTarget, CommandTrait},
+    command_trait::{CommandReplyTarget, CommandTrait, noop_callback},
     markdown::MarkdownString,
     storage::{ButtonData, pack_callback_data},
 };
@@ -27,22 +27,23 @@ impl Words {
 
     /// Build a regex pattern from the words: (?i)\b(word1|word2|word3)\b
     pub fn build_pattern(&self) -> Option<String> {
-        if self.0.is_empty() {
-            return None;
-        }
-        let escaped_words: Vec<String> = self.0.iter().map(|w| regex::escape(w)).collect();
-        Some(format!(r"(?i)\b({})\b", escaped_words.join("|")))
+        build_words_pattern(&self.0, true)
+    }
+
+    /// Build a regex pattern from the words without word boundaries: (?i)(word1|word2|word3)
+    pub fn build_substring_pattern(&self) -> Option<String> {
+        build_words_pattern(&self.0, false)
     }
 
     /// Parse a regex pattern back into Words
-    /// Expects pattern format: (?i)\b(word1|word2|word3)\b
+    /// Expects pattern format: (?i)\b(word1|word2|word3)\b or (?i)(word1|word2|word3)
     /// Returns None if pattern doesn't match this format
     pub fn read_pattern(pattern: &str) -> Option<Self> {
-        // Pattern to match: (?i)\b(word1|word2|word3)\b
-        // We need to extract the words from between \b( and )\b
-        let re = Regex::new(r"^\(\?i\)\\b\((.+)\)\\b$").ok()?;
+        // Pattern to match: (?i)\b(word1|word2|word3)\b or (?i)(word1|word2|word3)
+        // We need to extract the words from between the parentheses
+        let re = Regex::new(r"^\(\?i\)(?:\\b\((.+)\)\\b|\((.+)\))$").ok()?;
         let captures = re.captures(pattern)?;
-        let words_part = captures.get(1)?.as_str();
+        let words_part = captures.get(1).or_else(|| captures.get(2))?.as_str();
 
         // Split by | and unescape each word
         let words: Vec<String> = words_part
@@ -77,6 +78,23 @@ impl Words {
     }
 }
 
+/// Build a case-insensitive alternation pattern from the words
+/// With `word_boundary` the alternation is wrapped in `\b`: (?i)\b(word1|word2)\b
+/// Without it the words match anywhere: (?i)(word1|word2)
+/// Returns None if there are no words
+pub fn build_words_pattern(words: &[String], word_boundary: bool) -> Option<String> {
+    if words.is_empty() {
+        return None;
+    }
+    let escaped_words: Vec<String> = words.iter().map(|w| regex::escape(w)).collect();
+    let alternation = escaped_words.join("|");
+    if word_boundary {
+        Some(format!(r"(?i)\b({})\b", alternation))
+    } else {
+        Some(format!(r"(?i)({})", alternation))
+    }
+}
+
 impl Display for Words {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0.join("|"))
@@ -121,12 +139,15 @@ impl From<Words> for Vec<String> {
 /// Handles pagination internally - pass full word list and page number
 /// Automatically shows inactive buttons when at page boundaries
 /// Selected words are marked with a tick (✓)
+/// Two apply buttons let the user choose between whole-word and substring matching,
+/// the preview button shows the whole-word filter before applying it
 #[allow(clippy::too_many_arguments)]
 pub async fn select_word<
     NEXT: CommandTrait,
     PAGE: CommandTrait,
     BACK: CommandTrait,
     APPLY: CommandTrait,
+    PREVIEW: CommandTrait,
 >(
     target: &CommandReplyTarget,
     prompt: impl Fn(usize, usize, usize) -> MarkdownString,
@@ -136,6 +157,8 @@ pub async fn select_word<
     word_command: impl Fn(&str) -> NEXT,
     page_command: impl Fn(usize) -> PAGE,
     apply_command: APPLY,
+    substring_apply_command: APPLY,
+    preview_command: PREVIEW,
     back_command: Option<BACK>,
 ) -> ResponseResult<()> {
     const WORDS_PER_PAGE: usize = 20;
@@ -157,6 +180,8 @@ pub async fn select_word<
         total_pages,
         |page_num| page_command(page_num).to_command_string(false),
         apply_command.to_command_string(false),
+        substring_apply_command.to_command_string(false),
+        preview_command.to_command_string(false),
         back_command.as_ref(),
     );
 
@@ -188,6 +213,8 @@ fn create_word_menu_data(
     total_pages: usize,
     page_command: impl Fn(usize) -> String,
     apply_command: String,
+    substring_apply_command: String,
+    preview_command: String,
     back_command: Option<&impl CommandTrait>,
 ) -> Vec<Vec<ButtonData>> {
     const WORDS_PER_PAGE: usize = 20;
@@ -228,7 +255,7 @@ fn create_word_menu_data(
         buttons.push(row);
     }
 
-    // Add navigation buttons row: Prev, Next, Back, Apply
+    // Add navigation buttons row: Prev, Next, Back
     let mut nav_row: Vec<ButtonData> = Vec::new();
 
     // Previous page button
@@ -240,7 +267,10 @@ fn create_word_menu_data(
         ));
     } else {
         // On first page - inactive
-        nav_row.push(ButtonData::Callback("◁".to_string(), "noop".to_string()));
+        nav_row.push(ButtonData::Callback(
+            "◁".to_string(),
+            noop_callback(Some("Already on the first page")),
+        ));
     }
 
     // Next page button
@@ -252,7 +282,10 @@ fn create_word_menu_data(
         ));
     } else {
         // On last page - inactive
-        nav_row.push(ButtonData::Callback("▷".to_string(), "noop".to_string()));
+        nav_row.push(ButtonData::Callback(
+            "▷".to_string(),
+            noop_callback(Some("No more pages")),
+        ));
     }
 
     // Add back button if provided
@@ -263,13 +296,64 @@ fn create_word_menu_data(
         ));
     }
 
-    // Add apply button (switch inline query type)
-    nav_row.push(ButtonData::SwitchInlineQuery(
-        "✅ Apply".to_string(),
-        apply_command,
-    ));
-
     buttons.push(nav_row);
 
+    // Add apply buttons row (switch inline query type): whole words, substrings
+    buttons.push(vec![
+        ButtonData::SwitchInlineQuery("✅ Apply (whole words)".to_string(), apply_command),
+        ButtonData::SwitchInlineQuery("✅ Apply (substrings)".to_string(), substring_apply_command),
+    ]);
+    buttons.push(vec![ButtonData::Callback(
+        "🔍 Preview".to_string(),
+        preview_command,
+    )]);
+
     buttons
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn test_build_words_pattern_with_boundary() {
+        let pattern = build_words_pattern(&words(&["coffee", "tea"]), true).unwrap();
+        assert_eq!(pattern, r"(?i)\b(coffee|tea)\b");
+        let re = Regex::new(&pattern).unwrap();
+        assert!(re.is_match("Morning Coffee"));
+        assert!(!re.is_match("coffeehouse"));
+    }
+
+    #[test]
+    fn test_build_words_pattern_without_boundary() {
+        let pattern = build_words_pattern(&words(&["кофе", "tea"]), false).unwrap();
+        assert_eq!(pattern, r"(?i)(кофе|tea)");
+        let re = Regex::new(&pattern).unwrap();
+        assert!(re.is_match("КОФЕ утром"));
+        assert!(re.is_match("teahouse"));
+    }
+
+    #[test]
+    fn test_build_words_pattern_escapes_and_empty() {
+        assert_eq!(build_words_pattern(&[], true), None);
+        assert_eq!(build_words_pattern(&[], false), None);
+        assert_eq!(
+            build_words_pattern(&words(&["a.b"]), false).unwrap(),
+            r"(?i)(a\.b)"
+        );
+    }
+
+    #[test]
+    fn test_read_pattern_round_trip() {
+        let original = Words::new(words(&["coffee", "a.b", "(x)"]));
+        let with_boundary = original.build_pattern().unwrap();
+        let without_boundary = original.build_substring_pattern().unwrap();
+        assert_eq!(Words::read_pattern(&with_boundary), Some(original.clone()));
+        assert_eq!(Words::read_pattern(&without_boundary), Some(original));
+        assert_eq!(Words::read_pattern("coffee|tea"), None);
+    }
+}
@@ -11,7 +11,7 @@ use yoroolbot::{
     markdown_format,
 };
 
-use crate::storages::CategoryStorageTrait;
+use crate::{storages::CategoryStorageTrait, utils::fuzzy_match::did_you_mean};
 
 pub async fn update_category<NEXT: CommandTrait, BACK: CommandTrait>(
     target: &CommandReplyTarget,
@@ -28,7 +28,11 @@ pub async fn update_category<NEXT: CommandTrait, BACK: CommandTrait>(
         .unwrap_or_default();
     if !categories.contains_key(name) {
         let msg = target
-            .markdown_message(markdown_format!("❌ Category `{}` does not exist", name))
+            .markdown_message(markdown_format!(
+                "❌ Category `{}` does not exist\\.{}",
+                name,
+                @raw did_you_mean(name, categories.keys())
+            ))
             .await?;
         if let Some(back) = back_command {
             let menu = vec![vec![InlineKeyboardButton::callback(
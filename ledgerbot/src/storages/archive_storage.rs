@@ -0,0 +1,54 @@
+use std::{collections::HashMap, sync::Arc};
+
+use teloxide::types::ChatId;
+use tokio::sync::Mutex;
+
+use crate::storages::Expense;
+
+/// Trait for storage of archived expenses, kept out of the active list and reports
+#[async_trait::async_trait]
+pub trait ArchiveStorageTrait: Send + Sync {
+    /// Get archived expenses of a specific chat
+    async fn get_archived_expenses(&self, chat_id: ChatId) -> Vec<Expense>;
+
+    /// Add expenses to the chat's archive
+    async fn add_archived_expenses(&self, chat_id: ChatId, expenses: Vec<Expense>);
+
+    /// Remove and return all archived expenses of the chat
+    async fn take_archived_expenses(&self, chat_id: ChatId) -> Vec<Expense>;
+}
+
+type ArchiveStorageData = Arc<Mutex<HashMap<ChatId, Vec<Expense>>>>;
+
+/// Per-chat storage for archived expenses
+#[derive(Clone)]
+pub struct ArchiveStorage {
+    data: ArchiveStorageData,
+}
+
+impl ArchiveStorage {
+    pub fn new() -> Self {
+        Self {
+            data: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+/// Implement ArchiveStorageTrait for ArchiveStorage
+#[async_trait::async_trait]
+impl ArchiveStorageTrait for ArchiveStorage {
+    async fn get_archived_expenses(&self, chat_id: ChatId) -> Vec<Expense> {
+        let storage_guard = self.data.lock().await;
+        storage_guard.get(&chat_id).cloned().unwrap_or_default()
+    }
+
+    async fn add_archived_expenses(&self, chat_id: ChatId, expenses: Vec<Expense>) {
+        let mut storage_guard = self.data.lock().await;
+        storage_guard.entry(chat_id).or_default().extend(expenses);
+    }
+
+    async fn take_archived_expenses(&self, chat_id: ChatId) -> Vec<Expense> {
+        let mut storage_guard = self.data.lock().await;
+        storage_guard.remove(&chat_id).unwrap_or_default()
+    }
+}
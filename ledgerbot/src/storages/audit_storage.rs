@@ -0,0 +1,404 @@
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use teloxide::types::ChatId;
+use tokio::{fs, io::AsyncWriteExt, sync::Mutex};
+use yoroolbot::{markdown::MarkdownString, markdown_format};
+
+/// Maximum number of audit entries kept per chat, older entries are dropped
+pub const MAX_AUDIT_ENTRIES: usize = 1000;
+
+/// A single executed command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: i64,
+    /// Telegram id of the user who issued the command, if known
+    pub user_id: Option<u64>,
+    pub command: String,
+}
+
+/// Trait for audit log storage operations
+#[async_trait::async_trait]
+pub trait AuditStorageTrait: Send + Sync {
+    /// Append an entry to a chat's audit log
+    async fn add_audit_entry(&self, chat_id: ChatId, entry: AuditEntry);
+
+    /// Get the last `count` entries of a chat's audit log, oldest first
+    async fn get_last_audit_entries(&self, chat_id: ChatId, count: usize) -> Vec<AuditEntry>;
+
+    /// Forget the chat's audit log, deleting its file for persistent storage
+    async fn clear_chat_audit(&self, chat_id: ChatId) -> Result<(), MarkdownString>;
+
+    /// Write the chat's audit log to disk, no-op for in-memory storage
+    async fn flush(&self, _chat_id: ChatId) -> Result<(), MarkdownString> {
+        Ok(())
+    }
+}
+
+type AuditStorageData = Arc<Mutex<HashMap<ChatId, Vec<AuditEntry>>>>;
+
+/// Per-chat in-memory audit log
+#[derive(Clone)]
+pub struct AuditStorage {
+    data: AuditStorageData,
+}
+
+impl AuditStorage {
+    pub fn new() -> Self {
+        Self {
+            data: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Replace the whole audit log of a chat
+    async fn replace_chat_entries(&self, chat_id: ChatId, entries: Vec<AuditEntry>) {
+        let mut storage_guard = self.data.lock().await;
+        storage_guard.insert(chat_id, entries);
+    }
+
+    /// Get the whole audit log of a chat
+    async fn get_chat_entries(&self, chat_id: ChatId) -> Vec<AuditEntry> {
+        let storage_guard = self.data.lock().await;
+        storage_guard.get(&chat_id).cloned().unwrap_or_default()
+    }
+}
+
+/// Implement AuditStorageTrait for AuditStorage
+#[async_trait::async_trait]
+impl AuditStorageTrait for AuditStorage {
+    async fn add_audit_entry(&self, chat_id: ChatId, entry: AuditEntry) {
+        let mut storage_guard = self.data.lock().await;
+        let entries = storage_guard.entry(chat_id).or_default();
+        entries.push(entry);
+        if entries.len() > MAX_AUDIT_ENTRIES {
+            let excess = entries.len() - MAX_AUDIT_ENTRIES;
+            entries.drain(..excess);
+        }
+    }
+
+    async fn clear_chat_audit(&self, chat_id: ChatId) -> Result<(), MarkdownString> {
+        self.data.lock().await.remove(&chat_id);
+        Ok(())
+    }
+
+    async fn get_last_audit_entries(&self, chat_id: ChatId, count: usize) -> Vec<AuditEntry> {
+        let storage_guard = self.data.lock().await;
+        let Some(entries) = storage_guard.get(&chat_id) else {
+            return Vec::new();
+        };
+        entries[entries.len().saturating_sub(count)..].to_vec()
+    }
+}
+
+/// Serializable structure for audit log data saved as YAML
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AuditData {
+    pub entries: Vec<AuditEntry>,
+}
+
+/// Persistent audit log that saves each chat's entries to a YAML file named by chat ID
+/// New entries are appended to the file, it is rewritten only to drop old entries
+#[derive(Clone)]
+pub struct PersistentAuditStorage {
+    // Storage directory for audit files
+    storage_dir: PathBuf,
+    // In-memory storage using AuditStorage
+    memory_storage: AuditStorage,
+    // Track which chats have been loaded from disk: ChatId -> bool
+    loaded_chats: Arc<Mutex<HashMap<ChatId, bool>>>,
+    // Number of entries in each chat's file, the lock also serializes the writes
+    file_entries: Arc<Mutex<HashMap<ChatId, usize>>>,
+}
+
+impl PersistentAuditStorage {
+    /// Create a new persistent audit storage with the specified directory
+    pub fn new(storage_dir: PathBuf) -> Self {
+        Self {
+            storage_dir,
+            memory_storage: AuditStorage::new(),
+            loaded_chats: Arc::new(Mutex::new(HashMap::new())),
+            file_entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Get the file path for a chat's audit log
+    fn get_file_path(&self, chat_id: ChatId) -> PathBuf {
+        self.storage_dir.join(format!("{}.yaml", chat_id))
+    }
+
+    /// Load audit entries from disk, empty if the file is missing or unreadable
+    async fn load_chat_entries(&self, chat_id: ChatId) -> Vec<AuditEntry> {
+        match fs::read_to_string(self.get_file_path(chat_id)).await {
+            Ok(content) => serde_yaml::from_str::<AuditData>(&content)
+                .map(|data| data.entries)
+                .unwrap_or_default(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Replace the chat's file with the entries
+    async fn write_chat_file(
+        &self,
+        chat_id: ChatId,
+        entries: Vec<AuditEntry>,
+    ) -> Result<(), std::io::Error> {
+        fs::create_dir_all(&self.storage_dir).await?;
+        let content = to_yaml(&AuditData { entries })?;
+        fs::write(self.get_file_path(chat_id), content).await
+    }
+
+    /// Save audit entries to disk, replacing the file
+    async fn save_chat_entries(
+        &self,
+        chat_id: ChatId,
+        entries: Vec<AuditEntry>,
+    ) -> Result<(), std::io::Error> {
+        let mut file_entries_guard = self.file_entries.lock().await;
+        let count = entries.len();
+        self.write_chat_file(chat_id, entries).await?;
+        file_entries_guard.insert(chat_id, count);
+        Ok(())
+    }
+
+    /// Append an entry to the chat's file
+    /// The file is rewritten with the kept entries once it holds twice `MAX_AUDIT_ENTRIES`,
+    /// or while it has no entries yet and so no list to append to
+    async fn append_chat_entry(
+        &self,
+        chat_id: ChatId,
+        entry: AuditEntry,
+    ) -> Result<(), std::io::Error> {
+        let mut file_entries_guard = self.file_entries.lock().await;
+        let file_entries = file_entries_guard.entry(chat_id).or_default();
+        if *file_entries == 0 || *file_entries >= 2 * MAX_AUDIT_ENTRIES {
+            let entries = self.memory_storage.get_chat_entries(chat_id).await;
+            let count = entries.len();
+            self.write_chat_file(chat_id, entries).await?;
+            *file_entries = count;
+            return Ok(());
+        }
+        // Items of a top-level list serialize exactly like the items of `entries:`
+        let content = to_yaml(&[entry])?;
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(self.get_file_path(chat_id))
+            .await?;
+        file.write_all(content.as_bytes()).await?;
+        // Tokio completes file writes in the background, wait for this one
+        file.flush().await?;
+        *file_entries += 1;
+        Ok(())
+    }
+
+    /// Ensure audit entries are loaded for a chat ID (lazy loading)
+    async fn ensure_loaded(&self, chat_id: ChatId) {
+        let mut loaded_guard = self.loaded_chats.lock().await;
+        if loaded_guard.get(&chat_id).copied().unwrap_or(false) {
+            return;
+        }
+        let mut entries = self.load_chat_entries(chat_id).await;
+        self.file_entries
+            .lock()
+            .await
+            .insert(chat_id, entries.len());
+        entries.drain(..entries.len().saturating_sub(MAX_AUDIT_ENTRIES));
+        self.memory_storage
+            .replace_chat_entries(chat_id, entries)
+            .await;
+        loaded_guard.insert(chat_id, true);
+    }
+}
+
+/// Serialize audit data to YAML
+fn to_yaml<T: Serialize + ?Sized>(data: &T) -> Result<String, std::io::Error> {
+    serde_yaml::to_string(data).map_err(|e| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Failed to serialize audit log to YAML: {}", e),
+        )
+    })
+}
+
+/// Implement AuditStorageTrait for PersistentAuditStorage
+#[async_trait::async_trait]
+impl AuditStorageTrait for PersistentAuditStorage {
+    async fn add_audit_entry(&self, chat_id: ChatId, entry: AuditEntry) {
+        self.ensure_loaded(chat_id).await;
+        self.memory_storage
+            .add_audit_entry(chat_id, entry.clone())
+            .await;
+        if let Err(e) = self.append_chat_entry(chat_id, entry).await {
+            log::error!("Failed to save audit log for chat {}: {}", chat_id, e);
+        }
+    }
+
+    async fn get_last_audit_entries(&self, chat_id: ChatId, count: usize) -> Vec<AuditEntry> {
+        self.ensure_loaded(chat_id).await;
+        self.memory_storage
+            .get_last_audit_entries(chat_id, count)
+            .await
+    }
+
+    async fn clear_chat_audit(&self, chat_id: ChatId) -> Result<(), MarkdownString> {
+        // Hold both locks so that neither a load nor an append brings the entries back
+        let mut loaded_guard = self.loaded_chats.lock().await;
+        let mut file_entries_guard = self.file_entries.lock().await;
+        self.memory_storage.clear_chat_audit(chat_id).await?;
+        loaded_guard.insert(chat_id, true);
+        file_entries_guard.insert(chat_id, 0);
+        match fs::remove_file(self.get_file_path(chat_id)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(markdown_format!("{}", e.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn flush(&self, chat_id: ChatId) -> Result<(), MarkdownString> {
+        self.ensure_loaded(chat_id).await;
+        let entries = self.memory_storage.get_chat_entries(chat_id).await;
+        self.save_chat_entries(chat_id, entries)
+            .await
+            .map_err(|e| markdown_format!("{}", e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storages::test_dir::TestDir;
+
+    fn entry(timestamp: i64, command: &str) -> AuditEntry {
+        AuditEntry {
+            timestamp,
+            user_id: Some(42),
+            command: command.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_audit_append_and_last_entries() {
+        let storage = AuditStorage::new();
+        let chat_id = ChatId(1);
+        assert!(storage.get_last_audit_entries(chat_id, 5).await.is_empty());
+
+        for i in 0..5 {
+            storage
+                .add_audit_entry(chat_id, entry(i, &format!("/cmd{}", i)))
+                .await;
+        }
+
+        let last = storage.get_last_audit_entries(chat_id, 3).await;
+        assert_eq!(
+            last,
+            vec![entry(2, "/cmd2"), entry(3, "/cmd3"), entry(4, "/cmd4")]
+        );
+        assert_eq!(storage.get_last_audit_entries(chat_id, 10).await.len(), 5);
+        // Other chats are not affected
+        assert!(
+            storage
+                .get_last_audit_entries(ChatId(2), 10)
+                .await
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_audit_drops_oldest_entries() {
+        let storage = AuditStorage::new();
+        let chat_id = ChatId(1);
+        for i in 0..(MAX_AUDIT_ENTRIES as i64 + 10) {
+            storage.add_audit_entry(chat_id, entry(i, "/list")).await;
+        }
+
+        let all = storage.get_last_audit_entries(chat_id, usize::MAX).await;
+        assert_eq!(all.len(), MAX_AUDIT_ENTRIES);
+        assert_eq!(all[0].timestamp, 10);
+    }
+
+    #[test]
+    fn test_audit_data_yaml_serialization() {
+        let data = AuditData {
+            entries: vec![entry(1609459200, "/report Food")],
+        };
+        let yaml_str = serde_yaml::to_string(&data).expect("Failed to serialize to YAML");
+        assert!(yaml_str.contains("/report Food"));
+        let deserialized: AuditData =
+            serde_yaml::from_str(&yaml_str).expect("Failed to deserialize from YAML");
+        assert_eq!(deserialized.entries, data.entries);
+    }
+
+    #[tokio::test]
+    async fn test_persistent_audit_appends_and_compacts() {
+        let storage_dir = TestDir::new("audit_append");
+        let storage = PersistentAuditStorage::new(storage_dir.to_path_buf());
+        let chat_id = ChatId(1);
+        let file_path = storage.get_file_path(chat_id);
+        let read_file = || {
+            serde_yaml::from_str::<AuditData>(&std::fs::read_to_string(&file_path).unwrap())
+                .unwrap()
+                .entries
+        };
+
+        // Entries are appended to the file, multiline commands included
+        let entries = vec![
+            entry(1, "/report"),
+            entry(2, "/add_category Food\nDrinks"),
+            entry(3, "/list\nsecond line"),
+        ];
+        for entry in &entries {
+            storage.add_audit_entry(chat_id, entry.clone()).await;
+        }
+        assert_eq!(read_file(), entries);
+
+        // The file grows past the kept entries and is compacted to them at twice as many
+        for i in 4..=(2 * MAX_AUDIT_ENTRIES as i64) {
+            storage.add_audit_entry(chat_id, entry(i, "/list")).await;
+        }
+        assert_eq!(read_file().len(), 2 * MAX_AUDIT_ENTRIES);
+        storage
+            .add_audit_entry(chat_id, entry(2 * MAX_AUDIT_ENTRIES as i64 + 1, "/list"))
+            .await;
+        let compacted = read_file();
+        assert_eq!(compacted.len(), MAX_AUDIT_ENTRIES);
+        assert_eq!(
+            compacted.last().unwrap().timestamp,
+            2 * MAX_AUDIT_ENTRIES as i64 + 1
+        );
+
+        // A fresh storage keeps only the last entries of a long file
+        storage.add_audit_entry(chat_id, entry(0, "/report")).await;
+        let reloaded = PersistentAuditStorage::new(storage_dir.to_path_buf());
+        let last = reloaded.get_last_audit_entries(chat_id, usize::MAX).await;
+        assert_eq!(last.len(), MAX_AUDIT_ENTRIES);
+        assert_eq!(last.last().unwrap(), &entry(0, "/report"));
+    }
+
+    #[tokio::test]
+    async fn test_persistent_audit_clear_deletes_file() {
+        let storage_dir = TestDir::new("audit_clear");
+        let storage = PersistentAuditStorage::new(storage_dir.to_path_buf());
+        let chat_id = ChatId(1);
+        storage.add_audit_entry(chat_id, entry(1, "/report")).await;
+        storage.add_audit_entry(chat_id, entry(2, "/list")).await;
+
+        storage.clear_chat_audit(chat_id).await.unwrap();
+        assert!(!storage.get_file_path(chat_id).exists());
+        assert!(storage.get_last_audit_entries(chat_id, 10).await.is_empty());
+        let reloaded = PersistentAuditStorage::new(storage_dir.to_path_buf());
+        assert!(
+            reloaded
+                .get_last_audit_entries(chat_id, 10)
+                .await
+                .is_empty()
+        );
+
+        // The log starts over with the next entry
+        storage.add_audit_entry(chat_id, entry(3, "/help")).await;
+        let reloaded = PersistentAuditStorage::new(storage_dir.to_path_buf());
+        assert_eq!(
+            reloaded.get_last_audit_entries(chat_id, 10).await,
+            vec![entry(3, "/help")]
+        );
+    }
+}
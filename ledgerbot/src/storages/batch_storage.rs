@@ -1,32 +1,62 @@
 use std::{collections::HashMap, sync::Arc};
 
-use teloxide::types::ChatId;
+use teloxide::types::{ChatId, MessageId, UserId};
 use tokio::sync::Mutex;
 
 use crate::commands::Command;
 
+/// Maximum number of processed batch commands remembered per chat, older ones are dropped
+pub const MAX_BATCH_HISTORY: usize = 100;
+
+/// A parsed line waiting in a batch for execution
+#[derive(Debug, Clone)]
+pub struct BatchEntry {
+    pub result: Result<Command, String>,
+    /// The message with this line
+    pub message_id: MessageId,
+    /// 1-based number of the line in its message
+    pub line: usize,
+    /// The user who sent the message with this line, if known
+    pub user_id: Option<UserId>,
+}
+
+/// Outcome of a processed batch line: the command on success, the error description otherwise
+pub type BatchHistoryEntry = Result<String, String>;
+
 /// Trait for batch storage operations (temporary command batching)
 #[async_trait::async_trait]
 pub trait BatchStorageTrait: Send + Sync {
     /// Add commands to batch and return whether this is the first message in the batch
-    async fn add_to_batch(&self, chat_id: ChatId, commands: Vec<Result<Command, String>>) -> bool;
+    async fn add_to_batch(&self, chat_id: ChatId, commands: Vec<BatchEntry>) -> bool;
 
     /// Consume and remove batch data for a chat
-    async fn consume_batch(&self, chat_id: ChatId) -> Option<Vec<Result<Command, String>>>;
+    async fn consume_batch(&self, chat_id: ChatId) -> Option<Vec<BatchEntry>>;
+
+    /// Remember the outcomes of a processed batch
+    async fn add_to_history(&self, chat_id: ChatId, entries: Vec<BatchHistoryEntry>);
+
+    /// Get the last `count` outcomes of processed batches, oldest first
+    async fn get_history(&self, chat_id: ChatId, count: usize) -> Vec<BatchHistoryEntry>;
+
+    /// Forget the outcomes of processed batches for a chat
+    async fn clear_history(&self, chat_id: ChatId);
 }
 
-type BatchStorageData = Arc<Mutex<HashMap<ChatId, Vec<Result<Command, String>>>>>;
+type BatchStorageData = Arc<Mutex<HashMap<ChatId, Vec<BatchEntry>>>>;
+type BatchHistoryData = Arc<Mutex<HashMap<ChatId, Vec<BatchHistoryEntry>>>>;
 
 /// Per-chat batch storage for temporary command batching during message processing
 #[derive(Clone)]
 pub struct BatchStorage {
     data: BatchStorageData,
+    history: BatchHistoryData,
 }
 
 impl BatchStorage {
     pub fn new() -> Self {
         Self {
             data: Arc::new(Mutex::new(HashMap::new())),
+            history: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 }
@@ -34,7 +64,7 @@ impl BatchStorage {
 /// Implement BatchStorageTrait for BatchStorage
 #[async_trait::async_trait]
 impl BatchStorageTrait for BatchStorage {
-    async fn add_to_batch(&self, chat_id: ChatId, commands: Vec<Result<Command, String>>) -> bool {
+    async fn add_to_batch(&self, chat_id: ChatId, commands: Vec<BatchEntry>) -> bool {
         let mut storage_guard = self.data.lock().await;
         match storage_guard.get_mut(&chat_id) {
             Some(state) => {
@@ -50,8 +80,31 @@ impl BatchStorageTrait for BatchStorage {
         }
     }
 
-    async fn consume_batch(&self, chat_id: ChatId) -> Option<Vec<Result<Command, String>>> {
+    async fn consume_batch(&self, chat_id: ChatId) -> Option<Vec<BatchEntry>> {
         let mut storage_guard = self.data.lock().await;
         storage_guard.remove(&chat_id)
     }
+
+    async fn add_to_history(&self, chat_id: ChatId, entries: Vec<BatchHistoryEntry>) {
+        let mut history_guard = self.history.lock().await;
+        let history = history_guard.entry(chat_id).or_default();
+        history.extend(entries);
+        if history.len() > MAX_BATCH_HISTORY {
+            let excess = history.len() - MAX_BATCH_HISTORY;
+            history.drain(..excess);
+        }
+    }
+
+    async fn get_history(&self, chat_id: ChatId, count: usize) -> Vec<BatchHistoryEntry> {
+        let history_guard = self.history.lock().await;
+        let Some(history) = history_guard.get(&chat_id) else {
+            return Vec::new();
+        };
+        history[history.len().saturating_sub(count)..].to_vec()
+    }
+
+    async fn clear_history(&self, chat_id: ChatId) {
+        let mut history_guard = self.history.lock().await;
+        history_guard.remove(&chat_id);
+    }
 }
@@ -1,4 +1,8 @@
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::PathBuf,
+    sync::Arc,
+};
 
 use serde::{Deserialize, Serialize};
 use teloxide::types::ChatId;
@@ -7,6 +11,7 @@ use yoroolbot::{command_trait::CommandTrait, markdown::MarkdownString, markdown_
 
 use crate::commands::{
     command_add_filter::CommandAddFilter, command_categories::CommandCategories,
+    command_remove_filters::remove_filters_at,
 };
 
 /// Trait for category storage operations
@@ -33,6 +38,25 @@ pub trait CategoryStorageTrait: Send + Sync {
         regex_pattern: String,
     ) -> Result<(), MarkdownString>;
 
+    /// Insert a regex filter right after the filter at `index`, appending if out of range
+    /// Returns the position of the inserted filter
+    async fn insert_category_filter(
+        &self,
+        chat_id: ChatId,
+        category_name: String,
+        index: usize,
+        regex_pattern: String,
+    ) -> Result<usize, MarkdownString>;
+
+    /// Replace the regex filter at `index` in place, keeping the order of filters
+    async fn set_category_filter(
+        &self,
+        chat_id: ChatId,
+        category_name: &str,
+        index: usize,
+        regex_pattern: String,
+    ) -> Result<(), MarkdownString>;
+
     /// Remove a regex filter from a category
     async fn remove_category_filter(
         &self,
@@ -41,6 +65,16 @@ pub trait CategoryStorageTrait: Send + Sync {
         regex_pattern: &str,
     ) -> Result<(), MarkdownString>;
 
+    /// Remove the filters at the given positions of a category in one change
+    /// Positions refer to the filters before any removal. Returns the removed
+    /// `(position, filter)` pairs in ascending order and the positions out of range
+    async fn remove_category_filters_at(
+        &self,
+        chat_id: ChatId,
+        category_name: &str,
+        positions: &[usize],
+    ) -> Result<(Vec<(usize, String)>, Vec<usize>), MarkdownString>;
+
     /// Remove a category from a specific chat
     async fn remove_category(
         &self,
@@ -62,30 +96,126 @@ pub trait CategoryStorageTrait: Send + Sync {
         chat_id: ChatId,
         categories: HashMap<String, Vec<String>>,
     ) -> Result<(), MarkdownString>;
+
+    /// Forget the chat: its categories with their flags and the filters before their last change
+    /// Unlike replacing the categories with none, the chat is no longer known afterwards
+    async fn clear_chat_categories(&self, chat_id: ChatId) -> Result<(), MarkdownString>;
+
+    /// Get display colors of the chat's categories: `#RRGGBB` by category name
+    async fn get_category_colors(&self, chat_id: ChatId) -> HashMap<String, String>;
+
+    /// Set the display color of an existing category, used by image reports
+    async fn set_category_color(
+        &self,
+        chat_id: ChatId,
+        category_name: &str,
+        color: String,
+    ) -> Result<(), MarkdownString>;
+
+    /// Get the categories whose expenses are also listed, but not counted, in the uncategorized bucket
+    async fn get_also_other_categories(&self, chat_id: ChatId) -> BTreeSet<String>;
+
+    /// Set whether the expenses of an existing category are also listed in the uncategorized bucket
+    async fn set_category_also_other(
+        &self,
+        chat_id: ChatId,
+        category_name: &str,
+        enabled: bool,
+    ) -> Result<(), MarkdownString>;
+
+    /// Get the chat's filters as they were before their last change, if any changed
+    /// since the bot started
+    async fn get_previous_categories(
+        &self,
+        chat_id: ChatId,
+    ) -> Option<HashMap<String, Vec<String>>>;
+
+    /// Get a counter which changes whenever the chat's categories change
+    async fn get_chat_version(&self, chat_id: ChatId) -> u64;
+
+    /// Write the chat's categories to disk, no-op for in-memory storage
+    async fn flush(&self, _chat_id: ChatId) -> Result<(), MarkdownString> {
+        Ok(())
+    }
+
+    /// Get the ids of all chats which have categories stored, in ascending order
+    async fn get_known_chats(&self) -> Vec<ChatId>;
 }
 
 type CategoryStorageData = Arc<Mutex<HashMap<ChatId, HashMap<String, Vec<String>>>>>;
+type CategoryColorsData = Arc<Mutex<HashMap<ChatId, HashMap<String, String>>>>;
+type CategoryAlsoOtherData = Arc<Mutex<HashMap<ChatId, BTreeSet<String>>>>;
+
+/// Version of the categories file format written by this build
+/// 0: a bare map of category name to patterns, or `categories`/`colors` without a version
+/// 1: `version`, `categories`, optional `colors` and optional `also_other`
+pub const CATEGORY_DATA_VERSION: u32 = 1;
 
 /// Serializable structure for category data that can be saved/loaded as YAML
+/// Files are read with `CategoryData::migrate`, which upgrades older formats
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CategoryData {
+    /// Format version, missing in files written before versioning
+    #[serde(default)]
+    pub version: u32,
     /// Maps category name to a list of regex patterns
     pub categories: HashMap<String, Vec<String>>,
+    /// Maps category name to its `#RRGGBB` display color in image reports
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub colors: HashMap<String, String>,
+    /// Categories whose expenses are also listed, but not counted, in the uncategorized bucket
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub also_other: BTreeSet<String>,
 }
 
 impl CategoryData {
     pub fn new() -> Self {
+        Self::from_hashmap(HashMap::new())
+    }
+
+    pub fn from_hashmap(categories: HashMap<String, Vec<String>>) -> Self {
         Self {
-            categories: HashMap::new(),
+            version: CATEGORY_DATA_VERSION,
+            categories,
+            colors: HashMap::new(),
+            also_other: BTreeSet::new(),
         }
     }
 
-    pub fn from_hashmap(categories: HashMap<String, Vec<String>>) -> Self {
-        Self { categories }
+    /// Read category data of any known format version, upgrading it to the current one
+    /// Fields missing in older formats get their defaults
+    pub fn migrate(raw: serde_yaml::Value) -> Result<Self, serde_yaml::Error> {
+        let is_wrapped = raw.as_mapping().is_some_and(|map| {
+            map.contains_key("version")
+                || map
+                    .get("categories")
+                    .is_some_and(serde_yaml::Value::is_mapping)
+        });
+        let mut data = if is_wrapped {
+            serde_yaml::from_value::<CategoryData>(raw)?
+        } else {
+            // Version 0 files could be a bare map of category name to patterns
+            Self::from_hashmap(serde_yaml::from_value(raw)?)
+        };
+        if data.version > CATEGORY_DATA_VERSION {
+            log::warn!(
+                "Categories file has version {}, newer than supported {}, unknown fields are dropped",
+                data.version,
+                CATEGORY_DATA_VERSION
+            );
+        }
+        data.version = CATEGORY_DATA_VERSION;
+        Ok(data)
+    }
+
+    pub fn with_colors(mut self, colors: HashMap<String, String>) -> Self {
+        self.colors = colors;
+        self
     }
 
-    pub fn into_hashmap(self) -> HashMap<String, Vec<String>> {
-        self.categories
+    pub fn with_also_other(mut self, also_other: BTreeSet<String>) -> Self {
+        self.also_other = also_other;
+        self
     }
 }
 
@@ -100,19 +230,64 @@ impl Default for CategoryData {
 #[derive(Clone)]
 pub struct CategoryStorage {
     data: CategoryStorageData,
+    // Display colors: ChatId -> category name -> color
+    colors: CategoryColorsData,
+    // Categories also listed in the uncategorized bucket: ChatId -> category names
+    also_other: CategoryAlsoOtherData,
+    // Filters before their last change: ChatId -> category name -> patterns
+    previous: CategoryStorageData,
+    // Modification counters: ChatId -> version
+    versions: Arc<Mutex<HashMap<ChatId, u64>>>,
 }
 
 impl CategoryStorage {
     pub fn new() -> Self {
         Self {
             data: Arc::new(Mutex::new(HashMap::new())),
+            previous: Arc::new(Mutex::new(HashMap::new())),
+            colors: Arc::new(Mutex::new(HashMap::new())),
+            also_other: Arc::new(Mutex::new(HashMap::new())),
+            versions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Replace all display colors of the chat, as loaded from disk
+    async fn replace_category_colors(&self, chat_id: ChatId, colors: HashMap<String, String>) {
+        self.colors.lock().await.insert(chat_id, colors);
+    }
+
+    /// Replace all categories of the chat also listed in the uncategorized bucket, as loaded from disk
+    async fn replace_also_other_categories(&self, chat_id: ChatId, also_other: BTreeSet<String>) {
+        self.also_other.lock().await.insert(chat_id, also_other);
+    }
+
+    /// Remember the chat's filters right before they change, for /recategorize
+    /// Chats which are not in memory yet, like ones being loaded from disk, have nothing to remember
+    async fn remember_previous(
+        &self,
+        chat_id: ChatId,
+        categories: Option<HashMap<String, Vec<String>>>,
+    ) {
+        if let Some(categories) = categories {
+            self.previous.lock().await.insert(chat_id, categories);
         }
     }
+
+    /// Mark the chat's categories as changed
+    async fn bump_version(&self, chat_id: ChatId) {
+        *self.versions.lock().await.entry(chat_id).or_default() += 1;
+    }
 }
 
 /// Implement CategoryStorageTrait for CategoryStorage
 #[async_trait::async_trait]
 impl CategoryStorageTrait for CategoryStorage {
+    async fn get_known_chats(&self) -> Vec<ChatId> {
+        let mut chats: Vec<ChatId> = self.data.lock().await.keys().copied().collect();
+        chats.sort_by_key(|chat_id| chat_id.0);
+        chats
+    }
+
     async fn get_chat_categories(
         &self,
         chat_id: ChatId,
@@ -142,6 +317,8 @@ impl CategoryStorageTrait for CategoryStorage {
 
         // Add the new category
         chat_categories.insert(category_name.clone(), Vec::new());
+        drop(storage_guard);
+        self.bump_version(chat_id).await;
 
         Ok(())
     }
@@ -154,6 +331,7 @@ impl CategoryStorageTrait for CategoryStorage {
     ) -> Result<(), MarkdownString> {
         let mut storage_guard = self.data.lock().await;
         let chat_categories = storage_guard.entry(chat_id).or_default();
+        let previous = chat_categories.clone();
         let Some(patterns) = chat_categories.get_mut(&category_name) else {
             return Err(markdown_format!("Category {} not exists", category_name));
         };
@@ -165,6 +343,75 @@ impl CategoryStorageTrait for CategoryStorage {
             ));
         }
         patterns.push(regex_pattern);
+        drop(storage_guard);
+        self.remember_previous(chat_id, Some(previous)).await;
+        self.bump_version(chat_id).await;
+        Ok(())
+    }
+
+    async fn insert_category_filter(
+        &self,
+        chat_id: ChatId,
+        category_name: String,
+        index: usize,
+        regex_pattern: String,
+    ) -> Result<usize, MarkdownString> {
+        let mut storage_guard = self.data.lock().await;
+        let chat_categories = storage_guard.entry(chat_id).or_default();
+        let previous = chat_categories.clone();
+        let Some(patterns) = chat_categories.get_mut(&category_name) else {
+            return Err(markdown_format!("Category {} not exists", category_name));
+        };
+        if patterns.contains(&regex_pattern) {
+            return Err(markdown_format!(
+                "Filter `{}` already exists in category `{}`",
+                regex_pattern,
+                category_name
+            ));
+        }
+        let position = index.saturating_add(1).min(patterns.len());
+        patterns.insert(position, regex_pattern);
+        drop(storage_guard);
+        self.remember_previous(chat_id, Some(previous)).await;
+        self.bump_version(chat_id).await;
+        Ok(position)
+    }
+
+    async fn set_category_filter(
+        &self,
+        chat_id: ChatId,
+        category_name: &str,
+        index: usize,
+        regex_pattern: String,
+    ) -> Result<(), MarkdownString> {
+        let mut storage_guard = self.data.lock().await;
+        let chat_categories = storage_guard.entry(chat_id).or_default();
+        let previous = chat_categories.clone();
+        let Some(patterns) = chat_categories.get_mut(category_name) else {
+            return Err(markdown_format!("Category {} not exists", category_name));
+        };
+        if index >= patterns.len() {
+            return Err(markdown_format!(
+                "Filter \\#{} not found in category `{}`",
+                index,
+                category_name
+            ));
+        }
+        if patterns
+            .iter()
+            .enumerate()
+            .any(|(i, pattern)| i != index && *pattern == regex_pattern)
+        {
+            return Err(markdown_format!(
+                "Filter `{}` already exists in category `{}`",
+                regex_pattern,
+                category_name
+            ));
+        }
+        patterns[index] = regex_pattern;
+        drop(storage_guard);
+        self.remember_previous(chat_id, Some(previous)).await;
+        self.bump_version(chat_id).await;
         Ok(())
     }
 
@@ -178,6 +425,7 @@ impl CategoryStorageTrait for CategoryStorage {
         let Some(chat_categories) = storage_guard.get_mut(&chat_id) else {
             return Err(markdown_format!("Category {} not exists", category_name));
         };
+        let previous = chat_categories.clone();
         let Some(patterns) = chat_categories.get_mut(category_name) else {
             return Err(markdown_format!("Category {} not exists", category_name));
         };
@@ -189,9 +437,41 @@ impl CategoryStorageTrait for CategoryStorage {
             ));
         }
         patterns.retain(|p| p != regex_pattern);
+        drop(storage_guard);
+        self.remember_previous(chat_id, Some(previous)).await;
+        self.bump_version(chat_id).await;
         Ok(())
     }
 
+    async fn remove_category_filters_at(
+        &self,
+        chat_id: ChatId,
+        category_name: &str,
+        positions: &[usize],
+    ) -> Result<(Vec<(usize, String)>, Vec<usize>), MarkdownString> {
+        let mut storage_guard = self.data.lock().await;
+        let Some(chat_categories) = storage_guard.get_mut(&chat_id) else {
+            return Err(markdown_format!(
+                "Category `{}` does not exist",
+                category_name
+            ));
+        };
+        let previous = chat_categories.clone();
+        let Some(patterns) = chat_categories.get_mut(category_name) else {
+            return Err(markdown_format!(
+                "Category `{}` does not exist",
+                category_name
+            ));
+        };
+        let (removed, invalid) = remove_filters_at(patterns, positions);
+        drop(storage_guard);
+        if !removed.is_empty() {
+            self.remember_previous(chat_id, Some(previous)).await;
+            self.bump_version(chat_id).await;
+        }
+        Ok((removed, invalid))
+    }
+
     async fn remove_category(
         &self,
         chat_id: ChatId,
@@ -201,9 +481,19 @@ impl CategoryStorageTrait for CategoryStorage {
         let Some(chat_categories) = storage_guard.get_mut(&chat_id) else {
             return Err(markdown_format!("Category {} not exists", category_name));
         };
+        let previous = chat_categories.clone();
         if chat_categories.remove(category_name).is_none() {
             return Err(markdown_format!("Category {} not exists", category_name));
         }
+        drop(storage_guard);
+        self.remember_previous(chat_id, Some(previous)).await;
+        if let Some(colors) = self.colors.lock().await.get_mut(&chat_id) {
+            colors.remove(category_name);
+        }
+        if let Some(also_other) = self.also_other.lock().await.get_mut(&chat_id) {
+            also_other.remove(category_name);
+        }
+        self.bump_version(chat_id).await;
         Ok(())
     }
 
@@ -217,6 +507,7 @@ impl CategoryStorageTrait for CategoryStorage {
         let Some(chat_categories) = storage_guard.get_mut(&chat_id) else {
             return Err(markdown_format!("Category {} not exists", old_name));
         };
+        let previous = chat_categories.clone();
         if !chat_categories.contains_key(old_name) {
             return Err(markdown_format!("Category {} not exists", old_name));
         }
@@ -225,6 +516,19 @@ impl CategoryStorageTrait for CategoryStorage {
         }
         let patterns = chat_categories.remove(old_name).unwrap();
         chat_categories.insert(new_name.to_string(), patterns);
+        drop(storage_guard);
+        self.remember_previous(chat_id, Some(previous)).await;
+        if let Some(colors) = self.colors.lock().await.get_mut(&chat_id)
+            && let Some(color) = colors.remove(old_name)
+        {
+            colors.insert(new_name.to_string(), color);
+        }
+        if let Some(also_other) = self.also_other.lock().await.get_mut(&chat_id)
+            && also_other.remove(old_name)
+        {
+            also_other.insert(new_name.to_string());
+        }
+        self.bump_version(chat_id).await;
         Ok(())
     }
 
@@ -234,9 +538,125 @@ impl CategoryStorageTrait for CategoryStorage {
         categories: HashMap<String, Vec<String>>,
     ) -> Result<(), MarkdownString> {
         let mut storage_guard = self.data.lock().await;
-        storage_guard.insert(chat_id, categories);
+        // Forget the flags of the categories which are gone
+        if let Some(colors) = self.colors.lock().await.get_mut(&chat_id) {
+            colors.retain(|name, _| categories.contains_key(name));
+        }
+        if let Some(also_other) = self.also_other.lock().await.get_mut(&chat_id) {
+            also_other.retain(|name| categories.contains_key(name));
+        }
+        let previous = storage_guard.insert(chat_id, categories);
+        drop(storage_guard);
+        self.remember_previous(chat_id, previous).await;
+        self.bump_version(chat_id).await;
+        Ok(())
+    }
+
+    async fn clear_chat_categories(&self, chat_id: ChatId) -> Result<(), MarkdownString> {
+        let mut storage_guard = self.data.lock().await;
+        storage_guard.remove(&chat_id);
+        self.colors.lock().await.remove(&chat_id);
+        self.also_other.lock().await.remove(&chat_id);
+        self.previous.lock().await.remove(&chat_id);
+        drop(storage_guard);
+        self.bump_version(chat_id).await;
         Ok(())
     }
+
+    async fn get_category_colors(&self, chat_id: ChatId) -> HashMap<String, String> {
+        self.colors
+            .lock()
+            .await
+            .get(&chat_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    async fn set_category_color(
+        &self,
+        chat_id: ChatId,
+        category_name: &str,
+        color: String,
+    ) -> Result<(), MarkdownString> {
+        let exists = self
+            .data
+            .lock()
+            .await
+            .get(&chat_id)
+            .is_some_and(|categories| categories.contains_key(category_name));
+        if !exists {
+            return Err(markdown_format!("Category {} not exists", category_name));
+        }
+        self.colors
+            .lock()
+            .await
+            .entry(chat_id)
+            .or_default()
+            .insert(category_name.to_string(), color);
+        self.bump_version(chat_id).await;
+        Ok(())
+    }
+
+    async fn get_also_other_categories(&self, chat_id: ChatId) -> BTreeSet<String> {
+        self.also_other
+            .lock()
+            .await
+            .get(&chat_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    async fn set_category_also_other(
+        &self,
+        chat_id: ChatId,
+        category_name: &str,
+        enabled: bool,
+    ) -> Result<(), MarkdownString> {
+        let exists = self
+            .data
+            .lock()
+            .await
+            .get(&chat_id)
+            .is_some_and(|categories| categories.contains_key(category_name));
+        if !exists {
+            return Err(markdown_format!("Category {} not exists", category_name));
+        }
+        let mut also_other_guard = self.also_other.lock().await;
+        let also_other = also_other_guard.entry(chat_id).or_default();
+        if enabled {
+            also_other.insert(category_name.to_string());
+        } else {
+            also_other.remove(category_name);
+        }
+        drop(also_other_guard);
+        self.bump_version(chat_id).await;
+        Ok(())
+    }
+
+    async fn get_previous_categories(
+        &self,
+        chat_id: ChatId,
+    ) -> Option<HashMap<String, Vec<String>>> {
+        self.previous.lock().await.get(&chat_id).cloned()
+    }
+
+    async fn get_chat_version(&self, chat_id: ChatId) -> u64 {
+        self.versions
+            .lock()
+            .await
+            .get(&chat_id)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// Chat id of a categories file name: `<chat id>.yaml`
+fn parse_chat_file_name(file_name: &str) -> Option<ChatId> {
+    file_name
+        .strip_suffix(".yaml")?
+        .parse::<i64>()
+        .ok()
+        .map(ChatId)
 }
 
 /// Persistent category storage that saves data to text files named by chat ID
@@ -266,23 +686,71 @@ impl PersistentCategoryStorage {
         self.storage_dir.join(format!("{}.yaml", chat_id))
     }
 
+    /// Get the ids of the chats with a `<chat id>.yaml` file in the storage directory
+    /// Other files, like backups of corrupt files, and subdirectories are ignored
+    async fn scan_chat_files(&self) -> Vec<ChatId> {
+        let mut chats = Vec::new();
+        let mut entries = match fs::read_dir(&self.storage_dir).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::warn!(
+                    "Failed to read storage directory {:?}: {}",
+                    self.storage_dir,
+                    e
+                );
+                return chats;
+            }
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if entry.file_type().await.is_ok_and(|kind| kind.is_file())
+                && let Some(chat_id) = entry.file_name().to_str().and_then(parse_chat_file_name)
+            {
+                chats.push(chat_id);
+            }
+        }
+        chats
+    }
+
+    /// Get the path a corrupt categories file is moved to, stamped with the time in milliseconds
+    /// so that a later corrupt file doesn't overwrite an earlier backup
+    fn get_backup_file_path(&self, chat_id: ChatId, timestamp_millis: i64) -> PathBuf {
+        self.storage_dir
+            .join(format!("{}.yaml.{}.bak", chat_id, timestamp_millis))
+    }
+
     /// Load categories from disk for a specific chat ID
-    async fn load_chat_categories(&self, chat_id: ChatId) -> HashMap<String, Vec<String>> {
+    /// A file which fails to parse is moved aside as `<chat>.yaml.<millis>.bak` for manual recovery
+    async fn load_chat_categories(&self, chat_id: ChatId) -> CategoryData {
         let file_path = self.get_file_path(chat_id);
 
         match fs::read_to_string(&file_path).await {
             Ok(content) => {
-                match serde_yaml::from_str::<CategoryData>(&content) {
-                    Ok(category_data) => category_data.into_hashmap(),
-                    Err(_) => {
-                        // Failed to parse YAML, return empty categories
-                        HashMap::new()
+                match serde_yaml::from_str(&content).and_then(CategoryData::migrate) {
+                    Ok(category_data) => category_data,
+                    Err(e) => {
+                        // Keep the corrupt file instead of overwriting it on the next save
+                        let backup_path = self
+                            .get_backup_file_path(chat_id, chrono::Utc::now().timestamp_millis());
+                        log::warn!(
+                            "Failed to parse categories file {}: {}, moving it to {}",
+                            file_path.display(),
+                            e,
+                            backup_path.display()
+                        );
+                        if let Err(e) = fs::rename(&file_path, &backup_path).await {
+                            log::error!(
+                                "Failed to back up categories file {}: {}",
+                                file_path.display(),
+                                e
+                            );
+                        }
+                        CategoryData::new()
                     }
                 }
             }
             Err(_) => {
                 // File doesn't exist or can't be read, return empty categories
-                HashMap::new()
+                CategoryData::new()
             }
         }
     }
@@ -297,7 +765,11 @@ impl PersistentCategoryStorage {
         fs::create_dir_all(&self.storage_dir).await?;
 
         let file_path = self.get_file_path(chat_id);
-        let category_data = CategoryData::from_hashmap(categories.clone());
+        let colors = self.memory_storage.get_category_colors(chat_id).await;
+        let also_other = self.memory_storage.get_also_other_categories(chat_id).await;
+        let category_data = CategoryData::from_hashmap(categories.clone())
+            .with_colors(colors)
+            .with_also_other(also_other);
 
         match serde_yaml::to_string(&category_data) {
             Ok(content) => fs::write(&file_path, content).await,
@@ -310,23 +782,43 @@ impl PersistentCategoryStorage {
 
     /// Ensure categories are loaded for a chat ID (lazy loading)
     async fn ensure_loaded(&self, chat_id: ChatId) -> Result<(), MarkdownString> {
-        let loaded_guard = self.loaded_chats.lock().await;
+        // Hold the lock while loading so that the chat is loaded only once
+        let mut loaded_guard = self.loaded_chats.lock().await;
         if loaded_guard.get(&chat_id).copied().unwrap_or(false) {
             // Already loaded
             return Ok(());
         }
         // Not loaded yet, load from disk
-        drop(loaded_guard); // Release lock while doing I/O - TODO: what if someone else loads meanwhile?
-        let categories = self.load_chat_categories(chat_id).await;
+        let category_data = self.load_chat_categories(chat_id).await;
         self.memory_storage
-            .replace_categories(chat_id, categories)
-            .await
+            .replace_category_colors(chat_id, category_data.colors)
+            .await;
+        self.memory_storage
+            .replace_also_other_categories(chat_id, category_data.also_other)
+            .await;
+        self.memory_storage
+            .replace_categories(chat_id, category_data.categories)
+            .await?;
+        loaded_guard.insert(chat_id, true);
+        Ok(())
     }
 }
 
 /// Implement CategoryStorageTrait for PersistentCategoryStorage
 #[async_trait::async_trait]
 impl CategoryStorageTrait for PersistentCategoryStorage {
+    async fn get_known_chats(&self) -> Vec<ChatId> {
+        let mut chats = self.scan_chat_files().await;
+        // Chats loaded but not yet written
+        for chat_id in self.memory_storage.get_known_chats().await {
+            if !chats.contains(&chat_id) {
+                chats.push(chat_id);
+            }
+        }
+        chats.sort_by_key(|chat_id| chat_id.0);
+        chats
+    }
+
     async fn get_chat_categories(
         &self,
         chat_id: ChatId,
@@ -376,6 +868,46 @@ impl CategoryStorageTrait for PersistentCategoryStorage {
         Ok(())
     }
 
+    async fn insert_category_filter(
+        &self,
+        chat_id: ChatId,
+        category_name: String,
+        index: usize,
+        regex_pattern: String,
+    ) -> Result<usize, MarkdownString> {
+        self.ensure_loaded(chat_id).await?;
+        let position = self
+            .memory_storage
+            .insert_category_filter(chat_id, category_name, index, regex_pattern)
+            .await?;
+
+        // Save updated categories to disk
+        let categories = self.memory_storage.get_chat_categories(chat_id).await?;
+        self.save_chat_categories(chat_id, &categories)
+            .await
+            .map_err(|e| markdown_format!("{}", e.to_string()))?;
+        Ok(position)
+    }
+
+    async fn set_category_filter(
+        &self,
+        chat_id: ChatId,
+        category_name: &str,
+        index: usize,
+        regex_pattern: String,
+    ) -> Result<(), MarkdownString> {
+        self.ensure_loaded(chat_id).await?;
+        self.memory_storage
+            .set_category_filter(chat_id, category_name, index, regex_pattern)
+            .await?;
+
+        // Save updated categories to disk
+        let categories = self.memory_storage.get_chat_categories(chat_id).await?;
+        self.save_chat_categories(chat_id, &categories)
+            .await
+            .map_err(|e| markdown_format!("{}", e.to_string()))
+    }
+
     async fn remove_category_filter(
         &self,
         chat_id: ChatId,
@@ -395,6 +927,28 @@ impl CategoryStorageTrait for PersistentCategoryStorage {
         Ok(())
     }
 
+    async fn remove_category_filters_at(
+        &self,
+        chat_id: ChatId,
+        category_name: &str,
+        positions: &[usize],
+    ) -> Result<(Vec<(usize, String)>, Vec<usize>), MarkdownString> {
+        self.ensure_loaded(chat_id).await?;
+        let result = self
+            .memory_storage
+            .remove_category_filters_at(chat_id, category_name, positions)
+            .await?;
+
+        if !result.0.is_empty() {
+            // Save updated categories to disk
+            let categories = self.memory_storage.get_chat_categories(chat_id).await?;
+            self.save_chat_categories(chat_id, &categories)
+                .await
+                .map_err(|e| markdown_format!("{}", e.to_string()))?;
+        }
+        Ok(result)
+    }
+
     async fn remove_category(
         &self,
         chat_id: ChatId,
@@ -447,6 +1001,91 @@ impl CategoryStorageTrait for PersistentCategoryStorage {
             .map_err(|e| markdown_format!("{}", e.to_string()))?;
         Ok(())
     }
+
+    async fn clear_chat_categories(&self, chat_id: ChatId) -> Result<(), MarkdownString> {
+        // Hold the loaded flag so the file is not read back while it is being removed
+        let mut loaded_guard = self.loaded_chats.lock().await;
+        self.memory_storage.clear_chat_categories(chat_id).await?;
+        loaded_guard.insert(chat_id, true);
+        match fs::remove_file(self.get_file_path(chat_id)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(markdown_format!(
+                "Failed to remove categories file: {}",
+                e.to_string()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    async fn get_category_colors(&self, chat_id: ChatId) -> HashMap<String, String> {
+        if self.ensure_loaded(chat_id).await.is_err() {
+            return HashMap::new();
+        }
+        self.memory_storage.get_category_colors(chat_id).await
+    }
+
+    async fn get_also_other_categories(&self, chat_id: ChatId) -> BTreeSet<String> {
+        if self.ensure_loaded(chat_id).await.is_err() {
+            return BTreeSet::new();
+        }
+        self.memory_storage.get_also_other_categories(chat_id).await
+    }
+
+    async fn set_category_also_other(
+        &self,
+        chat_id: ChatId,
+        category_name: &str,
+        enabled: bool,
+    ) -> Result<(), MarkdownString> {
+        self.ensure_loaded(chat_id).await?;
+        self.memory_storage
+            .set_category_also_other(chat_id, category_name, enabled)
+            .await?;
+
+        // Save updated categories to disk
+        let categories = self.memory_storage.get_chat_categories(chat_id).await?;
+        self.save_chat_categories(chat_id, &categories)
+            .await
+            .map_err(|e| markdown_format!("{}", e.to_string()))?;
+        Ok(())
+    }
+
+    async fn set_category_color(
+        &self,
+        chat_id: ChatId,
+        category_name: &str,
+        color: String,
+    ) -> Result<(), MarkdownString> {
+        self.ensure_loaded(chat_id).await?;
+        self.memory_storage
+            .set_category_color(chat_id, category_name, color)
+            .await?;
+
+        // Save updated categories to disk
+        let categories = self.memory_storage.get_chat_categories(chat_id).await?;
+        self.save_chat_categories(chat_id, &categories)
+            .await
+            .map_err(|e| markdown_format!("{}", e.to_string()))?;
+        Ok(())
+    }
+
+    async fn get_previous_categories(
+        &self,
+        chat_id: ChatId,
+    ) -> Option<HashMap<String, Vec<String>>> {
+        self.memory_storage.get_previous_categories(chat_id).await
+    }
+
+    async fn get_chat_version(&self, chat_id: ChatId) -> u64 {
+        self.memory_storage.get_chat_version(chat_id).await
+    }
+
+    async fn flush(&self, chat_id: ChatId) -> Result<(), MarkdownString> {
+        self.ensure_loaded(chat_id).await?;
+        let categories = self.memory_storage.get_chat_categories(chat_id).await?;
+        self.save_chat_categories(chat_id, &categories)
+            .await
+            .map_err(|e| markdown_format!("{}", e.to_string()))
+    }
 }
 
 #[cfg(test)]
@@ -454,6 +1093,7 @@ mod tests {
     use std::collections::HashMap;
 
     use super::*;
+    use crate::storages::test_dir::TestDir;
 
     #[test]
     fn test_category_data_yaml_serialization() {
@@ -483,7 +1123,7 @@ mod tests {
         // Test deserialization from YAML
         let deserialized: CategoryData =
             serde_yaml::from_str(&yaml_str).expect("Failed to deserialize from YAML");
-        let deserialized_map = deserialized.into_hashmap();
+        let deserialized_map = deserialized.categories;
 
         // Verify the deserialized data matches original
         assert_eq!(deserialized_map, categories);
@@ -501,6 +1141,476 @@ mod tests {
         // Test deserialization of empty data
         let deserialized: CategoryData =
             serde_yaml::from_str(&yaml_str).expect("Failed to deserialize empty data");
-        assert!(deserialized.into_hashmap().is_empty());
+        assert!(deserialized.categories.is_empty());
+    }
+
+    #[test]
+    fn test_migrate_category_data() {
+        // Version 0: a bare map of patterns
+        let bare = "Food:\n- (?i)coffee\nTransport: []\n";
+        let data = CategoryData::migrate(serde_yaml::from_str(bare).unwrap()).unwrap();
+        assert_eq!(data.version, CATEGORY_DATA_VERSION);
+        assert_eq!(data.categories["Food"], vec!["(?i)coffee".to_string()]);
+        assert!(data.categories["Transport"].is_empty());
+        assert!(data.colors.is_empty());
+
+        // Version 0: wrapped, but without a version
+        let unversioned = "categories:\n  Food:\n  - (?i)coffee\n";
+        let data = CategoryData::migrate(serde_yaml::from_str(unversioned).unwrap()).unwrap();
+        assert_eq!(data.version, CATEGORY_DATA_VERSION);
+        assert_eq!(data.categories["Food"], vec!["(?i)coffee".to_string()]);
+
+        // A bare map may have a category called `categories`
+        let named = "categories:\n- (?i)misc\n";
+        let data = CategoryData::migrate(serde_yaml::from_str(named).unwrap()).unwrap();
+        assert_eq!(data.categories["categories"], vec!["(?i)misc".to_string()]);
+
+        // The current version round trips
+        let current = serde_yaml::to_string(
+            &CategoryData::new()
+                .with_colors(HashMap::from([("Food".to_string(), "#FF8800".to_string())])),
+        )
+        .unwrap();
+        assert!(current.contains("version: 1"));
+        let data = CategoryData::migrate(serde_yaml::from_str(&current).unwrap()).unwrap();
+        assert_eq!(data.colors["Food"], "#FF8800");
+
+        assert!(CategoryData::migrate(serde_yaml::from_str("categories: 5").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_parse_chat_file_name() {
+        assert_eq!(parse_chat_file_name("123.yaml"), Some(ChatId(123)));
+        assert_eq!(
+            parse_chat_file_name("-1001234567890.yaml"),
+            Some(ChatId(-1001234567890))
+        );
+        for name in [
+            "123.yaml.1700000000000.bak",
+            "123.yml",
+            "abc.yaml",
+            ".yaml",
+            "12 3.yaml",
+            "audit",
+        ] {
+            assert_eq!(parse_chat_file_name(name), None, "{}", name);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_persistent_known_chats() {
+        let storage_dir = TestDir::new("scan");
+        std::fs::create_dir_all(storage_dir.join("audit")).unwrap();
+        for name in [
+            "5.yaml",
+            "-100.yaml",
+            "7.yaml.1700000000000.bak",
+            "notes.txt",
+        ] {
+            std::fs::write(storage_dir.join(name), "categories: {}\n").unwrap();
+        }
+        std::fs::create_dir_all(storage_dir.join("9.yaml")).unwrap();
+        let storage = PersistentCategoryStorage::new(storage_dir.to_path_buf());
+
+        assert_eq!(
+            storage.get_known_chats().await,
+            vec![ChatId(-100), ChatId(5)]
+        );
+
+        // A chat with categories only in memory is known too
+        storage
+            .memory_storage
+            .add_category(ChatId(1), "Food".to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.get_known_chats().await,
+            vec![ChatId(-100), ChatId(1), ChatId(5)]
+        );
+
+        // A cleared chat is forgotten, file included, and stays so when accessed again
+        for chat_id in [ChatId(1), ChatId(5)] {
+            storage.clear_chat_categories(chat_id).await.unwrap();
+        }
+        assert!(!storage.get_file_path(ChatId(5)).exists());
+        assert!(
+            storage
+                .get_chat_categories(ChatId(5))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(storage.get_known_chats().await, vec![ChatId(-100)]);
+    }
+
+    #[tokio::test]
+    async fn test_persistent_loads_version_0_file() {
+        let storage_dir = TestDir::new("v0");
+        std::fs::create_dir_all(&storage_dir).unwrap();
+        let storage = PersistentCategoryStorage::new(storage_dir.to_path_buf());
+        let chat_id = ChatId(1);
+        let file_path = storage.get_file_path(chat_id);
+        std::fs::write(&file_path, "Food:\n- (?i)coffee\n").unwrap();
+
+        let categories = storage.get_chat_categories(chat_id).await.unwrap();
+        assert_eq!(categories["Food"], vec!["(?i)coffee".to_string()]);
+        assert!(storage.get_category_colors(chat_id).await.is_empty());
+
+        // The next save writes the current version
+        storage.flush(chat_id).await.unwrap();
+        let data: CategoryData =
+            serde_yaml::from_str(&std::fs::read_to_string(&file_path).unwrap()).unwrap();
+        assert_eq!(data.version, CATEGORY_DATA_VERSION);
+        assert_eq!(data.categories["Food"], vec!["(?i)coffee".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_persistent_flush_writes_current_state() {
+        let storage_dir = TestDir::new("flush");
+        let storage = PersistentCategoryStorage::new(storage_dir.to_path_buf());
+        let chat_id = ChatId(1);
+        storage
+            .add_category(chat_id, "Food".to_string())
+            .await
+            .unwrap();
+        storage
+            .add_category_filter(chat_id, "Food".to_string(), "(?i)coffee".to_string())
+            .await
+            .unwrap();
+
+        // Lose the file written on mutation, flush writes it again from memory
+        let file_path = storage.get_file_path(chat_id);
+        std::fs::remove_file(&file_path).unwrap();
+        storage.flush(chat_id).await.unwrap();
+
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        let data: CategoryData = serde_yaml::from_str(&content).unwrap();
+        assert_eq!(data.categories["Food"], vec!["(?i)coffee".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_persistent_category_colors_round_trip() {
+        let storage_dir = TestDir::new("colors");
+        let storage = PersistentCategoryStorage::new(storage_dir.to_path_buf());
+        let chat_id = ChatId(1);
+        storage
+            .add_category(chat_id, "Food".to_string())
+            .await
+            .unwrap();
+        assert!(
+            storage
+                .set_category_color(chat_id, "Travel", "#00FF00".to_string())
+                .await
+                .is_err()
+        );
+        storage
+            .set_category_color(chat_id, "Food", "#FF8800".to_string())
+            .await
+            .unwrap();
+
+        // A fresh storage loads the color from the file
+        let reloaded = PersistentCategoryStorage::new(storage_dir.to_path_buf());
+        let colors = reloaded.get_category_colors(chat_id).await;
+        assert_eq!(colors.get("Food").map(String::as_str), Some("#FF8800"));
+
+        // The color follows a rename and goes away with the category
+        reloaded
+            .rename_category(chat_id, "Food", "Meals")
+            .await
+            .unwrap();
+        assert!(
+            reloaded
+                .get_category_colors(chat_id)
+                .await
+                .contains_key("Meals")
+        );
+        reloaded.remove_category(chat_id, "Meals").await.unwrap();
+        assert!(reloaded.get_category_colors(chat_id).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_persistent_also_other_follows_category() {
+        let storage_dir = TestDir::new("also_other");
+        let storage = PersistentCategoryStorage::new(storage_dir.to_path_buf());
+        let chat_id = ChatId(1);
+        storage
+            .add_category(chat_id, "Food".to_string())
+            .await
+            .unwrap();
+        assert!(
+            storage
+                .set_category_also_other(chat_id, "Travel", true)
+                .await
+                .is_err()
+        );
+        storage
+            .set_category_also_other(chat_id, "Food", true)
+            .await
+            .unwrap();
+
+        // A fresh storage loads the flag from the file
+        let reloaded = PersistentCategoryStorage::new(storage_dir.to_path_buf());
+        assert_eq!(
+            reloaded.get_also_other_categories(chat_id).await,
+            BTreeSet::from(["Food".to_string()])
+        );
+
+        // The flag follows a rename and goes away with the category
+        reloaded
+            .rename_category(chat_id, "Food", "Meals")
+            .await
+            .unwrap();
+        assert_eq!(
+            reloaded.get_also_other_categories(chat_id).await,
+            BTreeSet::from(["Meals".to_string()])
+        );
+        reloaded.remove_category(chat_id, "Meals").await.unwrap();
+        reloaded
+            .add_category(chat_id, "Meals".to_string())
+            .await
+            .unwrap();
+        assert!(reloaded.get_also_other_categories(chat_id).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_file_moved_to_backup() {
+        let storage_dir = TestDir::new("corrupt");
+        std::fs::create_dir_all(&storage_dir).unwrap();
+        let storage = PersistentCategoryStorage::new(storage_dir.to_path_buf());
+        let chat_id = ChatId(1);
+        let corrupt = "categories: [not: a map";
+        std::fs::write(storage.get_file_path(chat_id), corrupt).unwrap();
+
+        let categories = storage.get_chat_categories(chat_id).await.unwrap();
+        assert!(categories.is_empty());
+        assert!(!storage.get_file_path(chat_id).exists());
+
+        // A file corrupted again is backed up next to the first one, not over it
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+        let corrupt_again = "categories: [still: not a map";
+        std::fs::write(storage.get_file_path(chat_id), corrupt_again).unwrap();
+        let storage = PersistentCategoryStorage::new(storage_dir.to_path_buf());
+        assert!(
+            storage
+                .get_chat_categories(chat_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let mut backups: Vec<String> = std::fs::read_dir(&storage_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "bak"))
+            .map(|path| std::fs::read_to_string(path).unwrap())
+            .collect();
+        backups.sort();
+        assert_eq!(backups, vec![corrupt, corrupt_again]);
+    }
+
+    #[tokio::test]
+    async fn test_previous_categories_before_last_change() {
+        let storage_dir = TestDir::new("previous");
+        let storage = PersistentCategoryStorage::new(storage_dir.to_path_buf());
+        let chat_id = ChatId(1);
+        let food = |patterns: &[&str]| {
+            HashMap::from([(
+                "Food".to_string(),
+                patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>(),
+            )])
+        };
+        storage
+            .add_category(chat_id, "Food".to_string())
+            .await
+            .unwrap();
+        storage
+            .add_category_filter(chat_id, "Food".to_string(), "coffee".to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.get_previous_categories(chat_id).await,
+            Some(food(&[]))
+        );
+
+        // A failed change keeps the previous filters
+        assert!(
+            storage
+                .add_category_filter(chat_id, "Food".to_string(), "coffee".to_string())
+                .await
+                .is_err()
+        );
+        storage
+            .set_category_filter(chat_id, "Food", 0, "tea".to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.get_previous_categories(chat_id).await,
+            Some(food(&["coffee"]))
+        );
+
+        // Loading the chat from disk is not a change
+        let reloaded = PersistentCategoryStorage::new(storage_dir.to_path_buf());
+        assert_eq!(
+            reloaded.get_chat_categories(chat_id).await.unwrap(),
+            food(&["tea"])
+        );
+        assert_eq!(reloaded.get_previous_categories(chat_id).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_set_category_filter_in_place() {
+        let storage = CategoryStorage::new();
+        let chat_id = ChatId(1);
+        storage
+            .add_category(chat_id, "Food".to_string())
+            .await
+            .unwrap();
+        for pattern in ["coffee", "lunch", "dinner"] {
+            storage
+                .add_category_filter(chat_id, "Food".to_string(), pattern.to_string())
+                .await
+                .unwrap();
+        }
+
+        storage
+            .set_category_filter(chat_id, "Food", 1, "brunch".to_string())
+            .await
+            .unwrap();
+        // Setting the same pattern again is not a duplicate
+        storage
+            .set_category_filter(chat_id, "Food", 1, "brunch".to_string())
+            .await
+            .unwrap();
+        let categories = storage.get_chat_categories(chat_id).await.unwrap();
+        assert_eq!(categories["Food"], vec!["coffee", "brunch", "dinner"]);
+
+        // Duplicates, missing filters and unknown categories are refused
+        for (category, index, pattern) in [
+            ("Food", 0, "dinner"),
+            ("Food", 3, "supper"),
+            ("Travel", 0, "taxi"),
+        ] {
+            assert!(
+                storage
+                    .set_category_filter(chat_id, category, index, pattern.to_string())
+                    .await
+                    .is_err()
+            );
+        }
+        let categories = storage.get_chat_categories(chat_id).await.unwrap();
+        assert_eq!(categories["Food"], vec!["coffee", "brunch", "dinner"]);
+    }
+
+    #[tokio::test]
+    async fn test_insert_category_filter_position() {
+        let storage = CategoryStorage::new();
+        let chat_id = ChatId(1);
+        storage
+            .add_category(chat_id, "Food".to_string())
+            .await
+            .unwrap();
+        for pattern in ["coffee", "lunch"] {
+            storage
+                .add_category_filter(chat_id, "Food".to_string(), pattern.to_string())
+                .await
+                .unwrap();
+        }
+
+        let position = storage
+            .insert_category_filter(chat_id, "Food".to_string(), 0, "latte".to_string())
+            .await
+            .unwrap();
+        assert_eq!(position, 1);
+        // Out of range index appends
+        let position = storage
+            .insert_category_filter(chat_id, "Food".to_string(), 10, "dinner".to_string())
+            .await
+            .unwrap();
+        assert_eq!(position, 3);
+        let categories = storage.get_chat_categories(chat_id).await.unwrap();
+        assert_eq!(
+            categories["Food"],
+            vec!["coffee", "latte", "lunch", "dinner"]
+        );
+
+        // Duplicates and unknown categories are refused
+        assert!(
+            storage
+                .insert_category_filter(chat_id, "Food".to_string(), 0, "lunch".to_string())
+                .await
+                .is_err()
+        );
+        assert!(
+            storage
+                .insert_category_filter(chat_id, "Travel".to_string(), 0, "taxi".to_string())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_persistent_insert_category_filter() {
+        let storage_dir = TestDir::new("insert");
+        let storage = PersistentCategoryStorage::new(storage_dir.to_path_buf());
+        let chat_id = ChatId(1);
+        storage
+            .add_category(chat_id, "Food".to_string())
+            .await
+            .unwrap();
+        storage
+            .add_category_filter(chat_id, "Food".to_string(), "coffee".to_string())
+            .await
+            .unwrap();
+        storage
+            .add_category_filter(chat_id, "Food".to_string(), "lunch".to_string())
+            .await
+            .unwrap();
+        storage
+            .insert_category_filter(chat_id, "Food".to_string(), 0, "latte".to_string())
+            .await
+            .unwrap();
+
+        // A fresh storage reads the inserted filter from disk in place
+        let reloaded = PersistentCategoryStorage::new(storage_dir.to_path_buf());
+        let categories = reloaded.get_chat_categories(chat_id).await.unwrap();
+        assert_eq!(categories["Food"], vec!["coffee", "latte", "lunch"]);
+    }
+
+    #[tokio::test]
+    async fn test_persistent_remove_category_filters_at() {
+        let storage_dir = TestDir::new("remove_at");
+        let storage = PersistentCategoryStorage::new(storage_dir.to_path_buf());
+        let chat_id = ChatId(1);
+        storage
+            .add_category(chat_id, "Food".to_string())
+            .await
+            .unwrap();
+        for pattern in ["coffee", "lunch", "dinner", "tea"] {
+            storage
+                .add_category_filter(chat_id, "Food".to_string(), pattern.to_string())
+                .await
+                .unwrap();
+        }
+
+        let (removed, invalid) = storage
+            .remove_category_filters_at(chat_id, "Food", &[3, 0, 7])
+            .await
+            .unwrap();
+        assert_eq!(
+            removed,
+            vec![(0, "coffee".to_string()), (3, "tea".to_string())]
+        );
+        assert_eq!(invalid, vec![7]);
+        assert!(
+            storage
+                .remove_category_filters_at(chat_id, "Travel", &[0])
+                .await
+                .is_err()
+        );
+
+        // A fresh storage reads the remaining filters from disk
+        let reloaded = PersistentCategoryStorage::new(storage_dir.to_path_buf());
+        let categories = reloaded.get_chat_categories(chat_id).await.unwrap();
+        assert_eq!(categories["Food"], vec!["lunch", "dinner"]);
     }
 }
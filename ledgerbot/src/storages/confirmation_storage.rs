@@ -0,0 +1,59 @@
+use std::{collections::HashMap, sync::Arc};
+
+use teloxide::types::ChatId;
+use tokio::sync::Mutex;
+
+/// Seconds after which a pending confirmation expires
+pub const CONFIRMATION_TIMEOUT_SECONDS: i64 = 60;
+
+/// Trait for pending confirmation storage operations
+/// A confirmation is requested by a destructive command and consumed by its Confirm button
+#[async_trait::async_trait]
+pub trait ConfirmationStorageTrait: Send + Sync {
+    /// Mark the action as waiting for confirmation, requested at `timestamp`
+    async fn set_pending_confirmation(&self, chat_id: ChatId, action: &str, timestamp: i64);
+
+    /// Remove the pending confirmation of the action
+    /// Returns true if it was requested no longer than the timeout before `now`
+    async fn take_pending_confirmation(&self, chat_id: ChatId, action: &str, now: i64) -> bool;
+
+    /// Remove all pending confirmations of the chat
+    async fn clear_pending_confirmations(&self, chat_id: ChatId);
+}
+
+type ConfirmationStorageData = Arc<Mutex<HashMap<(ChatId, String), i64>>>;
+
+/// Per-chat storage for pending confirmations
+#[derive(Clone)]
+pub struct ConfirmationStorage {
+    data: ConfirmationStorageData,
+}
+
+impl ConfirmationStorage {
+    pub fn new() -> Self {
+        Self {
+            data: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+/// Implement ConfirmationStorageTrait for ConfirmationStorage
+#[async_trait::async_trait]
+impl ConfirmationStorageTrait for ConfirmationStorage {
+    async fn set_pending_confirmation(&self, chat_id: ChatId, action: &str, timestamp: i64) {
+        let mut storage_guard = self.data.lock().await;
+        storage_guard.insert((chat_id, action.to_string()), timestamp);
+    }
+
+    async fn take_pending_confirmation(&self, chat_id: ChatId, action: &str, now: i64) -> bool {
+        let mut storage_guard = self.data.lock().await;
+        storage_guard
+            .remove(&(chat_id, action.to_string()))
+            .is_some_and(|timestamp| now - timestamp <= CONFIRMATION_TIMEOUT_SECONDS)
+    }
+
+    async fn clear_pending_confirmations(&self, chat_id: ChatId) {
+        let mut storage_guard = self.data.lock().await;
+        storage_guard.retain(|(chat, _), _| *chat != chat_id);
+    }
+}
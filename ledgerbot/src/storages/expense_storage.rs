@@ -1,14 +1,28 @@
 use std::{collections::HashMap, sync::Arc};
 
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use teloxide::types::ChatId;
 use tokio::sync::Mutex;
+use yoroolbot::markdown::MarkdownString;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::{commands::run_blocking, utils::amount::Amount};
+
+/// A stored expense, serializable for persistence and export
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Expense {
     pub timestamp: i64,
     pub description: String,
-    pub amount: f64,
+    pub amount: Amount,
+    /// Original author of a forwarded message the expense came from
+    #[serde(default)]
+    pub author: Option<String>,
+    /// Payment account, like cash or card, from an `@account` word of the description
+    #[serde(default)]
+    pub account: Option<String>,
+    /// ISO code of the currency written next to the amount, the chat's usual currency if missing
+    #[serde(default)]
+    pub currency: Option<String>,
 }
 
 /// Trait for expense storage operations
@@ -17,28 +31,67 @@ pub trait ExpenseStorageTrait: Send + Sync {
     /// Get expenses for a specific chat
     async fn get_chat_expenses(&self, chat_id: ChatId) -> Vec<Expense>;
 
+    /// Get expenses for a specific chat in chronological order,
+    /// expenses with the same timestamp keep the order they were added in
+    async fn get_chat_expenses_sorted(&self, chat_id: ChatId) -> Vec<Expense> {
+        let mut expenses = self.get_chat_expenses(chat_id).await;
+        expenses.sort_by_key(|expense| expense.timestamp);
+        expenses
+    }
+
     /// Add expenses to a specific chat's storage
-    async fn add_expenses(&self, chat_id: ChatId, expenses: Vec<(String, f64, i64)>);
+    async fn add_expenses(&self, chat_id: ChatId, expenses: Vec<Expense>);
 
     /// Add a single expense
-    async fn add_expense(&self, chat_id: ChatId, description: &str, amount: f64, timestamp: i64);
+    async fn add_expense(&self, chat_id: ChatId, expense: Expense) {
+        self.add_expenses(chat_id, vec![expense]).await;
+    }
 
     /// Clear all expenses for a specific chat
     async fn clear_chat_expenses(&self, chat_id: ChatId);
+
+    /// Remove the expenses whose description matches the pattern, returning the removed ones
+    async fn remove_expenses_matching(&self, chat_id: ChatId, pattern: &Regex) -> Vec<Expense>;
+
+    /// Remove the expenses dated before `timestamp`, returning the removed ones in order
+    async fn take_expenses_before(&self, chat_id: ChatId, timestamp: i64) -> Vec<Expense>;
+
+    /// Merge previously taken expenses back, each one before the expenses with the same timestamp
+    async fn restore_expenses(&self, chat_id: ChatId, expenses: Vec<Expense>);
+
+    /// Replace all expenses of a chat at once, readers never see a partially replaced list
+    async fn replace_all_expenses(&self, chat_id: ChatId, expenses: Vec<Expense>);
+
+    /// Get a counter which changes whenever the chat's expenses change
+    async fn get_chat_version(&self, chat_id: ChatId) -> u64;
+
+    /// Write the chat's expenses to disk, no-op for in-memory storage
+    async fn flush(&self, _chat_id: ChatId) -> Result<(), MarkdownString> {
+        Ok(())
+    }
 }
 
 /// Per-chat storage for expenses - each chat has its own expense list
+/// The lists are kept in chronological order, so no sorting is needed on read
 #[derive(Clone)]
 pub struct ExpenseStorage {
     data: Arc<Mutex<HashMap<ChatId, Vec<Expense>>>>,
+    // Modification counters: ChatId -> version
+    versions: Arc<Mutex<HashMap<ChatId, u64>>>,
 }
 
 impl ExpenseStorage {
     pub fn new() -> Self {
         Self {
             data: Arc::new(Mutex::new(HashMap::new())),
+            versions: Arc::new(Mutex::new(HashMap::new())),
         }
     }
+
+    /// Mark the chat's expenses as changed
+    async fn bump_version(&self, chat_id: ChatId) {
+        *self.versions.lock().await.entry(chat_id).or_default() += 1;
+    }
 }
 
 /// Implement ExpenseStorageTrait for ExpenseStorage
@@ -49,25 +102,341 @@ impl ExpenseStorageTrait for ExpenseStorage {
         storage_guard.get(&chat_id).cloned().unwrap_or_default()
     }
 
-    async fn add_expenses(&self, chat_id: ChatId, expenses: Vec<(String, f64, i64)>) {
+    async fn get_chat_expenses_sorted(&self, chat_id: ChatId) -> Vec<Expense> {
+        self.get_chat_expenses(chat_id).await
+    }
+
+    async fn add_expenses(&self, chat_id: ChatId, expenses: Vec<Expense>) {
         let mut storage_guard = self.data.lock().await;
         let chat_expenses = storage_guard.entry(chat_id).or_default();
-        for (description, amount, timestamp) in expenses {
-            chat_expenses.push(Expense {
-                description,
-                amount,
-                timestamp,
-            });
+        for expense in expenses {
+            // After all expenses with the same timestamp, keeping the order of addition
+            let position =
+                chat_expenses.partition_point(|existing| existing.timestamp <= expense.timestamp);
+            chat_expenses.insert(position, expense);
         }
-    }
-
-    async fn add_expense(&self, chat_id: ChatId, description: &str, amount: f64, timestamp: i64) {
-        self.add_expenses(chat_id, vec![(description.to_string(), amount, timestamp)])
-            .await;
+        drop(storage_guard);
+        self.bump_version(chat_id).await;
     }
 
     async fn clear_chat_expenses(&self, chat_id: ChatId) {
         let mut storage_guard = self.data.lock().await;
         storage_guard.remove(&chat_id);
+        drop(storage_guard);
+        self.bump_version(chat_id).await;
+    }
+
+    async fn remove_expenses_matching(&self, chat_id: ChatId, pattern: &Regex) -> Vec<Expense> {
+        // Matching runs on a blocking thread, which holds the lock until it is done
+        let mut storage_guard = self.data.clone().lock_owned().await;
+        let pattern = pattern.clone();
+        let removed: Vec<Expense> = run_blocking(move || {
+            let Some(chat_expenses) = storage_guard.get_mut(&chat_id) else {
+                return Vec::new();
+            };
+            let (removed, kept) = std::mem::take(chat_expenses)
+                .into_iter()
+                .partition(|expense| pattern.is_match(&expense.description));
+            *chat_expenses = kept;
+            removed
+        })
+        .await;
+        if !removed.is_empty() {
+            self.bump_version(chat_id).await;
+        }
+        removed
+    }
+
+    async fn take_expenses_before(&self, chat_id: ChatId, timestamp: i64) -> Vec<Expense> {
+        let mut storage_guard = self.data.lock().await;
+        let Some(chat_expenses) = storage_guard.get_mut(&chat_id) else {
+            return Vec::new();
+        };
+        // The list is chronological, so the older expenses are its head
+        let position = chat_expenses.partition_point(|expense| expense.timestamp < timestamp);
+        let taken: Vec<Expense> = chat_expenses.drain(..position).collect();
+        drop(storage_guard);
+        if !taken.is_empty() {
+            self.bump_version(chat_id).await;
+        }
+        taken
+    }
+
+    async fn restore_expenses(&self, chat_id: ChatId, expenses: Vec<Expense>) {
+        if expenses.is_empty() {
+            return;
+        }
+        let mut storage_guard = self.data.lock().await;
+        let chat_expenses = storage_guard.entry(chat_id).or_default();
+        let mut position = 0;
+        for expense in expenses {
+            // Restored expenses keep their own order and go before the ones added since
+            position += chat_expenses[position..]
+                .partition_point(|existing| existing.timestamp < expense.timestamp);
+            chat_expenses.insert(position, expense);
+            position += 1;
+        }
+        drop(storage_guard);
+        self.bump_version(chat_id).await;
+    }
+
+    async fn replace_all_expenses(&self, chat_id: ChatId, mut expenses: Vec<Expense>) {
+        expenses.sort_by_key(|expense| expense.timestamp);
+        let mut storage_guard = self.data.lock().await;
+        storage_guard.insert(chat_id, expenses);
+        drop(storage_guard);
+        self.bump_version(chat_id).await;
+    }
+
+    async fn get_chat_version(&self, chat_id: ChatId) -> u64 {
+        self.versions
+            .lock()
+            .await
+            .get(&chat_id)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expenses(description: &str, count: usize) -> Vec<Expense> {
+        (0..count)
+            .map(|i| Expense {
+                timestamp: 1609459200 + i as i64,
+                description: description.to_string(),
+                amount: Amount::from(1.0),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_replace_all_expenses_is_atomic() {
+        let storage = ExpenseStorage::new();
+        let chat_id = ChatId(1);
+        storage.add_expenses(chat_id, expenses("old", 100)).await;
+        let version = storage.get_chat_version(chat_id).await;
+
+        let readers: Vec<_> = (0..8)
+            .map(|_| {
+                let storage = storage.clone();
+                tokio::spawn(async move {
+                    for _ in 0..50 {
+                        let seen = storage.get_chat_expenses(chat_id).await;
+                        // Either the full old set or the full new set
+                        let all_old =
+                            seen.len() == 100 && seen.iter().all(|e| e.description == "old");
+                        let all_new =
+                            seen.len() == 200 && seen.iter().all(|e| e.description == "new");
+                        assert!(all_old || all_new, "partial expenses: {}", seen.len());
+                        tokio::task::yield_now().await;
+                    }
+                })
+            })
+            .collect();
+        storage
+            .replace_all_expenses(chat_id, expenses("new", 200))
+            .await;
+        for reader in readers {
+            reader.await.unwrap();
+        }
+
+        assert_eq!(storage.get_chat_expenses(chat_id).await.len(), 200);
+        assert_ne!(storage.get_chat_version(chat_id).await, version);
+    }
+
+    #[tokio::test]
+    async fn test_get_chat_expenses_sorted() {
+        let storage = ExpenseStorage::new();
+        let chat_id = ChatId(1);
+        let expense = |description: &str, timestamp| Expense {
+            timestamp,
+            description: description.to_string(),
+            amount: Amount::from(1.0),
+            ..Default::default()
+        };
+        storage
+            .add_expenses(
+                chat_id,
+                vec![expense("c", 300), expense("a1", 100), expense("b1", 200)],
+            )
+            .await;
+        storage
+            .add_expenses(chat_id, vec![expense("b2", 200), expense("a2", 100)])
+            .await;
+        storage.add_expense(chat_id, expense("first", 0)).await;
+
+        // Chronological, ties in the order of addition
+        let descriptions = |expenses: Vec<Expense>| {
+            expenses
+                .into_iter()
+                .map(|e| e.description)
+                .collect::<Vec<_>>()
+        };
+        let expected = ["first", "a1", "a2", "b1", "b2", "c"];
+        assert_eq!(
+            descriptions(storage.get_chat_expenses_sorted(chat_id).await),
+            expected
+        );
+
+        // Replacing the list sorts it the same way
+        let mut shuffled = storage.get_chat_expenses(chat_id).await;
+        shuffled.reverse();
+        storage.replace_all_expenses(chat_id, shuffled).await;
+        assert_eq!(
+            descriptions(storage.get_chat_expenses_sorted(chat_id).await),
+            ["first", "a2", "a1", "b2", "b1", "c"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remove_expenses_matching() {
+        let storage = ExpenseStorage::new();
+        let chat_id = ChatId(1);
+        storage.add_expenses(chat_id, expenses("Coffee", 2)).await;
+        storage.add_expenses(chat_id, expenses("Taxi", 1)).await;
+        storage
+            .add_expenses(chat_id, expenses("coffee beans", 1))
+            .await;
+        let version = storage.get_chat_version(chat_id).await;
+
+        // None: nothing changes
+        let removed = storage
+            .remove_expenses_matching(chat_id, &Regex::new("^Bus").unwrap())
+            .await;
+        assert!(removed.is_empty());
+        assert_eq!(storage.get_chat_version(chat_id).await, version);
+        assert_eq!(storage.get_chat_expenses(chat_id).await.len(), 4);
+
+        // Some: the others stay in chronological order
+        let removed = storage
+            .remove_expenses_matching(chat_id, &Regex::new("Coffee").unwrap())
+            .await;
+        assert_eq!(removed, expenses("Coffee", 2));
+        assert_ne!(storage.get_chat_version(chat_id).await, version);
+        let descriptions: Vec<String> = storage
+            .get_chat_expenses(chat_id)
+            .await
+            .into_iter()
+            .map(|e| e.description)
+            .collect();
+        assert_eq!(descriptions, ["Taxi", "coffee beans"]);
+
+        // All
+        let removed = storage
+            .remove_expenses_matching(chat_id, &Regex::new("").unwrap())
+            .await;
+        assert_eq!(removed.len(), 2);
+        assert!(storage.get_chat_expenses(chat_id).await.is_empty());
+
+        // A chat without expenses
+        assert!(
+            storage
+                .remove_expenses_matching(ChatId(2), &Regex::new("").unwrap())
+                .await
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_take_and_restore_expenses() {
+        let storage = ExpenseStorage::new();
+        let chat_id = ChatId(1);
+        let day_start = 1609459200; // 2021-01-01 00:00:00 UTC
+        let expense = |description: &str, timestamp| Expense {
+            timestamp,
+            description: description.to_string(),
+            amount: Amount::from(1.0),
+            ..Default::default()
+        };
+        let descriptions = |expenses: Vec<Expense>| {
+            expenses
+                .into_iter()
+                .map(|e| e.description)
+                .collect::<Vec<_>>()
+        };
+        storage
+            .add_expenses(
+                chat_id,
+                vec![
+                    expense("on the day", day_start),
+                    expense("last second before", day_start - 1),
+                    expense("later that day", day_start + 86399),
+                    expense("long ago", 0),
+                ],
+            )
+            .await;
+        let version = storage.get_chat_version(chat_id).await;
+
+        // Nothing that old: nothing changes
+        assert!(storage.take_expenses_before(chat_id, -1).await.is_empty());
+        assert_eq!(storage.get_chat_version(chat_id).await, version);
+
+        let taken = storage.take_expenses_before(chat_id, day_start).await;
+        assert_eq!(
+            descriptions(taken.clone()),
+            ["long ago", "last second before"]
+        );
+        assert_ne!(storage.get_chat_version(chat_id).await, version);
+        assert_eq!(
+            descriptions(storage.get_chat_expenses(chat_id).await),
+            ["on the day", "later that day"]
+        );
+
+        // Added since with the same date as a taken one: the restored one goes first
+        storage
+            .add_expense(chat_id, expense("added since", 0))
+            .await;
+        storage.restore_expenses(chat_id, taken).await;
+        assert_eq!(
+            descriptions(storage.get_chat_expenses(chat_id).await),
+            [
+                "long ago",
+                "added since",
+                "last second before",
+                "on the day",
+                "later that day"
+            ]
+        );
+
+        // A chat without expenses
+        assert!(
+            storage
+                .take_expenses_before(ChatId(2), day_start)
+                .await
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_expense_serde_round_trip() {
+        let with_author = Expense {
+            timestamp: 1609459200,
+            description: "Coffee".to_string(),
+            amount: Amount::from(5.5),
+            author: Some("Alice".to_string()),
+            ..Default::default()
+        };
+        let without_author = Expense {
+            author: None,
+            account: None,
+            currency: None,
+            ..with_author.clone()
+        };
+        let expenses = vec![with_author, without_author];
+        let yaml = serde_yaml::to_string(&expenses).unwrap();
+        assert_eq!(
+            serde_yaml::from_str::<Vec<Expense>>(&yaml).unwrap(),
+            expenses
+        );
+
+        // Optional fields may be missing altogether
+        let expense: Expense =
+            serde_yaml::from_str("timestamp: 1609459200\ndescription: Coffee\namount: 5.5\n")
+                .unwrap();
+        assert_eq!(expense, expenses[1]);
     }
 }
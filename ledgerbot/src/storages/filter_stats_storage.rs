@@ -0,0 +1,344 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use serde::{Deserialize, Serialize};
+use teloxide::types::ChatId;
+use tokio::{fs, sync::Mutex};
+use yoroolbot::{markdown::MarkdownString, markdown_format};
+
+/// Number of expenses categorized by each filter, keyed by `(category, pattern)`
+pub type FilterTotals = HashMap<(String, String), u64>;
+
+/// Identity of an expense for the statistics: its timestamp, its description and
+/// which one of the chat's expenses with both the same it is, counting from zero
+pub type ExpenseKey = (i64, String, usize);
+
+/// Filters a report assigned to the expenses, one `(expense, (category, pattern))` per
+/// categorized expense
+pub type FilterAssignments = Vec<(ExpenseKey, (String, String))>;
+
+/// Trait for cumulative filter statistics: how many expenses each filter has put into
+/// its category in reports, each expense counted only once
+#[async_trait::async_trait]
+pub trait FilterStatsStorageTrait: Send + Sync {
+    /// Get the chat's cumulative counts
+    async fn get_filter_totals(&self, chat_id: ChatId) -> FilterTotals;
+
+    /// Count the filters a report assigned, skipping the expenses counted by earlier reports
+    async fn add_filter_assignments(&self, chat_id: ChatId, assignments: FilterAssignments);
+
+    /// Forget the chat's counts
+    async fn clear_filter_totals(&self, chat_id: ChatId);
+
+    /// Write the chat's counts to disk, no-op for in-memory storage
+    async fn flush(&self, _chat_id: ChatId) -> Result<(), MarkdownString> {
+        Ok(())
+    }
+}
+
+/// Cumulative filter statistics of a chat
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FilterStats {
+    /// Expenses categorized by each filter
+    pub totals: FilterTotals,
+    /// Expenses already counted in the totals
+    pub counted: HashSet<ExpenseKey>,
+}
+
+/// Version of the filter statistics file format written by this build
+/// 1: `version`, `totals` and `counted`
+pub const FILTER_STATS_DATA_VERSION: u32 = 1;
+
+/// Serializable structure for the filter statistics of a chat, saved/loaded as YAML
+/// Both lists are sorted, so that unchanged statistics give an unchanged file
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct FilterStatsData {
+    /// Format version
+    #[serde(default)]
+    pub version: u32,
+    /// `(category, pattern, count)` of each filter
+    #[serde(default)]
+    pub totals: Vec<(String, String, u64)>,
+    /// Expenses already counted in the totals
+    #[serde(default)]
+    pub counted: Vec<ExpenseKey>,
+}
+
+impl From<FilterStats> for FilterStatsData {
+    fn from(stats: FilterStats) -> Self {
+        let mut totals: Vec<_> = stats
+            .totals
+            .into_iter()
+            .map(|((category, pattern), count)| (category, pattern, count))
+            .collect();
+        totals.sort();
+        let mut counted: Vec<_> = stats.counted.into_iter().collect();
+        counted.sort();
+        FilterStatsData {
+            version: FILTER_STATS_DATA_VERSION,
+            totals,
+            counted,
+        }
+    }
+}
+
+impl From<FilterStatsData> for FilterStats {
+    fn from(data: FilterStatsData) -> Self {
+        FilterStats {
+            totals: data
+                .totals
+                .into_iter()
+                .map(|(category, pattern, count)| ((category, pattern), count))
+                .collect(),
+            counted: data.counted.into_iter().collect(),
+        }
+    }
+}
+
+/// Per-chat storage for cumulative filter statistics
+#[derive(Clone)]
+pub struct FilterStatsStorage {
+    data: Arc<Mutex<HashMap<ChatId, FilterStats>>>,
+}
+
+impl FilterStatsStorage {
+    pub fn new() -> Self {
+        Self {
+            data: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Get all of the chat's statistics
+    async fn get_chat_stats(&self, chat_id: ChatId) -> FilterStats {
+        let storage_guard = self.data.lock().await;
+        storage_guard.get(&chat_id).cloned().unwrap_or_default()
+    }
+
+    /// Replace all of the chat's statistics
+    async fn set_chat_stats(&self, chat_id: ChatId, stats: FilterStats) {
+        let mut storage_guard = self.data.lock().await;
+        storage_guard.insert(chat_id, stats);
+    }
+}
+
+/// Implement FilterStatsStorageTrait for FilterStatsStorage
+#[async_trait::async_trait]
+impl FilterStatsStorageTrait for FilterStatsStorage {
+    async fn get_filter_totals(&self, chat_id: ChatId) -> FilterTotals {
+        let storage_guard = self.data.lock().await;
+        storage_guard
+            .get(&chat_id)
+            .map(|stats| stats.totals.clone())
+            .unwrap_or_default()
+    }
+
+    async fn add_filter_assignments(&self, chat_id: ChatId, assignments: FilterAssignments) {
+        let mut storage_guard = self.data.lock().await;
+        let stats = storage_guard.entry(chat_id).or_default();
+        for (expense, filter) in assignments {
+            if stats.counted.insert(expense) {
+                *stats.totals.entry(filter).or_default() += 1;
+            }
+        }
+    }
+
+    async fn clear_filter_totals(&self, chat_id: ChatId) {
+        let mut storage_guard = self.data.lock().await;
+        storage_guard.remove(&chat_id);
+    }
+}
+
+/// Persistent filter statistics storage that saves each chat's counts to a YAML file named by chat ID
+/// Files are loaded lazily, on the first access to the chat's counts
+#[derive(Clone)]
+pub struct PersistentFilterStatsStorage {
+    // Storage directory for filter statistics files
+    storage_dir: PathBuf,
+    // In-memory storage using FilterStatsStorage
+    memory_storage: FilterStatsStorage,
+    // Track which chats have been loaded from disk: ChatId -> bool
+    loaded_chats: Arc<Mutex<HashMap<ChatId, bool>>>,
+}
+
+impl PersistentFilterStatsStorage {
+    /// Create a new persistent filter statistics storage with the specified directory
+    pub fn new(storage_dir: PathBuf) -> Self {
+        Self {
+            storage_dir,
+            memory_storage: FilterStatsStorage::new(),
+            loaded_chats: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Get the file path for a chat's filter statistics
+    fn get_file_path(&self, chat_id: ChatId) -> PathBuf {
+        self.storage_dir.join(format!("{}.yaml", chat_id))
+    }
+
+    /// Load statistics from disk, empty if the file is missing or unreadable
+    async fn load_chat_stats(&self, chat_id: ChatId) -> FilterStats {
+        let Ok(content) = fs::read_to_string(self.get_file_path(chat_id)).await else {
+            return FilterStats::default();
+        };
+        match serde_yaml::from_str::<FilterStatsData>(&content) {
+            Ok(data) => {
+                if data.version > FILTER_STATS_DATA_VERSION {
+                    log::warn!(
+                        "Filter statistics file of chat {} has version {}, newer than supported {}, unknown fields are dropped",
+                        chat_id,
+                        data.version,
+                        FILTER_STATS_DATA_VERSION
+                    );
+                }
+                data.into()
+            }
+            Err(e) => {
+                log::error!(
+                    "Failed to parse filter statistics of chat {}: {}",
+                    chat_id,
+                    e
+                );
+                FilterStats::default()
+            }
+        }
+    }
+
+    /// Save statistics to disk for a specific chat ID
+    async fn save_chat_stats(
+        &self,
+        chat_id: ChatId,
+        stats: FilterStats,
+    ) -> Result<(), std::io::Error> {
+        fs::create_dir_all(&self.storage_dir).await?;
+        let content = serde_yaml::to_string(&FilterStatsData::from(stats)).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Failed to serialize filter statistics to YAML: {}", e),
+            )
+        })?;
+        fs::write(self.get_file_path(chat_id), content).await
+    }
+
+    /// Ensure statistics are loaded for a chat ID (lazy loading)
+    async fn ensure_loaded(&self, chat_id: ChatId) {
+        let mut loaded_guard = self.loaded_chats.lock().await;
+        if loaded_guard.get(&chat_id).copied().unwrap_or(false) {
+            return;
+        }
+        let stats = self.load_chat_stats(chat_id).await;
+        self.memory_storage.set_chat_stats(chat_id, stats).await;
+        loaded_guard.insert(chat_id, true);
+    }
+}
+
+/// Implement FilterStatsStorageTrait for PersistentFilterStatsStorage
+#[async_trait::async_trait]
+impl FilterStatsStorageTrait for PersistentFilterStatsStorage {
+    async fn get_filter_totals(&self, chat_id: ChatId) -> FilterTotals {
+        self.ensure_loaded(chat_id).await;
+        self.memory_storage.get_filter_totals(chat_id).await
+    }
+
+    async fn add_filter_assignments(&self, chat_id: ChatId, assignments: FilterAssignments) {
+        self.ensure_loaded(chat_id).await;
+        self.memory_storage
+            .add_filter_assignments(chat_id, assignments)
+            .await;
+        let stats = self.memory_storage.get_chat_stats(chat_id).await;
+        if let Err(e) = self.save_chat_stats(chat_id, stats).await {
+            log::error!(
+                "Failed to save filter statistics for chat {}: {}",
+                chat_id,
+                e
+            );
+        }
+    }
+
+    async fn clear_filter_totals(&self, chat_id: ChatId) {
+        // Hold the loaded flag so the file is not read back while it is being removed
+        let mut loaded_guard = self.loaded_chats.lock().await;
+        self.memory_storage.clear_filter_totals(chat_id).await;
+        loaded_guard.insert(chat_id, true);
+        match fs::remove_file(self.get_file_path(chat_id)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                log::error!(
+                    "Failed to remove filter statistics of chat {}: {}",
+                    chat_id,
+                    e
+                );
+            }
+            _ => {}
+        }
+    }
+
+    async fn flush(&self, chat_id: ChatId) -> Result<(), MarkdownString> {
+        self.ensure_loaded(chat_id).await;
+        let stats = self.memory_storage.get_chat_stats(chat_id).await;
+        self.save_chat_stats(chat_id, stats)
+            .await
+            .map_err(|e| markdown_format!("{}", e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storages::test_dir::TestDir;
+
+    fn assigned(
+        timestamp: i64,
+        description: &str,
+        pattern: &str,
+    ) -> (ExpenseKey, (String, String)) {
+        (
+            (timestamp, description.to_string(), 0),
+            ("Food".to_string(), pattern.to_string()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_persistent_filter_stats_count_each_expense_once() {
+        let storage_dir = TestDir::new("filter_stats");
+        let storage = PersistentFilterStatsStorage::new(storage_dir.to_path_buf());
+        let chat_id = ChatId(1);
+        let filter = ("Food".to_string(), "(?i)coffee".to_string());
+        storage
+            .add_filter_assignments(
+                chat_id,
+                vec![
+                    assigned(1, "Coffee", "(?i)coffee"),
+                    assigned(2, "Coffee", "(?i)coffee"),
+                ],
+            )
+            .await;
+        // The same expense again, even under another filter, counts for nothing
+        storage
+            .add_filter_assignments(
+                chat_id,
+                vec![
+                    assigned(1, "Coffee", "(?i)co"),
+                    assigned(3, "Coffee", "(?i)coffee"),
+                ],
+            )
+            .await;
+        let totals = storage.get_filter_totals(chat_id).await;
+        assert_eq!(totals, FilterTotals::from([(filter.clone(), 3)]));
+
+        // A fresh storage loads the counts and the counted expenses from the file
+        let reloaded = PersistentFilterStatsStorage::new(storage_dir.to_path_buf());
+        assert_eq!(reloaded.get_filter_totals(chat_id).await, totals);
+        reloaded
+            .add_filter_assignments(chat_id, vec![assigned(3, "Coffee", "(?i)coffee")])
+            .await;
+        assert_eq!(reloaded.get_filter_totals(chat_id).await, totals);
+
+        // Clearing removes the file too
+        reloaded.clear_filter_totals(chat_id).await;
+        assert!(reloaded.get_filter_totals(chat_id).await.is_empty());
+        let reloaded = PersistentFilterStatsStorage::new(storage_dir.to_path_buf());
+        assert!(reloaded.get_filter_totals(chat_id).await.is_empty());
+    }
+}
@@ -1,9 +1,38 @@
+mod archive_storage;
+mod audit_storage;
 mod batch_storage;
 mod category_storage;
+mod confirmation_storage;
 mod expense_storage;
+mod filter_stats_storage;
+mod pending_expense_storage;
+mod report_cache_storage;
+mod settings_storage;
 mod storage;
+#[cfg(test)]
+pub mod test_dir;
 
-pub use batch_storage::{BatchStorage, BatchStorageTrait};
-pub use category_storage::{CategoryStorageTrait, PersistentCategoryStorage};
+pub use archive_storage::{ArchiveStorage, ArchiveStorageTrait};
+pub use audit_storage::{AuditEntry, AuditStorage, AuditStorageTrait, PersistentAuditStorage};
+pub use batch_storage::{BatchEntry, BatchHistoryEntry, BatchStorage, BatchStorageTrait};
+#[cfg(test)]
+pub use category_storage::CategoryStorage;
+pub use category_storage::{CategoryData, CategoryStorageTrait, PersistentCategoryStorage};
+#[cfg(test)]
+pub use confirmation_storage::CONFIRMATION_TIMEOUT_SECONDS;
+pub use confirmation_storage::{ConfirmationStorage, ConfirmationStorageTrait};
 pub use expense_storage::{Expense, ExpenseStorage, ExpenseStorageTrait};
+pub use filter_stats_storage::{
+    FilterAssignments, FilterStatsStorage, FilterStatsStorageTrait, FilterTotals,
+    PersistentFilterStatsStorage,
+};
+pub use pending_expense_storage::{PendingExpenseStorage, PendingExpenseStorageTrait};
+pub use report_cache_storage::{
+    CachedReport, LastReportMessage, ReportCacheKey, ReportCacheStorage, ReportCacheStorageTrait,
+};
+pub use settings_storage::{
+    DEFAULT_REPORT_PAGE_SIZE, DEFAULT_UNCATEGORIZED_NAME, MAX_REPORT_PAGE_SIZE,
+    PersistentSettingsStorage, SETTINGS_DATA_VERSION, SettingsData, SettingsStorage,
+    SettingsStorageTrait,
+};
 pub use storage::{Storage, StorageTrait};
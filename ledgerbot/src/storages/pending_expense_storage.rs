@@ -0,0 +1,76 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use teloxide::types::ChatId;
+use tokio::sync::Mutex;
+
+use crate::storages::Expense;
+
+/// Trait for storage of expenses waiting for the user's confirmation
+/// Each expense gets its own id, so that several prompts may wait at the same time
+#[async_trait::async_trait]
+pub trait PendingExpenseStorageTrait: Send + Sync {
+    /// Remember the expense to store on Confirm, returning the id its buttons refer to
+    async fn add_pending_expense(&self, chat_id: ChatId, expense: Expense) -> u64;
+
+    /// Remove and return the chat's pending expense with the given id
+    async fn take_pending_expense(&self, chat_id: ChatId, id: u64) -> Option<Expense>;
+
+    /// Forget all pending expenses of the chat
+    async fn clear_pending_expenses(&self, chat_id: ChatId);
+}
+
+type PendingExpenseStorageData = Arc<Mutex<HashMap<ChatId, BTreeMap<u64, Expense>>>>;
+
+/// Per-chat storage for the expenses waiting for confirmation
+#[derive(Clone)]
+pub struct PendingExpenseStorage {
+    data: PendingExpenseStorageData,
+    // Id of the next pending expense, never reused
+    next_id: Arc<Mutex<u64>>,
+}
+
+impl PendingExpenseStorage {
+    pub fn new() -> Self {
+        Self {
+            data: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(Mutex::new(1)),
+        }
+    }
+}
+
+/// Implement PendingExpenseStorageTrait for PendingExpenseStorage
+#[async_trait::async_trait]
+impl PendingExpenseStorageTrait for PendingExpenseStorage {
+    async fn add_pending_expense(&self, chat_id: ChatId, expense: Expense) -> u64 {
+        let id = {
+            let mut next_id = self.next_id.lock().await;
+            let id = *next_id;
+            *next_id += 1;
+            id
+        };
+        let mut storage_guard = self.data.lock().await;
+        storage_guard
+            .entry(chat_id)
+            .or_default()
+            .insert(id, expense);
+        id
+    }
+
+    async fn take_pending_expense(&self, chat_id: ChatId, id: u64) -> Option<Expense> {
+        let mut storage_guard = self.data.lock().await;
+        let chat_expenses = storage_guard.get_mut(&chat_id)?;
+        let expense = chat_expenses.remove(&id);
+        if chat_expenses.is_empty() {
+            storage_guard.remove(&chat_id);
+        }
+        expense
+    }
+
+    async fn clear_pending_expenses(&self, chat_id: ChatId) {
+        let mut storage_guard = self.data.lock().await;
+        storage_guard.remove(&chat_id);
+    }
+}
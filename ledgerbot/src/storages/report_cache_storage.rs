@@ -0,0 +1,120 @@
+use std::{collections::HashMap, sync::Arc};
+
+use teloxide::types::{ChatId, MessageId};
+use tokio::sync::Mutex;
+use yoroolbot::{markdown::MarkdownString, storage::ButtonData};
+
+use crate::utils::{amount::Amount, format_amount::AmountStyle};
+
+/// Everything the rendered report summary depends on
+/// A cached report is valid only while the key is unchanged
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportCacheKey {
+    pub expenses_version: u64,
+    pub categories_version: u64,
+    pub min_amount: Option<f64>,
+    pub amount_style: AmountStyle,
+    pub uncategorized_name: String,
+    pub case_insensitive_filters: bool,
+    pub monthly_goal: Option<Amount>,
+    pub two_column_summary: bool,
+    /// Month of the goal progress, as `YYYY-MM`
+    pub current_month: String,
+}
+
+/// Rendered report summary: message and category selection menu
+pub type CachedReport = (MarkdownString, Vec<Vec<ButtonData>>);
+
+/// The chat's last report summary message, refreshed when its data changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LastReportMessage {
+    pub message_id: MessageId,
+    pub min_amount: Option<f64>,
+    /// Unix time the message was first sent
+    pub sent_at: i64,
+}
+
+/// Trait for rendered report cache operations
+#[async_trait::async_trait]
+pub trait ReportCacheStorageTrait: Send + Sync {
+    /// Get the cached report of a chat if it was rendered for the same key
+    async fn get_cached_report(
+        &self,
+        chat_id: ChatId,
+        key: &ReportCacheKey,
+    ) -> Option<CachedReport>;
+
+    /// Store the rendered report of a chat, replacing the previous one
+    async fn set_cached_report(&self, chat_id: ChatId, key: ReportCacheKey, report: CachedReport);
+
+    /// Drop the cached report of a chat
+    async fn clear_cached_report(&self, chat_id: ChatId);
+
+    /// Remember the chat's last report summary message, replacing the previous one
+    async fn set_last_report_message(&self, chat_id: ChatId, message: LastReportMessage);
+
+    /// Get the chat's last report summary message
+    async fn get_last_report_message(&self, chat_id: ChatId) -> Option<LastReportMessage>;
+
+    /// Forget the chat's last report summary message
+    async fn clear_last_report_message(&self, chat_id: ChatId);
+}
+
+type ReportCacheStorageData = Arc<Mutex<HashMap<ChatId, (ReportCacheKey, CachedReport)>>>;
+
+/// Per-chat cache holding the last rendered report summary
+#[derive(Clone)]
+pub struct ReportCacheStorage {
+    data: ReportCacheStorageData,
+    last_messages: Arc<Mutex<HashMap<ChatId, LastReportMessage>>>,
+}
+
+impl ReportCacheStorage {
+    pub fn new() -> Self {
+        Self {
+            data: Arc::new(Mutex::new(HashMap::new())),
+            last_messages: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+/// Implement ReportCacheStorageTrait for ReportCacheStorage
+#[async_trait::async_trait]
+impl ReportCacheStorageTrait for ReportCacheStorage {
+    async fn get_cached_report(
+        &self,
+        chat_id: ChatId,
+        key: &ReportCacheKey,
+    ) -> Option<CachedReport> {
+        let storage_guard = self.data.lock().await;
+        storage_guard
+            .get(&chat_id)
+            .filter(|(cached_key, _)| cached_key == key)
+            .map(|(_, report)| report.clone())
+    }
+
+    async fn set_cached_report(&self, chat_id: ChatId, key: ReportCacheKey, report: CachedReport) {
+        let mut storage_guard = self.data.lock().await;
+        storage_guard.insert(chat_id, (key, report));
+    }
+
+    async fn clear_cached_report(&self, chat_id: ChatId) {
+        let mut storage_guard = self.data.lock().await;
+        storage_guard.remove(&chat_id);
+    }
+
+    async fn set_last_report_message(&self, chat_id: ChatId, message: LastReportMessage) {
+        let mut last_messages_guard = self.last_messages.lock().await;
+        last_messages_guard.insert(chat_id, message);
+    }
+
+    async fn get_last_report_message(&self, chat_id: ChatId) -> Option<LastReportMessage> {
+        let last_messages_guard = self.last_messages.lock().await;
+        last_messages_guard.get(&chat_id).copied()
+    }
+
+    async fn clear_last_report_message(&self, chat_id: ChatId) {
+        let mut last_messages_guard = self.last_messages.lock().await;
+        last_messages_guard.remove(&chat_id);
+    }
+}
@@ -0,0 +1,304 @@
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use teloxide::types::ChatId;
+use tokio::{fs, sync::Mutex};
+use yoroolbot::{markdown::MarkdownString, markdown_format};
+
+use crate::utils::{
+    amount::Amount, expense_template::ExpenseTemplate, format_amount::AmountStyle,
+    timezone::UtcOffset,
+};
+
+/// Name of the bucket for expenses matching no category, unless changed per chat
+pub const DEFAULT_UNCATEGORIZED_NAME: &str = "Other";
+
+/// Records on one page of a category report, unless changed per chat
+pub const DEFAULT_REPORT_PAGE_SIZE: usize = 25;
+
+/// Largest page size allowed, to keep a page within one message
+pub const MAX_REPORT_PAGE_SIZE: usize = 50;
+
+/// Per-chat display settings
+/// Fields missing in a stored file get their defaults
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatSettings {
+    /// Group thousands in report amounts: 12,345.67 instead of 12345.67
+    pub amount_grouping: bool,
+    /// Custom name of the bucket for uncategorized expenses
+    pub uncategorized_name: Option<String>,
+    /// Match regex filters ignoring case, as if each started with `(?i)`
+    pub case_insensitive_filters: bool,
+    /// Overall spending goal for a calendar month
+    pub monthly_goal: Option<Amount>,
+    /// Show amounts in reports with a decimal comma: 12.345,67 instead of 12,345.67
+    pub decimal_comma: bool,
+    /// Ask to confirm single expenses before storing them
+    pub confirm_expenses: bool,
+    /// Don't show the persistent reply keyboard menu
+    pub hide_menu: bool,
+    /// Lay out the category summary in two columns when there are many categories
+    pub two_column_summary: bool,
+    /// Offset of the chat's time zone, for message dates and the current month
+    pub utc_offset: UtcOffset,
+    /// Message confirming an added expense
+    pub expense_template: ExpenseTemplate,
+    /// Custom number of records on one page of a category report
+    pub report_page_size: Option<usize>,
+    /// Join a line with only an amount to the line before it, see `pair_amount_lines`
+    pub pair_amount_lines: bool,
+}
+
+impl ChatSettings {
+    /// Name of the bucket for uncategorized expenses
+    pub fn uncategorized_name(&self) -> &str {
+        self.uncategorized_name
+            .as_deref()
+            .unwrap_or(DEFAULT_UNCATEGORIZED_NAME)
+    }
+
+    /// Number of records on one page of a category report
+    pub fn report_page_size(&self) -> usize {
+        self.report_page_size.unwrap_or(DEFAULT_REPORT_PAGE_SIZE)
+    }
+
+    /// How amounts are shown in reports
+    pub fn amount_style(&self) -> AmountStyle {
+        AmountStyle {
+            grouping: self.amount_grouping,
+            decimal_comma: self.decimal_comma,
+        }
+    }
+}
+
+/// Trait for per-chat settings storage operations
+#[async_trait::async_trait]
+pub trait SettingsStorageTrait: Send + Sync {
+    /// Get settings for a specific chat, defaults if never set
+    async fn get_chat_settings(&self, chat_id: ChatId) -> ChatSettings;
+
+    /// Replace settings for a specific chat
+    async fn set_chat_settings(&self, chat_id: ChatId, settings: ChatSettings);
+
+    /// Forget the chat's settings, so that it gets the defaults
+    async fn clear_chat_settings(&self, chat_id: ChatId);
+
+    /// Write the chat's settings to disk, no-op for in-memory storage
+    async fn flush(&self, _chat_id: ChatId) -> Result<(), MarkdownString> {
+        Ok(())
+    }
+}
+
+/// Version of the settings file format written by this build
+/// 1: `version` and `settings`
+pub const SETTINGS_DATA_VERSION: u32 = 1;
+
+/// Serializable structure for the settings of a chat, saved/loaded as YAML
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SettingsData {
+    /// Format version
+    #[serde(default)]
+    pub version: u32,
+    pub settings: ChatSettings,
+}
+
+type SettingsStorageData = Arc<Mutex<HashMap<ChatId, ChatSettings>>>;
+
+/// Per-chat storage for settings
+#[derive(Clone)]
+pub struct SettingsStorage {
+    data: SettingsStorageData,
+}
+
+impl SettingsStorage {
+    pub fn new() -> Self {
+        Self {
+            data: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+/// Implement SettingsStorageTrait for SettingsStorage
+#[async_trait::async_trait]
+impl SettingsStorageTrait for SettingsStorage {
+    async fn get_chat_settings(&self, chat_id: ChatId) -> ChatSettings {
+        let storage_guard = self.data.lock().await;
+        storage_guard.get(&chat_id).cloned().unwrap_or_default()
+    }
+
+    async fn set_chat_settings(&self, chat_id: ChatId, settings: ChatSettings) {
+        let mut storage_guard = self.data.lock().await;
+        storage_guard.insert(chat_id, settings);
+    }
+
+    async fn clear_chat_settings(&self, chat_id: ChatId) {
+        let mut storage_guard = self.data.lock().await;
+        storage_guard.remove(&chat_id);
+    }
+}
+
+/// Persistent settings storage that saves each chat's settings to a YAML file named by chat ID
+/// Files are loaded lazily, on the first access to the chat's settings
+#[derive(Clone)]
+pub struct PersistentSettingsStorage {
+    // Storage directory for settings files
+    storage_dir: PathBuf,
+    // In-memory storage using SettingsStorage
+    memory_storage: SettingsStorage,
+    // Track which chats have been loaded from disk: ChatId -> bool
+    loaded_chats: Arc<Mutex<HashMap<ChatId, bool>>>,
+}
+
+impl PersistentSettingsStorage {
+    /// Create a new persistent settings storage with the specified directory
+    pub fn new(storage_dir: PathBuf) -> Self {
+        Self {
+            storage_dir,
+            memory_storage: SettingsStorage::new(),
+            loaded_chats: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Get the file path for a chat's settings
+    fn get_file_path(&self, chat_id: ChatId) -> PathBuf {
+        self.storage_dir.join(format!("{}.yaml", chat_id))
+    }
+
+    /// Load settings from disk, defaults if the file is missing or unreadable
+    async fn load_chat_settings(&self, chat_id: ChatId) -> ChatSettings {
+        let Ok(content) = fs::read_to_string(self.get_file_path(chat_id)).await else {
+            return ChatSettings::default();
+        };
+        match serde_yaml::from_str::<SettingsData>(&content) {
+            Ok(data) => {
+                if data.version > SETTINGS_DATA_VERSION {
+                    log::warn!(
+                        "Settings file of chat {} has version {}, newer than supported {}, unknown fields are dropped",
+                        chat_id,
+                        data.version,
+                        SETTINGS_DATA_VERSION
+                    );
+                }
+                data.settings
+            }
+            Err(e) => {
+                log::error!("Failed to parse settings of chat {}: {}", chat_id, e);
+                ChatSettings::default()
+            }
+        }
+    }
+
+    /// Save settings to disk for a specific chat ID
+    async fn save_chat_settings(
+        &self,
+        chat_id: ChatId,
+        settings: ChatSettings,
+    ) -> Result<(), std::io::Error> {
+        fs::create_dir_all(&self.storage_dir).await?;
+        let data = SettingsData {
+            version: SETTINGS_DATA_VERSION,
+            settings,
+        };
+        let content = serde_yaml::to_string(&data).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Failed to serialize settings to YAML: {}", e),
+            )
+        })?;
+        fs::write(self.get_file_path(chat_id), content).await
+    }
+
+    /// Ensure settings are loaded for a chat ID (lazy loading)
+    async fn ensure_loaded(&self, chat_id: ChatId) {
+        let mut loaded_guard = self.loaded_chats.lock().await;
+        if loaded_guard.get(&chat_id).copied().unwrap_or(false) {
+            return;
+        }
+        let settings = self.load_chat_settings(chat_id).await;
+        self.memory_storage
+            .set_chat_settings(chat_id, settings)
+            .await;
+        loaded_guard.insert(chat_id, true);
+    }
+}
+
+/// Implement SettingsStorageTrait for PersistentSettingsStorage
+#[async_trait::async_trait]
+impl SettingsStorageTrait for PersistentSettingsStorage {
+    async fn get_chat_settings(&self, chat_id: ChatId) -> ChatSettings {
+        self.ensure_loaded(chat_id).await;
+        self.memory_storage.get_chat_settings(chat_id).await
+    }
+
+    async fn set_chat_settings(&self, chat_id: ChatId, settings: ChatSettings) {
+        self.ensure_loaded(chat_id).await;
+        self.memory_storage
+            .set_chat_settings(chat_id, settings.clone())
+            .await;
+        if let Err(e) = self.save_chat_settings(chat_id, settings).await {
+            log::error!("Failed to save settings for chat {}: {}", chat_id, e);
+        }
+    }
+
+    async fn clear_chat_settings(&self, chat_id: ChatId) {
+        // Hold the loaded flag so the file is not read back while it is being removed
+        let mut loaded_guard = self.loaded_chats.lock().await;
+        self.memory_storage.clear_chat_settings(chat_id).await;
+        loaded_guard.insert(chat_id, true);
+        match fs::remove_file(self.get_file_path(chat_id)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                log::error!("Failed to remove settings of chat {}: {}", chat_id, e);
+            }
+            _ => {}
+        }
+    }
+
+    async fn flush(&self, chat_id: ChatId) -> Result<(), MarkdownString> {
+        let settings = self.get_chat_settings(chat_id).await;
+        self.save_chat_settings(chat_id, settings)
+            .await
+            .map_err(|e| markdown_format!("{}", e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storages::test_dir::TestDir;
+
+    #[tokio::test]
+    async fn test_persistent_settings_round_trip() {
+        let storage_dir = TestDir::new("settings");
+        let storage = PersistentSettingsStorage::new(storage_dir.to_path_buf());
+        let chat_id = ChatId(1);
+        let mut settings = storage.get_chat_settings(chat_id).await;
+        assert_eq!(settings, ChatSettings::default());
+        settings.decimal_comma = true;
+        settings.uncategorized_name = Some("Misc".to_string());
+        settings.monthly_goal = Some(Amount::from(500.0));
+        settings.utc_offset = "+05:30".parse().unwrap();
+        settings.expense_template = "{description}: {amount}".parse().unwrap();
+        storage.set_chat_settings(chat_id, settings.clone()).await;
+
+        // A fresh storage loads the settings from the file
+        let reloaded = PersistentSettingsStorage::new(storage_dir.to_path_buf());
+        assert_eq!(reloaded.get_chat_settings(chat_id).await, settings);
+        assert_eq!(
+            reloaded.get_chat_settings(ChatId(2)).await,
+            ChatSettings::default()
+        );
+    }
+
+    #[test]
+    fn test_settings_missing_fields_default() {
+        let data: SettingsData =
+            serde_yaml::from_str("version: 1\nsettings:\n  hide_menu: true\n").unwrap();
+        assert!(data.settings.hide_menu);
+        assert_eq!(
+            data.settings.uncategorized_name(),
+            DEFAULT_UNCATEGORIZED_NAME
+        );
+        assert_eq!(data.settings.expense_template, ExpenseTemplate::default());
+    }
+}
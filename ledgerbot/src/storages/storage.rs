@@ -1,14 +1,23 @@
 use std::sync::Arc;
 
-use yoroolbot::storage::{CallbackDataStorage, CallbackDataStorageTrait};
+use teloxide::types::ChatId;
+use yoroolbot::{
+    markdown::MarkdownString,
+    storage::{CallbackDataStorage, CallbackDataStorageTrait},
+};
 
 use super::category_storage::CategoryStorage;
 use crate::storages::{
-    BatchStorage, BatchStorageTrait, CategoryStorageTrait, ExpenseStorage, ExpenseStorageTrait,
+    ArchiveStorage, ArchiveStorageTrait, AuditStorage, AuditStorageTrait, BatchStorage,
+    BatchStorageTrait, CategoryStorageTrait, ConfirmationStorage, ConfirmationStorageTrait,
+    ExpenseStorage, ExpenseStorageTrait, FilterStatsStorage, FilterStatsStorageTrait,
+    PendingExpenseStorage, PendingExpenseStorageTrait, ReportCacheStorage, ReportCacheStorageTrait,
+    SettingsStorage, SettingsStorageTrait,
 };
 
 /// Combined storage trait that provides all storage operations
 /// This trait allows converting to specific trait objects for functions that only need subset of functionality
+#[async_trait::async_trait]
 pub trait StorageTrait: Send + Sync {
     /// Convert to ExpenseStorageTrait trait object
     fn as_expense_storage(self: Arc<Self>) -> Arc<dyn ExpenseStorageTrait>;
@@ -21,6 +30,75 @@ pub trait StorageTrait: Send + Sync {
 
     /// Convert to CallbackDataStorageTrait trait object
     fn as_callback_data_storage(self: Arc<Self>) -> Arc<dyn CallbackDataStorageTrait>;
+
+    /// Convert to SettingsStorageTrait trait object
+    fn as_settings_storage(self: Arc<Self>) -> Arc<dyn SettingsStorageTrait>;
+
+    /// Convert to AuditStorageTrait trait object
+    fn as_audit_storage(self: Arc<Self>) -> Arc<dyn AuditStorageTrait>;
+
+    /// Convert to ReportCacheStorageTrait trait object
+    fn as_report_cache_storage(self: Arc<Self>) -> Arc<dyn ReportCacheStorageTrait>;
+
+    /// Convert to ConfirmationStorageTrait trait object
+    fn as_confirmation_storage(self: Arc<Self>) -> Arc<dyn ConfirmationStorageTrait>;
+
+    /// Convert to PendingExpenseStorageTrait trait object
+    fn as_pending_expense_storage(self: Arc<Self>) -> Arc<dyn PendingExpenseStorageTrait>;
+
+    /// Convert to ArchiveStorageTrait trait object
+    fn as_archive_storage(self: Arc<Self>) -> Arc<dyn ArchiveStorageTrait>;
+
+    /// Convert to FilterStatsStorageTrait trait object
+    fn as_filter_stats_storage(self: Arc<Self>) -> Arc<dyn FilterStatsStorageTrait>;
+
+    /// Forget everything about a chat: expenses, archived expenses, categories, settings, pending batch,
+    /// batch history, pending expenses, pending confirmations, filter statistics, audit log,
+    /// cached and last sent report and callback data of its buttons
+    async fn clear_chat_all(self: Arc<Self>, chat_id: ChatId) -> Result<(), MarkdownString> {
+        self.clone()
+            .as_expense_storage()
+            .clear_chat_expenses(chat_id)
+            .await;
+        self.clone()
+            .as_archive_storage()
+            .take_archived_expenses(chat_id)
+            .await;
+        self.clone()
+            .as_category_storage()
+            .clear_chat_categories(chat_id)
+            .await?;
+        self.clone()
+            .as_settings_storage()
+            .clear_chat_settings(chat_id)
+            .await;
+        let batch = self.clone().as_batch_storage();
+        batch.consume_batch(chat_id).await;
+        batch.clear_history(chat_id).await;
+        self.clone()
+            .as_pending_expense_storage()
+            .clear_pending_expenses(chat_id)
+            .await;
+        self.clone()
+            .as_confirmation_storage()
+            .clear_pending_confirmations(chat_id)
+            .await;
+        self.clone()
+            .as_filter_stats_storage()
+            .clear_filter_totals(chat_id)
+            .await;
+        self.clone()
+            .as_audit_storage()
+            .clear_chat_audit(chat_id)
+            .await?;
+        let report_cache = self.clone().as_report_cache_storage();
+        report_cache.clear_cached_report(chat_id).await;
+        report_cache.clear_last_report_message(chat_id).await;
+        self.as_callback_data_storage()
+            .clear_chat_callbacks(chat_id)
+            .await;
+        Ok(())
+    }
 }
 
 /// Main storage structure that holds all bot data
@@ -31,6 +109,13 @@ pub struct Storage {
     categories: Arc<dyn CategoryStorageTrait>,
     batch: Arc<dyn BatchStorageTrait>,
     callback_data: Arc<dyn CallbackDataStorageTrait>,
+    settings: Arc<dyn SettingsStorageTrait>,
+    audit: Arc<dyn AuditStorageTrait>,
+    report_cache: Arc<dyn ReportCacheStorageTrait>,
+    confirmations: Arc<dyn ConfirmationStorageTrait>,
+    pending_expenses: Arc<dyn PendingExpenseStorageTrait>,
+    archive: Arc<dyn ArchiveStorageTrait>,
+    filter_stats: Arc<dyn FilterStatsStorageTrait>,
 }
 
 impl Storage {
@@ -41,6 +126,13 @@ impl Storage {
             categories: Arc::new(CategoryStorage::new()),
             batch: Arc::new(BatchStorage::new()),
             callback_data: Arc::new(CallbackDataStorage::new()),
+            settings: Arc::new(SettingsStorage::new()),
+            audit: Arc::new(AuditStorage::new()),
+            report_cache: Arc::new(ReportCacheStorage::new()),
+            confirmations: Arc::new(ConfirmationStorage::new()),
+            pending_expenses: Arc::new(PendingExpenseStorage::new()),
+            archive: Arc::new(ArchiveStorage::new()),
+            filter_stats: Arc::new(FilterStatsStorage::new()),
         }
     }
 
@@ -50,6 +142,27 @@ impl Storage {
         self.categories = Arc::new(storage);
         self
     }
+
+    /// Builder-like method to configure settings storage
+    /// Replaces the settings storage with the provided implementation
+    pub fn settings_storage(mut self, storage: impl SettingsStorageTrait + 'static) -> Self {
+        self.settings = Arc::new(storage);
+        self
+    }
+
+    /// Builder-like method to configure audit log storage
+    /// Replaces the audit storage with the provided implementation
+    pub fn audit_storage(mut self, storage: impl AuditStorageTrait + 'static) -> Self {
+        self.audit = Arc::new(storage);
+        self
+    }
+
+    /// Builder-like method to configure filter statistics storage
+    /// Replaces the filter statistics storage with the provided implementation
+    pub fn filter_stats_storage(mut self, storage: impl FilterStatsStorageTrait + 'static) -> Self {
+        self.filter_stats = Arc::new(storage);
+        self
+    }
 }
 
 impl Default for Storage {
@@ -59,6 +172,7 @@ impl Default for Storage {
 }
 
 /// Implement StorageTrait for Storage to enable conversion to specific trait objects
+#[async_trait::async_trait]
 impl StorageTrait for Storage {
     fn as_expense_storage(self: Arc<Self>) -> Arc<dyn ExpenseStorageTrait> {
         self.expenses.clone()
@@ -75,4 +189,253 @@ impl StorageTrait for Storage {
     fn as_callback_data_storage(self: Arc<Self>) -> Arc<dyn CallbackDataStorageTrait> {
         self.callback_data.clone()
     }
+
+    fn as_settings_storage(self: Arc<Self>) -> Arc<dyn SettingsStorageTrait> {
+        self.settings.clone()
+    }
+
+    fn as_audit_storage(self: Arc<Self>) -> Arc<dyn AuditStorageTrait> {
+        self.audit.clone()
+    }
+
+    fn as_report_cache_storage(self: Arc<Self>) -> Arc<dyn ReportCacheStorageTrait> {
+        self.report_cache.clone()
+    }
+
+    fn as_confirmation_storage(self: Arc<Self>) -> Arc<dyn ConfirmationStorageTrait> {
+        self.confirmations.clone()
+    }
+
+    fn as_pending_expense_storage(self: Arc<Self>) -> Arc<dyn PendingExpenseStorageTrait> {
+        self.pending_expenses.clone()
+    }
+
+    fn as_archive_storage(self: Arc<Self>) -> Arc<dyn ArchiveStorageTrait> {
+        self.archive.clone()
+    }
+
+    fn as_filter_stats_storage(self: Arc<Self>) -> Arc<dyn FilterStatsStorageTrait> {
+        self.filter_stats.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use teloxide::types::MessageId;
+    use yoroolbot::markdown_format;
+
+    use super::*;
+    use crate::{
+        storages::{AuditEntry, BatchEntry, Expense, ReportCacheKey},
+        utils::amount::Amount,
+    };
+
+    fn report_key() -> ReportCacheKey {
+        ReportCacheKey {
+            expenses_version: 0,
+            categories_version: 0,
+            min_amount: None,
+            amount_style: Default::default(),
+            uncategorized_name: "Other".to_string(),
+            case_insensitive_filters: false,
+            monthly_goal: None,
+            two_column_summary: false,
+            current_month: "2024-01".to_string(),
+        }
+    }
+
+    async fn fill_chat(storage: &Arc<Storage>, chat_id: ChatId) -> String {
+        storage
+            .clone()
+            .as_expense_storage()
+            .add_expense(
+                chat_id,
+                Expense {
+                    timestamp: 0,
+                    description: "Coffee".to_string(),
+                    amount: Amount::from(5.0),
+                    ..Default::default()
+                },
+            )
+            .await;
+        storage
+            .clone()
+            .as_archive_storage()
+            .add_archived_expenses(
+                chat_id,
+                vec![Expense {
+                    timestamp: 0,
+                    description: "Tea".to_string(),
+                    amount: Amount::from(2.0),
+                    ..Default::default()
+                }],
+            )
+            .await;
+        storage
+            .clone()
+            .as_category_storage()
+            .add_category(chat_id, "Food".to_string())
+            .await
+            .unwrap();
+        let mut settings = storage
+            .clone()
+            .as_settings_storage()
+            .get_chat_settings(chat_id)
+            .await;
+        settings.decimal_comma = true;
+        storage
+            .clone()
+            .as_settings_storage()
+            .set_chat_settings(chat_id, settings)
+            .await;
+        storage
+            .clone()
+            .as_filter_stats_storage()
+            .add_filter_assignments(
+                chat_id,
+                vec![(
+                    (1609459200, "Coffee".to_string(), 0),
+                    ("Food".to_string(), "(?i)coffee".to_string()),
+                )],
+            )
+            .await;
+        let batch = storage.clone().as_batch_storage();
+        batch
+            .add_to_batch(
+                chat_id,
+                vec![BatchEntry {
+                    result: Err("Invalid line".to_string()),
+                    message_id: MessageId(1),
+                    line: 1,
+                    user_id: None,
+                }],
+            )
+            .await;
+        batch
+            .add_to_history(chat_id, vec![Ok("/report".to_string())])
+            .await;
+        storage
+            .clone()
+            .as_report_cache_storage()
+            .set_cached_report(chat_id, report_key(), (markdown_format!("Report"), vec![]))
+            .await;
+        storage
+            .clone()
+            .as_audit_storage()
+            .add_audit_entry(
+                chat_id,
+                AuditEntry {
+                    timestamp: 0,
+                    user_id: None,
+                    command: "/report".to_string(),
+                },
+            )
+            .await;
+        storage
+            .clone()
+            .as_confirmation_storage()
+            .set_pending_confirmation(chat_id, "clear", 0)
+            .await;
+        storage
+            .clone()
+            .as_callback_data_storage()
+            .store_callback_data(chat_id, 1, 0, "/report".to_string())
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_clear_chat_all() {
+        let storage = Arc::new(Storage::new());
+        let (chat_id, other_chat_id) = (ChatId(1), ChatId(2));
+        let reference = fill_chat(&storage, chat_id).await;
+        let other_reference = fill_chat(&storage, other_chat_id).await;
+
+        storage.clone().clear_chat_all(chat_id).await.unwrap();
+
+        let expenses = storage.clone().as_expense_storage();
+        let archive = storage.clone().as_archive_storage();
+        let categories = storage.clone().as_category_storage();
+        let settings = storage.clone().as_settings_storage();
+        let batch = storage.clone().as_batch_storage();
+        let filter_stats = storage.clone().as_filter_stats_storage();
+        let report_cache = storage.clone().as_report_cache_storage();
+        let callback_data = storage.clone().as_callback_data_storage();
+        let audit = storage.clone().as_audit_storage();
+        let confirmations = storage.clone().as_confirmation_storage();
+
+        assert!(expenses.get_chat_expenses(chat_id).await.is_empty());
+        assert!(archive.get_archived_expenses(chat_id).await.is_empty());
+        assert!(
+            categories
+                .get_chat_categories(chat_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            settings.get_chat_settings(chat_id).await,
+            Default::default()
+        );
+        assert!(batch.consume_batch(chat_id).await.is_none());
+        assert!(batch.get_history(chat_id, 10).await.is_empty());
+        assert!(filter_stats.get_filter_totals(chat_id).await.is_empty());
+        assert!(
+            report_cache
+                .get_cached_report(chat_id, &report_key())
+                .await
+                .is_none()
+        );
+        assert!(callback_data.get_callback_data(&reference).await.is_none());
+        assert!(audit.get_last_audit_entries(chat_id, 10).await.is_empty());
+        assert!(
+            !confirmations
+                .take_pending_confirmation(chat_id, "clear", 0)
+                .await
+        );
+
+        // The chat is not known anymore
+        assert_eq!(categories.get_known_chats().await, vec![other_chat_id]);
+
+        // The other chat is untouched
+        assert_eq!(expenses.get_chat_expenses(other_chat_id).await.len(), 1);
+        assert_eq!(archive.get_archived_expenses(other_chat_id).await.len(), 1);
+        assert!(
+            categories
+                .get_chat_categories(other_chat_id)
+                .await
+                .unwrap()
+                .contains_key("Food")
+        );
+        assert!(
+            settings
+                .get_chat_settings(other_chat_id)
+                .await
+                .decimal_comma
+        );
+        assert!(batch.consume_batch(other_chat_id).await.is_some());
+        assert_eq!(batch.get_history(other_chat_id, 10).await.len(), 1);
+        assert_eq!(filter_stats.get_filter_totals(other_chat_id).await.len(), 1);
+        assert!(
+            report_cache
+                .get_cached_report(other_chat_id, &report_key())
+                .await
+                .is_some()
+        );
+        assert!(
+            callback_data
+                .get_callback_data(&other_reference)
+                .await
+                .is_some()
+        );
+        assert_eq!(
+            audit.get_last_audit_entries(other_chat_id, 10).await.len(),
+            1
+        );
+        assert!(
+            confirmations
+                .take_pending_confirmation(other_chat_id, "clear", 0)
+                .await
+        );
+    }
 }
@@ -0,0 +1,35 @@
+use std::path::{Path, PathBuf};
+
+/// Temporary storage directory for tests, removed when dropped even if the test panics
+pub struct TestDir(PathBuf);
+
+impl TestDir {
+    /// Directory named after the test and the process, not created until the test needs it
+    pub fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("ledgerbot_{}_test_{}", name, std::process::id()));
+        // Leftovers of a killed run would leak into this one
+        let _ = std::fs::remove_dir_all(&path);
+        Self(path)
+    }
+}
+
+impl std::ops::Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TestDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
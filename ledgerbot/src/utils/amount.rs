@@ -0,0 +1,151 @@
+use std::{
+    fmt,
+    iter::Sum,
+    ops::{Add, AddAssign},
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Number of minor units (cents) in one unit of currency
+const MINOR_UNITS: i64 = 100;
+
+/// Money amount stored as integer minor units, so that sums are exact
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(i64);
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+
+    pub fn from_minor_units(minor_units: i64) -> Self {
+        Amount(minor_units)
+    }
+
+    pub fn minor_units(self) -> i64 {
+        self.0
+    }
+
+    /// Value in units of currency, for formatting and comparison with user input
+    pub fn as_f64(self) -> f64 {
+        self.0 as f64 / MINOR_UNITS as f64
+    }
+
+    pub fn checked_add(self, other: Amount) -> Option<Amount> {
+        self.0.checked_add(other.0).map(Amount)
+    }
+
+    pub fn checked_sub(self, other: Amount) -> Option<Amount> {
+        self.0.checked_sub(other.0).map(Amount)
+    }
+
+    pub fn checked_mul(self, factor: i64) -> Option<Amount> {
+        self.0.checked_mul(factor).map(Amount)
+    }
+}
+
+/// Rounds to the nearest minor unit
+impl From<f64> for Amount {
+    fn from(value: f64) -> Self {
+        Amount((value * MINOR_UNITS as f64).round() as i64)
+    }
+}
+
+/// Saturates instead of overflowing, use `checked_add` to detect overflow
+impl Add for Amount {
+    type Output = Amount;
+
+    fn add(self, other: Amount) -> Amount {
+        Amount(self.0.saturating_add(other.0))
+    }
+}
+
+impl AddAssign for Amount {
+    fn add_assign(&mut self, other: Amount) {
+        *self = *self + other;
+    }
+}
+
+impl Sum for Amount {
+    fn sum<I: Iterator<Item = Amount>>(iter: I) -> Self {
+        iter.fold(Amount::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Amount> for Amount {
+    fn sum<I: Iterator<Item = &'a Amount>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        let minor_units = MINOR_UNITS as u64;
+        write!(f, "{}{}.{:02}", sign, abs / minor_units, abs % minor_units)
+    }
+}
+
+/// Stored as a float to stay compatible with existing YAML
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.as_f64())
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        f64::deserialize(deserializer).map(Amount::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amount_sums_are_exact() {
+        assert_ne!(0.1 + 0.2, 0.3);
+        assert_eq!(Amount::from(0.1) + Amount::from(0.2), Amount::from(0.3));
+
+        let total: Amount = std::iter::repeat_n(Amount::from(0.1), 1000).sum();
+        assert_eq!(total, Amount::from(100.0));
+        assert_eq!(total.to_string(), "100.00");
+    }
+
+    #[test]
+    fn test_amount_conversions_and_display() {
+        assert_eq!(Amount::from(5.5).minor_units(), 550);
+        assert_eq!(Amount::from(2.675).minor_units(), 268);
+        assert_eq!(Amount::from(-3.05).to_string(), "-3.05");
+        assert_eq!(Amount::from(0.07).to_string(), "0.07");
+        assert_eq!(Amount::from(12.34).as_f64(), 12.34);
+    }
+
+    #[test]
+    fn test_amount_checked_arithmetic() {
+        let max = Amount::from_minor_units(i64::MAX);
+        assert_eq!(max.checked_add(Amount::from_minor_units(1)), None);
+        assert_eq!(max + Amount::from_minor_units(1), max);
+        assert_eq!(
+            Amount::from(1.5).checked_sub(Amount::from(2.0)),
+            Some(Amount::from(-0.5))
+        );
+        assert_eq!(Amount::from(5.0).checked_mul(3), Some(Amount::from(15.0)));
+        assert_eq!(max.checked_mul(2), None);
+    }
+
+    #[test]
+    fn test_amount_yaml_compatibility() {
+        // Old float and integer forms are accepted
+        assert_eq!(
+            serde_yaml::from_str::<Amount>("5.5").unwrap(),
+            Amount::from(5.5)
+        );
+        assert_eq!(
+            serde_yaml::from_str::<Amount>("12").unwrap(),
+            Amount::from(12.0)
+        );
+        let yaml = serde_yaml::to_string(&Amount::from(5.5)).unwrap();
+        assert_eq!(yaml.trim(), "5.5");
+    }
+}
@@ -0,0 +1,129 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
+use teloxide::utils::command::ParseError;
+
+/// Confirmation sent when no template is set
+pub const DEFAULT_EXPENSE_TEMPLATE: &str = "✅ Expense added: {date} {description} {amount}";
+
+const DATE: &str = "{date}";
+const DESCRIPTION: &str = "{description}";
+const AMOUNT: &str = "{amount}";
+
+/// Placeholders a template can't do without, `{date}` is optional
+const REQUIRED_PLACEHOLDERS: [&str; 2] = [DESCRIPTION, AMOUNT];
+
+/// Plain text template of the message confirming an added expense, with
+/// `{date}`, `{description}` and `{amount}` placeholders
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpenseTemplate(String);
+
+impl ExpenseTemplate {
+    /// Substitute the placeholders in a single pass, so that placeholder-like text
+    /// in the description is kept as is
+    pub fn render(&self, date: &str, description: &str, amount: &str) -> String {
+        let values = [(DATE, date), (DESCRIPTION, description), (AMOUNT, amount)];
+        let mut rendered = String::new();
+        let mut rest = self.0.as_str();
+        while let Some(start) = rest.find('{') {
+            rendered.push_str(&rest[..start]);
+            rest = &rest[start..];
+            match values.iter().find(|(name, _)| rest.starts_with(name)) {
+                Some((name, value)) => {
+                    rendered.push_str(value);
+                    rest = &rest[name.len()..];
+                }
+                None => {
+                    rendered.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        rendered.push_str(rest);
+        rendered
+    }
+}
+
+impl Default for ExpenseTemplate {
+    fn default() -> Self {
+        ExpenseTemplate(DEFAULT_EXPENSE_TEMPLATE.to_string())
+    }
+}
+
+impl Display for ExpenseTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for ExpenseTemplate {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let missing: Vec<&str> = REQUIRED_PLACEHOLDERS
+            .into_iter()
+            .filter(|placeholder| !s.contains(placeholder))
+            .collect();
+        if missing.is_empty() {
+            Ok(ExpenseTemplate(s.trim().to_string()))
+        } else {
+            Err(ParseError::IncorrectFormat(
+                format!("Template is missing {}", missing.join(" and ")).into(),
+            ))
+        }
+    }
+}
+
+/// Stored as the template text
+impl Serialize for ExpenseTemplate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ExpenseTemplate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(|e: ParseError| D::Error::custom(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_custom_template() {
+        let template: ExpenseTemplate = "💸 {amount} for {description} ({date})".parse().unwrap();
+        assert_eq!(
+            template.render("2024-01-05 10:00", "Coffee", "3.5"),
+            "💸 3.5 for Coffee (2024-01-05 10:00)"
+        );
+        // Placeholders in the values and unknown ones are left alone
+        assert_eq!(
+            template.render("", "{amount} {x}", "1"),
+            "💸 1 for {amount} {x} ()"
+        );
+        assert_eq!(
+            ExpenseTemplate::default().render("2024-01-05 10:00", "Coffee", "3.5"),
+            "✅ Expense added: 2024-01-05 10:00 Coffee 3.5"
+        );
+    }
+
+    #[test]
+    fn test_template_missing_placeholders() {
+        assert!(
+            "Added {description} {amount}"
+                .parse::<ExpenseTemplate>()
+                .is_ok()
+        );
+        for template in ["Added {description}", "Added {amount}", "Added", "{date}"] {
+            assert!(
+                template.parse::<ExpenseTemplate>().is_err(),
+                "{} should be rejected",
+                template
+            );
+        }
+    }
+}
@@ -1,56 +1,67 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+};
 
 use teloxide::types::ChatId;
 
 use crate::{
     menus::select_word::Words,
     storages::{Expense, StorageTrait},
+    utils::filter_pattern::compile_filter,
 };
 
-/// Extract unique words from uncategorized expenses
-/// Returns a sorted vector of unique words (lowercased) from expense descriptions
-/// that don't match any category patterns
-pub fn extract_words(
+/// Words of each expense that doesn't match any category pattern
+/// Words are lowercased with punctuation trimmed, words shorter than 2 characters are skipped
+pub fn uncategorized_words(
     expenses: &[Expense],
     categories: &HashMap<String, Vec<String>>,
-) -> Vec<String> {
+    case_insensitive: bool,
+) -> Vec<BTreeSet<String>> {
     // Build regex matchers for each category (from all patterns)
     let category_matchers: Vec<regex::Regex> = categories
         .values()
         .flat_map(|patterns| patterns.iter())
-        .filter_map(|pattern| regex::Regex::new(pattern).ok())
+        .filter_map(|pattern| compile_filter(pattern, case_insensitive).ok())
         .collect();
 
-    // Collect unique words from uncategorized expenses
-    let mut words = std::collections::HashSet::new();
-
-    for expense in expenses.iter() {
-        // Check if this expense matches any category
-        let matched = category_matchers
-            .iter()
-            .any(|re| re.is_match(&expense.description));
-
-        if !matched {
-            // Split description into words and collect them
-            for word in expense.description.split_whitespace() {
-                // Clean the word: lowercase, remove punctuation
-                let cleaned = word
-                    .to_lowercase()
-                    .trim_matches(|c: char| !c.is_alphanumeric())
-                    .to_string();
-
+    expenses
+        .iter()
+        .filter(|expense| {
+            !category_matchers
+                .iter()
+                .any(|re| re.is_match(&expense.description))
+        })
+        .map(|expense| {
+            expense
+                .description
+                .split_whitespace()
+                .map(|word| {
+                    // Clean the word: lowercase, remove punctuation
+                    word.to_lowercase()
+                        .trim_matches(|c: char| !c.is_alphanumeric())
+                        .to_string()
+                })
                 // Only include words that are at least 2 characters long
-                if cleaned.len() >= 2 {
-                    words.insert(cleaned);
-                }
-            }
-        }
-    }
+                .filter(|cleaned| cleaned.len() >= 2)
+                .collect()
+        })
+        .collect()
+}
 
-    // Convert to sorted vector
-    let mut result: Vec<String> = words.into_iter().collect();
-    result.sort();
-    result
+/// Extract unique words from uncategorized expenses
+/// Returns a sorted vector of unique words (lowercased) from expense descriptions
+/// that don't match any category patterns
+pub fn extract_words(
+    expenses: &[Expense],
+    categories: &HashMap<String, Vec<String>>,
+    case_insensitive: bool,
+) -> Vec<String> {
+    let words: BTreeSet<String> = uncategorized_words(expenses, categories, case_insensitive)
+        .into_iter()
+        .flatten()
+        .collect();
+    words.into_iter().collect()
 }
 
 pub fn merge_words(existing: &[String], available: &[String]) -> Vec<String> {
@@ -90,9 +101,14 @@ pub async fn extract_and_merge_words(
         .get_chat_categories(chat_id)
         .await
         .unwrap_or_default();
+    let settings = storage
+        .clone()
+        .as_settings_storage()
+        .get_chat_settings(chat_id)
+        .await;
 
     // Extract words from uncategorized expenses
-    let available_words = extract_words(&expenses, &categories);
+    let available_words = extract_words(&expenses, &categories, settings.case_insensitive_filters);
 
     let current_words: Vec<String> = words.map(|w| w.into()).unwrap_or_default();
     merge_words(&current_words, &available_words).into()
@@ -102,7 +118,10 @@ pub async fn extract_and_merge_words(
 mod tests {
     use std::collections::HashMap;
 
-    use crate::{storages::Expense, utils::extract_words::extract_words};
+    use crate::{
+        storages::Expense,
+        utils::{amount::Amount, extract_words::extract_words},
+    };
 
     #[test]
     fn test_extract_words() {
@@ -111,23 +130,27 @@ mod tests {
         let expenses = vec![
             Expense {
                 description: "Coffee at Starbucks".to_string(),
-                amount: 5.50,
+                amount: Amount::from(5.50),
                 timestamp,
+                ..Default::default()
             },
             Expense {
                 description: "Lunch at restaurant".to_string(),
-                amount: 12.00,
+                amount: Amount::from(12.00),
                 timestamp,
+                ..Default::default()
             },
             Expense {
                 description: "Bus ticket".to_string(),
-                amount: 2.75,
+                amount: Amount::from(2.75),
                 timestamp,
+                ..Default::default()
             },
             Expense {
                 description: "Taxi ride".to_string(),
-                amount: 15.00,
+                amount: Amount::from(15.00),
                 timestamp,
+                ..Default::default()
             },
         ];
 
@@ -137,7 +160,7 @@ mod tests {
         categories.insert("Food".to_string(), food_patterns);
 
         // Extract words from uncategorized expenses
-        let words = extract_words(&expenses, &categories);
+        let words = extract_words(&expenses, &categories, false);
 
         // "Lunch at restaurant" should be categorized as Food
         // So words should come from "Coffee at Starbucks", "Bus ticket", and "Taxi ride"
@@ -156,7 +179,7 @@ mod tests {
         // Test with no expenses
         let expenses = Vec::new();
         let categories = HashMap::new();
-        let words = extract_words(&expenses, &categories);
+        let words = extract_words(&expenses, &categories, false);
         assert_eq!(words.len(), 0);
     }
 
@@ -167,13 +190,15 @@ mod tests {
         let expenses = vec![
             Expense {
                 description: "Coffee".to_string(),
-                amount: 5.50,
+                amount: Amount::from(5.50),
                 timestamp,
+                ..Default::default()
             },
             Expense {
                 description: "Lunch".to_string(),
-                amount: 12.00,
+                amount: Amount::from(12.00),
                 timestamp,
+                ..Default::default()
             },
         ];
 
@@ -183,7 +208,7 @@ mod tests {
         categories.insert("Food".to_string(), food_patterns);
 
         // Extract words - should be empty as all are categorized
-        let words = extract_words(&expenses, &categories);
+        let words = extract_words(&expenses, &categories, false);
         assert_eq!(words.len(), 0);
     }
 }
@@ -0,0 +1,325 @@
+use regex::{Regex, RegexBuilder};
+
+/// Prefix marking a stored filter as a literal text instead of a regex
+pub const LITERAL_FILTER_PREFIX: &str = "literal:";
+
+/// Stored form of a literal filter matching descriptions containing the text
+pub fn literal_filter(text: &str) -> String {
+    format!("{}{}", LITERAL_FILTER_PREFIX, text)
+}
+
+/// Compile a stored filter into a regex
+/// Literal filters become escaped case-insensitive substring matches
+/// With `case_insensitive` regex filters ignore case too, unless they turn it off with `(?-i)`
+pub fn compile_filter(pattern: &str, case_insensitive: bool) -> Result<Regex, regex::Error> {
+    match pattern.strip_prefix(LITERAL_FILTER_PREFIX) {
+        Some(text) => Regex::new(&format!("(?i){}", regex::escape(text))),
+        None => RegexBuilder::new(pattern)
+            .case_insensitive(case_insensitive)
+            .build(),
+    }
+}
+
+/// Features of other regex engines, like PCRE, which the `regex` crate doesn't support
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnsupportedRegexFeature {
+    /// Lookahead and lookbehind: `(?=...)`, `(?!...)`, `(?<=...)`, `(?<!...)`
+    LookAround,
+    /// Backreferences to groups: `\1`, `\k<name>`
+    Backreference,
+    /// Atomic groups `(?>...)`
+    AtomicGroup,
+}
+
+impl UnsupportedRegexFeature {
+    /// What to write instead
+    pub fn hint(&self) -> &'static str {
+        match self {
+            UnsupportedRegexFeature::LookAround => {
+                "Lookahead and lookbehind are not supported. Make the surrounding text a part of the pattern instead, e.g. \"hot coffee\" rather than \"(?<=hot )coffee\""
+            }
+            UnsupportedRegexFeature::Backreference => {
+                "Backreferences like \\1 are not supported. Write the repeated text out, e.g. \"(ab)ab\" rather than \"(ab)\\1\""
+            }
+            UnsupportedRegexFeature::AtomicGroup => {
+                "Atomic groups (?>...) are not supported. Use a plain group \"(?:...)\" instead, matching here never backtracks slowly"
+            }
+        }
+    }
+}
+
+/// Recognize a compile error caused by a feature the `regex` crate doesn't support
+/// None for other errors, like unbalanced parentheses, whose own message is clear enough
+pub fn unsupported_regex_feature(
+    pattern: &str,
+    error: &regex::Error,
+) -> Option<UnsupportedRegexFeature> {
+    let regex::Error::Syntax(message) = error else {
+        return None;
+    };
+    if message.contains("look-around") {
+        Some(UnsupportedRegexFeature::LookAround)
+    } else if message.contains("backreferences are not supported") {
+        Some(UnsupportedRegexFeature::Backreference)
+    } else if message.contains("unrecognized flag") && pattern.contains("(?>") {
+        Some(UnsupportedRegexFeature::AtomicGroup)
+    } else {
+        None
+    }
+}
+
+/// Comparable form of a stored filter, equal for filters matching the same way
+/// Literal filters are compared as their regex, the characters of case-insensitive parts
+/// are folded to lowercase. Parts turning case-insensitivity off, like `(?-i:...)`, escape
+/// sequences like `\D` and Unicode class names like `\p{Lu}` are kept intact
+pub fn normalize_filter(pattern: &str, case_insensitive: bool) -> String {
+    let (regex, case_insensitive) = match pattern.trim().strip_prefix(LITERAL_FILTER_PREFIX) {
+        Some(text) => (regex::escape(text.trim()), true),
+        None => match pattern.trim().strip_prefix("(?i)") {
+            Some(rest) => (rest.to_string(), true),
+            None => (pattern.trim().to_string(), case_insensitive),
+        },
+    };
+    let mut folded = String::new();
+    if case_insensitive {
+        folded.push_str("(?i)");
+    }
+    // Case-insensitivity outside of each open group, the innermost last
+    let mut groups: Vec<bool> = Vec::new();
+    let mut current = case_insensitive;
+    let mut class_depth = 0;
+    let mut chars = regex.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                folded.push(c);
+                let Some(escaped) = chars.next() else {
+                    continue;
+                };
+                folded.push(escaped);
+                if matches!(escaped, 'p' | 'P') {
+                    if chars.peek() == Some(&'{') {
+                        for name_char in chars.by_ref() {
+                            folded.push(name_char);
+                            if name_char == '}' {
+                                break;
+                            }
+                        }
+                    } else {
+                        folded.extend(chars.next());
+                    }
+                }
+            }
+            '[' => {
+                class_depth += 1;
+                folded.push(c);
+            }
+            ']' if class_depth > 0 => {
+                class_depth -= 1;
+                folded.push(c);
+            }
+            '(' if class_depth == 0 => {
+                folded.push(c);
+                if chars.peek() != Some(&'?') {
+                    groups.push(current);
+                    continue;
+                }
+                folded.extend(chars.next());
+                let mut flags = String::new();
+                while let Some(&flag) = chars
+                    .peek()
+                    .filter(|f| f.is_ascii_alphabetic() || **f == '-')
+                {
+                    flags.push(flag);
+                    folded.push(flag);
+                    chars.next();
+                }
+                let flagged = case_insensitive_after_flags(&flags, current);
+                if chars.peek() == Some(&')') {
+                    // `(?flags)` applies to the rest of the enclosing group
+                    folded.extend(chars.next());
+                    current = flagged;
+                } else {
+                    // `(?flags:...)` and named groups
+                    groups.push(current);
+                    current = flagged;
+                }
+            }
+            ')' if class_depth == 0 => {
+                folded.push(c);
+                current = groups.pop().unwrap_or(current);
+            }
+            _ if current => folded.extend(c.to_lowercase()),
+            _ => folded.push(c),
+        }
+    }
+    folded
+}
+
+/// Case-insensitivity after a group's flags like `i`, `-i` or `x-i`
+fn case_insensitive_after_flags(flags: &str, case_insensitive: bool) -> bool {
+    let (enabled, disabled) = flags.split_once('-').unwrap_or((flags, ""));
+    if disabled.contains('i') {
+        false
+    } else if enabled.contains('i') {
+        true
+    } else {
+        case_insensitive
+    }
+}
+
+/// Find another filter of `existing` equivalent to `pattern` after normalization
+/// The pattern itself, if it's already there, doesn't count
+pub fn find_equivalent_filter<'a>(
+    existing: &'a [String],
+    pattern: &str,
+    case_insensitive: bool,
+) -> Option<&'a String> {
+    let normalized = normalize_filter(pattern, case_insensitive);
+    existing.iter().find(|filter| {
+        filter.as_str() != pattern && normalize_filter(filter, case_insensitive) == normalized
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unsupported_regex_feature() {
+        let feature = |pattern: &str| match compile_filter(pattern, false) {
+            Ok(_) => None,
+            Err(e) => unsupported_regex_feature(pattern, &e),
+        };
+        assert_eq!(
+            feature("(?<=hot )coffee"),
+            Some(UnsupportedRegexFeature::LookAround)
+        );
+        assert_eq!(
+            feature("coffee(?!maker)"),
+            Some(UnsupportedRegexFeature::LookAround)
+        );
+        assert_eq!(
+            feature(r"(ab)\1"),
+            Some(UnsupportedRegexFeature::Backreference)
+        );
+        assert_eq!(
+            feature("(?>ab)c"),
+            Some(UnsupportedRegexFeature::AtomicGroup)
+        );
+        // A valid pattern has no error, other errors keep their own message
+        assert!(compile_filter(r"(?i)\bcoffee\b", false).is_ok());
+        assert_eq!(feature("(unclosed"), None);
+    }
+
+    #[test]
+    fn test_literal_and_regex_filters() {
+        let filters = [literal_filter("C++ (book)"), r"(?i)\bcoffee\b".to_string()];
+        let regexes: Vec<Regex> = filters
+            .iter()
+            .map(|f| compile_filter(f, false).unwrap())
+            .collect();
+        let matches = |description: &str| regexes.iter().any(|re| re.is_match(description));
+
+        // Literal: case-insensitive substring, special characters taken as is
+        assert!(matches("Bought c++ (BOOK) today"));
+        assert!(!matches("C book"));
+        // Regex filter in the same category still works
+        assert!(matches("Coffee"));
+        assert!(!matches("Coffeemaker"));
+
+        // Text of literal filter is never parsed as regex
+        assert!(compile_filter(&literal_filter("[unclosed"), false).is_ok());
+        assert!(compile_filter("[unclosed", false).is_err());
+    }
+
+    #[test]
+    fn test_case_insensitive_setting() {
+        let matches = |pattern: &str, case_insensitive: bool, description: &str| {
+            compile_filter(pattern, case_insensitive)
+                .unwrap()
+                .is_match(description)
+        };
+
+        // Off: plain patterns are case-sensitive as before
+        assert!(!matches("coffee", false, "Coffee"));
+        assert!(matches("Coffee", false, "Coffee"));
+        // On: plain patterns match ignoring case
+        assert!(matches("coffee", true, "Coffee"));
+        assert!(matches(r"^taxi\b", true, "TAXI home"));
+        // Case sensitivity can still be requested explicitly
+        assert!(!matches("(?-i)coffee", true, "Coffee"));
+        // Literal filters ignore case either way
+        assert!(matches(&literal_filter("coffee"), false, "COFFEE"));
+    }
+
+    #[test]
+    fn test_find_equivalent_filter() {
+        let existing = vec![
+            "(?i)coffee".to_string(),
+            r"\bTaxi\b".to_string(),
+            literal_filter("C++"),
+        ];
+        let find = |pattern: &str, case_insensitive: bool| {
+            find_equivalent_filter(&existing, pattern, case_insensitive).cloned()
+        };
+
+        // The same filter is not another one, a whitespace-only difference is
+        assert_eq!(find("(?i)coffee", false), None);
+        assert_eq!(find(" (?i)coffee ", false), Some("(?i)coffee".to_string()));
+        // Case variants of a case-insensitive filter
+        assert_eq!(find("(?i)Coffee", false), Some("(?i)coffee".to_string()));
+        assert_eq!(
+            find(&literal_filter("COFFEE"), false),
+            Some("(?i)coffee".to_string())
+        );
+        assert_eq!(find(r"(?i)c\+\+", false), Some(literal_filter("C++")));
+        // Case variants of a case-sensitive filter differ, unless the chat ignores case
+        assert_eq!(find(r"\btaxi\b", false), None);
+        assert_eq!(find(r"\btaxi\b", true), Some(r"\bTaxi\b".to_string()));
+        // Escapes are not folded: \B is not \b
+        assert_eq!(find(r"\Btaxi\B", true), None);
+        // Genuinely distinct patterns
+        assert_eq!(find("(?i)tea", false), None);
+        assert_eq!(find("(?i)coffee|tea", false), None);
+    }
+
+    #[test]
+    fn test_normalize_filter_keeps_case_sensitive_parts() {
+        // Groups and flags turning case-insensitivity off
+        assert_eq!(
+            normalize_filter("(?i)Coffee (?-i:Shop)", false),
+            "(?i)coffee (?-i:Shop)"
+        );
+        assert_ne!(
+            normalize_filter("(?i)coffee (?-i:Shop)", false),
+            normalize_filter("(?i)coffee (?-i:shop)", false)
+        );
+        assert_eq!(
+            normalize_filter("(?i)Tea(?-i)Bag", false),
+            normalize_filter("(?i)tea(?-i)Bag", false)
+        );
+        assert_ne!(
+            normalize_filter("(?i)Tea(?-i)Bag", false),
+            normalize_filter("(?i)tea(?-i)bag", false)
+        );
+        // The setting of a group ends with it
+        assert_eq!(
+            normalize_filter("(?-i:A)B", true),
+            normalize_filter("(?-i:A)b", true)
+        );
+        // A case-sensitive filter turning it on for a part
+        assert_eq!(
+            normalize_filter("Coffee (?i:SHOP)", false),
+            "Coffee (?i:shop)"
+        );
+        // Unicode class names: \P{Lu} is not \P{lu}, \pL is not \pl
+        assert_eq!(normalize_filter(r"(?i)\P{Lu}X", false), r"(?i)\P{Lu}x");
+        assert_eq!(normalize_filter(r"(?i)\pLX", false), r"(?i)\pLx");
+        // Parentheses inside a character class don't open groups
+        assert_eq!(
+            normalize_filter("(?i)[(]A(?-i:B)C", false),
+            "(?i)[(]a(?-i:B)c"
+        );
+    }
+}
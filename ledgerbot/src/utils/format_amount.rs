@@ -0,0 +1,112 @@
+/// Format amount with the given number of decimal places
+/// With `grouping` the integer part is split into thousands: 12,345.67
+pub fn format_amount(value: f64, precision: usize, grouping: bool) -> String {
+    let plain = format!("{:.*}", precision, value);
+    if !grouping {
+        return plain;
+    }
+
+    let (sign, digits) = match plain.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", plain.as_str()),
+    };
+    let (integer_part, fraction_part) = match digits.split_once('.') {
+        Some((integer_part, fraction_part)) => (integer_part, Some(fraction_part)),
+        None => (digits, None),
+    };
+
+    let mut grouped = String::new();
+    for (i, digit) in integer_part.chars().enumerate() {
+        if i > 0 && (integer_part.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+
+    match fraction_part {
+        Some(fraction_part) => format!("{}{}.{}", sign, grouped, fraction_part),
+        None => format!("{}{}", sign, grouped),
+    }
+}
+
+/// How amounts are displayed in a chat, independent of how they are parsed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct AmountStyle {
+    /// Group thousands of the integer part
+    pub grouping: bool,
+    /// Use a decimal comma and, when grouping, dots between thousands: 12.345,67
+    pub decimal_comma: bool,
+}
+
+/// Format amount like `format_amount`, swapping the separators for decimal comma display
+pub fn format_amount_localized(value: f64, precision: usize, style: AmountStyle) -> String {
+    let formatted = format_amount(value, precision, style.grouping);
+    if !style.decimal_comma {
+        return formatted;
+    }
+    formatted
+        .chars()
+        .map(|c| match c {
+            '.' => ',',
+            ',' => '.',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_amount_grouping_magnitudes() {
+        assert_eq!(format_amount(0.0, 2, true), "0.00");
+        assert_eq!(format_amount(999.5, 2, true), "999.50");
+        assert_eq!(format_amount(1000.0, 2, true), "1,000.00");
+        assert_eq!(format_amount(12345.67, 2, true), "12,345.67");
+        assert_eq!(format_amount(1234567.891, 2, true), "1,234,567.89");
+        assert_eq!(format_amount(123456789.0, 0, true), "123,456,789");
+    }
+
+    #[test]
+    fn test_format_amount_grouping_negative() {
+        assert_eq!(format_amount(-5.5, 2, true), "-5.50");
+        assert_eq!(format_amount(-1234.5, 1, true), "-1,234.5");
+        assert_eq!(format_amount(-999999.999, 2, true), "-1,000,000.00");
+    }
+
+    #[test]
+    fn test_format_amount_plain() {
+        assert_eq!(format_amount(12345.67, 2, false), "12345.67");
+        assert_eq!(format_amount(-1234567.0, 0, false), "-1234567");
+        assert_eq!(format_amount(5.5, 3, false), "5.500");
+    }
+
+    #[test]
+    fn test_format_amount_localized() {
+        let style = |grouping, decimal_comma| AmountStyle {
+            grouping,
+            decimal_comma,
+        };
+        assert_eq!(
+            format_amount_localized(1234.56, 2, style(false, false)),
+            "1234.56"
+        );
+        assert_eq!(
+            format_amount_localized(1234.56, 2, style(true, false)),
+            "1,234.56"
+        );
+        assert_eq!(
+            format_amount_localized(1234.56, 2, style(false, true)),
+            "1234,56"
+        );
+        assert_eq!(
+            format_amount_localized(1234.56, 2, style(true, true)),
+            "1.234,56"
+        );
+        assert_eq!(
+            format_amount_localized(-1234567.0, 0, style(true, true)),
+            "-1.234.567"
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use yoroolbot::{markdown::MarkdownString, markdown_format};
+
+/// Maximum edit distance for a name to be suggested as a correction
+pub const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Levenshtein edit distance between two strings, counted in characters
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut prev_row: Vec<usize> = (0..=b_chars.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut row = vec![i + 1; b_chars.len() + 1];
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let substitution = prev_row[j] + usize::from(a_char != *b_char);
+            row[j + 1] = substitution.min(prev_row[j + 1] + 1).min(row[j] + 1);
+        }
+        prev_row = row;
+    }
+    prev_row[b_chars.len()]
+}
+
+/// Find the candidate closest to `name` (case-insensitive)
+/// Returns None if nothing is within MAX_SUGGESTION_DISTANCE or the name matches exactly
+pub fn closest_match<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a String>,
+) -> Option<&'a str> {
+    let lowercase_name = name.to_lowercase();
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.as_str() != name)
+        .map(|candidate| {
+            (
+                levenshtein(&lowercase_name, &candidate.to_lowercase()),
+                candidate,
+            )
+        })
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .min_by(|(a, a_name), (b, b_name)| a.cmp(b).then_with(|| a_name.cmp(b_name)))
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+/// Build " Did you mean `Food`?" note, or an empty string when there is no close match
+pub fn did_you_mean<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a String>,
+) -> MarkdownString {
+    match closest_match(name, candidates) {
+        Some(suggestion) => markdown_format!(" Did you mean `{}`?", suggestion),
+        None => MarkdownString::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("Food", "Food"), 0);
+        assert_eq!(levenshtein("Fod", "Food"), 1);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("еда", "Еда"), 1);
+        assert_eq!(levenshtein("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_closest_match_suggests_close_names() {
+        let categories = names(&["Food", "Transport", "Fun"]);
+        assert_eq!(closest_match("Fod", &categories), Some("Food"));
+        assert_eq!(closest_match("food", &categories), Some("Food"));
+        assert_eq!(closest_match("Transprot", &categories), Some("Transport"));
+    }
+
+    #[test]
+    fn test_closest_match_no_suggestion() {
+        let categories = names(&["Food", "Transport"]);
+        assert_eq!(closest_match("Groceries", &categories), None);
+        assert_eq!(closest_match("Fod", &names(&[])), None);
+        assert_eq!(closest_match("Food", &categories), None);
+        assert!(did_you_mean("Groceries", &categories).is_empty());
+        assert_eq!(
+            did_you_mean("Fod", &categories).as_str(),
+            " Did you mean `Food`?"
+        );
+    }
+}
@@ -1,5 +1,14 @@
+pub mod amount;
+pub mod expense_template;
 pub mod extract_words;
+pub mod filter_pattern;
+pub mod format_amount;
+pub mod fuzzy_match;
+pub mod parse_csv;
 pub mod parse_expenses;
+pub mod rate_limiter;
+pub mod storage_schema;
+pub mod timezone;
 
 /// Format Unix timestamp to a human-readable date string
 pub fn format_timestamp(timestamp: i64) -> String {
@@ -0,0 +1,240 @@
+use chrono::NaiveDate;
+
+use crate::{
+    commands::command_add_expense::cut_description,
+    storages::Expense,
+    utils::{amount::Amount, parse_expenses::parse_account},
+};
+
+/// Expenses read from a `date,description,amount` CSV document
+#[derive(Debug, Default, PartialEq)]
+pub struct CsvImport {
+    pub expenses: Vec<Expense>,
+    /// Rows which are not expenses: (line number, reason)
+    pub skipped: Vec<(usize, String)>,
+    /// Number of descriptions cut to the maximum length
+    pub truncated: usize,
+}
+
+/// Split CSV text into records of fields, each with the line number it starts on
+/// Quoted fields may contain commas, line breaks and quotes doubled as `""`
+/// A record with broken quoting is returned as an error and parsing resumes on the next line
+fn split_csv_records(text: &str) -> Vec<(usize, Result<Vec<String>, String>)> {
+    let mut records = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    while chars.peek().is_some() {
+        let start_line = line;
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut error = None;
+        loop {
+            match chars.next() {
+                None => {
+                    if quoted {
+                        error = Some("unterminated quoted field".to_string());
+                    }
+                    break;
+                }
+                Some('"') if quoted => {
+                    if chars.peek() == Some(&'"') {
+                        chars.next();
+                        field.push('"');
+                    } else {
+                        quoted = false;
+                        if !matches!(chars.peek(), None | Some(',' | '\r' | '\n')) {
+                            error = Some("text after a closing quote".to_string());
+                        }
+                    }
+                }
+                Some('"') if field.trim().is_empty() => {
+                    field.clear();
+                    quoted = true;
+                }
+                Some('"') => error = Some("quote inside an unquoted field".to_string()),
+                Some(',') if !quoted => fields.push(std::mem::take(&mut field)),
+                Some('\n') if !quoted => {
+                    line += 1;
+                    break;
+                }
+                Some('\r') if !quoted && chars.peek() == Some(&'\n') => {}
+                Some(c) => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            if error.is_some() {
+                // Drop the rest of the line, the next record starts after it
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                        break;
+                    }
+                }
+                break;
+            }
+        }
+        fields.push(field);
+        if error.is_none() && fields.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        records.push((start_line, error.map_or(Ok(fields), Err)));
+    }
+    records
+}
+
+/// Read expenses from CSV rows of `date,description,amount`, dates as `yyyy-mm-dd`
+/// A first row naming the columns is skipped as a header, malformed rows are reported
+/// Whitespace runs in descriptions, line breaks included, become single spaces like in
+/// expenses typed as text, and descriptions longer than `max_description_length` are cut
+pub fn parse_csv_expenses(text: &str, max_description_length: Option<usize>) -> CsvImport {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut import = CsvImport::default();
+    for (index, (line, record)) in split_csv_records(text).into_iter().enumerate() {
+        let fields = match record {
+            Ok(fields) => fields,
+            Err(reason) => {
+                import.skipped.push((line, reason));
+                continue;
+            }
+        };
+        let [date, description, amount] = fields.as_slice() else {
+            import
+                .skipped
+                .push((line, format!("expected 3 fields, found {}", fields.len())));
+            continue;
+        };
+        let (date, description, amount) = (date.trim(), description.trim(), amount.trim());
+        if index == 0 && date.eq_ignore_ascii_case("date") {
+            continue;
+        }
+        let Ok(date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+            import
+                .skipped
+                .push((line, format!("invalid date `{}`", date)));
+            continue;
+        };
+        if description.is_empty() {
+            import.skipped.push((line, "empty description".to_string()));
+            continue;
+        }
+        let Some(amount) = amount.parse::<f64>().ok().filter(|a| a.is_finite()) else {
+            import
+                .skipped
+                .push((line, format!("invalid amount `{}`", amount)));
+            continue;
+        };
+        let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+        let account = parse_account(&description);
+        let description = match max_description_length
+            .and_then(|max_length| cut_description(&description, max_length))
+        {
+            Some(truncated) => {
+                import.truncated += 1;
+                truncated
+            }
+            None => description,
+        };
+        import.expenses.push(Expense {
+            timestamp: date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp(),
+            description,
+            amount: Amount::from(amount),
+            author: None,
+            account,
+            currency: None,
+        });
+    }
+    import
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_expenses() {
+        let text = "\u{feff}Date,Description,Amount\r\n\
+            2024-01-05,Coffee,3.50\r\n\
+            2024-01-06,\"Lunch, with \"\"friends\"\"\",25\r\n\
+            \r\n\
+            2024-01-07,\"Two\nlines\",1\n\
+            2024-01-08,Taxi\n\
+            05.01.2024,Bus,2\n\
+            2024-01-09,Tea,free\n\
+            2024-01-10,Bad \"quote\",1\n\
+            2024-01-11,Cake,4\n";
+        let import = parse_csv_expenses(text, None);
+
+        let rows: Vec<(String, f64)> = import
+            .expenses
+            .iter()
+            .map(|e| (e.description.clone(), e.amount.as_f64()))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("Coffee".to_string(), 3.5),
+                ("Lunch, with \"friends\"".to_string(), 25.0),
+                // The line break inside the quoted field becomes a space
+                ("Two lines".to_string(), 1.0),
+                ("Cake".to_string(), 4.0),
+            ]
+        );
+        assert_eq!(
+            import.expenses[0].timestamp,
+            NaiveDate::from_ymd_opt(2024, 1, 5)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+                .and_utc()
+                .timestamp()
+        );
+        // Line numbers count the line break inside the quoted field
+        assert_eq!(
+            import.skipped,
+            vec![
+                (7, "expected 3 fields, found 2".to_string()),
+                (8, "invalid date `05.01.2024`".to_string()),
+                (9, "invalid amount `free`".to_string()),
+                (10, "quote inside an unquoted field".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_csv_without_header() {
+        let import = parse_csv_expenses("2024-01-05,Coffee,3.50\n2024-01-06,\"Tea", None);
+        assert_eq!(import.expenses.len(), 1);
+        assert_eq!(
+            import.skipped,
+            vec![(2, "unterminated quoted field".to_string())]
+        );
+        // A header anywhere but the first row is a malformed row
+        let import = parse_csv_expenses("2024-01-05,Coffee,3.50\ndate,description,amount", None);
+        assert_eq!(import.skipped.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_csv_normalizes_and_cuts_descriptions() {
+        let text = "2024-01-05,\"Coffee  with\r\n\tmilk\",3\n\
+            2024-01-06,Lunch at the office canteen,10\n\
+            2024-01-07,Tea,1\n";
+
+        let import = parse_csv_expenses(text, Some(10));
+        let descriptions: Vec<&str> = import
+            .expenses
+            .iter()
+            .map(|e| e.description.as_str())
+            .collect();
+        assert_eq!(descriptions, vec!["Coffee wi…", "Lunch at …", "Tea"]);
+        assert_eq!(import.truncated, 2);
+
+        // Without a limit only the whitespace is normalized
+        let import = parse_csv_expenses(text, None);
+        assert_eq!(import.expenses[0].description, "Coffee with milk");
+        assert_eq!(import.truncated, 0);
+    }
+}
@@ -1,25 +1,51 @@
-use chrono::{NaiveDate, TimeZone, Utc};
+use chrono::{NaiveDate, NaiveTime, TimeZone, Utc};
 use teloxide::utils::command::BotCommands;
 
 use crate::commands::{Command, command_add_expense::CommandAddExpense};
 
+/// Lines starting with this prefix are comments and are skipped
+pub const COMMENT_PREFIX: &str = "#";
+
+/// A line with only this marker makes all following lines of the message expenses,
+/// even the ones starting with `/`
+pub const IMPORT_RAW_MARKER: &str = "/import_raw";
+
+/// Decide whether a message line is a command rather than an expense
+/// In raw import mode no line is a command
+pub fn is_command_line(line: &str, raw_import: bool) -> bool {
+    !raw_import && line.starts_with('/')
+}
+
 /// Parse expense lines and commands from a message text
 /// Returns a vector of Results containing either successfully parsed Commands or error messages
 /// where text lines matching expense patterns are converted to Command::AddExpense variants
+/// Each result comes with the 1-based number of its line in `text`, to trace it back to the message
 ///
+/// Empty lines and comment lines starting with `#` are skipped
+/// Lines after an `/import_raw` line are all parsed as expenses
 /// If bot_name is provided, lines starting with the bot name will have it stripped
-/// timestamp is the Unix timestamp of the message date
+/// timestamp is the Unix timestamp of the message date in the chat's time zone,
+/// see `UtcOffset::local_timestamp`
+/// Expenses without explicit time of day are stored at midnight, or with
+/// `date_only_message_time` at the time of day of the message
+/// With `strict` a line without description or amount is an error instead of an incomplete expense
 pub fn parse_expenses(
     text: &str,
     bot_name: Option<&str>,
     timestamp: i64,
-) -> Vec<Result<Command, String>> {
+    date_only_message_time: bool,
+    strict: bool,
+) -> Vec<(usize, Result<Command, String>)> {
     let mut commands = Vec::new();
-    let message_date = Utc.timestamp_opt(timestamp, 0).unwrap().date_naive();
+    let message_datetime = Utc.timestamp_opt(timestamp, 0).unwrap();
+    let message_date = message_datetime.date_naive();
+    let default_time = date_only_message_time.then(|| message_datetime.time());
+    let mut raw_import = false;
 
-    for line in text.lines() {
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
         let mut line = line.trim();
-        if line.is_empty() {
+        if line.is_empty() || line.starts_with(COMMENT_PREFIX) {
             continue;
         }
 
@@ -38,28 +64,39 @@ pub fn parse_expenses(
             }
         }
 
-        // Remove bot name prefix if present (case-insensitive)
+        // Remove bot name mentions if present (case-insensitive)
+        // A `/command@botname` suffix is left to `Command::parse`, which checks the name
         if let Some(name) = bot_name {
-            let bot_name_lower = name.to_lowercase();
-            let line_lower = line.to_lowercase();
-
-            // Try to match @botname or botname at the start
-            if line_lower.starts_with(&format!("@{}", bot_name_lower)) {
-                line = line[name.len() + 1..].trim_start();
-            } else if line_lower.starts_with(&bot_name_lower) {
-                line = line[name.len()..].trim_start();
-            }
+            line = strip_bot_name(line, name);
         }
 
-        if !line.starts_with('/') {
+        if !raw_import && line == IMPORT_RAW_MARKER {
+            raw_import = true;
+            continue;
+        }
+
+        if !is_command_line(line, raw_import) {
             // Convert non-command lines to CommandAddExpense with explicit date
             // Check if line already starts with a date (YYYY-MM-DD format)
-            let parts: Vec<&str> = line.split_whitespace().collect();
+            let mut parts: Vec<&str> = line.split_whitespace().collect();
+            // A date elsewhere in the line is the expense date too: "Dinner on 2024-10-05 25.00"
+            // It's moved to the front, so that it's not part of the description,
+            // together with a time right after it: "Dinner on 2024-10-05 14:30 25.00"
+            if let Some(date_idx) = parts.iter().position(|word| is_date(word))
+                && date_idx > 0
+            {
+                let date_end = match parts.get(date_idx + 1) {
+                    Some(word) if looks_like_time(word) => date_idx + 2,
+                    _ => date_idx + 1,
+                };
+                let date_words: Vec<&str> = parts.drain(date_idx..date_end).collect();
+                parts.splice(0..0, date_words);
+            }
             let parsed_date = parts
                 .first()
                 .and_then(|first_word| NaiveDate::parse_from_str(first_word, "%Y-%m-%d").ok());
 
-            let (date, description_start_idx) = if let Some(explicit_date) = parsed_date {
+            let (date, mut description_start_idx) = if let Some(explicit_date) = parsed_date {
                 // Line has explicit date: "YYYY-MM-DD description amount"
                 (explicit_date, 1)
             } else {
@@ -67,30 +104,105 @@ pub fn parse_expenses(
                 (message_date, 0)
             };
 
+            // Optional time of day after the date: "YYYY-MM-DD HH:MM description amount"
+            let mut time = default_time;
+            if let Some(time_word) = parts.get(1).filter(|_| parsed_date.is_some())
+                && looks_like_time(time_word)
+            {
+                match NaiveTime::parse_from_str(time_word, "%H:%M")
+                    .or_else(|_| NaiveTime::parse_from_str(time_word, "%H:%M:%S"))
+                {
+                    Ok(explicit_time) => {
+                        time = Some(explicit_time);
+                        description_start_idx = 2;
+                    }
+                    Err(_) => {
+                        commands.push((
+                            line_number,
+                            Err(format!(
+                                "❌ Invalid time `{}` in line `{}`",
+                                time_word, line
+                            )),
+                        ));
+                        continue;
+                    }
+                }
+            }
+
             // Extract amount and description
-            let amount = parts.last().and_then(|s| s.parse::<f64>().ok());
-            let description_parts = &parts[description_start_idx..parts.len() - 1];
+            // A currency code may follow the amount as a separate word: "Lunch 12.50 USD"
+            let code_amount = match parts.len().checked_sub(2) {
+                Some(idx) if idx >= description_start_idx && is_currency_code(parts[idx + 1]) => {
+                    parse_number(parts[idx])
+                        .map(|amount| (idx, amount, Some(parts[idx + 1].to_string())))
+                }
+                _ => None,
+            };
+            let (amount_idx, mut amount, currency) = match code_amount {
+                Some((idx, amount, currency)) => (idx, Some(amount), currency),
+                None => {
+                    let parsed = parts.last().and_then(|s| parse_amount_with_currency(s));
+                    (
+                        parts.len().saturating_sub(1),
+                        parsed.as_ref().map(|(amount, _)| *amount),
+                        parsed.and_then(|(_, currency)| currency),
+                    )
+                }
+            };
+            // Quantity right before the amount multiplies it: "Coffee x3 5.00"
+            // The quantity stays in the description
+            if amount_idx > description_start_idx
+                && let Some(quantity) = parse_quantity(parts[amount_idx - 1])
+            {
+                amount = amount.map(|amount| amount * quantity as f64);
+            }
+            if amount.is_some_and(|amount| !amount.is_finite()) {
+                commands.push((
+                    line_number,
+                    Err(format!("❌ Amount is too large in line `{}`", line)),
+                ));
+                continue;
+            }
+            let description_end_idx = amount_idx.max(description_start_idx);
+            let description_parts = &parts[description_start_idx..description_end_idx];
             let description = if description_parts.is_empty() {
                 None
             } else {
                 Some(description_parts.join(" "))
             };
+            if strict && (description.is_none() || amount.is_none()) {
+                commands.push((
+                    line_number,
+                    Err(format!(
+                        "❌ Unrecognized line `{}`, expected `[<yyyy-mm-dd>] <description> <amount>`",
+                        line
+                    )),
+                ));
+                continue;
+            }
 
             // Create command object and push directly
             let cmd = CommandAddExpense {
                 date: Some(date),
                 description,
                 amount,
+                time,
+                author: None,
+                currency,
+                truncated_from: None,
             };
-            commands.push(Ok(Command::AddExpense(cmd)));
+            commands.push((line_number, Ok(Command::AddExpense(cmd))));
         } else {
             // Parse command lines
             match Command::parse(line, bot_name.unwrap_or("")) {
                 Ok(cmd) => {
-                    commands.push(Ok(cmd));
+                    commands.push((line_number, Ok(cmd)));
                 }
                 Err(e) => {
-                    commands.push(Err(format!("❌ Failed to parse command `{}`: {}", line, e)));
+                    commands.push((
+                        line_number,
+                        Err(format!("❌ Failed to parse command `{}`: {}", line, e)),
+                    ));
                 }
             }
         }
@@ -99,13 +211,204 @@ pub fn parse_expenses(
     commands
 }
 
+/// Join each line with only an amount to the line before it, if that line has no amount:
+/// "Coffee\n5.50" becomes "Coffee 5.50"
+/// Used before `parse_expenses` in chats which enabled `/pair_amount_lines`, for forwarded
+/// lists with descriptions and amounts on separate lines. Other lines are kept as they are
+/// The joined amount line is left empty, so that the following lines keep their numbers
+pub fn pair_amount_lines(text: &str) -> String {
+    let is_amount_only = |line: &str| match line.split_whitespace().collect::<Vec<_>>()[..] {
+        [amount] => parse_amount_with_currency(amount).is_some(),
+        [amount, code] => parse_number(amount).is_some() && is_currency_code(code),
+        _ => false,
+    };
+    let lacks_amount = |line: &str| {
+        !line.starts_with(COMMENT_PREFIX)
+            && !line.starts_with('/')
+            && line
+                .split_whitespace()
+                .next_back()
+                .is_some_and(|last| parse_amount_with_currency(last).is_none())
+    };
+
+    let mut lines: Vec<String> = Vec::new();
+    // Whether the last kept line waits for its amount
+    let mut waiting = false;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if waiting && is_amount_only(trimmed) {
+            if let Some(description) = lines.last_mut() {
+                *description = format!("{} {}", description.trim_end(), trimmed);
+            }
+            lines.push(String::new());
+            waiting = false;
+        } else {
+            waiting = !is_amount_only(trimmed) && lacks_amount(trimmed);
+            lines.push(line.to_string());
+        }
+    }
+    lines.join("\n")
+}
+
+/// Cut descriptions of parsed expenses longer than `max_length` characters
+/// Such a description is usually a paste mistake which would break report tables
+/// An /import gets the limit for the expenses of its document
+pub fn truncate_descriptions(
+    results: Vec<Result<Command, String>>,
+    max_length: usize,
+) -> Vec<Result<Command, String>> {
+    results
+        .into_iter()
+        .map(|result| match result {
+            Ok(Command::AddExpense(mut cmd)) => {
+                if cmd.truncate_description(max_length) {
+                    log::warn!(
+                        "Expense description of {} characters cut to {}",
+                        cmd.truncated_from.unwrap_or_default(),
+                        max_length
+                    );
+                }
+                Ok(Command::AddExpense(cmd))
+            }
+            Ok(Command::Import(mut cmd)) => {
+                cmd.max_description_length = Some(max_length);
+                Ok(Command::Import(cmd))
+            }
+            other => other,
+        })
+        .collect()
+}
+
+/// Remove the bot's mentions from a line: a leading `@botname` or `botname` word and
+/// a trailing `@botname` word. Telegram usernames are ASCII and case-insensitive
+fn strip_bot_name<'a>(line: &'a str, bot_name: &str) -> &'a str {
+    let is_mention = |word: &str| {
+        word.strip_prefix('@')
+            .is_some_and(|w| w.eq_ignore_ascii_case(bot_name))
+    };
+    let mut line = line;
+    if let Some(first_word) = line.split_whitespace().next()
+        && (is_mention(first_word) || first_word.eq_ignore_ascii_case(bot_name))
+    {
+        line = line[first_word.len()..].trim_start();
+    }
+    if let Some(last_word) = line.split_whitespace().next_back()
+        && last_word.len() < line.len()
+        && is_mention(last_word)
+    {
+        line = line[..line.len() - last_word.len()].trim_end();
+    }
+    line
+}
+
+/// Currency symbols recognized when attached to an amount, with the ISO codes they stand for:
+/// `$12`, `5.50€`
+const CURRENCY_SYMBOLS: &[(char, &str)] = &[
+    ('$', "USD"),
+    ('€', "EUR"),
+    ('£', "GBP"),
+    ('¥', "JPY"),
+    ('₽', "RUB"),
+    ('₹', "INR"),
+    ('₴', "UAH"),
+    ('₸', "KZT"),
+];
+
+/// ISO 4217 codes recognized after an amount: "Lunch 12.50 USD"
+/// Other uppercase words, like "Bought 2 TVS", stay in the description
+const CURRENCY_CODES: &[&str] = &[
+    "AED", "ARS", "AUD", "BGN", "BRL", "BYN", "CAD", "CHF", "CLP", "CNY", "COP", "CZK", "DKK",
+    "EGP", "EUR", "GBP", "GEL", "HKD", "HUF", "IDR", "ILS", "INR", "ISK", "JPY", "KRW", "KZT",
+    "MDL", "MXN", "MYR", "NOK", "NZD", "PHP", "PLN", "RON", "RSD", "RUB", "SAR", "SEK", "SGD",
+    "THB", "TRY", "TWD", "UAH", "USD", "UZS", "VND", "ZAR",
+];
+
+/// Parse an amount token, optionally with a leading or trailing currency symbol
+/// The currency is returned as its ISO code
+fn parse_amount_with_currency(word: &str) -> Option<(f64, Option<String>)> {
+    if let Some(amount) = parse_number(word) {
+        return Some((amount, None));
+    }
+    CURRENCY_SYMBOLS.iter().find_map(|(symbol, code)| {
+        let number = word
+            .strip_prefix(*symbol)
+            .or_else(|| word.strip_suffix(*symbol))?;
+        Some((parse_number(number)?, Some(code.to_string())))
+    })
+}
+
+/// Parse a finite number, `inf` and `NaN` are words, not amounts
+fn parse_number(word: &str) -> Option<f64> {
+    word.parse::<f64>().ok().filter(|number| number.is_finite())
+}
+
+/// Check if the word is a known ISO currency code
+fn is_currency_code(word: &str) -> bool {
+    CURRENCY_CODES.contains(&word)
+}
+
+/// Payment account named by the first `@account` word of a description, in lowercase
+/// The word stays in the description, the account only groups expenses for `/report_account`
+pub fn parse_account(description: &str) -> Option<String> {
+    description.split_whitespace().find_map(|word| {
+        let name = word.strip_prefix('@')?;
+        (!name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-'))
+        .then(|| name.to_lowercase())
+    })
+}
+
+/// Parse a quantity multiplier token: `x3` or `X3`
+fn parse_quantity(word: &str) -> Option<u32> {
+    let digits = word.strip_prefix(['x', 'X'])?;
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// Check if the word is a `YYYY-MM-DD` date
+fn is_date(word: &str) -> bool {
+    NaiveDate::parse_from_str(word, "%Y-%m-%d").is_ok()
+}
+
+/// Check if the word has the shape of a time of day: H:MM, HH:MM or HH:MM:SS
+fn looks_like_time(word: &str) -> bool {
+    let parts: Vec<&str> = word.split(':').collect();
+    (2..=3).contains(&parts.len())
+        && parts
+            .iter()
+            .all(|part| (1..=2).contains(&part.len()) && part.chars().all(|c| c.is_ascii_digit()))
+}
+
 #[cfg(test)]
 mod tests {
-    use chrono::NaiveDate;
+    use chrono::{NaiveDate, NaiveTime};
+    use yoroolbot::command_trait::CommandTrait;
+
+    /// Parse results without their line numbers
+    fn parse_results(
+        text: &str,
+        bot_name: Option<&str>,
+        timestamp: i64,
+        date_only_message_time: bool,
+        strict: bool,
+    ) -> Vec<Result<Command, String>> {
+        parse_expenses(text, bot_name, timestamp, date_only_message_time, strict)
+            .into_iter()
+            .map(|(_, result)| result)
+            .collect()
+    }
 
     use super::*;
-    use crate::commands::{
-        command_add_category::CommandAddCategory, command_add_filter::CommandAddFilter,
+    use crate::{
+        commands::{
+            command_add_category::CommandAddCategory, command_add_filter::CommandAddFilter,
+            command_report::CommandReport,
+        },
+        utils::timezone::UtcOffset,
     };
 
     #[test]
@@ -113,7 +416,7 @@ mod tests {
         // Test parsing expenses with date prefix
         let text = "2024-10-05 Coffee 5.50\n2024-10-06 Lunch 12.00";
         let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC (message timestamp)
-        let results = parse_expenses(text, None, timestamp);
+        let results = parse_results(text, None, timestamp, false, false);
 
         assert_eq!(results.len(), 2);
 
@@ -130,12 +433,108 @@ mod tests {
             && cmd.amount == Some(12.00)));
     }
 
+    #[test]
+    fn test_parse_expenses_with_date_and_time() {
+        let text = "2024-10-05 14:30 Coffee 5.50\n2024-10-05 9:05:30 Tea 3";
+        let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC (message timestamp)
+        let results = parse_results(text, None, timestamp, false, false);
+
+        assert_eq!(results.len(), 2);
+        assert!(matches!(&results[0], Ok(Command::AddExpense(cmd))
+            if cmd.date == Some(NaiveDate::from_ymd_opt(2024, 10, 5).unwrap())
+            && cmd.time == NaiveTime::from_hms_opt(14, 30, 0)
+            && cmd.description == Some("Coffee".to_string())
+            && cmd.amount == Some(5.50)));
+        assert!(matches!(&results[1], Ok(Command::AddExpense(cmd))
+            if cmd.time == NaiveTime::from_hms_opt(9, 5, 30)
+            && cmd.description == Some("Tea".to_string())));
+
+        // The time survives the command string round trip
+        let Ok(Command::AddExpense(cmd)) = &results[0] else {
+            panic!("Expected AddExpense");
+        };
+        let reparsed = Command::parse(&cmd.to_command_string(true), "").unwrap();
+        assert_eq!(reparsed, Command::AddExpense(cmd.clone()));
+    }
+
+    #[test]
+    fn test_parse_expenses_date_only_time() {
+        let text = "2024-10-05 Coffee 5.50\nTea 3";
+        let timestamp = 1609459200 + 8 * 3600 + 15 * 60; // 2021-01-01 08:15:00 UTC
+
+        // Midnight by default
+        let results = parse_results(text, None, timestamp, false, false);
+        assert!(matches!(&results[0], Ok(Command::AddExpense(cmd)) if cmd.time.is_none()));
+        assert!(matches!(&results[1], Ok(Command::AddExpense(cmd)) if cmd.time.is_none()));
+
+        // Message time of day when configured
+        let results = parse_results(text, None, timestamp, true, false);
+        for result in &results {
+            assert!(matches!(result, Ok(Command::AddExpense(cmd))
+                if cmd.time == NaiveTime::from_hms_opt(8, 15, 0)));
+        }
+    }
+
+    #[test]
+    fn test_parse_expenses_local_date_near_midnight() {
+        let timestamp = 1609459200 - 30 * 60; // 2020-12-31 23:30:00 UTC
+        let date = |offset: &str| {
+            let offset: UtcOffset = offset.parse().unwrap();
+            match &parse_results(
+                "Tea 3",
+                None,
+                offset.local_timestamp(timestamp),
+                true,
+                false,
+            )[0]
+            {
+                Ok(Command::AddExpense(cmd)) => (cmd.date.unwrap(), cmd.time.unwrap()),
+                other => panic!("Unexpected {:?}", other),
+            }
+        };
+        // Already the next day east of UTC, still the same day west of it
+        assert_eq!(
+            date("+03:00"),
+            (
+                NaiveDate::from_ymd_opt(2021, 1, 1).unwrap(),
+                NaiveTime::from_hms_opt(2, 30, 0).unwrap()
+            )
+        );
+        assert_eq!(
+            date("UTC").0,
+            NaiveDate::from_ymd_opt(2020, 12, 31).unwrap()
+        );
+        assert_eq!(
+            date("-05:00"),
+            (
+                NaiveDate::from_ymd_opt(2020, 12, 31).unwrap(),
+                NaiveTime::from_hms_opt(18, 30, 0).unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_expenses_invalid_time() {
+        let text = "2024-10-05 25:00 Coffee 5.50\n2024-10-05 12:61 Tea 3\n2024-10-05 10:00\nCoffee 12:30 4";
+        let results = parse_results(text, None, 1609459200, false, false);
+
+        assert_eq!(results.len(), 4);
+        assert!(matches!(&results[0], Err(msg) if msg.contains("25:00")));
+        assert!(matches!(&results[1], Err(msg) if msg.contains("12:61")));
+        // Date and time without description and amount doesn't panic
+        assert!(matches!(&results[2], Ok(Command::AddExpense(cmd))
+            if cmd.description.is_none() && cmd.amount.is_none()));
+        // Time-like words are only treated as time right after the date
+        assert!(matches!(&results[3], Ok(Command::AddExpense(cmd))
+            if cmd.description == Some("Coffee 12:30".to_string()) && cmd.time.is_none()));
+    }
+
     #[test]
     fn test_parse_expenses_with_different_date_formats() {
         // Test YYYY-MM-DD date format
         let text = "2024-10-05 Coffee 5.50\n2024-10-06 Tea 3.00";
         let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC (message timestamp)
-        let results = parse_expenses(text, None, timestamp);
+        let results = parse_results(text, None, timestamp, false, false);
 
         assert_eq!(results.len(), 2);
 
@@ -157,7 +556,7 @@ mod tests {
         // Test parsing expenses without date (should use message timestamp)
         let text = "Coffee 5.50\nLunch 12.00";
         let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC
-        let results = parse_expenses(text, None, timestamp);
+        let results = parse_results(text, None, timestamp, false, false);
 
         assert_eq!(results.len(), 2);
 
@@ -179,7 +578,7 @@ mod tests {
         // Test mixing expenses with and without dates
         let text = "2024-10-05 Coffee 5.50\nLunch 12.00\n2024-10-06 Dinner 15.00";
         let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC (message timestamp)
-        let results = parse_expenses(text, None, timestamp);
+        let results = parse_results(text, None, timestamp, false, false);
 
         assert_eq!(results.len(), 3);
 
@@ -207,7 +606,7 @@ mod tests {
         // Test removing bot name prefix
         let text = "@testbot Coffee 5.50\ntestbot Lunch 12.00\nBus ticket 2.75";
         let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC
-        let results = parse_expenses(text, Some("testbot"), timestamp);
+        let results = parse_results(text, Some("testbot"), timestamp, false, false);
 
         assert_eq!(results.len(), 3);
 
@@ -233,7 +632,7 @@ mod tests {
         // Test that lines starting with '/' are collected as commands
         let text = "/help\nCoffee 5.50\n/report\nLunch 12.00";
         let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC
-        let results = parse_expenses(text, None, timestamp);
+        let results = parse_results(text, None, timestamp, false, false);
 
         assert_eq!(results.len(), 4);
 
@@ -261,7 +660,7 @@ mod tests {
         // Test mixed input with bot name and commands
         let text = "@mybot Coffee 5.50\n/help\nmybot Lunch 12.00\nBus ticket 2.75\n/report";
         let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC
-        let results = parse_expenses(text, Some("mybot"), timestamp);
+        let results = parse_results(text, Some("mybot"), timestamp, false, false);
 
         assert_eq!(results.len(), 5);
 
@@ -295,7 +694,7 @@ mod tests {
         // Test that bot name matching is case-insensitive
         let text = "@TESTBOT Coffee 5.50\nTestBot Lunch 12.00";
         let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC
-        let results = parse_expenses(text, Some("testbot"), timestamp);
+        let results = parse_results(text, Some("testbot"), timestamp, false, false);
 
         assert_eq!(results.len(), 2);
 
@@ -315,7 +714,7 @@ mod tests {
         // Test that commands work with bot name prefix
         let text = "@mybot /help\nmybot /report\n/clear_expenses";
         let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC
-        let results = parse_expenses(text, Some("mybot"), timestamp);
+        let results = parse_results(text, Some("mybot"), timestamp, false, false);
 
         assert_eq!(results.len(), 3);
 
@@ -324,26 +723,62 @@ mod tests {
         assert!(matches!(&results[2], Ok(Command::ClearExpenses(_))));
     }
 
+    #[test]
+    fn test_parse_commands_with_bot_name_suffix() {
+        let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC
+        let text = "/report@mybot\n/report@MyBot\nCoffee 5.50\n/help@MYBOT";
+        let results = parse_results(text, Some("mybot"), timestamp, false, false);
+        assert_eq!(results.len(), 4);
+        assert!(matches!(&results[0], Ok(Command::Report(_))));
+        assert!(matches!(&results[1], Ok(Command::Report(_))));
+        assert!(matches!(&results[2], Ok(Command::AddExpense(_))));
+        assert!(matches!(&results[3], Ok(Command::Help(_))));
+
+        // The bot's own name may differ in case from the mention
+        let results = parse_results("/report@mybot", Some("MyBot"), timestamp, false, false);
+        assert!(matches!(&results[0], Ok(Command::Report(_))));
+
+        // Commands addressed to another bot are rejected
+        let results = parse_results("/report@otherbot", Some("mybot"), timestamp, false, false);
+        assert!(matches!(&results[0], Err(e) if e.contains("otherbot")));
+    }
+
+    #[test]
+    fn test_parse_expenses_bot_name_whole_word() {
+        let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC
+        // Trailing mentions are removed, a word merely starting with the name is kept
+        let text = "/report @MyBot\nCoffee 5.50 @mybot\nmybottle 3.00\n@mybot";
+        let results = parse_results(text, Some("mybot"), timestamp, false, false);
+        assert_eq!(results.len(), 4);
+        assert!(matches!(&results[0], Ok(Command::Report(cmd)) if cmd.category.is_none()));
+        assert!(matches!(&results[1], Ok(Command::AddExpense(cmd))
+            if cmd.description == Some("Coffee".to_string()) && cmd.amount == Some(5.50)));
+        assert!(matches!(&results[2], Ok(Command::AddExpense(cmd))
+            if cmd.description == Some("mybottle".to_string()) && cmd.amount == Some(3.00)));
+        assert!(matches!(&results[3], Ok(Command::AddExpense(cmd))
+            if cmd.description.is_none() && cmd.amount.is_none()));
+    }
+
     #[test]
     fn test_parse_commands_from_keyboard_buttons() {
         // Test that commands are extracted from keyboard button text like "📋 /report"
         let text = "📋 /report";
         let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC
-        let results = parse_expenses(text, None, timestamp);
+        let results = parse_results(text, None, timestamp, false, false);
 
         assert_eq!(results.len(), 1);
         assert!(matches!(&results[0], Ok(Command::Report(_))));
 
         // Test multiple buttons
         let text2 = "🗑️ /clear_expenses";
-        let results2 = parse_expenses(text2, None, timestamp);
+        let results2 = parse_results(text2, None, timestamp, false, false);
 
         assert_eq!(results2.len(), 1);
         assert!(matches!(&results2[0], Ok(Command::ClearExpenses(_))));
 
         // Test with category command
         let text3 = "📂 /categories";
-        let results3 = parse_expenses(text3, None, timestamp);
+        let results3 = parse_results(text3, None, timestamp, false, false);
 
         assert_eq!(results3.len(), 1);
         assert!(matches!(&results3[0], Ok(Command::Categories(_))));
@@ -378,7 +813,7 @@ mod tests {
             /list\n\
         ";
         let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC
-        let results = parse_expenses(text, None, timestamp);
+        let results = parse_results(text, None, timestamp, false, false);
 
         // Check that all commands and expense were extracted (total 13)
         assert_eq!(results.len(), 13);
@@ -394,7 +829,7 @@ mod tests {
 
         // Commands with parameters (4 commands)
         assert!(
-            matches!(&results[7], Ok(Command::AddCategory(CommandAddCategory { name }))
+            matches!(&results[7], Ok(Command::AddCategory(CommandAddCategory { name, .. }))
             if name == &Some("Food".to_string()))
         );
 
@@ -422,4 +857,465 @@ mod tests {
         // Duplicate command without parameters to verify repeatability
         assert!(matches!(&results[12], Ok(Command::List(_))));
     }
+
+    #[test]
+    fn test_parse_report_with_min_amount() {
+        let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC
+        let text = "/report min 10\n/report min 2.5 Food 1\n/report Food";
+        let results = parse_results(text, None, timestamp, false, false);
+
+        assert_eq!(results.len(), 3);
+        assert!(
+            matches!(&results[0], Ok(Command::Report(CommandReport { category: None, page: None, min_amount, collapse: false }))
+            if *min_amount == Some(10.0))
+        );
+        assert!(
+            matches!(&results[1], Ok(Command::Report(CommandReport { category, page, min_amount, collapse: false }))
+            if category == &Some("Food".to_string())
+            && *page == Some(1)
+            && *min_amount == Some(2.5))
+        );
+        assert!(
+            matches!(&results[2], Ok(Command::Report(CommandReport { category, min_amount: None, .. }))
+            if category == &Some("Food".to_string()))
+        );
+
+        // Command string round-trips through the parser
+        let report = CommandReport {
+            category: Some("Food".to_string()),
+            page: Some(1),
+            min_amount: Some(2.5),
+            collapse: false,
+        };
+        let command_string = report.to_report_command_string(false);
+        assert_eq!(command_string, "/report min 2.5 Food 1");
+        let reparsed = parse_results(&command_string, None, timestamp, false, false);
+        assert!(matches!(&reparsed[0], Ok(Command::Report(parsed)) if parsed == &report));
+    }
+
+    #[test]
+    fn test_parse_report_collapse() {
+        let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC
+        let text = "/report collapse Food\n/report min 5 collapse Food 2";
+        let results = parse_results(text, None, timestamp, false, false);
+
+        assert_eq!(results.len(), 2);
+        assert!(
+            matches!(&results[0], Ok(Command::Report(CommandReport { category, min_amount: None, collapse: true, .. }))
+            if category == &Some("Food".to_string()))
+        );
+        let expected = CommandReport {
+            category: Some("Food".to_string()),
+            page: Some(2),
+            min_amount: Some(5.0),
+            collapse: true,
+        };
+        assert!(matches!(&results[1], Ok(Command::Report(parsed)) if parsed == &expected));
+
+        // Command string round-trips through the parser
+        let command_string = expected.to_report_command_string(false);
+        assert_eq!(command_string, "/report collapse min 5 Food 2");
+        let reparsed = parse_results(&command_string, None, timestamp, false, false);
+        assert!(matches!(&reparsed[0], Ok(Command::Report(parsed)) if parsed == &expected));
+    }
+
+    #[test]
+    fn test_parse_report_keyword_category() {
+        let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC
+        let report = |text: &str| match &parse_results(text, None, timestamp, false, false)[0] {
+            Ok(Command::Report(report)) => report.clone(),
+            other => panic!("Unexpected parse result {:?}", other),
+        };
+
+        // `--` ends the keywords, a category may be named like one of them
+        let expected = CommandReport {
+            category: Some("min".to_string()),
+            page: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(report("/report -- min 2"), expected);
+        assert_eq!(expected.to_report_command_string(false), "/report -- min 2");
+        let expected = CommandReport {
+            category: Some("collapse".to_string()),
+            min_amount: Some(5.0),
+            ..Default::default()
+        };
+        assert_eq!(report("/report min 5 -- collapse"), expected);
+        assert_eq!(
+            expected.to_report_command_string(false),
+            "/report min 5 -- collapse "
+        );
+
+        // Without `--` the words are keywords
+        assert_eq!(report("/report min 2").min_amount, Some(2.0));
+
+        // Non-finite thresholds are rejected
+        for text in [
+            "/report min NaN",
+            "/report min inf",
+            "/report min -inf Food",
+        ] {
+            assert!(
+                parse_results(text, None, timestamp, false, false)[0].is_err(),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_expenses_quantity_multiplier() {
+        let timestamp = 1609459200;
+        let amount_and_description =
+            |text: &str| match &parse_results(text, None, timestamp, false, false)[0] {
+                Ok(Command::AddExpense(cmd)) => {
+                    (cmd.description.clone().unwrap(), cmd.amount.unwrap())
+                }
+                other => panic!("Unexpected parse result {:?}", other),
+            };
+
+        assert_eq!(
+            amount_and_description("Coffee x3 5.00"),
+            ("Coffee x3".to_string(), 15.0)
+        );
+        assert_eq!(
+            amount_and_description("2024-01-01 Coffee X3 5.00"),
+            ("Coffee X3".to_string(), 15.0)
+        );
+        assert_eq!(
+            amount_and_description("Coffee 5.00"),
+            ("Coffee".to_string(), 5.0)
+        );
+        // Not a multiplier: not right before the amount, or not just digits after "x"
+        assert_eq!(
+            amount_and_description("x3 cable 5.00"),
+            ("x3 cable".to_string(), 5.0)
+        );
+        assert_eq!(
+            amount_and_description("Galaxy x3pro 5.00"),
+            ("Galaxy x3pro".to_string(), 5.0)
+        );
+    }
+
+    #[test]
+    fn test_parse_expenses_non_finite_amount() {
+        let timestamp = 1609459200;
+        let parse_one = |text: &str, strict: bool| {
+            parse_results(text, None, timestamp, false, strict)
+                .pop()
+                .unwrap()
+        };
+
+        // `inf` and `NaN` are not amounts, the line is an expense without one
+        for text in [
+            "Coffee inf",
+            "Tea NaN",
+            "Juice -inf $",
+            "Water inf€",
+            "Soda NaN USD",
+        ] {
+            match parse_one(text, false) {
+                Ok(Command::AddExpense(cmd)) => assert_eq!(cmd.amount, None, "{}", text),
+                other => panic!("Unexpected parse result {:?} for {}", other, text),
+            }
+            assert!(parse_one(text, true).is_err(), "{}", text);
+        }
+
+        // A quantity can't overflow the amount either
+        assert!(parse_one("Gold x1000 1e306", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_expenses_currency() {
+        let timestamp = 1609459200;
+        let parse_one = |text: &str| match &parse_results(text, None, timestamp, false, false)[0] {
+            Ok(Command::AddExpense(cmd)) => (
+                cmd.description.clone().unwrap(),
+                cmd.amount.unwrap(),
+                cmd.currency.clone(),
+            ),
+            other => panic!("Unexpected parse result {:?}", other),
+        };
+
+        assert_eq!(
+            parse_one("Lunch $12"),
+            ("Lunch".to_string(), 12.0, Some("USD".to_string()))
+        );
+        assert_eq!(
+            parse_one("Coffee 12€"),
+            ("Coffee".to_string(), 12.0, Some("EUR".to_string()))
+        );
+        assert_eq!(
+            parse_one("Dinner 12.50 USD"),
+            ("Dinner".to_string(), 12.5, Some("USD".to_string()))
+        );
+        assert_eq!(parse_one("Taxi 12.50"), ("Taxi".to_string(), 12.5, None));
+        assert_eq!(
+            parse_one("2024-01-01 Coffee x2 $3"),
+            ("Coffee x2".to_string(), 6.0, Some("USD".to_string()))
+        );
+        // Not an amount: a symbol alone or a code without a number before it
+        assert!(matches!(
+            &parse_results("Lunch $", None, timestamp, false, false)[0],
+            Ok(Command::AddExpense(CommandAddExpense { amount: None, .. }))
+        ));
+        assert_eq!(
+            parse_one("Trip to NYC 40"),
+            ("Trip to NYC".to_string(), 40.0, None)
+        );
+        // Only known codes are currencies, other uppercase words are the description
+        assert!(matches!(
+            &parse_results("Bought 2 TVS", None, timestamp, false, false)[0],
+            Ok(Command::AddExpense(CommandAddExpense {
+                amount: None,
+                currency: None,
+                ..
+            }))
+        ));
+        assert_eq!(
+            parse_one("Bought 2 TVS 900"),
+            ("Bought 2 TVS".to_string(), 900.0, None)
+        );
+    }
+
+    #[test]
+    fn test_is_command_line() {
+        assert!(is_command_line("/report", false));
+        assert!(!is_command_line("Coffee 5", false));
+        assert!(!is_command_line("/report", true));
+    }
+
+    #[test]
+    fn test_parse_expenses_strict() {
+        let timestamp = 1609459200;
+        let text = "Coffee 5\nno amount here\n2024-10-05 7\n/report";
+
+        // Lenient: unrecognized lines become incomplete expenses
+        let results = parse_results(text, None, timestamp, false, false);
+        assert_eq!(results.len(), 4);
+        assert!(matches!(&results[1], Ok(Command::AddExpense(cmd))
+            if cmd.description == Some("no amount".to_string()) && cmd.amount.is_none()));
+        assert!(matches!(&results[2], Ok(Command::AddExpense(cmd))
+            if cmd.description.is_none() && cmd.amount == Some(7.0)));
+
+        // Strict: they are errors, everything else is parsed as before
+        let results = parse_results(text, None, timestamp, false, true);
+        assert_eq!(results.len(), 4);
+        assert!(matches!(&results[0], Ok(Command::AddExpense(cmd))
+            if cmd.amount == Some(5.0)));
+        assert!(matches!(&results[1], Err(msg) if msg.contains("no amount here")));
+        assert!(matches!(&results[2], Err(msg) if msg.contains("2024-10-05 7")));
+        assert!(matches!(&results[3], Ok(Command::Report(_))));
+    }
+
+    #[test]
+    fn test_parse_expenses_import_raw() {
+        let timestamp = 1609459200;
+        let text = "/report\n/import_raw\n/dev/null subscription 5\n2024-10-05 /r/rust mug 12\n/import_raw 1";
+        let results = parse_results(text, None, timestamp, false, false);
+
+        assert_eq!(results.len(), 4);
+        // Lines before the marker are still commands
+        assert!(matches!(&results[0], Ok(Command::Report(_))));
+        assert!(matches!(&results[1], Ok(Command::AddExpense(cmd))
+            if cmd.description == Some("/dev/null subscription".to_string())
+            && cmd.amount == Some(5.0)));
+        assert!(matches!(&results[2], Ok(Command::AddExpense(cmd))
+            if cmd.date == Some(NaiveDate::from_ymd_opt(2024, 10, 5).unwrap())
+            && cmd.description == Some("/r/rust mug".to_string())
+            && cmd.amount == Some(12.0)));
+        // A repeated marker word is just a description
+        assert!(matches!(&results[3], Ok(Command::AddExpense(cmd))
+            if cmd.description == Some("/import_raw".to_string())));
+
+        // Without the marker such lines fail to parse as commands
+        let results = parse_results("/dev/null subscription 5", None, timestamp, false, false);
+        assert!(results[0].is_err());
+    }
+
+    #[test]
+    fn test_parse_expenses_date_anywhere() {
+        let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC
+        let parse_with_time =
+            |text: &str| match parse_results(text, None, timestamp, false, false).remove(0) {
+                Ok(Command::AddExpense(cmd)) => (cmd.date, cmd.time, cmd.description, cmd.amount),
+                other => panic!("Expected an expense from {:?}, got {:?}", text, other),
+            };
+        let parse = |text: &str| {
+            let (date, _, description, amount) = parse_with_time(text);
+            (date, description, amount)
+        };
+        let date = |day| NaiveDate::from_ymd_opt(2024, 10, day);
+
+        // Start
+        assert_eq!(
+            parse("2024-10-05 Dinner 25.00"),
+            (date(5), Some("Dinner".to_string()), Some(25.0))
+        );
+        // Middle
+        assert_eq!(
+            parse("Dinner on 2024-10-05 25.00"),
+            (date(5), Some("Dinner on".to_string()), Some(25.0))
+        );
+        // Middle, with a time following the date
+        assert_eq!(
+            parse_with_time("Dinner on 2024-10-05 14:30 25"),
+            (
+                date(5),
+                NaiveTime::from_hms_opt(14, 30, 0),
+                Some("Dinner on".to_string()),
+                Some(25.0)
+            )
+        );
+        // End, after the amount
+        assert_eq!(
+            parse("Dinner 25.00 2024-10-05"),
+            (date(5), Some("Dinner".to_string()), Some(25.0))
+        );
+        // The first date wins, later ones stay in the description
+        assert_eq!(
+            parse("Tickets 2024-10-06 for 2024-10-07 40"),
+            (
+                date(6),
+                Some("Tickets for 2024-10-07".to_string()),
+                Some(40.0)
+            )
+        );
+        // Absent: the message date
+        assert_eq!(
+            parse("Dinner 25.00"),
+            (
+                NaiveDate::from_ymd_opt(2021, 1, 1),
+                Some("Dinner".to_string()),
+                Some(25.0)
+            )
+        );
+        // Not a valid date, kept in the description
+        assert_eq!(
+            parse("Order 2024-13-45 10"),
+            (
+                NaiveDate::from_ymd_opt(2021, 1, 1),
+                Some("Order 2024-13-45".to_string()),
+                Some(10.0)
+            )
+        );
+    }
+
+    #[test]
+    fn test_pair_amount_lines() {
+        // Paired, also with a currency and a date
+        assert_eq!(
+            pair_amount_lines("Coffee\n5.50\n2024-10-05 Lunch\n12 USD\nTaxi\n€7"),
+            "Coffee 5.50\n\n2024-10-05 Lunch 12 USD\n\nTaxi €7\n"
+        );
+
+        // Unpaired: lines with amounts, a blank line in between, comments and commands
+        for text in [
+            "Coffee 5\n3",
+            "Coffee\n\n5",
+            "# Receipts\n5",
+            "/report\n5",
+            "Coffee\nTea",
+            "5\n6",
+        ] {
+            assert_eq!(pair_amount_lines(text), text);
+        }
+
+        // Interleaved: an amount pairs with one line only, the rest stays as is
+        assert_eq!(
+            pair_amount_lines("Coffee\n5\n7\nTea 3\nCake\nBread\n2\n/list"),
+            "Coffee 5\n\n7\nTea 3\nCake\nBread 2\n\n/list"
+        );
+
+        // The paired lines parse as complete expenses
+        let results = parse_results(
+            &pair_amount_lines("Coffee\n5.50\nTea\n3"),
+            None,
+            1609459200,
+            false,
+            true,
+        );
+        assert!(matches!(&results[..], [
+            Ok(Command::AddExpense(coffee)),
+            Ok(Command::AddExpense(tea)),
+        ] if coffee.description.as_deref() == Some("Coffee") && coffee.amount == Some(5.5)
+            && tea.description.as_deref() == Some("Tea") && tea.amount == Some(3.0)));
+    }
+
+    #[test]
+    fn test_parse_expenses_line_numbers() {
+        // Blank and comment lines are skipped but keep their numbers
+        let results = parse_expenses(
+            "Coffee 5\n\n# Receipts\nTea 3\n/unknown_command",
+            None,
+            1609459200,
+            false,
+            false,
+        );
+        let lines: Vec<usize> = results.iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, vec![1, 4, 5]);
+        assert!(results[2].1.is_err());
+
+        // Paired amount lines leave the numbers of the following lines unchanged
+        let text = "Coffee\n5\nTea 3";
+        let results = parse_expenses(&pair_amount_lines(text), None, 1609459200, false, false);
+        let lines: Vec<usize> = results.iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_parse_account() {
+        assert_eq!(parse_account("Lunch @Card"), Some("card".to_string()));
+        assert_eq!(parse_account("@cash Taxi"), Some("cash".to_string()));
+        assert_eq!(
+            parse_account("Rent @bank_1 @cash"),
+            Some("bank_1".to_string())
+        );
+        assert_eq!(parse_account("Coffee"), None);
+        assert_eq!(parse_account("Gift @ shop"), None);
+        assert_eq!(parse_account("Mail me@example.com"), None);
+        assert_eq!(parse_account("Dinner @home!"), None);
+
+        // The account word stays in the description
+        let results = parse_results("2024-10-05 Lunch @card 12.50", None, 0, false, false);
+        assert!(matches!(&results[0], Ok(Command::AddExpense(cmd))
+            if cmd.description.as_deref() == Some("Lunch @card")
+            && cmd.amount == Some(12.5)));
+    }
+
+    #[test]
+    fn test_truncate_descriptions() {
+        let timestamp = 1609459200;
+        let description_after = |description: String| {
+            let text = format!("{} 5", description);
+            let results =
+                truncate_descriptions(parse_results(&text, None, timestamp, false, false), 10);
+            match &results[0] {
+                Ok(Command::AddExpense(cmd)) => {
+                    (cmd.description.clone().unwrap(), cmd.truncated_from)
+                }
+                other => panic!("Unexpected parse result {:?}", other),
+            }
+        };
+
+        // Just under and at the limit are kept as is
+        assert_eq!(description_after("a".repeat(9)), ("a".repeat(9), None));
+        assert_eq!(description_after("a".repeat(10)), ("a".repeat(10), None));
+        // Well over the limit is cut to the limit with an ellipsis
+        let (description, truncated_from) = description_after("é".repeat(5000));
+        assert_eq!(description, format!("{}…", "é".repeat(9)));
+        assert_eq!(description.chars().count(), 10);
+        assert_eq!(truncated_from, Some(5000));
+        // Commands other than expenses are untouched
+        let results =
+            truncate_descriptions(parse_results("/report", None, timestamp, false, false), 1);
+        assert!(matches!(&results[0], Ok(Command::Report(_))));
+        // An import gets the limit for the expenses of its document
+        let results =
+            truncate_descriptions(parse_results("/import", None, timestamp, false, false), 10);
+        match &results[0] {
+            Ok(Command::Import(cmd)) => assert_eq!(cmd.max_description_length, Some(10)),
+            other => panic!("Unexpected parse result {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,232 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use teloxide::types::ChatId;
+
+/// Source of the current time, replaced in tests to control refill
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// Clock using the system monotonic time
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Time in which an empty bucket refills completely
+const REFILL_WINDOW: Duration = Duration::from_secs(60);
+
+/// Bucket state: available tokens, the time they were last refilled and the time
+/// the chat was last told about the limit
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    notified_at: Option<Instant>,
+}
+
+/// Buckets of the chats with the time idle ones were last dropped
+struct Buckets {
+    by_chat: HashMap<ChatId, Bucket>,
+    swept_at: Instant,
+}
+
+/// A command refused for exceeding the limit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimited {
+    /// Whether to tell the chat to slow down, true at most once per refill window
+    pub notify: bool,
+}
+
+/// Per-chat token bucket rate limiter
+/// Each chat may run up to `commands_per_minute` commands in a burst,
+/// tokens are refilled continuously at the same rate per minute
+/// Buckets idle for a whole refill window are full again, so they are dropped
+pub struct RateLimiter<C: Clock = SystemClock> {
+    commands_per_minute: Option<u32>,
+    clock: C,
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter<SystemClock> {
+    /// Create a rate limiter, `None` disables limiting
+    pub fn new(commands_per_minute: Option<u32>) -> Self {
+        Self::with_clock(commands_per_minute, SystemClock)
+    }
+}
+
+impl<C: Clock> RateLimiter<C> {
+    /// Create a rate limiter with a custom clock
+    pub fn with_clock(commands_per_minute: Option<u32>, clock: C) -> Self {
+        let swept_at = clock.now();
+        Self {
+            commands_per_minute,
+            clock,
+            buckets: Mutex::new(Buckets {
+                by_chat: HashMap::new(),
+                swept_at,
+            }),
+        }
+    }
+
+    /// Take a token for one command of the chat
+    /// Fails if the chat is over the limit and the command should be dropped
+    pub fn try_acquire(&self, chat_id: ChatId) -> Result<(), RateLimited> {
+        let Some(commands_per_minute) = self.commands_per_minute else {
+            return Ok(());
+        };
+        let capacity = commands_per_minute as f64;
+        let now = self.clock.now();
+        let mut buckets = self.buckets.lock().unwrap();
+        if now.saturating_duration_since(buckets.swept_at) >= REFILL_WINDOW {
+            buckets.by_chat.retain(|_, bucket| {
+                now.saturating_duration_since(bucket.refilled_at) < REFILL_WINDOW
+            });
+            buckets.swept_at = now;
+        }
+        let bucket = buckets.by_chat.entry(chat_id).or_insert(Bucket {
+            tokens: capacity,
+            refilled_at: now,
+            notified_at: None,
+        });
+
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        let refill = elapsed.as_secs_f64() * capacity / REFILL_WINDOW.as_secs_f64();
+        bucket.tokens = (bucket.tokens + refill).min(capacity);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        let notify = bucket
+            .notified_at
+            .is_none_or(|notified_at| now.saturating_duration_since(notified_at) >= REFILL_WINDOW);
+        if notify {
+            bucket.notified_at = Some(now);
+        }
+        Err(RateLimited { notify })
+    }
+
+    /// Number of chats with a bucket kept
+    #[cfg(test)]
+    fn bucket_count(&self) -> usize {
+        self.buckets.lock().unwrap().by_chat.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// Clock advanced manually by the test
+    #[derive(Clone)]
+    struct ManualClock(Arc<Mutex<Instant>>);
+
+    impl ManualClock {
+        fn new() -> Self {
+            Self(Arc::new(Mutex::new(Instant::now())))
+        }
+
+        fn advance(&self, duration: Duration) {
+            *self.0.lock().unwrap() += duration;
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> Instant {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    #[test]
+    fn test_rate_limiter_burst_and_refill() {
+        let clock = ManualClock::new();
+        let limiter = RateLimiter::with_clock(Some(3), clock.clone());
+        let chat_id = ChatId(1);
+
+        // Full bucket allows a burst up to the limit
+        assert!(limiter.try_acquire(chat_id).is_ok());
+        assert!(limiter.try_acquire(chat_id).is_ok());
+        assert!(limiter.try_acquire(chat_id).is_ok());
+        assert!(limiter.try_acquire(chat_id).is_err());
+
+        // 3 per minute: one token every 20 seconds
+        clock.advance(Duration::from_secs(19));
+        assert!(limiter.try_acquire(chat_id).is_err());
+        clock.advance(Duration::from_secs(1));
+        assert!(limiter.try_acquire(chat_id).is_ok());
+        assert!(limiter.try_acquire(chat_id).is_err());
+
+        // Long idle time refills only up to the capacity
+        clock.advance(Duration::from_secs(600));
+        for _ in 0..3 {
+            assert!(limiter.try_acquire(chat_id).is_ok());
+        }
+        assert!(limiter.try_acquire(chat_id).is_err());
+    }
+
+    #[test]
+    fn test_rate_limiter_per_chat_and_disabled() {
+        let limiter = RateLimiter::with_clock(Some(1), ManualClock::new());
+        assert!(limiter.try_acquire(ChatId(1)).is_ok());
+        assert!(limiter.try_acquire(ChatId(1)).is_err());
+        // Other chats have their own bucket
+        assert!(limiter.try_acquire(ChatId(2)).is_ok());
+
+        let unlimited = RateLimiter::with_clock(None, ManualClock::new());
+        for _ in 0..100 {
+            assert!(unlimited.try_acquire(ChatId(1)).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_rate_limiter_notifies_once_per_window() {
+        let clock = ManualClock::new();
+        let limiter = RateLimiter::with_clock(Some(1), clock.clone());
+        let chat_id = ChatId(1);
+        assert!(limiter.try_acquire(chat_id).is_ok());
+
+        // Only the first refused command of a window is answered
+        assert_eq!(
+            limiter.try_acquire(chat_id),
+            Err(RateLimited { notify: true })
+        );
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(
+            limiter.try_acquire(chat_id),
+            Err(RateLimited { notify: false })
+        );
+        clock.advance(Duration::from_secs(30));
+        assert!(limiter.try_acquire(chat_id).is_ok());
+        assert_eq!(
+            limiter.try_acquire(chat_id),
+            Err(RateLimited { notify: true })
+        );
+    }
+
+    #[test]
+    fn test_rate_limiter_drops_idle_buckets() {
+        let clock = ManualClock::new();
+        let limiter = RateLimiter::with_clock(Some(2), clock.clone());
+        for chat in 0..10 {
+            assert!(limiter.try_acquire(ChatId(chat)).is_ok());
+        }
+        assert_eq!(limiter.bucket_count(), 10);
+
+        // Chats idle for a whole window are full again and forgotten
+        clock.advance(Duration::from_secs(60));
+        assert!(limiter.try_acquire(ChatId(1)).is_ok());
+        assert_eq!(limiter.bucket_count(), 1);
+        assert!(limiter.try_acquire(ChatId(1)).is_ok());
+        assert!(limiter.try_acquire(ChatId(1)).is_err());
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use crate::{
+    storages::{CategoryData, Expense, SETTINGS_DATA_VERSION, SettingsData},
+    utils::amount::Amount,
+};
+
+/// Annotated examples of the storage formats, one YAML document per format
+/// Printed by `--dump-schema` for authors of external tooling
+pub fn format_storage_schema() -> String {
+    let categories = CategoryData::from_hashmap(HashMap::from([(
+        "Food".to_string(),
+        vec!["(?i)coffee".to_string(), "literal:lunch".to_string()],
+    )]))
+    .with_colors(HashMap::from([("Food".to_string(), "#FF8800".to_string())]));
+    let expenses = vec![
+        Expense {
+            timestamp: 1609459200,
+            description: "Coffee".to_string(),
+            amount: Amount::from(5.5),
+            author: None,
+            account: None,
+            currency: None,
+        },
+        Expense {
+            timestamp: 1609502400,
+            description: "Lunch @card".to_string(),
+            amount: Amount::from(12.0),
+            author: Some("Alice".to_string()),
+            account: Some("card".to_string()),
+            currency: Some("EUR".to_string()),
+        },
+    ];
+    let mut settings = SettingsData {
+        version: SETTINGS_DATA_VERSION,
+        ..Default::default()
+    };
+    settings.settings.decimal_comma = true;
+    settings.settings.utc_offset = "+03:00".parse().unwrap_or_default();
+    [
+        format!(
+            "# Categories of a chat, stored as <storage dir>/<chat id>.yaml\n\
+             # version: format version, older files are upgraded when loaded\n\
+             # Maps category names to filters: regexes, or text prefixed with `literal:`\n\
+             # colors: optional, `#RRGGBB` color of a category in image reports\n{}",
+            serde_yaml::to_string(&categories).unwrap_or_default()
+        ),
+        format!(
+            "# List of expenses\n\
+             # timestamp: Unix seconds, UTC\n\
+             # amount: in currency units, at most two decimals\n\
+             # author: optional, original author of a forwarded message\n\
+             # account: optional, payment account named by an `@account` word of the description\n\
+             # currency: optional, ISO code written next to the amount, the chat's usual one if missing\n{}",
+            serde_yaml::to_string(&expenses).unwrap_or_default()
+        ),
+        format!(
+            "# Settings of a chat, stored as <storage dir>/settings/<chat id>.yaml\n\
+             # version: format version\n\
+             # settings: the chat's settings, missing ones have their defaults\n{}",
+            serde_yaml::to_string(&settings).unwrap_or_default()
+        ),
+    ]
+    .join("---\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[test]
+    fn test_storage_schema_parses_back() {
+        let schema = format_storage_schema();
+        let mut documents = serde_yaml::Deserializer::from_str(&schema);
+
+        let categories = CategoryData::deserialize(documents.next().unwrap()).unwrap();
+        assert_eq!(
+            categories.categories["Food"],
+            vec!["(?i)coffee".to_string(), "literal:lunch".to_string()]
+        );
+
+        let expenses = Vec::<Expense>::deserialize(documents.next().unwrap()).unwrap();
+        assert_eq!(expenses.len(), 2);
+        assert_eq!(expenses[0].author, None);
+        assert_eq!(expenses[1].author.as_deref(), Some("Alice"));
+        assert_eq!(expenses[0].account, None);
+        assert_eq!(expenses[1].account.as_deref(), Some("card"));
+
+        let settings = SettingsData::deserialize(documents.next().unwrap()).unwrap();
+        assert!(settings.settings.decimal_comma);
+        assert_eq!(settings.settings.utc_offset.to_string(), "UTC+03:00");
+
+        assert!(documents.next().is_none());
+    }
+}
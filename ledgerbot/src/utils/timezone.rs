@@ -0,0 +1,146 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
+use teloxide::utils::command::ParseError;
+
+/// Largest offset from UTC in use, UTC+14:00
+const MAX_OFFSET_SECONDS: i32 = 14 * 3600;
+
+/// Fixed offset of a chat's time zone from UTC
+/// Expense timestamps hold the chat's local wall-clock time, so the offset is only
+/// applied to moments in time: the message date and the current time
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct UtcOffset {
+    seconds: i32,
+}
+
+impl UtcOffset {
+    /// Offset of the given number of seconds east of UTC, if it is within ±14 hours
+    pub fn from_seconds(seconds: i32) -> Option<Self> {
+        (-MAX_OFFSET_SECONDS..=MAX_OFFSET_SECONDS)
+            .contains(&seconds)
+            .then_some(UtcOffset { seconds })
+    }
+
+    /// Local wall-clock time of a moment, as a Unix timestamp like the stored expenses
+    pub fn local_timestamp(&self, timestamp: i64) -> i64 {
+        timestamp + self.seconds as i64
+    }
+}
+
+impl Display for UtcOffset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.seconds == 0 {
+            return write!(f, "UTC");
+        }
+        let sign = if self.seconds < 0 { '-' } else { '+' };
+        let minutes = self.seconds.abs() / 60;
+        write!(f, "UTC{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+    }
+}
+
+impl FromStr for UtcOffset {
+    type Err = ParseError;
+
+    /// Parse `UTC`, `GMT` or an offset with an optional `UTC`/`GMT` prefix:
+    /// `+3`, `-05`, `+03:00`, `+0530`, `UTC-4`, `GMT+05:45`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || {
+            ParseError::IncorrectFormat(
+                format!(
+                    "Unknown time zone `{}`, expected an offset like `+03:00` or `UTC-5`",
+                    s
+                )
+                .into(),
+            )
+        };
+        let offset = s
+            .get(..3)
+            .filter(|prefix| {
+                prefix.eq_ignore_ascii_case("UTC") || prefix.eq_ignore_ascii_case("GMT")
+            })
+            .map_or(s, |_| &s[3..]);
+        if offset.is_empty() && !s.is_empty() {
+            // Just `UTC` or `GMT`
+            return Ok(UtcOffset::default());
+        }
+        let (sign, digits) = if let Some(digits) = offset.strip_prefix('+') {
+            (1, digits)
+        } else if let Some(digits) = offset.strip_prefix('-') {
+            (-1, digits)
+        } else {
+            return Err(error());
+        };
+        let (hours, minutes) = match digits.split_once(':') {
+            Some((hours, minutes)) => (hours, minutes),
+            None if digits.len() == 4 => digits.split_at(2),
+            None => (digits, "0"),
+        };
+        let number = |part: &str| {
+            (!part.is_empty() && part.len() <= 2 && part.chars().all(|c| c.is_ascii_digit()))
+                .then(|| part.parse::<i32>().ok())
+                .flatten()
+        };
+        match (number(hours), number(minutes)) {
+            (Some(hours), Some(minutes)) if minutes < 60 => {
+                UtcOffset::from_seconds(sign * (hours * 3600 + minutes * 60)).ok_or_else(error)
+            }
+            _ => Err(error()),
+        }
+    }
+}
+
+/// Stored as shown to the user: `UTC+05:30`
+impl Serialize for UtcOffset {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for UtcOffset {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(|e: ParseError| D::Error::custom(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_utc_offset() {
+        let parse = |s: &str| s.parse::<UtcOffset>().map(|offset| offset.to_string());
+        assert_eq!(parse("UTC").unwrap(), "UTC");
+        assert_eq!(parse("gmt").unwrap(), "UTC");
+        assert_eq!(parse("+0").unwrap(), "UTC");
+        assert_eq!(parse("+3").unwrap(), "UTC+03:00");
+        assert_eq!(parse("-05").unwrap(), "UTC-05:00");
+        assert_eq!(parse("+05:30").unwrap(), "UTC+05:30");
+        assert_eq!(parse("+0545").unwrap(), "UTC+05:45");
+        assert_eq!(parse("UTC-4").unwrap(), "UTC-04:00");
+        assert_eq!(parse("GMT+14").unwrap(), "UTC+14:00");
+        for invalid in [
+            "",
+            "3",
+            "+15",
+            "-14:30",
+            "+3:60",
+            "+123",
+            "UTC+",
+            "Europe/Berlin",
+            "+3h",
+        ] {
+            assert!(parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_local_timestamp() {
+        let timestamp = 1609459200; // 2021-01-01 00:00:00 UTC
+        let offset: UtcOffset = "-05:30".parse().unwrap();
+        assert_eq!(offset.local_timestamp(timestamp), timestamp - 19800);
+        assert_eq!(UtcOffset::default().local_timestamp(timestamp), timestamp);
+    }
+}
@@ -1,11 +1,13 @@
 use std::{any::TypeId, error::Error, fmt::Display, str::FromStr, sync::Arc};
 
 use teloxide::{
-    Bot,
-    payloads::{EditMessageReplyMarkupSetters, SendMessage},
+    ApiError, Bot, RequestError,
+    payloads::{
+        AnswerCallbackQuery, AnswerCallbackQuerySetters, EditMessageReplyMarkupSetters, SendMessage,
+    },
     prelude::{Message, Requester, ResponseResult},
     requests::JsonRequest,
-    types::{Chat, MessageId},
+    types::{CallbackQueryId, Chat, MessageId},
     utils::command::ParseError,
 };
 
@@ -21,9 +23,48 @@ pub struct CommandReplyTarget {
     pub msg_id: Option<MessageId>,
     pub batch: bool,
     pub callback_data_storage: Arc<dyn CallbackDataStorageTrait>,
+    /// Id of the callback query when the command was invoked from an inline button
+    pub callback_query_id: Option<CallbackQueryId>,
 }
 
 impl CommandReplyTarget {
+    /// Target sending new messages to the chat, as for a command from a text message
+    pub fn new(
+        bot: Bot,
+        chat: Chat,
+        callback_data_storage: Arc<dyn CallbackDataStorageTrait>,
+    ) -> Self {
+        CommandReplyTarget {
+            bot,
+            chat,
+            msg_id: None,
+            batch: false,
+            callback_data_storage,
+            callback_query_id: None,
+        }
+    }
+
+    /// Reply by editing the given message instead of sending a new one
+    pub fn with_msg_id(mut self, msg_id: impl Into<Option<MessageId>>) -> Self {
+        self.msg_id = msg_id.into();
+        self
+    }
+
+    /// Mark the command as executed as a part of a batch
+    pub fn with_batch(mut self, batch: bool) -> Self {
+        self.batch = batch;
+        self
+    }
+
+    /// Answer the given callback query once the command is processed
+    pub fn with_callback_query(
+        mut self,
+        callback_query_id: impl Into<Option<CallbackQueryId>>,
+    ) -> Self {
+        self.callback_query_id = callback_query_id.into();
+        self
+    }
+
     /// Send a markdown message without a menu
     pub async fn markdown_message(&self, text: MarkdownString) -> ResponseResult<Message> {
         self.bot
@@ -103,9 +144,12 @@ impl CommandReplyTarget {
     {
         // Pack callback data and attach keyboard to the message
         let keyboard = pack_callback_data(callback_data_storage, chat_id, message_id.0, menu).await;
-        bot.edit_message_reply_markup(chat_id, message_id)
-            .reply_markup(keyboard)
-            .await?;
+        // Re-rendering a menu with the same buttons is not an error
+        ignore_not_modified(
+            bot.edit_message_reply_markup(chat_id, message_id)
+                .reply_markup(keyboard)
+                .await,
+        )?;
         Ok(())
     }
 
@@ -117,6 +161,89 @@ impl CommandReplyTarget {
         self.bot
             .edit_markdown_message_text(self.chat.id, message_id, text)
     }
+
+    /// Answer the callback query the command was invoked from, stopping the button spinner
+    /// The optional text is shown as a toast. Does nothing for text-message invocations
+    pub async fn answer_callback(&self, text: Option<MarkdownString>) -> ResponseResult<()> {
+        if let Some(request) = self.answer_callback_request(text) {
+            request.await?;
+        }
+        Ok(())
+    }
+
+    /// Build the answerCallbackQuery request, None if there is no callback query to answer
+    fn answer_callback_request(
+        &self,
+        text: Option<MarkdownString>,
+    ) -> Option<JsonRequest<AnswerCallbackQuery>> {
+        let callback_query_id = self.callback_query_id.clone()?;
+        let request = self.bot.answer_callback_query(callback_query_id);
+        Some(match text {
+            // Toasts are plain text, so drop the markdown escaping
+            Some(text) => request.text(unescape_markdown(text.as_str())),
+            None => request,
+        })
+    }
+}
+
+/// Callback data of inactive buttons, pressing them does nothing
+pub const NOOP_CALLBACK: &str = "noop";
+
+/// Callback data for an inactive button, optionally showing a toast when pressed
+pub fn noop_callback(toast: Option<&str>) -> String {
+    match toast {
+        Some(toast) => format!("{} {}", NOOP_CALLBACK, toast),
+        None => NOOP_CALLBACK.to_string(),
+    }
+}
+
+/// Parse callback data made by `noop_callback`
+/// Returns None if it is not an inactive button, Some(None) if the button is silent
+pub fn parse_noop_callback(data: &str) -> Option<Option<&str>> {
+    let rest = data.strip_prefix(NOOP_CALLBACK)?;
+    if rest.is_empty() {
+        return Some(None);
+    }
+    let toast = rest.strip_prefix(' ')?.trim();
+    Some((!toast.is_empty()).then_some(toast))
+}
+
+/// Whether Telegram refused an edit because it doesn't change the message
+/// teloxide only recognizes the exact wording, so other wordings of the same refusal match too
+pub fn is_message_not_modified(error: &RequestError) -> bool {
+    match error {
+        RequestError::Api(ApiError::MessageNotModified) => true,
+        RequestError::Api(ApiError::Unknown(description)) => {
+            description.starts_with("Bad Request: message is not modified")
+        }
+        _ => false,
+    }
+}
+
+/// Treat the "message is not modified" error of an edit request as success
+/// Returns None if the message was left as is
+pub fn ignore_not_modified<T>(result: ResponseResult<T>) -> ResponseResult<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(error) if is_message_not_modified(&error) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Remove MarkdownV2 escaping backslashes for contexts which show plain text
+fn unescape_markdown(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(next_c) = chars.next() {
+                result.push(next_c);
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
 }
 
 #[derive(Default, Debug, Clone, PartialEq)]
@@ -172,14 +299,37 @@ where
     Ok(parsed)
 }
 
-fn split_with_screened_spaces(arg: &str) -> Vec<String> {
+/// Split the first line of the arguments by spaces, honoring backslash-screened spaces
+pub fn split_with_screened_spaces(arg: &str) -> Vec<String> {
+    split_screened_words(arg, usize::MAX).0
+}
+
+/// Split the arguments like `split_with_screened_spaces`, but keep the text after the
+/// first `count - 1` arguments, following lines included, as the last argument verbatim
+pub fn split_with_multiline_tail(arg: &str, count: usize) -> Vec<String> {
+    if count == 0 {
+        return Vec::new();
+    }
+    let (mut args, tail) = split_screened_words(arg, count - 1);
+    let tail = tail.trim();
+    if !tail.is_empty() {
+        args.push(tail.to_string());
+    }
+    args
+}
+
+/// Take up to `max` screened words from the first line
+/// Returns the words and the text left after them
+fn split_screened_words(arg: &str, max: usize) -> (Vec<String>, &str) {
     let mut args = Vec::new();
     let mut current = String::new();
-    let mut chars = arg.lines().next().unwrap_or("").chars().peekable();
-    while let Some(c) = chars.next() {
+    let mut chars = arg.char_indices().peekable();
+    while args.len() < max
+        && let Some((pos, c)) = chars.next()
+    {
         match c {
             '\\' => {
-                if let Some(&next_c) = chars.peek() {
+                if let Some(&(_, next_c)) = chars.peek() {
                     if next_c == '\\' {
                         current.push('\\');
                         chars.next();
@@ -199,16 +349,24 @@ fn split_with_screened_spaces(arg: &str) -> Vec<String> {
                     current.clear();
                 }
             }
+            '\n' | '\r' => {
+                if !current.is_empty() {
+                    args.push(current);
+                }
+                return (args, &arg[pos..]);
+            }
             _ => current.push(c),
         }
     }
     if !current.is_empty() {
         args.push(current);
     }
-    args
+    let rest = chars.peek().map_or("", |&(pos, _)| &arg[pos..]);
+    (args, rest)
 }
 
-fn screen_spaces(s: &str) -> String {
+/// Screen spaces and backslashes so the value survives `split_with_screened_spaces`
+pub fn screen_spaces(s: &str) -> String {
     s.replace('\\', "\\\\").replace(' ', "\\ ")
 }
 
@@ -227,6 +385,9 @@ pub trait CommandTrait: Sized + Clone {
 
     const NAME: &'static str;
     const PLACEHOLDERS: &[&'static str];
+    /// The last argument captures the rest of the text, following lines included, verbatim
+    /// Meant for free text like notes, or a list of values like `/remove_filters` positions
+    const MULTILINE_LAST_ARG: bool = false;
 
     #[allow(clippy::get_first)]
     fn parse_arguments(args: String) -> Result<(Self,), ParseError> {
@@ -268,7 +429,11 @@ pub trait CommandTrait: Sized + Clone {
                 || TypeId::of::<Self::I>() == TypeId::of::<EmptyArg>()
         );
 
-        let args = split_with_screened_spaces(&args);
+        let args = if Self::MULTILINE_LAST_ARG {
+            split_with_multiline_tail(&args, Self::PLACEHOLDERS.len())
+        } else {
+            split_with_screened_spaces(&args)
+        };
         if args.len() > Self::PLACEHOLDERS.len() {
             return Err(ParseError::TooManyArguments {
                 expected: Self::PLACEHOLDERS.len(),
@@ -562,7 +727,11 @@ pub trait CommandTrait: Sized + Clone {
                 let part = params[i]
                     .clone()
                     .unwrap_or(Self::PLACEHOLDERS[i].to_string());
-                command_parts.push(screen_spaces(&part));
+                if Self::MULTILINE_LAST_ARG && i == Self::PLACEHOLDERS.len() - 1 {
+                    command_parts.push(part);
+                } else {
+                    command_parts.push(screen_spaces(&part));
+                }
             }
         }
         let mut command = command_parts.join(" ");
@@ -571,6 +740,12 @@ pub trait CommandTrait: Sized + Clone {
         }
         command
     }
+
+    /// Usage line for the command: given arguments are kept, missing ones shown as placeholders
+    /// Shown when a command is invoked with missing required arguments
+    fn usage_hint(&self) -> MarkdownString {
+        crate::markdown_format!("Usage: `{}`", self.to_command_string(true))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -603,3 +778,291 @@ impl CommandTrait for NoopCommand {
         Self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use teloxide::{
+        requests::HasPayload,
+        types::{ChatId, ChatKind, ChatPrivate},
+    };
+
+    use super::*;
+    use crate::{markdown_string, storage::CallbackDataStorage};
+
+    fn target(callback_query_id: Option<CallbackQueryId>) -> CommandReplyTarget {
+        CommandReplyTarget::new(
+            Bot::new("TOKEN"),
+            Chat {
+                id: ChatId(1),
+                kind: ChatKind::Private(ChatPrivate {
+                    username: None,
+                    first_name: None,
+                    last_name: None,
+                }),
+            },
+            Arc::new(CallbackDataStorage::new()),
+        )
+        .with_callback_query(callback_query_id)
+    }
+
+    /// Telegram reply refusing a request, for requests whose result is not checked
+    const BAD_REQUEST_REPLY: &str =
+        r#"{"ok":false,"error_code":400,"description":"Bad Request: test"}"#;
+
+    /// Serve a single Telegram API request with the given JSON reply
+    /// Returns the API url to point the bot to and the handle yielding the called method
+    fn serve_one_request(reply: &'static str) -> (url::Url, std::thread::JoinHandle<String>) {
+        use std::io::{BufRead, BufReader, Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = url::Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some(value) = header.to_ascii_lowercase().strip_prefix("content-length:") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                reply.len(),
+                reply
+            )
+            .unwrap();
+            // The request path is `/bot<token>/<method>`
+            let path = request_line.split_whitespace().nth(1).unwrap();
+            path.rsplit('/').next().unwrap().to_ascii_lowercase()
+        });
+        (url, server)
+    }
+
+    /// Bot sending its requests to the given API url
+    fn with_api_url(mut target: CommandReplyTarget, url: url::Url) -> CommandReplyTarget {
+        target.bot = Bot::new("TOKEN").set_api_url(url);
+        target
+    }
+
+    /// Telegram method called by `markdown_message` of the target
+    async fn markdown_message_method(target: &CommandReplyTarget) -> String {
+        let (url, server) = serve_one_request(BAD_REQUEST_REPLY);
+        let target = with_api_url(target.clone(), url);
+        assert!(
+            target
+                .markdown_message(markdown_string!("Hi"))
+                .await
+                .is_err()
+        );
+        server.join().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reply_target_builder() {
+        // Text message invocation: new messages, nothing to answer
+        let message_target = target(None);
+        assert_eq!(message_target.msg_id, None);
+        assert!(!message_target.batch);
+        assert!(message_target.answer_callback_request(None).is_none());
+        assert_eq!(
+            markdown_message_method(&message_target).await,
+            "sendmessage"
+        );
+
+        // Callback invocation: edits the menu message and answers the query
+        let callback_target = message_target
+            .clone()
+            .with_msg_id(MessageId(7))
+            .with_callback_query(CallbackQueryId("42".to_string()));
+        assert_eq!(callback_target.msg_id, Some(MessageId(7)));
+        assert!(callback_target.answer_callback_request(None).is_some());
+        assert_eq!(
+            markdown_message_method(&callback_target).await,
+            "editmessagetext"
+        );
+
+        // Batch invocation keeps sending new messages
+        let batch_target = callback_target.with_batch(true).with_msg_id(None);
+        assert!(batch_target.batch);
+        assert_eq!(batch_target.msg_id, None);
+        assert_eq!(markdown_message_method(&batch_target).await, "sendmessage");
+    }
+
+    #[test]
+    fn test_answer_callback_uses_callback_query_id() {
+        let target = target(Some(CallbackQueryId("42".to_string())));
+        let request = target
+            .answer_callback_request(Some(markdown_string!("Done\\!")))
+            .unwrap();
+        let payload = request.payload_ref();
+        assert_eq!(payload.callback_query_id, CallbackQueryId("42".to_string()));
+        assert_eq!(payload.text.as_deref(), Some("Done!"));
+
+        let request = target.answer_callback_request(None).unwrap();
+        assert_eq!(request.payload_ref().text, None);
+    }
+
+    #[test]
+    fn test_answer_callback_skipped_without_callback_query() {
+        let target = target(None);
+        assert!(target.answer_callback_request(None).is_none());
+    }
+
+    #[test]
+    fn test_noop_callback_toast() {
+        assert_eq!(noop_callback(None), "noop");
+        assert_eq!(parse_noop_callback(&noop_callback(None)), Some(None));
+
+        let data = noop_callback(Some("No more pages"));
+        assert_eq!(data, "noop No more pages");
+        assert_eq!(parse_noop_callback(&data), Some(Some("No more pages")));
+
+        // Other callback data is not an inactive button
+        assert_eq!(parse_noop_callback("/report"), None);
+        assert_eq!(parse_noop_callback("noopish"), None);
+    }
+
+    #[tokio::test]
+    async fn test_ignore_not_modified() {
+        // Edit request of the target answered by Telegram with the given reply
+        let edit = |reply: &'static str| async move {
+            let (url, server) = serve_one_request(reply);
+            let target = with_api_url(target(None), url);
+            let result = ignore_not_modified(
+                target
+                    .edit_markdown_message_text(MessageId(7), markdown_string!("Hi"))
+                    .await,
+            );
+            assert_eq!(server.join().unwrap(), "editmessagetext");
+            result
+        };
+
+        let result = edit(
+            r#"{"ok":false,"error_code":400,"description":"Bad Request: message is not modified: specified new message content and reply markup are exactly the same as a current content and reply markup of the message"}"#,
+        )
+        .await;
+        assert!(matches!(result, Ok(None)));
+
+        // A differently worded refusal, which teloxide parses as an unknown error
+        let result = edit(
+            r#"{"ok":false,"error_code":400,"description":"Bad Request: message is not modified: specified new message content and reply markup are exactly the same as a content and reply markup of the current message"}"#,
+        )
+        .await;
+        assert!(matches!(result, Ok(None)));
+
+        let result = edit(
+            r#"{"ok":true,"result":{"message_id":7,"date":0,"chat":{"id":1,"type":"private"},"text":"Hi"}}"#,
+        )
+        .await;
+        assert!(matches!(result, Ok(Some(message)) if message.id == MessageId(7)));
+
+        // Other errors are passed through
+        let result = edit(
+            r#"{"ok":false,"error_code":400,"description":"Bad Request: message to edit not found"}"#,
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(RequestError::Api(ApiError::MessageToEditNotFound))
+        ));
+        assert!(!is_message_not_modified(&RequestError::Api(
+            ApiError::BotBlocked
+        )));
+    }
+
+    /// Command with a free text argument spanning multiple lines
+    #[derive(Debug, Clone)]
+    struct NoteCommand {
+        title: Option<String>,
+        text: Option<String>,
+    }
+
+    impl CommandTrait for NoteCommand {
+        type A = String;
+        type B = String;
+        type C = EmptyArg;
+        type D = EmptyArg;
+        type E = EmptyArg;
+        type F = EmptyArg;
+        type G = EmptyArg;
+        type H = EmptyArg;
+        type I = EmptyArg;
+        type Context = ();
+        const NAME: &'static str = "note";
+        const PLACEHOLDERS: &[&'static str] = &["<title>", "<text>"];
+        const MULTILINE_LAST_ARG: bool = true;
+        fn from_arguments(
+            title: Option<Self::A>,
+            text: Option<Self::B>,
+            _c: Option<Self::C>,
+            _d: Option<Self::D>,
+            _e: Option<Self::E>,
+            _f: Option<Self::F>,
+            _g: Option<Self::G>,
+            _h: Option<Self::H>,
+            _i: Option<Self::I>,
+        ) -> Self {
+            NoteCommand { title, text }
+        }
+        fn param1(&self) -> Option<&Self::A> {
+            self.title.as_ref()
+        }
+        fn param2(&self) -> Option<&Self::B> {
+            self.text.as_ref()
+        }
+    }
+
+    #[test]
+    fn test_split_with_multiline_tail() {
+        // Single line: the last argument keeps its spaces
+        assert_eq!(
+            split_with_multiline_tail("Trip\\ plan buy tickets  early", 2),
+            vec!["Trip plan", "buy tickets  early"]
+        );
+        // Following lines belong to the last argument
+        assert_eq!(
+            split_with_multiline_tail("Trip first line\nsecond line\n", 2),
+            vec!["Trip", "first line\nsecond line"]
+        );
+        // The tail may start on the next line
+        assert_eq!(
+            split_with_multiline_tail("Trip\r\nfirst\nsecond", 2),
+            vec!["Trip", "first\nsecond"]
+        );
+        assert_eq!(split_with_multiline_tail("Trip", 2), vec!["Trip"]);
+        assert!(split_with_multiline_tail("", 2).is_empty());
+
+        // The default splitting still only looks at the first line
+        assert_eq!(
+            split_with_screened_spaces("a\\ b c\nd"),
+            vec!["a b".to_string(), "c".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_multiline_last_arg() {
+        let (note,) = NoteCommand::parse_arguments("Trip one line".to_string()).unwrap();
+        assert_eq!(note.title.as_deref(), Some("Trip"));
+        assert_eq!(note.text.as_deref(), Some("one line"));
+
+        let (note,) = NoteCommand::parse_arguments("Trip line one\nline two".to_string()).unwrap();
+        assert_eq!(note.text.as_deref(), Some("line one\nline two"));
+
+        // The text is kept verbatim in the command string and parsed back
+        let command = note.to_command_string(false);
+        assert_eq!(command, "/note Trip line one\nline two");
+        let args = command.strip_prefix("/note ").unwrap().to_string();
+        let (reparsed,) = NoteCommand::parse_arguments(args).unwrap();
+        assert_eq!(reparsed.text, note.text);
+    }
+}
@@ -25,7 +25,7 @@ macro_rules! md_process_args {
             $($processed)*
             {
                 let content: String = $code_content.into();
-                format!("```{}\n{}\n```", $lang, content)
+                format!("```{}\n{}\n```", $lang, $crate::markdown::escape_code(&content))
             },
         ])
     };
@@ -36,7 +36,7 @@ macro_rules! md_process_args {
             $($processed)*
             {
                 let content: String = $code_content.into();
-                format!("```\n{}\n```", content)
+                format!("```\n{}\n```", $crate::markdown::escape_code(&content))
             },
         ])
     };
@@ -77,7 +77,7 @@ macro_rules! md_process_args {
 /// # Special Argument Modifiers
 ///
 /// - `@raw`: Pass a MarkdownString without re-escaping (for pre-formatted markdown)
-/// - `@code`: Wrap content in a code block (```). Content is not escaped.
+/// - `@code`: Wrap content in a code block (```). Only backticks and backslashes are escaped.
 /// - `@code "lang"`: Wrap content in a language-specific code block (```lang)
 ///
 /// You can mix these modifiers and regular arguments in any order.
@@ -42,9 +42,16 @@ impl MarkdownString {
     pub fn escape<T: Into<String>>(input: T) -> Self {
         let input_string = input.into();
         let escaped = teloxide::utils::markdown::escape(&input_string);
-        let mut result = MarkdownString::default();
-        result.push(&MarkdownString::from_validated_string(escaped));
-        result
+        if text_len(&escaped) > TELEGRAM_MAX_MESSAGE_LENGTH {
+            // Already escaped, so cut it directly instead of escaping it again
+            let mut result = truncate_escaped(
+                &escaped,
+                TELEGRAM_MAX_MESSAGE_LENGTH - text_len(TRUNCATION_MARKER),
+            );
+            result.push_str(TRUNCATION_MARKER);
+            return MarkdownString(result, true);
+        }
+        MarkdownString(escaped, false)
     }
 
     /// Creates an empty MarkdownString.
@@ -66,14 +73,18 @@ impl MarkdownString {
     #[doc(hidden)]
     pub fn from_validated_string(s: impl Into<String>) -> Self {
         let s: String = s.into();
-        if s.len() > TELEGRAM_MAX_MESSAGE_LENGTH {
+        if text_len(&s) > TELEGRAM_MAX_MESSAGE_LENGTH {
             // Truncate, escape and mark as truncated
             let safe_length = TELEGRAM_MAX_MESSAGE_LENGTH - 100; // additional space for escaping
-            let truncated_str = s[..safe_length].to_string();
-            let mut escaped_truncated_str = MarkdownString::escape(truncated_str);
-            let truncation_marker = markdown_string!(TRUNCATION_MARKER);
-            escaped_truncated_str.push(&truncation_marker);
-            return MarkdownString(escaped_truncated_str.0, true);
+            let truncated_str = truncate_text(&s, safe_length);
+            let escaped = teloxide::utils::markdown::escape(&truncated_str);
+            // Escaping may grow the text again, so cut it without splitting escape sequences
+            let mut result = truncate_escaped(
+                &escaped,
+                TELEGRAM_MAX_MESSAGE_LENGTH - text_len(TRUNCATION_MARKER),
+            );
+            result.push_str(TRUNCATION_MARKER);
+            return MarkdownString(result, true);
         }
         MarkdownString(s, false)
     }
@@ -95,6 +106,21 @@ impl MarkdownString {
         self.0
     }
 
+    /// Returns true if there is no content
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the length in characters rather than bytes
+    pub fn char_len(&self) -> usize {
+        self.0.chars().count()
+    }
+
+    /// Returns the length in UTF-16 code units, the unit of Telegram's message length limit
+    pub fn utf16_len(&self) -> usize {
+        text_len(&self.0)
+    }
+
     /// Check if the MarkdownString has been truncated due to length limits
     pub fn is_truncated(&self) -> bool {
         self.1
@@ -112,9 +138,11 @@ impl MarkdownString {
             return;
         }
         let truncation_marker = markdown_string!(TRUNCATION_MARKER);
-        let combined_length = self.0.len() + other.0.len() + truncation_marker.as_str().len();
+        let self_length = text_len(&self.0);
+        let marker_length = text_len(truncation_marker.as_str());
+        let combined_length = self_length + text_len(&other.0) + marker_length;
         if combined_length > TELEGRAM_MAX_MESSAGE_LENGTH {
-            if self.0.len() + truncation_marker.as_str().len() <= TELEGRAM_MAX_MESSAGE_LENGTH {
+            if self_length + marker_length <= TELEGRAM_MAX_MESSAGE_LENGTH {
                 // Can fit truncation marker
                 self.0.push_str(truncation_marker.as_str());
             }
@@ -123,6 +151,103 @@ impl MarkdownString {
             self.0.push_str(other.as_str());
         }
     }
+
+    /// Creates a MarkdownString of `pattern` repeated `count` times, without escaping it again
+    /// Useful for separators and spacing built from already valid fragments
+    ///
+    /// # Example
+    /// ```rust
+    /// use yoroolbot::{markdown::MarkdownString, markdown_string};
+    ///
+    /// let separator = MarkdownString::repeat(&markdown_string!("\\-"), 3);
+    /// assert_eq!(separator.as_str(), "\\-\\-\\-");
+    /// ```
+    pub fn repeat(pattern: &MarkdownString, count: usize) -> Self {
+        let mut result = MarkdownString::new();
+        for _ in 0..count {
+            if result.is_truncated() {
+                break;
+            }
+            result.push(pattern);
+        }
+        result
+    }
+
+    /// Creates a code block of plain text lines, escaping only what is special inside it:
+    /// backticks and backslashes
+    ///
+    /// # Example
+    /// ```rust
+    /// use yoroolbot::markdown::MarkdownString;
+    ///
+    /// let table = MarkdownString::code_block_from_lines(&["a `b`".to_string(), "c".to_string()]);
+    /// assert_eq!(table.as_str(), "```\na \\`b\\`\nc\n```");
+    /// ```
+    pub fn code_block_from_lines(lines: &[String]) -> Self {
+        MarkdownString::from_validated_string(format!(
+            "```\n{}\n```",
+            escape_code(&lines.join("\n"))
+        ))
+    }
+
+    /// Returns the text as the user sees it: escaping reverted and formatting removed
+    /// Escaped backslashes (`\\\\`) stay as one backslash, code keeps its content verbatim
+    /// and links keep only their text
+    ///
+    /// # Example
+    /// ```rust
+    /// use yoroolbot::markdown_string;
+    ///
+    /// let markdown = markdown_string!("*Total:* `5\\.50` \\(paid\\)");
+    /// assert_eq!(markdown.to_plain_text(), "Total: 5.50 (paid)");
+    /// ```
+    pub fn to_plain_text(&self) -> String {
+        let mut result = String::new();
+        let mut chars = self.0.chars().peekable();
+        let mut in_code = false;
+        let mut in_code_block = false;
+        let mut in_link_url = false;
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    if let Some(escaped) = chars.next()
+                        && !in_link_url
+                    {
+                        result.push(escaped);
+                    }
+                }
+                '`' if chars.peek() == Some(&'`') => {
+                    chars.next();
+                    chars.next();
+                    if in_code_block {
+                        // The line break before the closing delimiter belongs to the markup
+                        if result.ends_with('\n') {
+                            result.pop();
+                        }
+                    } else {
+                        // Skip the optional language name and the line break after it
+                        let header: String = chars.clone().take_while(|c| *c != '\n').collect();
+                        if !header.contains(char::is_whitespace) && !header.contains('`') {
+                            chars.nth(header.chars().count());
+                        }
+                    }
+                    in_code_block = !in_code_block;
+                }
+                _ if in_code_block => result.push(c),
+                '`' => in_code = !in_code,
+                _ if in_code => result.push(c),
+                ')' if in_link_url => in_link_url = false,
+                _ if in_link_url => {}
+                ']' if chars.peek() == Some(&'(') => {
+                    chars.next();
+                    in_link_url = true;
+                }
+                '*' | '_' | '~' | '|' | '[' | ']' => {}
+                _ => result.push(c),
+            }
+        }
+        result
+    }
 }
 
 impl fmt::Display for MarkdownString {
@@ -239,10 +364,67 @@ impl Add<&MarkdownString> for &MarkdownString {
     }
 }
 
+// Collect MarkdownStrings by concatenation, the parts are already escaped
+impl FromIterator<MarkdownString> for MarkdownString {
+    fn from_iter<I: IntoIterator<Item = MarkdownString>>(iter: I) -> Self {
+        let mut result = MarkdownString::new();
+        for item in iter {
+            result.push(&item);
+        }
+        result
+    }
+}
+
+/// Escape text for a code block, where only backticks and backslashes are special
+/// Used by the `@code` modifier of `markdown_format!`
+pub fn escape_code(content: &str) -> String {
+    teloxide::utils::markdown::escape_code(content)
+}
+
 /// Maximum message length allowed by Telegram Bot API
 /// See: https://core.telegram.org/bots/api#sendmessage
 const TELEGRAM_MAX_MESSAGE_LENGTH: usize = 4096;
 
+/// Length of the text as counted against TELEGRAM_MAX_MESSAGE_LENGTH
+/// Telegram counts UTF-16 code units, so emoji outside the BMP count as two
+fn text_len(s: &str) -> usize {
+    s.chars().map(char::len_utf16).sum()
+}
+
+/// Cut plain text to at most `max_length` UTF-16 code units on a character boundary
+fn truncate_text(s: &str, max_length: usize) -> String {
+    let mut length = 0;
+    s.chars()
+        .take_while(|c| {
+            length += c.len_utf16();
+            length <= max_length
+        })
+        .collect()
+}
+
+/// Cut escaped markdown text to at most `max_length`, never separating a backslash
+/// from the character it escapes
+fn truncate_escaped(escaped: &str, max_length: usize) -> String {
+    let mut result = String::new();
+    let mut length = 0;
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        let mut sequence = String::from(c);
+        if c == '\\'
+            && let Some(next_c) = chars.next()
+        {
+            sequence.push(next_c);
+        }
+        let sequence_length = text_len(&sequence);
+        if length + sequence_length > max_length {
+            break;
+        }
+        length += sequence_length;
+        result.push_str(&sequence);
+    }
+    result
+}
+
 /// Trait for sending markdown messages with Bot
 ///
 /// This trait provides a convenient method for sending MarkdownString messages
@@ -352,6 +534,106 @@ mod tests {
     use super::*;
     use crate::{markdown_format, markdown_string};
 
+    #[test]
+    fn test_code_block_from_lines() {
+        let lines = vec![
+            "Name `quoted`".to_string(),
+            "C:\\path\\to".to_string(),
+            "*not bold* 1.50 (x)".to_string(),
+        ];
+        let block = MarkdownString::code_block_from_lines(&lines);
+        assert_eq!(
+            block.as_str(),
+            "```\nName \\`quoted\\`\nC:\\\\path\\\\to\n*not bold* 1.50 (x)\n```"
+        );
+        assert_eq!(block.to_plain_text(), lines.join("\n"));
+        // Same result as the `@code` modifier of `markdown_format!`
+        assert_eq!(block, markdown_format!("{}", @code lines.join("\n")));
+    }
+
+    #[test]
+    fn test_is_empty_and_char_len() {
+        assert!(MarkdownString::new().is_empty());
+        assert_eq!(MarkdownString::new().char_len(), 0);
+        let markdown = MarkdownString::escape("💰 5.50");
+        assert!(!markdown.is_empty());
+        // "💰 5\\.50" - the emoji is 4 bytes but one character
+        assert_eq!(markdown.char_len(), 7);
+        assert_eq!(markdown.as_str().len(), 10);
+    }
+
+    #[test]
+    fn test_emoji_content_counted_in_utf16_units() {
+        // 4-byte emoji take two UTF-16 code units each
+        let markdown = MarkdownString::escape("💰".repeat(10));
+        assert_eq!(markdown.char_len(), 10);
+        assert_eq!(markdown.utf16_len(), 20);
+        assert_eq!(MarkdownString::escape("é€").utf16_len(), 2);
+
+        // 2048 emoji are exactly at the 4096 units limit
+        let markdown = MarkdownString::escape("💰".repeat(2048));
+        assert!(!markdown.is_truncated());
+        assert_eq!(markdown.utf16_len(), TELEGRAM_MAX_MESSAGE_LENGTH);
+
+        // 3000 emoji are under 4096 characters but exceed the real limit
+        let markdown = MarkdownString::escape("💰".repeat(3000));
+        assert!(markdown.is_truncated());
+        assert!(markdown.utf16_len() <= TELEGRAM_MAX_MESSAGE_LENGTH);
+        // Truncation never splits an emoji
+        assert!(
+            markdown
+                .as_str()
+                .trim_end_matches(TRUNCATION_MARKER)
+                .chars()
+                .all(|c| c == '💰')
+        );
+    }
+
+    #[test]
+    fn test_push_boundary_in_utf16_units() {
+        let marker_length = text_len(TRUNCATION_MARKER);
+        let mut combined = MarkdownString::new();
+        combined.push(&MarkdownString::escape("🍕".repeat(1000)));
+        combined.push(&MarkdownString::escape("🍕".repeat(1000)));
+        assert!(!combined.is_truncated());
+        assert_eq!(combined.utf16_len(), 4000);
+
+        // Fits only while the truncation marker still has room after it
+        let fitting = (TELEGRAM_MAX_MESSAGE_LENGTH - 4000 - marker_length) / 2;
+        combined.push(&MarkdownString::escape("🍕".repeat(fitting)));
+        assert!(!combined.is_truncated());
+
+        combined.push(&MarkdownString::escape("🍕"));
+        assert!(combined.is_truncated());
+        assert!(combined.utf16_len() <= TELEGRAM_MAX_MESSAGE_LENGTH);
+        assert!(combined.as_str().ends_with(TRUNCATION_MARKER));
+    }
+
+    #[test]
+    fn test_oversized_content_truncated_within_limit() {
+        // Every character needs escaping, so escaping doubles the length
+        let markdown = MarkdownString::escape("-".repeat(5000));
+        assert!(markdown.is_truncated());
+        assert!(markdown.utf16_len() <= TELEGRAM_MAX_MESSAGE_LENGTH);
+        assert!(markdown.as_str().ends_with(TRUNCATION_MARKER));
+        // No dangling escape before the marker
+        assert!(
+            markdown
+                .as_str()
+                .trim_end_matches(TRUNCATION_MARKER)
+                .ends_with("\\-")
+        );
+
+        let markdown = MarkdownString::escape("🍕".repeat(5000));
+        assert!(markdown.is_truncated());
+        assert!(markdown.utf16_len() <= TELEGRAM_MAX_MESSAGE_LENGTH);
+
+        // Validated markdown over the limit is escaped and cut as well
+        let markdown = MarkdownString::from_validated_string("\\-".repeat(3000));
+        assert!(markdown.is_truncated());
+        assert!(markdown.utf16_len() <= TELEGRAM_MAX_MESSAGE_LENGTH);
+    }
+
     #[test]
     fn test_escape_constructor() {
         // Test basic escaping
@@ -530,6 +812,46 @@ mod tests {
         assert_eq!(combined.as_str(), "User: Alice said: *Hello\\!*");
     }
 
+    #[test]
+    fn test_collect_markdown_strings() {
+        // Collecting nothing gives an empty string
+        let empty: MarkdownString = Vec::<MarkdownString>::new().into_iter().collect();
+        assert!(empty.is_empty());
+
+        // Collecting a single item keeps it unchanged
+        let single: MarkdownString = vec![MarkdownString::escape("a.b")].into_iter().collect();
+        assert_eq!(single.as_str(), "a\\.b");
+
+        // Collecting several items concatenates them without re-escaping
+        let multiple: MarkdownString = vec![
+            MarkdownString::test_template("*bold*"),
+            MarkdownString::escape(" and "),
+            MarkdownString::escape("1.5!"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(multiple.as_str(), "*bold* and 1\\.5\\!");
+    }
+
+    #[test]
+    fn test_repeat() {
+        let pattern = MarkdownString::escape("-.");
+        assert!(MarkdownString::repeat(&pattern, 0).is_empty());
+        assert_eq!(MarkdownString::repeat(&pattern, 1), pattern);
+        // Fragments are concatenated as is, not escaped again
+        assert_eq!(
+            MarkdownString::repeat(&pattern, 3).as_str(),
+            "\\-\\.\\-\\.\\-\\."
+        );
+        let bold = MarkdownString::test_template("*x*");
+        assert_eq!(MarkdownString::repeat(&bold, 2).as_str(), "*x**x*");
+
+        // Repeating past the message limit truncates like push
+        let long = MarkdownString::repeat(&pattern, TELEGRAM_MAX_MESSAGE_LENGTH);
+        assert!(long.is_truncated());
+        assert!(long.utf16_len() <= TELEGRAM_MAX_MESSAGE_LENGTH);
+    }
+
     #[test]
     fn test_direct_construction() {
         // Test direct construction without escaping (replaces raw test)
@@ -835,7 +1157,7 @@ mod tests {
     fn test_markdown_format_raw_prefix_complex() {
         // Real-world example: combining pre-formatted regex pattern with regular text
         // @raw and regular arguments can be mixed in any order
-        let words = vec![
+        let words = [
             "word1".to_string(),
             "word2".to_string(),
             "word3".to_string(),
@@ -996,4 +1318,47 @@ mod tests {
             "*Important*: ```\nName   Value\nTest     123\n```"
         );
     }
+
+    #[test]
+    fn test_markdown_format_code_modifier_escapes_backticks() {
+        // Backticks and backslashes would end or break the code block
+        let content = "a`b\\c *d*";
+        let result = markdown_format!("{}", @code content);
+        assert_eq!(result.as_str(), "```\na\\`b\\\\c *d*\n```");
+        crate::markdown::validate_markdownv2_format(result.as_str());
+    }
+
+    #[test]
+    fn test_to_plain_text_escaped_chars() {
+        let markdown = MarkdownString::escape("Price: 5.50 (total) - 10% off! a\\b #tag");
+        assert_eq!(
+            markdown.to_plain_text(),
+            "Price: 5.50 (total) - 10% off! a\\b #tag"
+        );
+        assert_eq!(MarkdownString::new().to_plain_text(), "");
+    }
+
+    #[test]
+    fn test_to_plain_text_formatting_delimiters() {
+        let markdown = markdown_string!("*bold* _italic_ __underline__ ~strike~ ||spoiler||");
+        assert_eq!(
+            markdown.to_plain_text(),
+            "bold italic underline strike spoiler"
+        );
+        let markdown = markdown_format!("*{}*: _{}_", "a*b", "c_d");
+        assert_eq!(markdown.to_plain_text(), "a*b: c_d");
+        let markdown = markdown_string!("[site](https://example\\.com/a\\)b) \\[1\\]");
+        assert_eq!(markdown.to_plain_text(), "site [1]");
+    }
+
+    #[test]
+    fn test_to_plain_text_code_spans() {
+        let markdown = markdown_string!("Run `a*b_c` now");
+        assert_eq!(markdown.to_plain_text(), "Run a*b_c now");
+        let content = "x `y` \\z *w*";
+        let markdown = markdown_format!("Table:\n{}", @code content);
+        assert_eq!(markdown.to_plain_text(), "Table:\nx `y` \\z *w*");
+        let markdown = markdown_string!("```rust\nlet a = 1;\n```");
+        assert_eq!(markdown.to_plain_text(), "let a = 1;");
+    }
 }
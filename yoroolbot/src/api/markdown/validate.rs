@@ -34,10 +34,11 @@ pub const fn validate_markdownv2_format(format_str: &str) {
         if !is_escaped {
             match current_char {
                 // Basic formatting characters must be balanced
-                b'*' => asterisk_count = asterisk_count.wrapping_add(1),
-                b'_' => underscore_count = underscore_count.wrapping_add(1),
-                b'~' => tilde_count = tilde_count.wrapping_add(1),
-                b'|' => pipe_count = pipe_count.wrapping_add(1),
+                // Inside code they are plain text
+                b'*' if !in_code && !in_pre => asterisk_count = asterisk_count.wrapping_add(1),
+                b'_' if !in_code && !in_pre => underscore_count = underscore_count.wrapping_add(1),
+                b'~' if !in_code && !in_pre => tilde_count = tilde_count.wrapping_add(1),
+                b'|' if !in_code && !in_pre => pipe_count = pipe_count.wrapping_add(1),
 
                 // Code formatting validation
                 b'`' => {
@@ -54,61 +55,45 @@ pub const fn validate_markdownv2_format(format_str: &str) {
                 }
 
                 // Link formatting validation
-                b'[' => square_bracket_count = square_bracket_count.wrapping_add(1),
-                b']' => {
+                b'[' if !in_code && !in_pre => {
+                    square_bracket_count = square_bracket_count.wrapping_add(1)
+                }
+                b']' if !in_code && !in_pre => {
                     assert!(
                         square_bracket_count > 0,
                         "Unmatched closing square bracket ']' in markdown format string"
                     );
                     square_bracket_count = square_bracket_count.wrapping_sub(1);
                 }
-                b'(' => {
-                    // Only count if it's potentially part of a link (after ])
-                    if prev_char == b']' {
-                        paren_count = paren_count.wrapping_add(1);
-                    }
+                // Only count if it's potentially part of a link (after ])
+                b'(' if prev_char == b']' && !in_code && !in_pre => {
+                    paren_count = paren_count.wrapping_add(1);
                 }
-                b')' => {
-                    if paren_count > 0 {
-                        paren_count = paren_count.wrapping_sub(1);
-                    }
+                b')' if paren_count > 0 => {
+                    paren_count = paren_count.wrapping_sub(1);
                 }
 
                 // Reserved characters that should be escaped (compile-time check)
-                b'!' => {
-                    if !in_code && !in_pre && !is_escaped {
-                        panic!("Unescaped '!' in MarkdownV2 format string. Use \\! to escape it.");
-                    }
+                b'!' if !in_code && !in_pre && !is_escaped => {
+                    panic!("Unescaped '!' in MarkdownV2 format string. Use \\! to escape it.");
                 }
-                b'.' => {
-                    if !in_code && !in_pre && !is_escaped {
-                        panic!("Unescaped '.' in MarkdownV2 format string. Use \\. to escape it.");
-                    }
+                b'.' if !in_code && !in_pre && !is_escaped => {
+                    panic!("Unescaped '.' in MarkdownV2 format string. Use \\. to escape it.");
                 }
-                b'-' => {
-                    if !in_code && !in_pre && !is_escaped {
-                        panic!("Unescaped '-' in MarkdownV2 format string. Use \\- to escape it.");
-                    }
+                b'-' if !in_code && !in_pre && !is_escaped => {
+                    panic!("Unescaped '-' in MarkdownV2 format string. Use \\- to escape it.");
                 }
-                b'+' => {
-                    if !in_code && !in_pre && !is_escaped {
-                        panic!("Unescaped '+' in MarkdownV2 format string. Use \\+ to escape it.");
-                    }
+                b'+' if !in_code && !in_pre && !is_escaped => {
+                    panic!("Unescaped '+' in MarkdownV2 format string. Use \\+ to escape it.");
                 }
-                b'=' => {
-                    if !in_code && !in_pre && !is_escaped {
-                        panic!("Unescaped '=' in MarkdownV2 format string. Use \\= to escape it.");
-                    }
+                b'=' if !in_code && !in_pre && !is_escaped => {
+                    panic!("Unescaped '=' in MarkdownV2 format string. Use \\= to escape it.");
                 }
-                b'>' => {
-                    if !in_code && !in_pre && !is_escaped {
-                        panic!("Unescaped '>' in MarkdownV2 format string. Use \\> to escape it.");
-                    }
+                b'>' if !in_code && !in_pre && !is_escaped => {
+                    panic!("Unescaped '>' in MarkdownV2 format string. Use \\> to escape it.");
                 }
-                b'#' => {
-                    if !in_code && !in_pre && !is_escaped {
-                        panic!("Unescaped '#' in MarkdownV2 format string. Use \\# to escape it.");
-                    }
+                b'#' if !in_code && !in_pre && !is_escaped => {
+                    panic!("Unescaped '#' in MarkdownV2 format string. Use \\# to escape it.");
                 }
                 b'{' => {
                     // Allow format placeholders like {}
@@ -140,23 +125,23 @@ pub const fn validate_markdownv2_format(format_str: &str) {
 
     // Validate balanced formatting
     assert!(
-        asterisk_count % 2 == 0,
+        asterisk_count.is_multiple_of(2),
         "Unmatched asterisks (*) in MarkdownV2 format string - bold formatting must be balanced"
     );
     assert!(
-        underscore_count % 2 == 0,
+        underscore_count.is_multiple_of(2),
         "Unmatched underscores (_) in MarkdownV2 format string - italic formatting must be balanced"
     );
     assert!(
-        backtick_count % 2 == 0,
+        backtick_count.is_multiple_of(2),
         "Unmatched backticks (`) in MarkdownV2 format string - code formatting must be balanced"
     );
     assert!(
-        tilde_count % 2 == 0,
+        tilde_count.is_multiple_of(2),
         "Unmatched tildes (~) in MarkdownV2 format string - strikethrough formatting must be balanced"
     );
     assert!(
-        pipe_count % 2 == 0,
+        pipe_count.is_multiple_of(2),
         "Unmatched pipes (|) in MarkdownV2 format string - spoiler formatting must be balanced"
     );
     assert!(
@@ -176,6 +161,8 @@ pub const fn validate_markdownv2_format(format_str: &str) {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn test_compile_time_validation() {
         // These should compile successfully
@@ -193,7 +180,7 @@ mod tests {
             }
 
             assert!(
-                asterisk_count % 2 == 0,
+                asterisk_count.is_multiple_of(2),
                 "Unmatched asterisks in markdown format string"
             );
         };
@@ -249,20 +236,14 @@ mod tests {
                         b'|' => pipe_count = pipe_count.wrapping_add(1),
                         b'`' => backtick_count = backtick_count.wrapping_add(1),
                         b'[' => square_bracket_count = square_bracket_count.wrapping_add(1),
-                        b']' => {
-                            if square_bracket_count > 0 {
-                                square_bracket_count = square_bracket_count.wrapping_sub(1);
-                            }
+                        b']' if square_bracket_count > 0 => {
+                            square_bracket_count = square_bracket_count.wrapping_sub(1);
                         }
-                        b'(' => {
-                            if prev_char == b']' {
-                                paren_count = paren_count.wrapping_add(1);
-                            }
+                        b'(' if prev_char == b']' => {
+                            paren_count = paren_count.wrapping_add(1);
                         }
-                        b')' => {
-                            if paren_count > 0 {
-                                paren_count = paren_count.wrapping_sub(1);
-                            }
+                        b')' if paren_count > 0 => {
+                            paren_count = paren_count.wrapping_sub(1);
                         }
                         _ => {}
                     }
@@ -274,27 +255,27 @@ mod tests {
 
             // Validate all formatting is balanced
             assert!(
-                asterisk_count % 2 == 0,
+                asterisk_count.is_multiple_of(2),
                 "Pattern '{}' has unmatched asterisks",
                 pattern
             );
             assert!(
-                underscore_count % 2 == 0,
+                underscore_count.is_multiple_of(2),
                 "Pattern '{}' has unmatched underscores",
                 pattern
             );
             assert!(
-                backtick_count % 2 == 0,
+                backtick_count.is_multiple_of(2),
                 "Pattern '{}' has unmatched backticks",
                 pattern
             );
             assert!(
-                tilde_count % 2 == 0,
+                tilde_count.is_multiple_of(2),
                 "Pattern '{}' has unmatched tildes",
                 pattern
             );
             assert!(
-                pipe_count % 2 == 0,
+                pipe_count.is_multiple_of(2),
                 "Pattern '{}' has unmatched pipes",
                 pattern
             );
@@ -311,6 +292,13 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_formatting_characters_inside_code() {
+        // Formatting characters are plain text inside code, they don't need to be balanced
+        validate_markdownv2_format("```\nsnake_case *star [x](y ~|\n```");
+        validate_markdownv2_format("Pattern `a_b*` and *bold*");
+    }
+
     // Note: These patterns would cause compile errors if used with the markdown_string! macro:
     // Invalid examples (unbalanced formatting):
     // "*unmatched bold" - unmatched asterisk
@@ -2,6 +2,7 @@ use std::{collections::HashMap, fmt::Display, str::FromStr, sync::Arc};
 
 use teloxide::types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup};
 use tokio::sync::Mutex;
+use url::Url;
 
 /// Represents different types of inline keyboard buttons
 #[derive(Clone)]
@@ -10,6 +11,8 @@ pub enum ButtonData {
     Callback(String, String),
     /// Switch inline query button with label and query text
     SwitchInlineQuery(String, String),
+    /// Link button with label and URL opened by the client
+    Url(String, Url),
 }
 
 impl From<(String, String)> for ButtonData {
@@ -43,6 +46,9 @@ pub trait CallbackDataStorageTrait: Send + Sync {
 
     /// Clear all callback data for a specific message
     async fn clear_message_callbacks(&self, chat_id: ChatId, message_id: i32);
+
+    /// Clear all stored callback data for a chat
+    async fn clear_chat_callbacks(&self, chat_id: ChatId);
 }
 
 /// The key for the callback data storage map
@@ -148,6 +154,11 @@ impl CallbackDataStorageTrait for CallbackDataStorage {
         let mut storage_guard = self.data.lock().await;
         storage_guard.retain(|key, _| key.chat_id != chat_id || key.message_id != message_id);
     }
+
+    async fn clear_chat_callbacks(&self, chat_id: ChatId) {
+        let mut storage_guard = self.data.lock().await;
+        storage_guard.retain(|key, _| key.chat_id != chat_id);
+    }
 }
 
 /// Pack callback data into an InlineKeyboardMarkup, storing long data in storage
@@ -156,7 +167,7 @@ impl CallbackDataStorageTrait for CallbackDataStorage {
 /// This function takes rows of button data where each row contains ButtonData enum values.
 /// For callback buttons, if the callback_data is longer than 64 bytes or contains non-ASCII
 /// characters, it stores the data in CallbackDataStorage and replaces it with a short reference.
-/// For switch inline query buttons, the query text is used directly without storage.
+/// For switch inline query and URL buttons, the data is used directly without storage.
 ///
 /// **Important:** This function clears any previously stored callback data for this message
 /// to prevent memory leaks when updating message buttons.
@@ -210,6 +221,9 @@ where
                     ));
                     // Don't increment button_pos for inline query buttons as they don't use storage
                 }
+                ButtonData::Url(label, url) => {
+                    button_row.push(InlineKeyboardButton::url(label, url));
+                }
             }
         }
         button_rows.push(button_row);
@@ -240,3 +254,38 @@ pub async fn unpack_callback_data(
     // Not a reference or not found in storage, return as-is
     callback_data.to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use teloxide::types::InlineKeyboardButtonKind;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pack_url_button() {
+        let storage: Arc<dyn CallbackDataStorageTrait> = Arc::new(CallbackDataStorage::new());
+        let url = Url::parse("https://example.com/docs").unwrap();
+        let markup = pack_callback_data(
+            &storage,
+            ChatId(1),
+            10,
+            vec![vec![
+                ButtonData::Callback("Next".to_string(), "/next".to_string()),
+                ButtonData::Url("Docs".to_string(), url.clone()),
+            ]],
+        )
+        .await;
+
+        let row = &markup.inline_keyboard[0];
+        assert_eq!(row.len(), 2);
+        assert_eq!(row[1].text, "Docs");
+        match &row[1].kind {
+            InlineKeyboardButtonKind::Url(button_url) => assert_eq!(button_url, &url),
+            kind => panic!("Expected URL button, got {:?}", kind),
+        }
+        assert!(matches!(
+            &row[0].kind,
+            InlineKeyboardButtonKind::CallbackData(data) if data == "/next"
+        ));
+    }
+}
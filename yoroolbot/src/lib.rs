@@ -7,7 +7,7 @@ pub(crate) mod api;
 pub mod markdown {
     // Re-export types and traits from internal API
     pub use crate::api::markdown::{
-        string::{MarkdownString, MarkdownStringMessage},
+        string::{MarkdownString, MarkdownStringMessage, escape_code},
         validate::validate_markdownv2_format,
     };
 }
@@ -16,7 +16,9 @@ pub mod markdown {
 pub mod command_trait {
     // Re-export types and traits from internal API
     pub use crate::api::command_trait::{
-        CommandReplyTarget, CommandTrait, EmptyArg, NoopCommand, ParseCommandArg,
+        CommandReplyTarget, CommandTrait, EmptyArg, NOOP_CALLBACK, NoopCommand, ParseCommandArg,
+        ignore_not_modified, is_message_not_modified, noop_callback, parse_noop_callback,
+        screen_spaces, split_with_multiline_tail, split_with_screened_spaces,
     };
 }
 
@@ -27,4 +29,6 @@ pub mod storage {
         ButtonData, CallbackDataStorage, CallbackDataStorageTrait, pack_callback_data,
         unpack_callback_data,
     };
+    /// URL type used by `ButtonData::Url`
+    pub use url::Url;
 }